@@ -0,0 +1,28 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+  println!("cargo::rerun-if-changed=build.rs");
+  println!("cargo::rerun-if-changed=cbindgen.toml");
+  println!("cargo::rerun-if-changed=src");
+
+  let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+  let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+    .unwrap_or_else(|e| panic!("failed to read cbindgen.toml: {e}"));
+
+  // The header is checked in so that hosts building without cargo can consume it. It's only
+  // rewritten when its contents change.
+  cbindgen::Builder::new()
+    .with_crate(&crate_dir)
+    .with_config(config)
+    .generate()
+    .unwrap_or_else(|e| panic!("failed to generate the C header: {e}"))
+    .write_to_file(crate_dir.join("include/capture.h"));
+}
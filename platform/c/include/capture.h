@@ -0,0 +1,252 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#pragma once
+
+/* Warning: this file is autogenerated by cbindgen. Don't modify this manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define CAPTURE_LOG_LEVEL_TRACE 0
+
+#define CAPTURE_LOG_LEVEL_DEBUG 1
+
+#define CAPTURE_LOG_LEVEL_INFO 2
+
+#define CAPTURE_LOG_LEVEL_WARNING 3
+
+#define CAPTURE_LOG_LEVEL_ERROR 4
+
+/**
+ * The platforms a host can identify as, which determine how its logs are processed. Part of the
+ * C ABI.
+ */
+#define CAPTURE_PLATFORM_ANDROID 0
+
+#define CAPTURE_PLATFORM_APPLE 1
+
+#define CAPTURE_PLATFORM_ELECTRON 2
+
+/**
+ * A session that lasts until a new one is started explicitly.
+ */
+#define CAPTURE_SESSION_STRATEGY_FIXED 0
+
+/**
+ * A session that ends after a period without logs.
+ */
+#define CAPTURE_SESSION_STRATEGY_ACTIVITY_BASED 1
+
+/**
+ * Fields handed to a metadata provider callback, which adds to them with `capture_fields_insert`.
+ */
+typedef struct CaptureFields CaptureFields;
+
+/**
+ * Describes how sessions are started. Callbacks may be called from any thread, and are passed
+ * `context` back.
+ */
+typedef struct CaptureSessionStrategy {
+  void *context;
+  /**
+   * `CAPTURE_SESSION_STRATEGY_FIXED` or `CAPTURE_SESSION_STRATEGY_ACTIVITY_BASED`.
+   */
+  uint32_t strategy_type;
+  /**
+   * How long an activity-based session lasts without logs.
+   */
+  int64_t inactivity_threshold_mins;
+  /**
+   * Writes a new NUL-terminated session ID of fewer than `capacity` bytes into `buffer`,
+   * returning false on failure. Only used by fixed sessions, which generate a random UUID when
+   * this is null.
+   */
+  bool (*generate_session_id)(void *context, char *buffer, uintptr_t capacity);
+  /**
+   * Called with the new session ID whenever an activity-based session changes. May be null.
+   */
+  void (*session_id_changed)(void *context, const char *session_id);
+} CaptureSessionStrategy;
+
+/**
+ * Provides the time and fields attached to every log. Callbacks may be called from any thread, and
+ * are passed `context` back.
+ */
+typedef struct CaptureMetadataProvider {
+  void *context;
+  /**
+   * Returns the current time in milliseconds since the Unix epoch. The system clock is used when
+   * this is null.
+   */
+  int64_t (*timestamp_ms)(void *context);
+  /**
+   * Adds out-of-the-box fields to `ootb` and custom fields to `custom` with
+   * `capture_fields_insert`. May be null.
+   */
+  void (*fields)(void *context, struct CaptureFields *ootb, struct CaptureFields *custom);
+} CaptureMetadataProvider;
+
+/**
+ * A host callback that takes nothing but its context.
+ */
+typedef void (*CaptureCallback)(void *context);
+
+/**
+ * Callbacks through which the logger asks the host for resource utilization and session replay
+ * data, and to start or stop listening for events. Any of them may be null. Callbacks may be
+ * called from any thread, and are passed `context` back.
+ */
+typedef struct CaptureTargets {
+  void *context;
+  CaptureCallback resource_utilization_tick;
+  CaptureCallback session_replay_capture_screen;
+  CaptureCallback session_replay_capture_screenshot;
+  CaptureCallback events_listener_start;
+  CaptureCallback events_listener_stop;
+} CaptureTargets;
+
+/**
+ * A string field attached to a log.
+ */
+typedef struct CaptureField {
+  const char *key;
+  const char *value;
+} CaptureField;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a logger that stores its state under `sdk_directory` and uploads to `api_url`. The
+ * logger doesn't run until it's started with `capture_logger_start`. Returns the logger's ID, or
+ * -1 if the logger couldn't be created.
+ *
+ * `platform` is one of the `CAPTURE_PLATFORM_*` values, the platform the host identifies as, and
+ * `os` names the host operating system, e.g. `linux`. `app_id`, `app_version` and `os_version`
+ * may be null.
+ *
+ * # Safety
+ * Every string must be null or NUL-terminated, and the callbacks in `session_strategy`,
+ * `metadata_provider` and `targets` must stay callable, from any thread, until the logger is
+ * destroyed.
+ */
+int64_t capture_logger_create(const char *sdk_directory,
+                              const char *api_key,
+                              const char *api_url,
+                              const char *app_id,
+                              const char *app_version,
+                              uint32_t platform,
+                              const char *os,
+                              const char *os_version,
+                              const char *model,
+                              struct CaptureSessionStrategy session_strategy,
+                              struct CaptureMetadataProvider metadata_provider,
+                              struct CaptureTargets targets,
+                              bool start_in_sleep_mode);
+
+/**
+ * Starts the logger's runtime. Calls after the first have no effect.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+ */
+void capture_logger_start(int64_t logger_id);
+
+/**
+ * Writes a log with `count` string fields. `log_level` is one of the `CAPTURE_LOG_LEVEL_*`
+ * values, and `log_type` a `LogType` value from the logging protos, 0 being a normal log.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, `message`
+ * must be NUL-terminated, and `fields` must be null or point to `count` fields.
+ */
+void capture_logger_log(int64_t logger_id,
+                        uint32_t log_level,
+                        uint32_t log_type,
+                        const char *message,
+                        const struct CaptureField *fields,
+                        uintptr_t count);
+
+/**
+ * Adds a field to every subsequent log, replacing any field with the same key.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and `key`
+ * and `value` must be NUL-terminated.
+ */
+void capture_logger_add_field(int64_t logger_id, const char *key, const char *value);
+
+/**
+ * Removes a field added with `capture_logger_add_field`.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and `key`
+ * must be NUL-terminated.
+ */
+void capture_logger_remove_field(int64_t logger_id, const char *key);
+
+/**
+ * Ends the current session and starts a new one.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+ */
+void capture_logger_start_new_session(int64_t logger_id);
+
+/**
+ * Copies the current session ID into `buffer` as a NUL-terminated string, truncated to fit its
+ * `capacity` bytes. Returns the session ID's length, so like `snprintf` a result of `capacity` or
+ * more means it was truncated.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and
+ * `buffer` must be null or valid for writes of `capacity` bytes.
+ */
+uintptr_t capture_logger_session_id(int64_t logger_id, char *buffer, uintptr_t capacity);
+
+/**
+ * Flushes the logger's state to disk, waiting up to a second for it to complete if `blocking` is
+ * true.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+ */
+void capture_logger_flush(int64_t logger_id, bool blocking);
+
+/**
+ * Shuts the logger down, waiting for the shutdown to complete if `blocking` is true.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+ */
+void capture_logger_shutdown(int64_t logger_id, bool blocking);
+
+/**
+ * Shuts the logger down and frees it.
+ *
+ * # Safety
+ * `logger_id` must have been returned by `capture_logger_create`, and must not be used again.
+ */
+void capture_logger_destroy(int64_t logger_id);
+
+/**
+ * Adds a string field, replacing any field with the same key. Returns false if either string is
+ * null or not valid UTF-8.
+ *
+ * # Safety
+ * `fields` must be the pointer handed to the metadata provider callback, and `key` and `value`
+ * must be NUL-terminated strings.
+ */
+bool capture_fields_insert(struct CaptureFields *fields, const char *key, const char *value);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -0,0 +1,305 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./bridge_test.rs"]
+mod tests;
+
+use crate::ffi::{copy_to_buffer, optional_string, required_str};
+use crate::fields::{CaptureField, annotated_fields};
+use crate::metadata::{CaptureMetadataProvider, MetadataProvider};
+use crate::session::{self, CaptureSessionStrategy};
+use crate::targets::{CaptureTargets, Target};
+use anyhow::anyhow;
+use bd_api::Platform;
+use bd_error_reporter::reporter::{with_handle_unexpected, with_handle_unexpected_or};
+use bd_hyper_network::HyperNetwork;
+use bd_logger::{Block, CaptureSession};
+use bd_proto::protos::logging::payload::LogType;
+use bd_shutdown::ComponentShutdownTrigger;
+use platform_shared::metadata::Mobile;
+use platform_shared::storage::FileStorage;
+use platform_shared::{LoggerHolder, LoggerId};
+use protobuf::Enum as _;
+use std::ffi::c_char;
+use std::path::Path;
+use std::sync::Arc;
+
+// The name of the key-value storage file within the SDK directory.
+const STORAGE_FILE_NAME: &str = "capture_storage.json";
+
+// The values of `bd_logger::log_level`, which are part of the C ABI.
+pub const CAPTURE_LOG_LEVEL_TRACE: u32 = 0;
+pub const CAPTURE_LOG_LEVEL_DEBUG: u32 = 1;
+pub const CAPTURE_LOG_LEVEL_INFO: u32 = 2;
+pub const CAPTURE_LOG_LEVEL_WARNING: u32 = 3;
+pub const CAPTURE_LOG_LEVEL_ERROR: u32 = 4;
+
+/// The platforms a host can identify as, which determine how its logs are processed. Part of the
+/// C ABI.
+pub const CAPTURE_PLATFORM_ANDROID: u32 = 0;
+pub const CAPTURE_PLATFORM_APPLE: u32 = 1;
+pub const CAPTURE_PLATFORM_ELECTRON: u32 = 2;
+
+fn platform(value: u32) -> anyhow::Result<Platform> {
+  match value {
+    CAPTURE_PLATFORM_ANDROID => Ok(Platform::Android),
+    CAPTURE_PLATFORM_APPLE => Ok(Platform::Apple),
+    CAPTURE_PLATFORM_ELECTRON => Ok(Platform::Electron),
+    _ => Err(anyhow!("unknown platform {value}")),
+  }
+}
+
+/// Creates a logger that stores its state under `sdk_directory` and uploads to `api_url`. The
+/// logger doesn't run until it's started with `capture_logger_start`. Returns the logger's ID, or
+/// -1 if the logger couldn't be created.
+///
+/// `platform` is one of the `CAPTURE_PLATFORM_*` values, the platform the host identifies as, and
+/// `os` names the host operating system, e.g. `linux`. `app_id`, `app_version` and `os_version`
+/// may be null.
+///
+/// # Safety
+/// Every string must be null or NUL-terminated, and the callbacks in `session_strategy`,
+/// `metadata_provider` and `targets` must stay callable, from any thread, until the logger is
+/// destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_create(
+  sdk_directory: *const c_char,
+  api_key: *const c_char,
+  api_url: *const c_char,
+  app_id: *const c_char,
+  app_version: *const c_char,
+  platform: u32,
+  os: *const c_char,
+  os_version: *const c_char,
+  model: *const c_char,
+  session_strategy: CaptureSessionStrategy,
+  metadata_provider: CaptureMetadataProvider,
+  targets: CaptureTargets,
+  start_in_sleep_mode: bool,
+) -> i64 {
+  with_handle_unexpected_or(
+    || {
+      let sdk_directory = unsafe { required_str(sdk_directory, "sdk_directory") }?;
+      std::fs::create_dir_all(sdk_directory)?;
+
+      let storage = Box::new(FileStorage::open(
+        Path::new(sdk_directory).join(STORAGE_FILE_NAME),
+      ));
+      let store = Arc::new(bd_key_value::Store::new(storage));
+      let device = Arc::new(bd_logger::Device::new(store.clone()));
+
+      let session = session::create(session_strategy, sdk_directory.as_ref())?;
+
+      let static_metadata = Arc::new(Mobile::generic(
+        self::platform(platform)?,
+        unsafe { optional_string(app_id) }?,
+        unsafe { optional_string(app_version) }?,
+        unsafe { required_str(os, "os") }?.to_string(),
+        unsafe { optional_string(os_version) }?,
+        device.clone(),
+        unsafe { required_str(model, "model") }?.to_string(),
+      ));
+      let initial_ootb_fields = static_metadata.static_log_fields();
+
+      let network_shutdown = ComponentShutdownTrigger::default();
+      let network = HyperNetwork::run_on_thread(
+        unsafe { required_str(api_url, "api_url") }?,
+        network_shutdown.make_shutdown(),
+      );
+
+      let logger = bd_logger::LoggerBuilder::new(bd_logger::InitParams {
+        sdk_directory: sdk_directory.into(),
+        api_key: unsafe { required_str(api_key, "api_key") }?.to_string(),
+        session,
+        metadata_provider: Arc::new(MetadataProvider(metadata_provider)),
+        initial_ootb_fields,
+        resource_utilization_target: Box::new(Target(targets)),
+        session_replay_target: Box::new(Target(targets)),
+        events_listener_target: Box::new(Target(targets)),
+        network: Box::new(network),
+        store,
+        device,
+        static_metadata,
+        start_in_sleep_mode,
+      })
+      .with_internal_logger(true)
+      .build()
+      .map(|(logger, _, future, _)| {
+        LoggerHolder::new(
+          logger,
+          Box::pin(async move {
+            // The network runs on its own thread for as long as the logger runtime does.
+            let _network_shutdown = network_shutdown;
+            future.await
+          }),
+        )
+      })?;
+
+      Ok(logger.into_raw().into())
+    },
+    -1,
+    "c create logger",
+  )
+}
+
+/// Starts the logger's runtime. Calls after the first have no effect.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_start(logger_id: i64) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  logger.start();
+}
+
+/// Writes a log with `count` string fields. `log_level` is one of the `CAPTURE_LOG_LEVEL_*`
+/// values, and `log_type` a `LogType` value from the logging protos, 0 being a normal log.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, `message`
+/// must be NUL-terminated, and `fields` must be null or point to `count` fields.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_log(
+  logger_id: i64,
+  log_level: u32,
+  log_type: u32,
+  message: *const c_char,
+  fields: *const CaptureField,
+  count: usize,
+) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  with_handle_unexpected(
+    move || -> anyhow::Result<()> {
+      let message = unsafe { required_str(message, "message") }?.to_string();
+      let fields = unsafe { annotated_fields(fields, count) }?;
+
+      logger.log(
+        log_level,
+        LogType::from_i32(log_type.try_into().unwrap_or_default()).unwrap_or(LogType::NORMAL),
+        message.into(),
+        fields,
+        [].into(),
+        None,
+        &CaptureSession::default(),
+      );
+
+      Ok(())
+    },
+    "c write log",
+  );
+}
+
+/// Adds a field to every subsequent log, replacing any field with the same key.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and `key`
+/// and `value` must be NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_add_field(
+  logger_id: i64,
+  key: *const c_char,
+  value: *const c_char,
+) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  with_handle_unexpected(
+    move || -> anyhow::Result<()> {
+      let key = unsafe { required_str(key, "key") }?.to_string();
+      let value = unsafe { required_str(value, "value") }?.to_string();
+
+      logger.add_log_field(key, value.into());
+
+      Ok(())
+    },
+    "c add field",
+  );
+}
+
+/// Removes a field added with `capture_logger_add_field`.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and `key`
+/// must be NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_remove_field(logger_id: i64, key: *const c_char) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  with_handle_unexpected(
+    move || -> anyhow::Result<()> {
+      let key = unsafe { required_str(key, "key") }?;
+      logger.remove_log_field(key);
+
+      Ok(())
+    },
+    "c remove field",
+  );
+}
+
+/// Ends the current session and starts a new one.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_start_new_session(logger_id: i64) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  with_handle_unexpected(|| logger.start_new_session(), "c start new session");
+}
+
+/// Copies the current session ID into `buffer` as a NUL-terminated string, truncated to fit its
+/// `capacity` bytes. Returns the session ID's length, so like `snprintf` a result of `capacity` or
+/// more means it was truncated.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed, and
+/// `buffer` must be null or valid for writes of `capacity` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_session_id(
+  logger_id: i64,
+  buffer: *mut c_char,
+  capacity: usize,
+) -> usize {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  let session_id = with_handle_unexpected_or(|| logger.session_id(), String::new(), "c session id");
+  unsafe { copy_to_buffer(&session_id, buffer, capacity) }
+}
+
+/// Flushes the logger's state to disk, waiting up to a second for it to complete if `blocking` is
+/// true.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_flush(logger_id: i64, blocking: bool) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  let block = if blocking {
+    Block::Yes {
+      timeout: std::time::Duration::from_secs(1),
+      poll_callback: None,
+    }
+  } else {
+    Block::No
+  };
+  logger.flush_state(block);
+}
+
+/// Shuts the logger down, waiting for the shutdown to complete if `blocking` is true.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create` and not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_shutdown(logger_id: i64, blocking: bool) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  logger.shutdown(blocking);
+}
+
+/// Shuts the logger down and frees it.
+///
+/// # Safety
+/// `logger_id` must have been returned by `capture_logger_create`, and must not be used again.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_logger_destroy(logger_id: i64) {
+  unsafe { LoggerHolder::destroy(logger_id) };
+}
@@ -0,0 +1,194 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use super::{
+  CAPTURE_LOG_LEVEL_DEBUG,
+  CAPTURE_LOG_LEVEL_ERROR,
+  CAPTURE_LOG_LEVEL_INFO,
+  CAPTURE_LOG_LEVEL_TRACE,
+  CAPTURE_LOG_LEVEL_WARNING,
+  CAPTURE_PLATFORM_ELECTRON,
+  capture_logger_add_field,
+  capture_logger_create,
+  capture_logger_destroy,
+  capture_logger_log,
+  capture_logger_shutdown,
+  capture_logger_start,
+  platform,
+};
+use crate::fields::CaptureField;
+use crate::metadata::CaptureMetadataProvider;
+use crate::session::{CAPTURE_SESSION_STRATEGY_FIXED, CaptureSessionStrategy};
+use crate::targets::CaptureTargets;
+use bd_api::Platform;
+use bd_logger::{DataValue, log_level};
+use bd_proto::protos::logging::payload::LogType;
+use platform_test_helpers::{
+  create_test_api_server_instance,
+  destroy_test_api_server_instance,
+  server_instance_await_next_stream,
+  server_instance_await_stream_closed,
+  server_instance_configure_aggressive_uploads_impl,
+};
+use protobuf::Enum as _;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ptr::null_mut;
+
+#[test]
+fn abi_values_match_the_logger() {
+  assert_eq!(CAPTURE_LOG_LEVEL_TRACE, log_level::TRACE);
+  assert_eq!(CAPTURE_LOG_LEVEL_DEBUG, log_level::DEBUG);
+  assert_eq!(CAPTURE_LOG_LEVEL_INFO, log_level::INFO);
+  assert_eq!(CAPTURE_LOG_LEVEL_WARNING, log_level::WARNING);
+  assert_eq!(CAPTURE_LOG_LEVEL_ERROR, log_level::ERROR);
+  assert_eq!(LogType::NORMAL.value(), 0);
+}
+
+#[test]
+fn platform_comes_from_the_caller() {
+  assert!(matches!(platform(0), Ok(Platform::Android)));
+  assert!(matches!(platform(1), Ok(Platform::Apple)));
+  assert!(matches!(
+    platform(CAPTURE_PLATFORM_ELECTRON),
+    Ok(Platform::Electron)
+  ));
+  assert!(platform(3).is_err());
+}
+
+#[ctor::ctor(unsafe)]
+fn test_global_init() {
+  bd_test_helpers_core::test_global_init();
+}
+
+fn string_value(value: DataValue) -> Option<String> {
+  match value {
+    DataValue::String(value) => Some(value.to_string()),
+    DataValue::SharedString(value) => Some(value.to_string()),
+    DataValue::StaticString(value) => Some(value.to_string()),
+    _ => None,
+  }
+}
+
+#[test]
+fn logger_uploads_logs_to_the_api_server() {
+  let server = create_test_api_server_instance(false, -1);
+  let handle = unsafe { &mut *server };
+  let sdk_directory = tempfile::tempdir().unwrap();
+
+  let string = |value: &str| CString::new(value).unwrap();
+  let (directory, api_key, api_url) = (
+    string(sdk_directory.path().to_str().unwrap()),
+    string("test!"),
+    string(&format!("http://localhost:{}", handle.port)),
+  );
+  let (app_id, app_version, os, os_version, model) = (
+    string("io.bitdrift.daemon"),
+    string("1.0.0"),
+    string("linux"),
+    string("6.8"),
+    string("x86_64"),
+  );
+  let logger_id = unsafe {
+    capture_logger_create(
+      directory.as_ptr(),
+      api_key.as_ptr(),
+      api_url.as_ptr(),
+      app_id.as_ptr(),
+      app_version.as_ptr(),
+      CAPTURE_PLATFORM_ELECTRON,
+      os.as_ptr(),
+      os_version.as_ptr(),
+      model.as_ptr(),
+      CaptureSessionStrategy {
+        context: null_mut(),
+        strategy_type: CAPTURE_SESSION_STRATEGY_FIXED,
+        inactivity_threshold_mins: 0,
+        generate_session_id: None,
+        session_id_changed: None,
+      },
+      CaptureMetadataProvider {
+        context: null_mut(),
+        timestamp_ms: None,
+        fields: None,
+      },
+      CaptureTargets {
+        context: null_mut(),
+        resource_utilization_tick: None,
+        session_replay_capture_screen: None,
+        session_replay_capture_screenshot: None,
+        events_listener_start: None,
+        events_listener_stop: None,
+      },
+      false,
+    )
+  };
+  assert_ne!(logger_id, -1);
+  unsafe { capture_logger_start(logger_id) };
+
+  let stream_id = unsafe { server_instance_await_next_stream(server) };
+  assert_ne!(stream_id, -1, "the logger never connected");
+  server_instance_configure_aggressive_uploads_impl(handle, stream_id).unwrap();
+
+  let (global_key, global_value) = (string("global"), string("set for every log"));
+  let (key, value, message) = (string("key"), string("value"), string("hello"));
+  let field = CaptureField {
+    key: key.as_ptr(),
+    value: value.as_ptr(),
+  };
+  unsafe {
+    capture_logger_add_field(logger_id, global_key.as_ptr(), global_value.as_ptr());
+    capture_logger_log(
+      logger_id,
+      CAPTURE_LOG_LEVEL_INFO,
+      LogType::NORMAL.value().try_into().unwrap(),
+      message.as_ptr(),
+      &raw const field,
+      1,
+    );
+  }
+
+  // Uploads may start with logs the SDK wrote before the one under test.
+  let mut uploaded = None;
+  for _ in 0 .. 5 {
+    let Some(upload) = handle.blocking_next_log_upload() else {
+      break;
+    };
+    uploaded = upload
+      .logs()
+      .iter()
+      .find(|log| log.message() == "hello")
+      .map(|log| {
+        let fields: HashMap<String, Option<String>> = log
+          .typed_fields()
+          .into_iter()
+          .map(|(key, value)| (key.to_string(), string_value(value)))
+          .collect();
+        (log.log_level(), fields)
+      });
+    if uploaded.is_some() {
+      break;
+    }
+  }
+
+  let (level, fields) = uploaded.expect("the log was never uploaded");
+  let field = |key: &str| fields.get(key).cloned().flatten();
+  assert_eq!(level, log_level::INFO);
+  assert_eq!(field("key").as_deref(), Some("value"));
+  assert_eq!(field("global").as_deref(), Some("set for every log"));
+  assert_eq!(field("app_id").as_deref(), Some("io.bitdrift.daemon"));
+  assert_eq!(field("os").as_deref(), Some("linux"));
+
+  unsafe {
+    capture_logger_shutdown(logger_id, true);
+    capture_logger_destroy(logger_id);
+  }
+  assert!(unsafe { server_instance_await_stream_closed(server, stream_id, 5_000) });
+  unsafe { destroy_test_api_server_instance(server) };
+}
@@ -0,0 +1,56 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./ffi_test.rs"]
+mod tests;
+
+use anyhow::bail;
+use std::ffi::{CStr, c_char};
+
+// Borrows a string argument the host must provide.
+//
+// # Safety
+// `ptr` must be null or point to a NUL-terminated string that outlives the returned reference.
+pub(crate) unsafe fn required_str<'a>(ptr: *const c_char, name: &str) -> anyhow::Result<&'a str> {
+  if ptr.is_null() {
+    bail!("{name} must not be null");
+  }
+
+  Ok(unsafe { CStr::from_ptr(ptr) }.to_str()?)
+}
+
+// Copies a string argument the host may leave null.
+//
+// # Safety
+// `ptr` must be null or point to a NUL-terminated string.
+pub(crate) unsafe fn optional_string(ptr: *const c_char) -> anyhow::Result<Option<String>> {
+  if ptr.is_null() {
+    return Ok(None);
+  }
+
+  Ok(Some(unsafe { CStr::from_ptr(ptr) }.to_str()?.to_string()))
+}
+
+// Copies `value` into the host's `buffer` of `capacity` bytes as a NUL-terminated string,
+// truncating it to fit. Returns the length of `value`, so that like `snprintf` a result of
+// `capacity` or more tells the host that the value was truncated.
+//
+// # Safety
+// `buffer` must be null or valid for writes of `capacity` bytes.
+pub(crate) unsafe fn copy_to_buffer(value: &str, buffer: *mut c_char, capacity: usize) -> usize {
+  if buffer.is_null() || capacity == 0 {
+    return value.len();
+  }
+
+  let copied = value.len().min(capacity - 1);
+  unsafe {
+    std::ptr::copy_nonoverlapping(value.as_ptr().cast::<c_char>(), buffer, copied);
+    buffer.add(copied).write(0);
+  }
+  value.len()
+}
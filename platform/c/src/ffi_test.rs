@@ -0,0 +1,41 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{copy_to_buffer, optional_string, required_str};
+use std::ffi::{CStr, c_char};
+use std::ptr::{null, null_mut};
+
+#[test]
+fn null_strings_are_rejected_or_absent() {
+  assert!(unsafe { required_str(null(), "api_key") }.is_err());
+  assert_eq!(unsafe { optional_string(null()) }.ok(), Some(None));
+  assert_eq!(
+    unsafe { required_str(c"key".as_ptr(), "api_key") }.ok(),
+    Some("key")
+  );
+  assert_eq!(
+    unsafe { optional_string(c"1.0".as_ptr()) }.ok(),
+    Some(Some("1.0".to_string()))
+  );
+}
+
+#[test]
+fn values_are_copied_like_snprintf() {
+  let mut buffer = [1 as c_char; 8];
+
+  assert_eq!(unsafe { copy_to_buffer("abc", buffer.as_mut_ptr(), 8) }, 3);
+  assert_eq!(unsafe { CStr::from_ptr(buffer.as_ptr()) }, c"abc");
+
+  // A value that doesn't fit is truncated, and its full length tells the host to retry.
+  assert_eq!(
+    unsafe { copy_to_buffer("abcdefghij", buffer.as_mut_ptr(), 8) },
+    10
+  );
+  assert_eq!(unsafe { CStr::from_ptr(buffer.as_ptr()) }, c"abcdefg");
+
+  assert_eq!(unsafe { copy_to_buffer("abc", null_mut(), 0) }, 3);
+}
@@ -0,0 +1,82 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use crate::ffi::required_str;
+use bd_error_reporter::reporter::with_handle_unexpected_or;
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields, LogFields};
+use std::ffi::c_char;
+
+/// A string field attached to a log.
+#[repr(C)]
+pub struct CaptureField {
+  pub key: *const c_char,
+  pub value: *const c_char,
+}
+
+/// Fields handed to a metadata provider callback, which adds to them with `capture_fields_insert`.
+#[derive(Default)]
+pub struct CaptureFields {
+  fields: LogFields,
+}
+
+impl CaptureFields {
+  pub(crate) fn into_inner(self) -> LogFields {
+    self.fields
+  }
+}
+
+/// Adds a string field, replacing any field with the same key. Returns false if either string is
+/// null or not valid UTF-8.
+///
+/// # Safety
+/// `fields` must be the pointer handed to the metadata provider callback, and `key` and `value`
+/// must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_fields_insert(
+  fields: *mut CaptureFields,
+  key: *const c_char,
+  value: *const c_char,
+) -> bool {
+  with_handle_unexpected_or(
+    || {
+      let Some(fields) = (unsafe { fields.as_mut() }) else {
+        return Ok(false);
+      };
+      let key = unsafe { required_str(key, "key") }?.to_string();
+      let value = unsafe { required_str(value, "value") }?.to_string();
+
+      fields.fields.insert(key.into(), value.into());
+      Ok(true)
+    },
+    false,
+    "c insert field",
+  )
+}
+
+// Converts the `count` fields the host passed to a log call.
+//
+// # Safety
+// `fields` must be null or point to `count` fields whose strings are NUL-terminated.
+pub(crate) unsafe fn annotated_fields(
+  fields: *const CaptureField,
+  count: usize,
+) -> anyhow::Result<AnnotatedLogFields> {
+  if fields.is_null() {
+    return Ok(AnnotatedLogFields::default());
+  }
+
+  let mut annotated = AnnotatedLogFields::with_capacity(count);
+  for field in unsafe { std::slice::from_raw_parts(fields, count) } {
+    let key = unsafe { required_str(field.key, "field key") }?.to_string();
+    let value = unsafe { required_str(field.value, "field value") }?.to_string();
+
+    // Like the other platforms, host fields aren't told apart from out-of-the-box ones yet.
+    annotated.insert(key.into(), AnnotatedLogField::new_ootb(value));
+  }
+
+  Ok(annotated)
+}
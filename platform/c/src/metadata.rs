@@ -0,0 +1,63 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use crate::fields::CaptureFields;
+use bd_logger::LogFields;
+use std::ffi::c_void;
+use time::OffsetDateTime;
+
+/// Provides the time and fields attached to every log. Callbacks may be called from any thread, and
+/// are passed `context` back.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CaptureMetadataProvider {
+  pub context: *mut c_void,
+  /// Returns the current time in milliseconds since the Unix epoch. The system clock is used when
+  /// this is null.
+  pub timestamp_ms: Option<unsafe extern "C" fn(context: *mut c_void) -> i64>,
+  /// Adds out-of-the-box fields to `ootb` and custom fields to `custom` with
+  /// `capture_fields_insert`. May be null.
+  pub fields: Option<
+    unsafe extern "C" fn(
+      context: *mut c_void,
+      ootb: *mut CaptureFields,
+      custom: *mut CaptureFields,
+    ),
+  >,
+}
+
+#[allow(clippy::non_send_fields_in_send_ty)]
+pub(crate) struct MetadataProvider(pub(crate) CaptureMetadataProvider);
+
+// The host guarantees that its callbacks can be called from any thread.
+unsafe impl Sync for MetadataProvider {}
+unsafe impl Send for MetadataProvider {}
+
+impl bd_logger::MetadataProvider for MetadataProvider {
+  fn timestamp(&self) -> anyhow::Result<OffsetDateTime> {
+    let Some(timestamp_ms) = self.0.timestamp_ms else {
+      return Ok(OffsetDateTime::now_utc());
+    };
+
+    let timestamp_ms = unsafe { timestamp_ms(self.0.context) };
+    Ok(OffsetDateTime::from_unix_timestamp_nanos(
+      i128::from(timestamp_ms) * 1_000_000,
+    )?)
+  }
+
+  fn fields(&self) -> anyhow::Result<(LogFields, LogFields)> {
+    let mut ootb_fields = CaptureFields::default();
+    let mut custom_fields = CaptureFields::default();
+    if let Some(fields) = self.0.fields {
+      unsafe {
+        fields(self.0.context, &raw mut ootb_fields, &raw mut custom_fields);
+      }
+    }
+
+    Ok((custom_fields.into_inner(), ootb_fields.into_inner()))
+  }
+}
@@ -0,0 +1,108 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use anyhow::{anyhow, bail};
+use bd_session::activity_based::Callbacks as ActivityBasedStrategyCallbacks;
+use bd_session::fixed::{Callbacks as FixedStrategyCallbacks, UUIDCallbacks};
+use bd_session::{Strategy, StrategyWithWorker};
+use std::ffi::{CStr, c_char, c_void};
+use std::path::Path;
+use std::sync::Arc;
+use time::Duration;
+
+/// A session that lasts until a new one is started explicitly.
+pub const CAPTURE_SESSION_STRATEGY_FIXED: u32 = 0;
+
+/// A session that ends after a period without logs.
+pub const CAPTURE_SESSION_STRATEGY_ACTIVITY_BASED: u32 = 1;
+
+// The size of the buffer a host-generated session ID is written into, including its NUL.
+const SESSION_ID_CAPACITY: usize = 128;
+
+/// Describes how sessions are started. Callbacks may be called from any thread, and are passed
+/// `context` back.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CaptureSessionStrategy {
+  pub context: *mut c_void,
+  /// `CAPTURE_SESSION_STRATEGY_FIXED` or `CAPTURE_SESSION_STRATEGY_ACTIVITY_BASED`.
+  pub strategy_type: u32,
+  /// How long an activity-based session lasts without logs.
+  pub inactivity_threshold_mins: i64,
+  /// Writes a new NUL-terminated session ID of fewer than `capacity` bytes into `buffer`,
+  /// returning false on failure. Only used by fixed sessions, which generate a random UUID when
+  /// this is null.
+  pub generate_session_id: Option<
+    unsafe extern "C" fn(context: *mut c_void, buffer: *mut c_char, capacity: usize) -> bool,
+  >,
+  /// Called with the new session ID whenever an activity-based session changes. May be null.
+  pub session_id_changed:
+    Option<unsafe extern "C" fn(context: *mut c_void, session_id: *const c_char)>,
+}
+
+#[allow(clippy::non_send_fields_in_send_ty)]
+struct SessionCallbacks(CaptureSessionStrategy);
+
+// The host guarantees that its callbacks can be called from any thread.
+unsafe impl Sync for SessionCallbacks {}
+unsafe impl Send for SessionCallbacks {}
+
+pub(crate) fn create(
+  strategy: CaptureSessionStrategy,
+  sdk_directory: &Path,
+) -> anyhow::Result<StrategyWithWorker> {
+  Ok(match strategy.strategy_type {
+    CAPTURE_SESSION_STRATEGY_FIXED if strategy.generate_session_id.is_none() => {
+      Strategy::fixed(sdk_directory, Arc::new(UUIDCallbacks))
+    },
+    CAPTURE_SESSION_STRATEGY_FIXED => {
+      Strategy::fixed(sdk_directory, Arc::new(SessionCallbacks(strategy)))
+    },
+    CAPTURE_SESSION_STRATEGY_ACTIVITY_BASED => Strategy::activity_based(
+      sdk_directory,
+      Duration::minutes(strategy.inactivity_threshold_mins),
+      Arc::new(SessionCallbacks(strategy)),
+      Arc::new(bd_time::SystemTimeProvider {}),
+    ),
+    strategy_type => bail!("invalid session strategy type: {strategy_type}"),
+  })
+}
+
+impl FixedStrategyCallbacks for SessionCallbacks {
+  fn generate_session_id(&self) -> anyhow::Result<String> {
+    let generate = self
+      .0
+      .generate_session_id
+      .ok_or_else(|| anyhow!("no session ID generator"))?;
+
+    let mut buffer = [0 as c_char; SESSION_ID_CAPACITY];
+    if !unsafe { generate(self.0.context, buffer.as_mut_ptr(), SESSION_ID_CAPACITY) } {
+      bail!("host failed to generate a session ID");
+    }
+    // Guards against a host that filled the buffer without terminating it.
+    buffer[SESSION_ID_CAPACITY - 1] = 0;
+
+    Ok(
+      unsafe { CStr::from_ptr(buffer.as_ptr()) }
+        .to_str()?
+        .to_string(),
+    )
+  }
+}
+
+impl ActivityBasedStrategyCallbacks for SessionCallbacks {
+  fn session_id_changed(&self, session_id: &str) {
+    let Some(session_id_changed) = self.0.session_id_changed else {
+      return;
+    };
+    let Ok(session_id) = std::ffi::CString::new(session_id) else {
+      return;
+    };
+
+    unsafe { session_id_changed(self.0.context, session_id.as_ptr()) };
+  }
+}
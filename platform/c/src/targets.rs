@@ -0,0 +1,66 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use std::ffi::c_void;
+
+/// A host callback that takes nothing but its context.
+pub type CaptureCallback = Option<unsafe extern "C" fn(context: *mut c_void)>;
+
+/// Callbacks through which the logger asks the host for resource utilization and session replay
+/// data, and to start or stop listening for events. Any of them may be null. Callbacks may be
+/// called from any thread, and are passed `context` back.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CaptureTargets {
+  pub context: *mut c_void,
+  pub resource_utilization_tick: CaptureCallback,
+  pub session_replay_capture_screen: CaptureCallback,
+  pub session_replay_capture_screenshot: CaptureCallback,
+  pub events_listener_start: CaptureCallback,
+  pub events_listener_stop: CaptureCallback,
+}
+
+#[allow(clippy::non_send_fields_in_send_ty)]
+pub(crate) struct Target(pub(crate) CaptureTargets);
+
+// The host guarantees that its callbacks can be called from any thread.
+unsafe impl Send for Target {}
+unsafe impl Sync for Target {}
+
+impl Target {
+  fn call(&self, callback: CaptureCallback) {
+    if let Some(callback) = callback {
+      unsafe { callback(self.0.context) };
+    }
+  }
+}
+
+impl bd_logger::ResourceUtilizationTarget for Target {
+  fn tick(&self) {
+    self.call(self.0.resource_utilization_tick);
+  }
+}
+
+impl bd_logger::SessionReplayTarget for Target {
+  fn capture_screen(&self) {
+    self.call(self.0.session_replay_capture_screen);
+  }
+
+  fn capture_screenshot(&self) {
+    self.call(self.0.session_replay_capture_screenshot);
+  }
+}
+
+impl bd_logger::EventsListenerTarget for Target {
+  fn start(&self) {
+    self.call(self.0.events_listener_start);
+  }
+
+  fn stop(&self) {
+    self.call(self.0.events_listener_stop);
+  }
+}
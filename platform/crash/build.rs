@@ -0,0 +1,46 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use std::env;
+
+fn main() {
+  println!("cargo::rerun-if-changed=build.rs");
+  println!("cargo::rerun-if-changed=src/monitors/terminate.cc");
+  println!("cargo::rerun-if-changed=src/monitors/terminate_test.cc");
+
+  // The terminate monitor is only installed where the signal monitor is, since it shares its
+  // unwinder and module map.
+  if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() == Ok("apple") {
+    return;
+  }
+
+  // On Android the shim links the NDK's static libc++ rather than cc's default of the shared one,
+  // so libcapture doesn't need `libc++_shared.so` packaged next to it. The catch is that the
+  // terminate handler is process-wide only within a single copy of libc++: it sees exceptions
+  // thrown by code linked against libcapture's copy, not those raised in other native libraries
+  // that bring their own `libc++_shared.so` or static copy.
+  let is_android = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("android");
+  let mut shim = cc::Build::new();
+  shim
+    .cpp(true)
+    .std("c++17")
+    .file("src/monitors/terminate.cc");
+  if is_android {
+    shim.cpp_link_stdlib("c++_static");
+    // The static libc++ leaves the C++ ABI runtime to a separate archive.
+    println!("cargo::rustc-link-lib=static=c++abi");
+  }
+  shim.compile("bd_crash_terminate");
+
+  // Only linked by the unit tests, which name it in a `#[link]` attribute.
+  cc::Build::new()
+    .cpp(true)
+    .std("c++17")
+    .cargo_metadata(false)
+    .file("src/monitors/terminate_test.cc")
+    .compile("bd_crash_terminate_test");
+}
@@ -0,0 +1,201 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./annotations_test.rs"]
+mod tests;
+
+use crate::schema::{self, AnnotationTable, RawAnnotation};
+use std::ptr::{addr_of, addr_of_mut, null_mut};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering, fence};
+
+pub(crate) static ANNOTATION_TABLE: AtomicPtr<AnnotationTable> = AtomicPtr::new(null_mut());
+
+// Serializes writers. Readers never take it since the crash-time snapshot runs in signal handlers.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sets the annotation `key` to `value`, replacing any value previously set for the same key.
+/// Annotations are written straight into the crash state file so they are included in a crash
+/// record even if the process is killed by a signal.
+///
+/// Keys must be between 1 and 32 bytes long. Values longer than 128 bytes are truncated. Returns
+/// false if the key is invalid, if all annotation slots are in use, or if the crash reporter has
+/// not been configured yet.
+pub fn set_annotation(key: &str, value: &str) -> bool {
+  if !is_valid_key(key) {
+    return false;
+  }
+  let table = ANNOTATION_TABLE.load(Ordering::Acquire);
+  if table.is_null() {
+    return false;
+  }
+
+  let _guard = WRITE_LOCK
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner);
+  let Some(entry) = find_entry(table, key.as_bytes()).or_else(|| find_free_entry(table)) else {
+    return false;
+  };
+
+  let value = truncate_value(value);
+  write_entry(entry, |entry| unsafe {
+    addr_of_mut!((*entry).key_len).write_volatile(u8::try_from(key.len()).unwrap_or(0));
+    addr_of_mut!((*entry).value_len).write_volatile(u8::try_from(value.len()).unwrap_or(0));
+    write_bytes(addr_of_mut!((*entry).key).cast::<u8>(), key.as_bytes());
+    write_bytes(addr_of_mut!((*entry).value).cast::<u8>(), value);
+  });
+  true
+}
+
+/// Removes the annotation `key`. Returns false if no annotation is set for the key.
+pub fn remove_annotation(key: &str) -> bool {
+  let table = ANNOTATION_TABLE.load(Ordering::Acquire);
+  if table.is_null() {
+    return false;
+  }
+
+  let _guard = WRITE_LOCK
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner);
+  let Some(entry) = find_entry(table, key.as_bytes()) else {
+    return false;
+  };
+
+  write_entry(entry, |entry| unsafe {
+    addr_of_mut!((*entry).key_len).write_volatile(0);
+    addr_of_mut!((*entry).value_len).write_volatile(0);
+  });
+  true
+}
+
+pub(crate) unsafe fn prime_annotation_table(table_ptr: *mut AnnotationTable) {
+  // Annotations only describe the run that set them, so every launch starts from an empty table.
+  let table = unsafe { &mut *table_ptr };
+  table.magic = schema::ANNOTATION_MAGIC;
+  table.capacity = schema::ANNOTATION_CAPACITY;
+  table.reserved = 0;
+  table.entries.fill(RawAnnotation::default());
+  ANNOTATION_TABLE.store(table_ptr, Ordering::Release);
+}
+
+// Calls `f` with a consistent copy of every annotation that is set, in slot order. Entries that are
+// being changed while they are copied are skipped.
+//
+// This is called from signal handlers, so it must stay async-signal-safe: it doesn't take the
+// write lock, and entries are copied onto the stack one at a time.
+pub(crate) fn for_each_annotation(mut f: impl FnMut(&RawAnnotation) -> bool) {
+  let table = ANNOTATION_TABLE.load(Ordering::Acquire);
+  if table.is_null() {
+    return;
+  }
+
+  for index in 0 .. schema::ANNOTATION_CAPACITY as usize {
+    let Some(copy) = read_entry(entry_ptr(table, index)) else {
+      continue;
+    };
+    if copy.key_len == 0 {
+      continue;
+    }
+
+    if !f(&copy) {
+      return;
+    }
+  }
+}
+
+fn find_entry(table: *mut AnnotationTable, key: &[u8]) -> Option<*mut RawAnnotation> {
+  // Only called with the write lock held, so entries can't change underneath the lookup.
+  (0 .. schema::ANNOTATION_CAPACITY as usize)
+    .map(|index| entry_ptr(table, index))
+    .find(|entry| {
+      read_entry(*entry)
+        .is_some_and(|copy| copy.key_len != 0 && &copy.key[.. usize::from(copy.key_len)] == key)
+    })
+}
+
+fn find_free_entry(table: *mut AnnotationTable) -> Option<*mut RawAnnotation> {
+  (0 .. schema::ANNOTATION_CAPACITY as usize)
+    .map(|index| entry_ptr(table, index))
+    .find(|entry| read_entry(*entry).is_some_and(|copy| copy.key_len == 0))
+}
+
+fn write_entry(entry: *mut RawAnnotation, write: impl FnOnce(*mut RawAnnotation)) {
+  // The sequence number is odd while the entry is being changed, so a crash snapshot taken halfway
+  // through a write skips the entry instead of reading a mix of the old and new value.
+  let sequence = sequence(entry);
+  let start = sequence.load(Ordering::Relaxed) | 1;
+  sequence.store(start, Ordering::Relaxed);
+  fence(Ordering::Release);
+  write(entry);
+  sequence.store(start + 1, Ordering::Release);
+}
+
+fn read_entry(entry: *mut RawAnnotation) -> Option<RawAnnotation> {
+  let before = sequence(entry).load(Ordering::Acquire);
+  if before % 2 != 0 {
+    return None;
+  }
+
+  let mut copy = RawAnnotation::default();
+  unsafe {
+    copy.key_len = addr_of!((*entry).key_len).read_volatile();
+    copy.value_len = addr_of!((*entry).value_len).read_volatile();
+    read_bytes(addr_of!((*entry).key).cast::<u8>(), &mut copy.key);
+    read_bytes(addr_of!((*entry).value).cast::<u8>(), &mut copy.value);
+  }
+  fence(Ordering::Acquire);
+  if sequence(entry).load(Ordering::Relaxed) != before {
+    return None;
+  }
+
+  copy.key_len = copy
+    .key_len
+    .min(u8::try_from(schema::ANNOTATION_KEY_CAPACITY).unwrap_or(0));
+  copy.value_len = copy
+    .value_len
+    .min(u8::try_from(schema::ANNOTATION_VALUE_CAPACITY).unwrap_or(0));
+  Some(copy)
+}
+
+unsafe fn write_bytes(target: *mut u8, bytes: &[u8]) {
+  for (offset, byte) in bytes.iter().enumerate() {
+    unsafe {
+      target.add(offset).write_volatile(*byte);
+    }
+  }
+}
+
+unsafe fn read_bytes(source: *const u8, bytes: &mut [u8]) {
+  for (offset, byte) in bytes.iter_mut().enumerate() {
+    *byte = unsafe { source.add(offset).read_volatile() };
+  }
+}
+
+fn entry_ptr(table: *mut AnnotationTable, index: usize) -> *mut RawAnnotation {
+  unsafe {
+    addr_of_mut!((*table).entries)
+      .cast::<RawAnnotation>()
+      .add(index)
+  }
+}
+
+fn sequence<'a>(entry: *mut RawAnnotation) -> &'a AtomicU64 {
+  unsafe { AtomicU64::from_ptr(addr_of_mut!((*entry).sequence)) }
+}
+
+const fn is_valid_key(key: &str) -> bool {
+  !key.is_empty() && key.len() <= schema::ANNOTATION_KEY_CAPACITY
+}
+
+fn truncate_value(value: &str) -> &[u8] {
+  let mut len = value.len().min(schema::ANNOTATION_VALUE_CAPACITY);
+  while !value.is_char_boundary(len) {
+    len -= 1;
+  }
+  &value.as_bytes()[.. len]
+}
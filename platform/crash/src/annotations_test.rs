@@ -0,0 +1,130 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{for_each_annotation, prime_annotation_table, remove_annotation, set_annotation};
+use crate::schema::{self, AnnotationTable};
+use crate::test_support::test_crash_record_guard;
+
+fn snapshot() -> Vec<(String, String)> {
+  let mut annotations = Vec::new();
+  for_each_annotation(|annotation| {
+    let key = &annotation.key[.. usize::from(annotation.key_len)];
+    let value = &annotation.value[.. usize::from(annotation.value_len)];
+    annotations.push((
+      String::from_utf8(key.to_vec()).unwrap(),
+      String::from_utf8(value.to_vec()).unwrap(),
+    ));
+    true
+  });
+  annotations
+}
+
+fn primed_table() -> Box<AnnotationTable> {
+  let mut table = Box::<AnnotationTable>::default();
+  unsafe {
+    prime_annotation_table(&raw mut *table);
+  }
+  table
+}
+
+#[test]
+fn set_annotation_fails_before_table_is_primed() {
+  let _guard = test_crash_record_guard();
+
+  assert!(!set_annotation("screen", "home"));
+  assert!(snapshot().is_empty());
+}
+
+#[test]
+fn set_annotation_replaces_existing_value() {
+  let _guard = test_crash_record_guard();
+  let table = primed_table();
+
+  assert!(set_annotation("screen", "home"));
+  assert!(set_annotation("user_tier", "free"));
+  assert!(set_annotation("screen", "settings"));
+
+  assert_eq!(
+    snapshot(),
+    vec![
+      ("screen".to_string(), "settings".to_string()),
+      ("user_tier".to_string(), "free".to_string()),
+    ]
+  );
+  assert_eq!(table.magic, schema::ANNOTATION_MAGIC);
+}
+
+#[test]
+fn remove_annotation_frees_its_slot() {
+  let _guard = test_crash_record_guard();
+  let _table = primed_table();
+
+  assert!(set_annotation("screen", "home"));
+  assert!(remove_annotation("screen"));
+  assert!(!remove_annotation("screen"));
+  assert!(snapshot().is_empty());
+
+  for index in 0 .. schema::ANNOTATION_CAPACITY {
+    assert!(set_annotation(&format!("key {index}"), "value"));
+  }
+  assert!(!set_annotation("one too many", "value"));
+  assert!(remove_annotation("key 3"));
+  assert!(set_annotation("one too many", "value"));
+  assert_eq!(snapshot().len(), schema::ANNOTATION_CAPACITY as usize);
+}
+
+#[test]
+fn set_annotation_validates_key_and_truncates_value() {
+  let _guard = test_crash_record_guard();
+  let _table = primed_table();
+
+  assert!(!set_annotation("", "value"));
+  assert!(!set_annotation(
+    &"k".repeat(schema::ANNOTATION_KEY_CAPACITY + 1),
+    "value"
+  ));
+
+  let key = "k".repeat(schema::ANNOTATION_KEY_CAPACITY);
+  let value = "v".repeat(schema::ANNOTATION_VALUE_CAPACITY - 1) + "é";
+  assert!(set_annotation(&key, &value));
+
+  assert_eq!(
+    snapshot(),
+    vec![(key, "v".repeat(schema::ANNOTATION_VALUE_CAPACITY - 1))]
+  );
+}
+
+#[test]
+fn snapshot_skips_entries_that_are_being_changed() {
+  let _guard = test_crash_record_guard();
+  let mut table = primed_table();
+
+  assert!(set_annotation("complete", "value"));
+  assert!(set_annotation("torn", "value"));
+  // Simulate a writer that was interrupted halfway through changing the second entry.
+  table.entries[1].sequence += 1;
+
+  assert_eq!(
+    snapshot(),
+    vec![("complete".to_string(), "value".to_string())]
+  );
+}
+
+#[test]
+fn prime_annotation_table_clears_previous_run() {
+  let _guard = test_crash_record_guard();
+  let mut table = primed_table();
+  assert!(set_annotation("screen", "home"));
+
+  unsafe {
+    prime_annotation_table(&raw mut *table);
+  }
+
+  assert!(snapshot().is_empty());
+}
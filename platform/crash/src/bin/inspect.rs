@@ -0,0 +1,66 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+//! Dumps a crash state file as JSON.
+//!
+//! Usage: `bd-crash-inspect [--raw] <state-file>`
+//!
+//! With `--raw`, records the crash reporter would reject (uncommitted, failing their checksum or
+//! holding an unknown crash kind) are decoded as well. Rejections are always listed per record.
+
+use bd_crash_reporter::inspect::inspect_state_file;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: bd-crash-inspect [--raw] <state-file>";
+
+fn main() -> ExitCode {
+  let mut raw = false;
+  let mut path = None;
+  for arg in std::env::args_os().skip(1) {
+    if arg == "--raw" {
+      raw = true;
+    } else if arg == "-h" || arg == "--help" {
+      println!("{USAGE}");
+      return ExitCode::SUCCESS;
+    } else if path.is_none() {
+      path = Some(PathBuf::from(arg));
+    } else {
+      return usage_error(&arg);
+    }
+  }
+
+  let Some(path) = path else {
+    eprintln!("{USAGE}");
+    return ExitCode::from(2);
+  };
+
+  let bytes = match std::fs::read(&path) {
+    Ok(bytes) => bytes,
+    Err(error) => {
+      eprintln!("failed to read {}: {error}", path.display());
+      return ExitCode::FAILURE;
+    },
+  };
+
+  match serde_json::to_string_pretty(&inspect_state_file(&bytes, raw)) {
+    Ok(json) => {
+      println!("{json}");
+      ExitCode::SUCCESS
+    },
+    Err(error) => {
+      eprintln!("failed to serialize {}: {error}", path.display());
+      ExitCode::FAILURE
+    },
+  }
+}
+
+fn usage_error(arg: &OsString) -> ExitCode {
+  eprintln!("unexpected argument {}\n{USAGE}", arg.to_string_lossy());
+  ExitCode::from(2)
+}
@@ -0,0 +1,163 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./breadcrumbs_test.rs"]
+mod tests;
+
+use crate::schema::{self, BreadcrumbRing, RawBreadcrumb};
+use std::ptr::{addr_of, addr_of_mut, null_mut};
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering, fence};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) static BREADCRUMB_RING: AtomicPtr<BreadcrumbRing> = AtomicPtr::new(null_mut());
+
+/// Severity of a breadcrumb. Values are persisted and exposed over the C ABI, so they must not be
+/// renumbered.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreadcrumbLevel {
+  Trace   = 0,
+  Debug   = 1,
+  Info    = 2,
+  Warning = 3,
+  Error   = 4,
+}
+
+impl BreadcrumbLevel {
+  pub(crate) const fn from_u8(value: u8) -> Option<Self> {
+    match value {
+      0 => Some(Self::Trace),
+      1 => Some(Self::Debug),
+      2 => Some(Self::Info),
+      3 => Some(Self::Warning),
+      4 => Some(Self::Error),
+      _ => None,
+    }
+  }
+}
+
+/// Appends a breadcrumb to the current run's ring, overwriting the oldest one once the ring is
+/// full. Messages longer than the ring's message capacity are truncated. This is lock-free and
+/// cheap enough to call from any thread at any time; it is a no-op until the crash reporter has
+/// been configured.
+pub fn record_breadcrumb(level: BreadcrumbLevel, message: &str) {
+  let ring = BREADCRUMB_RING.load(Ordering::Acquire);
+  if ring.is_null() {
+    return;
+  }
+
+  let message = truncate_message(message);
+  let index = next_index(ring).fetch_add(1, Ordering::Relaxed);
+  let entry = entry_ptr(ring, index);
+
+  // Mark the entry as being written before touching its payload, and publish the final sequence
+  // number only once the payload is complete.
+  sequence(entry).store(2 * index + 1, Ordering::Relaxed);
+  fence(Ordering::Release);
+  unsafe {
+    addr_of_mut!((*entry).timestamp_ms).write_volatile(current_timestamp_ms());
+    addr_of_mut!((*entry).level).write_volatile(level as u8);
+    addr_of_mut!((*entry).message_len).write_volatile(u8::try_from(message.len()).unwrap_or(0));
+    let target = addr_of_mut!((*entry).message).cast::<u8>();
+    for (offset, byte) in message.iter().enumerate() {
+      target.add(offset).write_volatile(*byte);
+    }
+  }
+  sequence(entry).store(2 * (index + 1), Ordering::Release);
+}
+
+pub(crate) unsafe fn prime_breadcrumb_ring(ring_ptr: *mut BreadcrumbRing) {
+  // Breadcrumbs only describe the run that wrote them, so every launch starts from an empty ring.
+  // Fields are assigned in place since the ring lives in the mapping.
+  let ring = unsafe { &mut *ring_ptr };
+  ring.magic = schema::BREADCRUMB_MAGIC;
+  ring.capacity = schema::BREADCRUMB_CAPACITY;
+  ring.reserved = 0;
+  ring.next = 0;
+  ring.entries.fill(RawBreadcrumb::default());
+  BREADCRUMB_RING.store(ring_ptr, Ordering::Release);
+}
+
+// Calls `f` with a consistent copy of every complete breadcrumb, newest first. Entries that are
+// being written or were overwritten while they were copied are skipped.
+//
+// This is called from signal handlers, so it must stay async-signal-safe: entries are copied onto
+// the stack one at a time and nothing is allocated.
+pub(crate) fn for_each_breadcrumb_newest_first(mut f: impl FnMut(&RawBreadcrumb) -> bool) {
+  let ring = BREADCRUMB_RING.load(Ordering::Acquire);
+  if ring.is_null() {
+    return;
+  }
+
+  let next = next_index(ring).load(Ordering::Acquire);
+  let oldest = next.saturating_sub(u64::from(schema::BREADCRUMB_CAPACITY));
+  for index in (oldest .. next).rev() {
+    let entry = entry_ptr(ring, index);
+    let expected = 2 * (index + 1);
+    if sequence(entry).load(Ordering::Acquire) != expected {
+      continue;
+    }
+
+    let copy = unsafe { read_entry(entry) };
+    fence(Ordering::Acquire);
+    if sequence(entry).load(Ordering::Relaxed) != expected {
+      continue;
+    }
+
+    if !f(&copy) {
+      return;
+    }
+  }
+}
+
+unsafe fn read_entry(entry: *const RawBreadcrumb) -> RawBreadcrumb {
+  let mut copy = RawBreadcrumb::default();
+  unsafe {
+    copy.timestamp_ms = addr_of!((*entry).timestamp_ms).read_volatile();
+    copy.level = addr_of!((*entry).level).read_volatile();
+    copy.message_len = addr_of!((*entry).message_len).read_volatile();
+    let source = addr_of!((*entry).message).cast::<u8>();
+    for (offset, byte) in copy.message.iter_mut().enumerate() {
+      *byte = source.add(offset).read_volatile();
+    }
+  }
+  copy
+}
+
+fn next_index<'a>(ring: *mut BreadcrumbRing) -> &'a AtomicU64 {
+  unsafe { AtomicU64::from_ptr(addr_of_mut!((*ring).next)) }
+}
+
+fn entry_ptr(ring: *mut BreadcrumbRing, index: u64) -> *mut RawBreadcrumb {
+  let slot = usize::try_from(index % u64::from(schema::BREADCRUMB_CAPACITY)).unwrap_or(0);
+  unsafe {
+    addr_of_mut!((*ring).entries)
+      .cast::<RawBreadcrumb>()
+      .add(slot)
+  }
+}
+
+fn sequence<'a>(entry: *mut RawBreadcrumb) -> &'a AtomicU64 {
+  unsafe { AtomicU64::from_ptr(addr_of!((*entry).sequence).cast_mut()) }
+}
+
+fn truncate_message(message: &str) -> &[u8] {
+  let mut len = message.len().min(schema::BREADCRUMB_MESSAGE_CAPACITY);
+  while !message.is_char_boundary(len) {
+    len -= 1;
+  }
+  &message.as_bytes()[.. len]
+}
+
+fn current_timestamp_ms() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |duration| {
+      u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+    })
+}
@@ -0,0 +1,166 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{
+  BreadcrumbLevel,
+  for_each_breadcrumb_newest_first,
+  prime_breadcrumb_ring,
+  record_breadcrumb,
+};
+use crate::schema::{self, BreadcrumbRing};
+use crate::test_support::test_crash_record_guard;
+use std::thread;
+
+fn snapshot() -> Vec<(u8, String)> {
+  let mut breadcrumbs = Vec::new();
+  for_each_breadcrumb_newest_first(|breadcrumb| {
+    let message = &breadcrumb.message[.. usize::from(breadcrumb.message_len)];
+    breadcrumbs.push((
+      breadcrumb.level,
+      String::from_utf8(message.to_vec()).unwrap(),
+    ));
+    true
+  });
+  breadcrumbs
+}
+
+#[test]
+fn record_breadcrumb_is_noop_before_ring_is_primed() {
+  let _guard = test_crash_record_guard();
+
+  record_breadcrumb(BreadcrumbLevel::Info, "dropped");
+
+  assert!(snapshot().is_empty());
+}
+
+#[test]
+fn snapshot_returns_breadcrumbs_newest_first() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  record_breadcrumb(BreadcrumbLevel::Info, "first");
+  record_breadcrumb(BreadcrumbLevel::Error, "second");
+
+  assert_eq!(
+    snapshot(),
+    vec![
+      (BreadcrumbLevel::Error as u8, "second".to_string()),
+      (BreadcrumbLevel::Info as u8, "first".to_string()),
+    ]
+  );
+  assert_eq!(ring.magic, schema::BREADCRUMB_MAGIC);
+  assert!(ring.entries[0].timestamp_ms > 0);
+}
+
+#[test]
+fn ring_keeps_only_the_most_recent_breadcrumbs() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  let total = schema::BREADCRUMB_CAPACITY + 5;
+  for index in 0 .. total {
+    record_breadcrumb(BreadcrumbLevel::Debug, &format!("crumb {index}"));
+  }
+
+  let breadcrumbs = snapshot();
+  assert_eq!(breadcrumbs.len(), schema::BREADCRUMB_CAPACITY as usize);
+  assert_eq!(breadcrumbs[0].1, format!("crumb {}", total - 1));
+  assert_eq!(breadcrumbs.last().unwrap().1, "crumb 5");
+}
+
+#[test]
+fn record_breadcrumb_truncates_message_on_char_boundary() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  let message = "a".repeat(schema::BREADCRUMB_MESSAGE_CAPACITY - 1) + "é";
+  record_breadcrumb(BreadcrumbLevel::Info, &message);
+
+  assert_eq!(
+    snapshot()[0].1,
+    "a".repeat(schema::BREADCRUMB_MESSAGE_CAPACITY - 1)
+  );
+}
+
+#[test]
+fn snapshot_skips_entries_that_are_being_written() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  record_breadcrumb(BreadcrumbLevel::Info, "complete");
+  record_breadcrumb(BreadcrumbLevel::Info, "torn");
+  // Simulate a writer that was interrupted halfway through the second entry.
+  ring.entries[1].sequence = 3;
+
+  assert_eq!(
+    snapshot(),
+    vec![(BreadcrumbLevel::Info as u8, "complete".to_string())]
+  );
+}
+
+#[test]
+fn prime_breadcrumb_ring_clears_previous_run() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+  record_breadcrumb(BreadcrumbLevel::Info, "previous run");
+
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  assert!(snapshot().is_empty());
+}
+
+#[test]
+fn concurrent_appends_are_all_recorded() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<BreadcrumbRing>::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+  }
+
+  let threads = (0 .. 5)
+    .map(|thread| {
+      thread::spawn(move || {
+        for index in 0 .. 10 {
+          record_breadcrumb(BreadcrumbLevel::Info, &format!("{thread}-{index}"));
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+  for thread in threads {
+    thread.join().unwrap();
+  }
+
+  let mut messages = snapshot()
+    .into_iter()
+    .map(|(_, message)| message)
+    .collect::<Vec<_>>();
+  messages.sort();
+  let mut expected = (0 .. 5)
+    .flat_map(|thread| (0 .. 10).map(move |index| format!("{thread}-{index}")))
+    .collect::<Vec<_>>();
+  expected.sort();
+  assert_eq!(messages, expected);
+}
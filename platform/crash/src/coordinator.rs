@@ -9,9 +9,10 @@
 #[path = "./coordinator_test.rs"]
 mod tests;
 
-use crate::monitors;
 use crate::previous::PreviousCrashState;
-use crate::store::{self, CrashStateStore};
+use crate::session::{self, Heartbeat};
+use crate::store::{self, CrashStateStore, StoreKind};
+use crate::{crash_log, crash_loop, monitors, process_state};
 use anyhow::Result;
 use std::ffi::CStr;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -26,33 +27,49 @@ use std::sync::atomic::{AtomicBool, Ordering};
 // state store.
 pub(crate) struct Coordinator {
   previous_crash_state: PreviousCrashState,
+  // Declared before the store so it's dropped, and its thread joined, before the mapping goes
+  // away.
+  _heartbeat: Heartbeat,
   _store: Box<dyn CrashStateStore>,
   started: AtomicBool,
 }
 
 impl Coordinator {
-  pub(crate) fn new(path: &CStr) -> Result<Self> {
+  pub(crate) fn new(
+    path: &CStr,
+    store_kind: StoreKind,
+    process_name: Option<&str>,
+  ) -> Result<Self> {
     // Configuration flow:
-    // 1. Open the persisted state store at `path`.
+    // 1. Open the persisted state store at `path`, using the layout selected by `store_kind` and
+    //    the file of the process named `process_name`.
     // 2. Parse and cache the previous launch's crash state from the existing mmap contents.
-    // 3. Prime a fresh empty record for the current run before any monitor can write into it.
+    // 3. Prime a fresh empty record for the current run before any monitor can write into it, and
+    //    mark the run as running until a clean shutdown is requested.
+    // 4. Hand the previous launch's crash to the attached logger, unless it was already logged.
+    // 5. Start refreshing the session heartbeat so an unclean termination can be dated.
     //
     // This ordering preserves the core invariants for the crate: the previous run is read before
     // the record is reset, and the shared crash record pointer only becomes visible once it points
     // at a live mmap owned by this coordinator.
-    let mut store = store::open(path)?;
+    process_state::mark_launched();
+    let mut store = store::open(path, store_kind, process_name)?;
     let previous_crash_state = store.previous_crash_state();
 
     log::debug!(
-      "loaded previous crash state: did_crash={} timestamp_secs={}",
+      "loaded previous crash state: did_crash={} timestamp_secs={} history_len={} termination={:?}",
       previous_crash_state.did_crash,
-      previous_crash_state.timestamp_secs
+      previous_crash_state.timestamp_secs,
+      previous_crash_state.history.len(),
+      previous_crash_state.termination.kind
     );
 
     store.prepare_current_run()?;
+    crash_log::set_previous_crash(&previous_crash_state);
 
     Ok(Self {
       previous_crash_state,
+      _heartbeat: Heartbeat::start(session::HEARTBEAT_INTERVAL),
       _store: store,
       started: AtomicBool::new(false),
     })
@@ -62,6 +79,7 @@ impl Coordinator {
     // Installing the coordinator means installing the crash monitors for the current process. The
     // store has already been prepared in `new()`, so `start()` only has to make the monitor side
     // active and keep that transition idempotent for repeated callers.
+    session::mark_running();
     let was_started = self.started.swap(true, Ordering::AcqRel);
     if was_started {
       log::debug!("bitdrift crash coordinator start requested while already started");
@@ -70,6 +88,7 @@ impl Coordinator {
 
     let installed = monitors::install();
     if installed {
+      crash_loop::mark_started();
       log::debug!("installed bitdrift crash monitors");
     } else {
       log::warn!("failed to install bitdrift crash monitors");
@@ -86,6 +105,15 @@ impl Coordinator {
       log::debug!("uninstalling bitdrift crash monitors");
       monitors::uninstall();
     }
+    self.mark_clean_shutdown();
+  }
+
+  #[allow(clippy::unused_self)]
+  pub(crate) fn mark_clean_shutdown(&self) {
+    // The next launch reports a clean exit unless the run is started again or crashes first. Only
+    // the marker changes: monitors stay installed so a crash during teardown is still recorded.
+    log::debug!("marking bitdrift crash session as cleanly shut down");
+    session::mark_clean_exit();
   }
 
   pub(crate) const fn previous_crash_state(&self) -> &PreviousCrashState {
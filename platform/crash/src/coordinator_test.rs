@@ -9,6 +9,7 @@
 
 use super::Coordinator;
 use crate::previous::{PreviousCrashDetails, PreviousCrashState};
+use crate::store::StoreKind;
 use crate::test_support::test_crash_record_guard;
 use anyhow::Result;
 use std::ffi::CString;
@@ -25,7 +26,7 @@ fn coordinator_defaults_to_no_previous_crash() -> Result<()> {
       .as_bytes(),
   )?;
 
-  let coordinator = Coordinator::new(&path)?;
+  let coordinator = Coordinator::new(&path, StoreKind::History, None)?;
 
   assert_eq!(
     *coordinator.previous_crash_state(),
@@ -50,7 +51,7 @@ fn make_coordinator() -> Result<(Coordinator, tempfile::TempDir)> {
       .to_string_lossy()
       .as_bytes(),
   )?;
-  let coordinator = Coordinator::new(&path)?;
+  let coordinator = Coordinator::new(&path, StoreKind::History, None)?;
   Ok((coordinator, tempdir))
 }
 
@@ -0,0 +1,66 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./crash_callback_test.rs"]
+mod tests;
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// Called while a crash is being recorded, to let the host add a few bytes of its own state to the
+/// record. The callback writes at most `capacity` bytes to `scratch` and returns how many it wrote.
+/// Returning `0` leaves the record without host data, and returning more than `capacity` discards
+/// whatever was written.
+///
+/// The callback may run inside a signal handler, on a small alternate stack, while the rest of the
+/// process is in an arbitrary state. It must therefore be async-signal-safe: no allocation, no
+/// locks, no logging and no calls into a language runtime such as the JVM or Objective-C. Reading
+/// plain memory and atomics the host prepared ahead of time is fine. The record is only committed
+/// once the callback returns, so a callback that crashes or never returns loses the crash record.
+pub type CrashCallback =
+  unsafe extern "C" fn(scratch: *mut u8, capacity: usize, context: *mut c_void) -> usize;
+
+/// The largest amount of host data a crash record holds.
+pub const CRASH_CALLBACK_SCRATCH_CAPACITY: usize = 256;
+
+// A callback and the context it was registered with. Published as a single pointer so a crash reads
+// both without taking a lock, and never pairs a callback with another registration's context.
+struct Registration {
+  callback: CrashCallback,
+  context: *mut c_void,
+}
+
+// Replaced registrations are leaked rather than freed, since a crash on another thread may still be
+// reading them. Hosts register a callback a handful of times at most.
+static REGISTRATION: AtomicPtr<Registration> = AtomicPtr::new(null_mut());
+
+/// Registers `callback` to be invoked whenever a crash is recorded, replacing any callback
+/// registered before. `context` is passed to every invocation. Passing `None` unregisters the
+/// callback.
+///
+/// # Safety
+///
+/// `callback` must follow the contract documented on [`CrashCallback`], and `context` must stay
+/// valid for as long as the callback is registered.
+pub unsafe fn set_crash_callback(callback: Option<CrashCallback>, context: *mut c_void) {
+  let registration = callback.map_or(null_mut(), |callback| {
+    Box::into_raw(Box::new(Registration { callback, context }))
+  });
+  REGISTRATION.store(registration, Ordering::Release);
+}
+
+// Hands `scratch` to the registered callback and returns how many bytes it filled, or `None` when
+// there is no callback or it broke the contract. Async-signal-safe as long as the callback is.
+pub(crate) fn invoke(scratch: &mut [u8]) -> Option<usize> {
+  let registration = unsafe { REGISTRATION.load(Ordering::Acquire).as_ref() }?;
+  scratch.fill(0);
+  let len =
+    unsafe { (registration.callback)(scratch.as_mut_ptr(), scratch.len(), registration.context) };
+  (len > 0 && len <= scratch.len()).then_some(len)
+}
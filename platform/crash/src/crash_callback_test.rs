@@ -0,0 +1,124 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{invoke, set_crash_callback};
+use crate::test_support::test_crash_record_guard;
+use std::ffi::c_void;
+use std::ptr::{copy_nonoverlapping, null_mut, without_provenance_mut};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Copies the null-terminated string passed as the context into the scratch region.
+unsafe extern "C" fn copy_context(
+  scratch: *mut u8,
+  capacity: usize,
+  context: *mut c_void,
+) -> usize {
+  let value = unsafe { std::ffi::CStr::from_ptr(context.cast()) }.to_bytes();
+  let len = value.len().min(capacity);
+  unsafe {
+    copy_nonoverlapping(value.as_ptr(), scratch, len);
+  }
+  len
+}
+
+unsafe extern "C" fn overrun_capacity(_: *mut u8, capacity: usize, _: *mut c_void) -> usize {
+  capacity + 1
+}
+
+// Writes the callback's own tag, or `!` when it's handed another registration's context.
+unsafe extern "C" fn tag_first(scratch: *mut u8, _: usize, context: *mut c_void) -> usize {
+  unsafe { *scratch = if context.addr() == 1 { b'1' } else { b'!' } };
+  1
+}
+
+unsafe extern "C" fn tag_second(scratch: *mut u8, _: usize, context: *mut c_void) -> usize {
+  unsafe { *scratch = if context.addr() == 2 { b'2' } else { b'!' } };
+  1
+}
+
+unsafe extern "C" fn write_nothing(_: *mut u8, _: usize, _: *mut c_void) -> usize {
+  0
+}
+
+#[test]
+fn invoke_without_callback_writes_nothing() {
+  let _guard = test_crash_record_guard();
+  let mut scratch = [0xaa; 8];
+
+  assert_eq!(invoke(&mut scratch), None);
+  assert_eq!(scratch, [0xaa; 8]);
+}
+
+#[test]
+fn invoke_passes_context_and_capacity_to_callback() {
+  let _guard = test_crash_record_guard();
+  unsafe {
+    set_crash_callback(Some(copy_context), c"request-42".as_ptr().cast_mut().cast());
+  }
+  let mut scratch = [0xaa; 16];
+
+  assert_eq!(invoke(&mut scratch), Some(10));
+  assert_eq!(&scratch[.. 10], b"request-42");
+  // Bytes the callback didn't write are zeroed rather than left over from earlier data.
+  assert_eq!(&scratch[10 ..], &[0; 6]);
+
+  let mut scratch = [0; 7];
+  assert_eq!(invoke(&mut scratch), Some(7));
+  assert_eq!(&scratch, b"request");
+}
+
+#[test]
+fn invoke_ignores_lengths_outside_the_scratch_region() {
+  let _guard = test_crash_record_guard();
+  let mut scratch = [0; 8];
+
+  unsafe {
+    set_crash_callback(Some(overrun_capacity), null_mut());
+  }
+  assert_eq!(invoke(&mut scratch), None);
+
+  unsafe {
+    set_crash_callback(Some(write_nothing), null_mut());
+  }
+  assert_eq!(invoke(&mut scratch), None);
+}
+
+#[test]
+fn unregistering_stops_invoking_callback() {
+  let _guard = test_crash_record_guard();
+  unsafe {
+    set_crash_callback(Some(overrun_capacity), null_mut());
+    set_crash_callback(None, null_mut());
+  }
+
+  assert_eq!(invoke(&mut [0; 8]), None);
+}
+
+#[test]
+fn invoke_never_pairs_a_callback_with_another_registrations_context() {
+  let _guard = test_crash_record_guard();
+  let done = AtomicBool::new(false);
+
+  std::thread::scope(|scope| {
+    scope.spawn(|| {
+      for _ in 0 .. 10_000 {
+        unsafe {
+          set_crash_callback(Some(tag_first), without_provenance_mut(1));
+          set_crash_callback(Some(tag_second), without_provenance_mut(2));
+        }
+      }
+      done.store(true, Ordering::Relaxed);
+    });
+
+    while !done.load(Ordering::Relaxed) {
+      let mut scratch = [0; 1];
+      if invoke(&mut scratch).is_some() {
+        assert_ne!(scratch[0], b'!');
+      }
+    }
+  });
+}
@@ -0,0 +1,226 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./crash_log_test.rs"]
+mod tests;
+
+use crate::previous::{CallStack, PreviousCrashDetails, PreviousCrashState};
+use crate::report;
+use crate::schema::{self, ReportedCrashState};
+use std::ptr::{addr_of, addr_of_mut, null_mut};
+use std::sync::atomic::{AtomicPtr, Ordering, fence};
+use std::sync::{Mutex, MutexGuard};
+
+pub(crate) static REPORTED_CRASH_STATE: AtomicPtr<ReportedCrashState> = AtomicPtr::new(null_mut());
+
+// How many of the crashing thread's innermost frames are logged.
+const TOP_FRAME_COUNT: usize = 5;
+
+type CrashLogger = Box<dyn Fn(&PreviousLaunchCrash) + Send>;
+
+static CRASH_LOG: Mutex<CrashLog> = Mutex::new(CrashLog {
+  logger: None,
+  last_logger_id: 0,
+  pending: None,
+});
+
+struct CrashLog {
+  // The attached logger, along with the ID its token carries.
+  logger: Option<(u64, CrashLogger)>,
+  last_logger_id: u64,
+  // The previous launch's crash, until it has been handed to a logger.
+  pending: Option<(PreviousLaunchCrash, ReportedCrashState)>,
+}
+
+/// A summary of the crash that ended the previous launch, handed to the logger attached with
+/// [`attach_crash_logger`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreviousLaunchCrash {
+  /// The kind of crash: `NSException`, `Signal`, `RustPanic` or `CxxException`.
+  pub kind: String,
+  /// When the crash was recorded, in seconds since the Unix epoch.
+  pub timestamp_secs: u64,
+  /// The exception type or signal name, e.g. `NSInvalidArgumentException` or `SIGSEGV`.
+  pub exception_name: String,
+  /// The exception reason or panic message, if the crash carried one.
+  pub exception_reason: Option<String>,
+  /// The innermost frames of the crashing thread, innermost first. Native frames are formatted as
+  /// the binary followed by the return address, and a Rust panic's location as `file:line:column`.
+  pub top_frames: Vec<String>,
+}
+
+/// Identifies a logger attached with [`attach_crash_logger`], so that detaching it doesn't detach
+/// a logger attached after it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrashLoggerToken(u64);
+
+/// Attaches `logger` to the crash reporter, replacing any logger attached before. Once the crash
+/// reporter is configured, a crash that ended the previous launch is handed to the logger exactly
+/// once: it's persisted as logged, so neither a logger attached later nor a later launch sees it
+/// again.
+///
+/// The logger is called while the crash reporter holds a lock, so it must not attach or detach a
+/// logger itself.
+#[must_use = "the logger can only be detached with the returned token"]
+pub fn attach_crash_logger(
+  logger: impl Fn(&PreviousLaunchCrash) + Send + 'static,
+) -> CrashLoggerToken {
+  let mut crash_log = crash_log_lock();
+  crash_log.last_logger_id += 1;
+  let id = crash_log.last_logger_id;
+  crash_log.logger = Some((id, Box::new(logger)));
+  crash_log.flush();
+  CrashLoggerToken(id)
+}
+
+/// Detaches the logger identified by `token`, unless another logger has replaced it since. A
+/// previous launch crash that hasn't been logged yet is kept for the next logger.
+pub fn detach_crash_logger(CrashLoggerToken(token_id): CrashLoggerToken) {
+  let mut crash_log = crash_log_lock();
+  if crash_log
+    .logger
+    .as_ref()
+    .is_some_and(|(id, _)| *id == token_id)
+  {
+    crash_log.logger = None;
+  }
+}
+
+// Detaches whichever logger is attached, for tests that reset the crash reporter's global state.
+#[cfg(test)]
+pub(crate) fn detach_any_crash_logger() {
+  crash_log_lock().logger = None;
+}
+
+// Publishes the persisted reported crash state. Unlike the other regions it isn't reset: it still
+// names the last crash that was logged, possibly by an earlier launch.
+pub(crate) unsafe fn prime_reported_crash_state(state_ptr: *mut ReportedCrashState) {
+  REPORTED_CRASH_STATE.store(state_ptr, Ordering::Release);
+}
+
+// Queues the previous launch's crash for the attached logger, unless there was none or it was
+// already logged. Called once the current run's state has been primed.
+pub(crate) fn set_previous_crash(previous: &PreviousCrashState) {
+  let mut crash_log = crash_log_lock();
+  crash_log.pending = None;
+  if !previous.did_crash {
+    return;
+  }
+
+  let identity = ReportedCrashState {
+    magic: schema::REPORTED_CRASH_MAGIC,
+    timestamp_secs: previous.timestamp_secs,
+    pid: previous.pid,
+    crash_kind: previous.kind.into(),
+    reserved: [0; 3],
+  };
+  if reported_crash() == Some(identity) {
+    log::debug!("previous launch crash was already logged");
+    return;
+  }
+
+  let Some(crash) = summarize(previous) else {
+    return;
+  };
+  crash_log.pending = Some((crash, identity));
+  crash_log.flush();
+}
+
+impl CrashLog {
+  fn flush(&mut self) {
+    let Some((_, logger)) = &self.logger else {
+      return;
+    };
+    let Some((crash, identity)) = self.pending.take() else {
+      return;
+    };
+
+    logger(&crash);
+    mark_reported(&identity);
+  }
+}
+
+fn summarize(previous: &PreviousCrashState) -> Option<PreviousLaunchCrash> {
+  let description = report::error_description(&previous.details)?;
+  let top_frames = match &previous.details {
+    PreviousCrashDetails::RustPanic(panic) => panic
+      .file
+      .as_deref()
+      .map(|file| format!("{}:{}:{}", file.to_string_lossy(), panic.line, panic.column))
+      .into_iter()
+      .collect(),
+    details => details.call_stack().map(top_frames).unwrap_or_default(),
+  };
+
+  Some(PreviousLaunchCrash {
+    kind: format!("{:?}", previous.kind),
+    timestamp_secs: previous.timestamp_secs,
+    exception_name: description.name,
+    exception_reason: description.reason,
+    top_frames,
+  })
+}
+
+fn top_frames(call_stack: &CallStack) -> Vec<String> {
+  call_stack
+    .frames
+    .iter()
+    .take(TOP_FRAME_COUNT)
+    .map(|frame| {
+      frame.binary_name.as_deref().map_or_else(
+        || format!("{:#x}", frame.return_address),
+        |binary_name| {
+          format!(
+            "{} {:#x}",
+            binary_name.to_string_lossy(),
+            frame.return_address
+          )
+        },
+      )
+    })
+    .collect()
+}
+
+fn reported_crash() -> Option<ReportedCrashState> {
+  let state = REPORTED_CRASH_STATE.load(Ordering::Acquire);
+  (!state.is_null()).then(|| unsafe {
+    ReportedCrashState {
+      magic: addr_of!((*state).magic).read_volatile(),
+      timestamp_secs: addr_of!((*state).timestamp_secs).read_volatile(),
+      pid: addr_of!((*state).pid).read_volatile(),
+      crash_kind: addr_of!((*state).crash_kind).read_volatile(),
+      reserved: [0; 3],
+    }
+  })
+}
+
+fn mark_reported(identity: &ReportedCrashState) {
+  let state = REPORTED_CRASH_STATE.load(Ordering::Acquire);
+  if state.is_null() {
+    return;
+  }
+
+  // The magic is cleared while the identity changes, so a run that dies halfway leaves a state that
+  // matches no crash rather than a mix of two.
+  unsafe {
+    addr_of_mut!((*state).magic).write_volatile(0);
+    fence(Ordering::Release);
+    addr_of_mut!((*state).timestamp_secs).write_volatile(identity.timestamp_secs);
+    addr_of_mut!((*state).pid).write_volatile(identity.pid);
+    addr_of_mut!((*state).crash_kind).write_volatile(identity.crash_kind);
+    fence(Ordering::Release);
+    addr_of_mut!((*state).magic).write_volatile(identity.magic);
+  }
+}
+
+fn crash_log_lock() -> MutexGuard<'static, CrashLog> {
+  match CRASH_LOG.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
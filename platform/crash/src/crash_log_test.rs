@@ -0,0 +1,170 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{PreviousLaunchCrash, attach_crash_logger, detach_crash_logger};
+use crate::coordinator::Coordinator;
+use crate::schema;
+use crate::store::{StoreKind, open};
+use crate::test_support::test_crash_record_guard;
+use crate::writer::{StackFrameRecord, record_signal};
+use anyhow::Result;
+use std::ffi::CString;
+use std::fs::{read, write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+fn state_path(directory: &Path) -> Result<CString> {
+  Ok(CString::new(
+    directory.join("state.bin").to_string_lossy().as_bytes(),
+  )?)
+}
+
+// Simulates a launch that crashes with a `SIGSEGV` after preparing its run.
+fn crashing_launch(path: &CString, kind: StoreKind) -> Result<()> {
+  let mut store = open(path, kind, None)?;
+  store.prepare_current_run()?;
+  let frames = (1 ..= 8).map(|index| StackFrameRecord {
+    return_address: index * 0x10,
+    binary_name: Some("libapp.so"),
+    ..StackFrameRecord::default()
+  });
+  record_signal(libc::SIGSEGV, 1, 0xdead, 7, frames);
+  Ok(())
+}
+
+// Attaches a logger that collects every crash it's handed.
+fn attach_collecting_logger() -> Arc<Mutex<Vec<PreviousLaunchCrash>>> {
+  let logged = Arc::new(Mutex::new(Vec::new()));
+  let sink = logged.clone();
+  let _ = attach_crash_logger(move |crash| sink.lock().unwrap().push(crash.clone()));
+  logged
+}
+
+#[test]
+fn previous_crash_is_logged_once() -> Result<()> {
+  for kind in [StoreKind::History, StoreKind::DoubleBuffered] {
+    let _guard = test_crash_record_guard();
+    let tempdir = tempfile::tempdir()?;
+    let path = state_path(tempdir.path())?;
+
+    crashing_launch(&path, kind)?;
+    let coordinator = Coordinator::new(&path, kind, None)?;
+    let first = attach_collecting_logger();
+    let second = attach_collecting_logger();
+
+    let logged = first.lock().unwrap().clone();
+    assert_eq!(logged.len(), 1, "{kind:?}");
+    let crash = &logged[0];
+    assert_eq!(crash.kind, "Signal");
+    assert_eq!(
+      crash.timestamp_secs,
+      coordinator.previous_crash_state().timestamp_secs
+    );
+    assert_eq!(crash.exception_name, "SIGSEGV");
+    assert_eq!(
+      crash.exception_reason.as_deref(),
+      Some("code 1 at 0xdead on thread 7")
+    );
+    assert_eq!(
+      crash.top_frames,
+      vec![
+        "libapp.so 0x10",
+        "libapp.so 0x20",
+        "libapp.so 0x30",
+        "libapp.so 0x40",
+        "libapp.so 0x50",
+      ]
+    );
+    assert!(second.lock().unwrap().is_empty(), "{kind:?}");
+  }
+  Ok(())
+}
+
+#[test]
+fn logger_attached_before_configure_is_handed_previous_crash() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  crashing_launch(&path, StoreKind::History)?;
+  let logged = attach_collecting_logger();
+  assert!(logged.lock().unwrap().is_empty());
+
+  let _coordinator = Coordinator::new(&path, StoreKind::History, None)?;
+  assert_eq!(logged.lock().unwrap().len(), 1);
+  Ok(())
+}
+
+#[test]
+fn clean_previous_launch_is_not_logged() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  let logged = attach_collecting_logger();
+  drop(Coordinator::new(&path, StoreKind::History, None)?);
+  let _coordinator = Coordinator::new(&path, StoreKind::History, None)?;
+
+  assert!(logged.lock().unwrap().is_empty());
+  Ok(())
+}
+
+#[test]
+fn logged_crash_is_not_logged_again_on_relaunch() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+  let file_path = tempdir.path().join("state.bin");
+
+  crashing_launch(&path, StoreKind::History)?;
+  let before = read(&file_path)?;
+  let logged = attach_collecting_logger();
+  drop(Coordinator::new(&path, StoreKind::History, None)?);
+  assert_eq!(logged.lock().unwrap().len(), 1);
+  let after = read(&file_path)?;
+
+  // Simulates a launch that logged the crash but died before any of its own state reached the
+  // disk, so the next launch sees the same previous crash again along with the reported state.
+  let reported = schema::reported_crash_state_offset(schema::CRASH_HISTORY_CAPACITY) ..;
+  let mut relaunch = before.clone();
+  relaunch[reported.clone()].copy_from_slice(&after[reported]);
+  write(&file_path, &relaunch)?;
+  drop(Coordinator::new(&path, StoreKind::History, None)?);
+  assert_eq!(logged.lock().unwrap().len(), 1);
+
+  // Without the reported state, the crash is logged again.
+  write(&file_path, &before)?;
+  let _coordinator = Coordinator::new(&path, StoreKind::History, None)?;
+  assert_eq!(logged.lock().unwrap().len(), 2);
+  Ok(())
+}
+
+#[test]
+fn detaching_a_replaced_logger_keeps_its_replacement() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  crashing_launch(&path, StoreKind::History)?;
+  let replaced = attach_crash_logger(|_| {});
+  let logged = Arc::new(Mutex::new(Vec::new()));
+  let sink = logged.clone();
+  let current = attach_crash_logger(move |crash| sink.lock().unwrap().push(crash.clone()));
+  detach_crash_logger(replaced);
+
+  drop(Coordinator::new(&path, StoreKind::History, None)?);
+  assert_eq!(logged.lock().unwrap().len(), 1);
+
+  // Once detached with its own token, the logger isn't handed the next crash.
+  detach_crash_logger(current);
+  crashing_launch(&path, StoreKind::History)?;
+  let _coordinator = Coordinator::new(&path, StoreKind::History, None)?;
+  assert_eq!(logged.lock().unwrap().len(), 1);
+  Ok(())
+}
@@ -0,0 +1,103 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./crash_loop_test.rs"]
+mod tests;
+
+use crate::previous::PreviousCrashState;
+use crate::schema::{self, CrashLoopState};
+use crate::session;
+use std::ptr::{addr_of, addr_of_mut, null_mut};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::time::Duration;
+
+pub(crate) static CRASH_LOOP_STATE: AtomicPtr<CrashLoopState> = AtomicPtr::new(null_mut());
+
+// Used until the host configures a window.
+pub(crate) const DEFAULT_CRASH_LOOP_WINDOW: Duration = Duration::from_secs(10);
+
+/// Returns how many consecutive launches, up to and including the previous one, crashed within the
+/// crash loop window after the crash reporter was started. Hosts can use this to skip risky
+/// initialization while the app is crash looping. Returns 0 before the crash reporter has been
+/// configured, and after `reset_consecutive_launch_crashes`.
+#[must_use]
+pub fn consecutive_launch_crashes() -> u32 {
+  with_state(|state| unsafe { addr_of!((*state).consecutive_crashes).read_volatile() }).unwrap_or(0)
+}
+
+/// Marks the current launch as stable. The consecutive crash count is reset, and a crash later in
+/// this launch no longer counts towards it, even if it happens within the crash loop window.
+pub fn reset_consecutive_launch_crashes() {
+  with_state(|state| unsafe {
+    addr_of_mut!((*state).consecutive_crashes).write_volatile(0);
+    addr_of_mut!((*state).stable).write_volatile(1);
+  });
+}
+
+/// Sets how soon after the crash reporter is started a crash has to happen to count towards the
+/// consecutive launch crashes. The window is persisted and applies to the current launch and every
+/// later one until it's changed. Returns false if the crash reporter has not been configured yet.
+#[must_use]
+pub fn set_crash_loop_window(window: Duration) -> bool {
+  let window_secs = u32::try_from(window.as_secs()).unwrap_or(u32::MAX);
+  with_state(|state| unsafe { addr_of_mut!((*state).window_secs).write_volatile(window_secs) })
+    .is_some()
+}
+
+pub(crate) unsafe fn prime_crash_loop_state(state_ptr: *mut CrashLoopState, consecutive: u32) {
+  // The state still holds the previous run's contents here, so a configured window carries over.
+  let state = unsafe { &mut *state_ptr };
+  let window_secs = if state.magic == schema::CRASH_LOOP_MAGIC && state.window_secs != 0 {
+    state.window_secs
+  } else {
+    u32::try_from(DEFAULT_CRASH_LOOP_WINDOW.as_secs()).unwrap_or(u32::MAX)
+  };
+  state.magic = schema::CRASH_LOOP_MAGIC;
+  state.window_secs = window_secs;
+  state.consecutive_crashes = consecutive;
+  state.started_at_secs = 0;
+  state.stable = 0;
+  state.reserved = 0;
+  CRASH_LOOP_STATE.store(state_ptr, Ordering::Release);
+}
+
+pub(crate) fn mark_started() {
+  with_state(|state| unsafe {
+    addr_of_mut!((*state).started_at_secs).write_volatile(session::current_timestamp_secs());
+  });
+}
+
+// Derives the current launch's count from the state the previous run left behind. A previous run
+// that crashed early extends the streak; any other outcome ends it.
+pub(crate) fn count_consecutive_crashes(
+  previous: &CrashLoopState,
+  previous_crash: &PreviousCrashState,
+) -> u32 {
+  if previous.magic != schema::CRASH_LOOP_MAGIC
+    || !previous_crash.did_crash
+    || previous.stable != 0
+    || previous.started_at_secs == 0
+  {
+    return 0;
+  }
+
+  let crashed_early = previous_crash
+    .timestamp_secs
+    .checked_sub(previous.started_at_secs)
+    .is_some_and(|elapsed| elapsed <= u64::from(previous.window_secs));
+  if crashed_early {
+    previous.consecutive_crashes.saturating_add(1)
+  } else {
+    0
+  }
+}
+
+fn with_state<T>(access: impl FnOnce(*mut CrashLoopState) -> T) -> Option<T> {
+  let state = CRASH_LOOP_STATE.load(Ordering::Acquire);
+  (!state.is_null()).then(|| access(state))
+}
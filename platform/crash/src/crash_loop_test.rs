@@ -0,0 +1,121 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{
+  consecutive_launch_crashes,
+  count_consecutive_crashes,
+  mark_started,
+  prime_crash_loop_state,
+  reset_consecutive_launch_crashes,
+  set_crash_loop_window,
+};
+use crate::previous::PreviousCrashState;
+use crate::schema::{self, CrashLoopState};
+use crate::test_support::test_crash_record_guard;
+use std::time::Duration;
+
+fn previous_run(consecutive_crashes: u32) -> CrashLoopState {
+  CrashLoopState {
+    magic: schema::CRASH_LOOP_MAGIC,
+    window_secs: 10,
+    consecutive_crashes,
+    started_at_secs: 1_000,
+    ..CrashLoopState::default()
+  }
+}
+
+fn crash_at(timestamp_secs: u64) -> PreviousCrashState {
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs,
+    ..PreviousCrashState::default()
+  }
+}
+
+#[test]
+fn early_crash_extends_the_streak() {
+  assert_eq!(
+    count_consecutive_crashes(&previous_run(0), &crash_at(1_000)),
+    1
+  );
+  assert_eq!(
+    count_consecutive_crashes(&previous_run(2), &crash_at(1_010)),
+    3
+  );
+}
+
+#[test]
+fn other_outcomes_end_the_streak() {
+  assert_eq!(
+    count_consecutive_crashes(&previous_run(2), &crash_at(1_011)),
+    0
+  );
+  assert_eq!(
+    count_consecutive_crashes(&previous_run(2), &PreviousCrashState::default()),
+    0
+  );
+
+  let stable = CrashLoopState {
+    stable: 1,
+    ..previous_run(2)
+  };
+  assert_eq!(count_consecutive_crashes(&stable, &crash_at(1_000)), 0);
+
+  let never_started = CrashLoopState {
+    started_at_secs: 0,
+    ..previous_run(2)
+  };
+  assert_eq!(count_consecutive_crashes(&never_started, &crash_at(5)), 0);
+
+  // A crash dated before the monitors were started, e.g. after the clock moved backwards.
+  assert_eq!(
+    count_consecutive_crashes(&previous_run(2), &crash_at(999)),
+    0
+  );
+  assert_eq!(
+    count_consecutive_crashes(&CrashLoopState::default(), &crash_at(1_000)),
+    0
+  );
+}
+
+#[test]
+fn prime_keeps_configured_window() {
+  let _guard = test_crash_record_guard();
+  assert_eq!(consecutive_launch_crashes(), 0);
+  assert!(!set_crash_loop_window(Duration::from_secs(30)));
+
+  let mut state = CrashLoopState::default();
+  unsafe {
+    prime_crash_loop_state(&raw mut state, 2);
+  }
+  assert_eq!(state.window_secs, 10);
+  assert_eq!(consecutive_launch_crashes(), 2);
+
+  assert!(set_crash_loop_window(Duration::from_secs(30)));
+  mark_started();
+  assert!(state.started_at_secs > 0);
+
+  unsafe {
+    prime_crash_loop_state(&raw mut state, 0);
+  }
+  assert_eq!(state.window_secs, 30);
+  assert_eq!(state.started_at_secs, 0);
+}
+
+#[test]
+fn reset_marks_launch_stable() {
+  let _guard = test_crash_record_guard();
+  let mut state = CrashLoopState::default();
+  unsafe {
+    prime_crash_loop_state(&raw mut state, 3);
+  }
+
+  reset_consecutive_launch_crashes();
+
+  assert_eq!(consecutive_launch_crashes(), 0);
+  assert_eq!(state.stable, 1);
+}
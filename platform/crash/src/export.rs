@@ -0,0 +1,122 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./export_test.rs"]
+mod tests;
+
+use crate::previous::{CallStack, PreviousCrashDetails, PreviousCrashState};
+use serde_json::{Value, json};
+use std::ffi::CString;
+use std::fmt::Write as _;
+
+// Version of the exported document. Adding fields or crash kinds doesn't change it; it's only
+// bumped when an existing field changes its meaning, so hosts can reject documents they'd misread.
+pub(crate) const EXPORT_FORMAT_VERSION: u32 = 1;
+
+// Serializes everything known about the previous launch into a self-describing document. Crash
+// kinds are identified by name and their details are nested under `details`, so a new crash kind
+// only shows up as a new `kind` value. Addresses are hex strings since JSON numbers can't hold
+// every `u64` precisely, and the host's opaque crash callback data is a string of hex digits.
+pub(crate) fn previous_state_json(state: &PreviousCrashState) -> Value {
+  let mut document = crash_json(state);
+  document["format_version"] = json!(EXPORT_FORMAT_VERSION);
+  document["did_crash"] = json!(state.did_crash);
+  document["termination"] = json!({
+    "kind": format!("{:?}", state.termination.kind),
+    "started_at_secs": state.termination.started_at_secs,
+    "last_heartbeat_secs": state.termination.last_heartbeat_secs,
+  });
+  document["consecutive_launch_crashes"] = json!(state.consecutive_launch_crashes);
+  document["history"] = state.history.iter().map(crash_json).collect();
+  document
+}
+
+// Serializes a single crash, without the launch-level fields that only the store populates.
+pub(crate) fn crash_json(state: &PreviousCrashState) -> Value {
+  let details = match &state.details {
+    PreviousCrashDetails::None => Value::Null,
+    PreviousCrashDetails::NSException(exception) => json!({
+      "name": exception.name.as_ref().map(lossy),
+      "reason": exception.reason.as_ref().map(lossy),
+      "frames": frames_json(&exception.call_stack),
+    }),
+    PreviousCrashDetails::Signal(signal) => json!({
+      "signal": signal.signal,
+      "code": signal.code,
+      "fault_address": hex(signal.fault_address),
+      "thread_id": signal.thread_id,
+      "frames": frames_json(&signal.call_stack),
+    }),
+    PreviousCrashDetails::RustPanic(panic) => json!({
+      "message": panic.message.as_ref().map(lossy),
+      "file": panic.file.as_ref().map(lossy),
+      "line": panic.line,
+      "column": panic.column,
+      "thread_name": panic.thread_name.as_ref().map(lossy),
+    }),
+    PreviousCrashDetails::CxxException(exception) => json!({
+      "type_name": exception.type_name.as_ref().map(lossy),
+      "what": exception.what.as_ref().map(lossy),
+      "frames": frames_json(&exception.call_stack),
+    }),
+  };
+
+  json!({
+    "timestamp_secs": state.timestamp_secs,
+    "pid": state.pid,
+    "kind": format!("{:?}", state.kind),
+    "details": details,
+    "annotations": state.annotations.iter().map(|annotation| json!({
+      "key": lossy(&annotation.key),
+      "value": lossy(&annotation.value),
+    })).collect::<Vec<_>>(),
+    "breadcrumbs": state.breadcrumbs.iter().map(|breadcrumb| json!({
+      "timestamp_ms": breadcrumb.timestamp_ms,
+      "level": format!("{:?}", breadcrumb.level),
+      "message": lossy(&breadcrumb.message),
+    })).collect::<Vec<_>>(),
+    "process_state": state.process_state.map(|process_state| json!({
+      "uptime_ms": process_state.uptime_ms,
+      "resident_bytes": process_state.resident_bytes,
+      "thread_count": process_state.thread_count,
+      "open_fd_count": process_state.open_fd_count,
+      "app_state": format!("{:?}", process_state.app_state),
+    })),
+    "host_data": state.host_data.as_deref().map(hex_bytes),
+  })
+}
+
+fn frames_json(call_stack: &CallStack) -> Value {
+  call_stack
+    .frames
+    .iter()
+    .map(|frame| {
+      json!({
+        "return_address": hex(frame.return_address),
+        "image_load_address": hex(frame.image_load_address),
+        "binary_name": frame.binary_name.as_ref().map(lossy),
+        "image_id": frame.image_id.as_ref().map(lossy),
+      })
+    })
+    .collect()
+}
+
+pub(crate) fn hex(value: impl Into<u64>) -> String {
+  format!("{:#x}", value.into())
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+  bytes.iter().fold(String::new(), |mut hex, byte| {
+    let _ = write!(hex, "{byte:02x}");
+    hex
+  })
+}
+
+fn lossy(value: &CString) -> String {
+  value.to_string_lossy().into_owned()
+}
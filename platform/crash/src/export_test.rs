@@ -0,0 +1,157 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{EXPORT_FORMAT_VERSION, previous_state_json};
+use crate::breadcrumbs::BreadcrumbLevel;
+use crate::previous::{
+  Annotation,
+  AppState,
+  Breadcrumb,
+  CallStack,
+  CrashKind,
+  NSExceptionCrashInfo,
+  PreviousCrashDetails,
+  PreviousCrashState,
+  PreviousTermination,
+  ProcessState,
+  RustPanicCrashInfo,
+  StackFrame,
+  TerminationKind,
+};
+use serde_json::json;
+
+fn rust_panic(timestamp_secs: u64) -> PreviousCrashState {
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs,
+    pid: 7,
+    kind: CrashKind::RustPanic,
+    details: PreviousCrashDetails::RustPanic(Box::new(RustPanicCrashInfo {
+      message: Some(c"boom".to_owned()),
+      file: Some(c"src/lib.rs".to_owned()),
+      line: 12,
+      column: 5,
+      thread_name: None,
+    })),
+    ..PreviousCrashState::default()
+  }
+}
+
+#[test]
+fn exports_previous_state() {
+  let frame = StackFrame {
+    return_address: 0x1234,
+    image_load_address: 0x1000,
+    binary_name: Some(c"MyApp".to_owned()),
+    image_id: None,
+  };
+  let state = PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: 1_700_000_000,
+    pid: 42,
+    kind: CrashKind::NSException,
+    details: PreviousCrashDetails::NSException(Box::new(NSExceptionCrashInfo {
+      name: Some(c"NSRangeException".to_owned()),
+      reason: None,
+      call_stack: CallStack {
+        return_addresses: vec![frame.return_address],
+        frames: vec![frame],
+      },
+    })),
+    annotations: vec![Annotation {
+      key: c"screen".to_owned(),
+      value: c"checkout".to_owned(),
+    }],
+    breadcrumbs: vec![Breadcrumb {
+      timestamp_ms: 1_700_000_000_000,
+      level: BreadcrumbLevel::Warning,
+      message: c"low memory".to_owned(),
+    }],
+    process_state: Some(ProcessState {
+      uptime_ms: 1_500,
+      resident_bytes: 4_096,
+      thread_count: 3,
+      open_fd_count: 9,
+      app_state: AppState::Foreground,
+    }),
+    host_data: Some(b"req\x01\xff".to_vec()),
+    termination: PreviousTermination {
+      kind: TerminationKind::Crash,
+      started_at_secs: 1_699_999_000,
+      last_heartbeat_secs: 1_699_999_995,
+    },
+    consecutive_launch_crashes: 2,
+    history: vec![rust_panic(1_600_000_000)],
+  };
+
+  assert_eq!(
+    previous_state_json(&state),
+    json!({
+      "format_version": EXPORT_FORMAT_VERSION,
+      "did_crash": true,
+      "timestamp_secs": 1_700_000_000,
+      "pid": 42,
+      "kind": "NSException",
+      "details": {
+        "name": "NSRangeException",
+        "reason": null,
+        "frames": [{
+          "return_address": "0x1234",
+          "image_load_address": "0x1000",
+          "binary_name": "MyApp",
+          "image_id": null,
+        }],
+      },
+      "annotations": [{ "key": "screen", "value": "checkout" }],
+      "breadcrumbs": [{
+        "timestamp_ms": 1_700_000_000_000_u64,
+        "level": "Warning",
+        "message": "low memory",
+      }],
+      "process_state": {
+        "uptime_ms": 1_500,
+        "resident_bytes": 4_096,
+        "thread_count": 3,
+        "open_fd_count": 9,
+        "app_state": "Foreground",
+      },
+      "host_data": "72657101ff",
+      "termination": {
+        "kind": "Crash",
+        "started_at_secs": 1_699_999_000,
+        "last_heartbeat_secs": 1_699_999_995,
+      },
+      "consecutive_launch_crashes": 2,
+      "history": [{
+        "timestamp_secs": 1_600_000_000,
+        "pid": 7,
+        "kind": "RustPanic",
+        "details": {
+          "message": "boom",
+          "file": "src/lib.rs",
+          "line": 12,
+          "column": 5,
+          "thread_name": null,
+        },
+        "annotations": [],
+        "breadcrumbs": [],
+        "process_state": null,
+        "host_data": null,
+      }],
+    })
+  );
+}
+
+#[test]
+fn exports_state_without_crash() {
+  let document = previous_state_json(&PreviousCrashState::default());
+  assert_eq!(document["did_crash"], false);
+  assert_eq!(document["kind"], "None");
+  assert_eq!(document["details"], json!(null));
+  assert_eq!(document["termination"]["kind"], "Unknown");
+  assert_eq!(document["history"], json!([]));
+}
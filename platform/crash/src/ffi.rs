@@ -6,18 +6,37 @@
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
 //! C ABI entrypoints for configuring crash reporting, toggling monitor installation, and reading
-//! cached previous-launch crash state from non-Rust callers.
+//! cached previous-launch crash state from non-Rust callers, along with the Rust equivalents used
+//! by hosts that link the crate directly, such as the Android JNI bindings.
 
 #[cfg(test)]
 #[path = "./ffi_test.rs"]
 mod tests;
 
+use crate::breadcrumbs::{self, BreadcrumbLevel};
 use crate::coordinator::Coordinator;
-use crate::previous::{NSExceptionCrashInfo, PreviousCrashDetails, PreviousCrashState};
-use std::ffi::CStr;
+use crate::crash_callback::{self, CrashCallback};
+use crate::previous::{
+  Annotation,
+  Breadcrumb,
+  CxxExceptionCrashInfo,
+  NSExceptionCrashInfo,
+  PreviousCrashDetails,
+  PreviousCrashState,
+  ProcessState,
+  RustPanicCrashInfo,
+  SignalCrashInfo,
+};
+use crate::store::StoreKind;
+use crate::symbolication::{SymbolicationStack, SymbolizerFormat};
+use crate::{annotations, crash_loop, export, process_state, report};
+use std::ffi::{CStr, CString, c_void};
 use std::os::raw::c_char;
-use std::ptr::null;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::ptr::{null, null_mut};
 use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
 
 static COORDINATOR: OnceLock<Coordinator> = OnceLock::new();
 static CONFIGURE_LOCK: Mutex<()> = Mutex::new(());
@@ -34,6 +53,62 @@ fn previous_nsexception(previous_state: &PreviousCrashState) -> Option<&NSExcept
   Some(exception.as_ref())
 }
 
+const fn previous_signal(previous_state: &PreviousCrashState) -> Option<&SignalCrashInfo> {
+  let PreviousCrashDetails::Signal(signal) = &previous_state.details else {
+    return None;
+  };
+
+  Some(signal)
+}
+
+fn previous_rust_panic(previous_state: &PreviousCrashState) -> Option<&RustPanicCrashInfo> {
+  let PreviousCrashDetails::RustPanic(panic) = &previous_state.details else {
+    return None;
+  };
+
+  Some(panic.as_ref())
+}
+
+fn previous_cxx_exception(previous_state: &PreviousCrashState) -> Option<&CxxExceptionCrashInfo> {
+  let PreviousCrashDetails::CxxException(exception) = &previous_state.details else {
+    return None;
+  };
+
+  Some(exception.as_ref())
+}
+
+fn previous_process_state() -> Option<ProcessState> {
+  previous_crash_state().and_then(|state| state.process_state)
+}
+
+fn c_string_or_null(value: Option<&CString>) -> *const c_char {
+  value.map_or(null(), |value| value.as_ptr())
+}
+
+fn crash_history_entry(index: u32) -> Option<&'static PreviousCrashState> {
+  previous_crash_state()?
+    .history
+    .get(usize::try_from(index).ok()?)
+}
+
+fn previous_breadcrumb(index: u32) -> Option<&'static Breadcrumb> {
+  previous_crash_state()?
+    .breadcrumbs
+    .get(usize::try_from(index).ok()?)
+}
+
+fn previous_annotation(index: u32) -> Option<&'static Annotation> {
+  previous_crash_state()?
+    .annotations
+    .get(usize::try_from(index).ok()?)
+}
+
+fn previous_state_json(previous_state: &PreviousCrashState) -> *mut c_char {
+  // Escaped JSON never contains a null byte, so this only fails if serialization itself is broken.
+  CString::new(export::previous_state_json(previous_state).to_string())
+    .map_or(null_mut(), CString::into_raw)
+}
+
 fn configure_lock() -> MutexGuard<'static, ()> {
   match CONFIGURE_LOCK.lock() {
     Ok(guard) => guard,
@@ -41,21 +116,7 @@ fn configure_lock() -> MutexGuard<'static, ()> {
   }
 }
 
-/// # Safety
-///
-/// `state_path` must point to a valid, immutable, null-terminated C string for the duration of the
-/// call. It must not alias memory that is concurrently modified.
-///
-/// This function is idempotent for the process: it initializes the persisted crash state store,
-/// snapshots the previous launch's crash state, and prepares the current run's shared record, but
-/// it does not install crash monitors yet.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn capture_bitdrift_crash_configure(state_path: *const c_char) -> bool {
-  if state_path.is_null() {
-    log::debug!("capture_bitdrift_crash_configure called with null state path");
-    return false;
-  }
-
+fn configure_coordinator(path: &CStr, store_kind: StoreKind, process_name: Option<&str>) -> bool {
   let _guard = configure_lock();
   if COORDINATOR.get().is_some() {
     return true;
@@ -63,8 +124,7 @@ pub unsafe extern "C" fn capture_bitdrift_crash_configure(state_path: *const c_c
 
   // Serialize first-time initialization so a second caller cannot construct and immediately drop a
   // new coordinator while the shared mmap-backed crash record still points into the original one.
-  let path = unsafe { CStr::from_ptr(state_path) };
-  let coordinator = match Coordinator::new(path) {
+  let coordinator = match Coordinator::new(path, store_kind, process_name) {
     Ok(coordinator) => coordinator,
     Err(error) => {
       log::warn!("failed to configure bitdrift crash coordinator: {error:#}");
@@ -75,6 +135,116 @@ pub unsafe extern "C" fn capture_bitdrift_crash_configure(state_path: *const c_c
   COORDINATOR.set(coordinator).is_ok()
 }
 
+/// Configures the crash reporter from Rust. Same as `capture_bitdrift_crash_configure_for_process`,
+/// including the rules for `process_name`.
+#[must_use]
+pub fn configure(state_path: &Path, process_name: Option<&str>) -> bool {
+  let Ok(path) = CString::new(state_path.as_os_str().as_bytes()) else {
+    log::debug!("bitdrift crash reporter state path contains a null byte");
+    return false;
+  };
+  configure_coordinator(&path, StoreKind::History, process_name)
+}
+
+/// Installs the crash monitors from Rust. Same as `capture_bitdrift_crash_start`.
+#[must_use]
+pub fn start() -> bool {
+  capture_bitdrift_crash_start()
+}
+
+/// Marks the current run as cleanly shut down from Rust. Same as
+/// `capture_bitdrift_crash_mark_clean_shutdown`.
+pub fn mark_clean_shutdown() {
+  capture_bitdrift_crash_mark_clean_shutdown();
+}
+
+/// Returns whether the previous launch crashed, or `None` when the crash reporter has not been
+/// configured yet. Same as `capture_bitdrift_crash_did_crash_last_launch`.
+#[must_use]
+pub fn did_crash_last_launch() -> Option<bool> {
+  previous_crash_state().map(|state| state.did_crash)
+}
+
+/// # Safety
+///
+/// `state_path` must point to a valid, immutable, null-terminated C string for the duration of the
+/// call. It must not alias memory that is concurrently modified.
+///
+/// This function is idempotent for the process: it initializes the persisted crash state store,
+/// snapshots the previous launch's crash state, and prepares the current run's shared record, but
+/// it does not install crash monitors yet. The state file is locked for the lifetime of the
+/// process, so apps that run several processes sharing `state_path` use
+/// `capture_bitdrift_crash_configure_for_process` instead.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_configure(state_path: *const c_char) -> bool {
+  unsafe { capture_bitdrift_crash_configure_for_process(state_path, null()) }
+}
+
+/// # Safety
+///
+/// `state_path` must point to a valid, immutable, null-terminated C string for the duration of the
+/// call. It must not alias memory that is concurrently modified. `process_name` must be null or
+/// meet the same requirements.
+///
+/// Same as `capture_bitdrift_crash_configure`, for apps that run several processes sharing
+/// `state_path`, such as an app and its extensions. The main process passes a null `process_name`
+/// and every other process passes a stable name of its own, such as its bundle identifier or
+/// Android process name. A named process uses a sibling file next to `state_path`, so each process
+/// reads back its own previous launch, and the previous crash state includes the crashes recorded
+/// by all of them. Names may only contain ASCII letters, digits, `.`, `-` and `_`. Returns `false`
+/// for an invalid name, or if another live process uses the same name.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_configure_for_process(
+  state_path: *const c_char,
+  process_name: *const c_char,
+) -> bool {
+  unsafe {
+    capture_bitdrift_crash_configure_with_store(state_path, process_name, StoreKind::History as u8)
+  }
+}
+
+/// # Safety
+///
+/// `state_path` must point to a valid, immutable, null-terminated C string for the duration of the
+/// call. It must not alias memory that is concurrently modified. `process_name` must be null or
+/// meet the same requirements.
+///
+/// Same as `capture_bitdrift_crash_configure_for_process`, but selects the layout of the state
+/// file. `store_kind` is `0` for the default crash history ring, which keeps the last few crashes,
+/// or `1` for a double-buffered file whose two slots are used in turns so the previous launch's
+/// record is never written to during the current launch. Returns `false` for an unknown
+/// `store_kind`. The layout should not change between launches: switching carries over only the
+/// most recent crash, and only when switching to the double-buffered layout.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_configure_with_store(
+  state_path: *const c_char,
+  process_name: *const c_char,
+  store_kind: u8,
+) -> bool {
+  if state_path.is_null() {
+    log::debug!("capture_bitdrift_crash_configure called with null state path");
+    return false;
+  }
+  let Some(store_kind) = StoreKind::from_u8(store_kind) else {
+    log::debug!("capture_bitdrift_crash_configure called with unknown store kind {store_kind}");
+    return false;
+  };
+  let process_name = if process_name.is_null() {
+    None
+  } else if let Ok(process_name) = unsafe { CStr::from_ptr(process_name) }.to_str() {
+    Some(process_name)
+  } else {
+    log::debug!("capture_bitdrift_crash_configure called with non-UTF-8 process name");
+    return false;
+  };
+
+  configure_coordinator(
+    unsafe { CStr::from_ptr(state_path) },
+    store_kind,
+    process_name,
+  )
+}
+
 /// Activate crash monitor installation for the current process. Returns `false` if the
 /// coordinator has not been configured yet or monitor installation fails.
 #[unsafe(no_mangle)]
@@ -82,8 +252,8 @@ pub extern "C" fn capture_bitdrift_crash_start() -> bool {
   COORDINATOR.get().is_some_and(Coordinator::start)
 }
 
-/// Uninstall previously-registered crash monitors for the current process. This does not destroy
-/// the coordinator or clear persisted crash state.
+/// Uninstall previously-registered crash monitors for the current process and mark the current run
+/// as cleanly shut down. This does not destroy the coordinator or clear persisted crash state.
 #[unsafe(no_mangle)]
 pub extern "C" fn capture_bitdrift_crash_stop() {
   if let Some(coordinator) = COORDINATOR.get() {
@@ -91,6 +261,56 @@ pub extern "C" fn capture_bitdrift_crash_stop() {
   }
 }
 
+/// Mark the current run as cleanly shut down without uninstalling crash monitors, e.g. when the
+/// host application is about to terminate normally. The next launch then reports a clean exit
+/// unless a crash is recorded first. `capture_bitdrift_crash_stop` marks a clean shutdown as well.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_mark_clean_shutdown() {
+  if let Some(coordinator) = COORDINATOR.get() {
+    coordinator.mark_clean_shutdown();
+  }
+}
+
+/// Return how the previous launch ended: `0` when unknown (including before configuration), `1`
+/// for a clean exit, `2` for a recorded crash and `3` for an unclean termination that left no crash
+/// record, such as an out-of-memory or watchdog kill.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_termination_kind() -> u8 {
+  previous_crash_state().map_or(0, |state| state.termination.kind as u8)
+}
+
+/// Return the last heartbeat of the previous launch in seconds since the Unix epoch, or `0` when
+/// the previous launch's session state is unknown. The heartbeat is refreshed every few seconds
+/// while the process is running.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_heartbeat_secs() -> u64 {
+  previous_crash_state().map_or(0, |state| state.termination.last_heartbeat_secs)
+}
+
+/// Return how many consecutive launches, up to and including the previous one, crashed within the
+/// crash loop window after `capture_bitdrift_crash_start`. Returns `0` before configuration and
+/// after `capture_bitdrift_crash_reset_consecutive_launch_crashes`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_consecutive_launch_crashes() -> u32 {
+  crash_loop::consecutive_launch_crashes()
+}
+
+/// Mark the current launch as stable once the host is past its risky initialization. This resets
+/// the consecutive launch crash count, and a crash later in this launch no longer counts towards
+/// it.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_reset_consecutive_launch_crashes() {
+  crash_loop::reset_consecutive_launch_crashes();
+}
+
+/// Set how many seconds after `capture_bitdrift_crash_start` a crash has to happen to count towards
+/// the consecutive launch crashes. The window is persisted until it's changed and defaults to 10
+/// seconds. Returns `false` if the coordinator has not been configured yet.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_set_crash_loop_window_secs(window_secs: u32) -> bool {
+  crash_loop::set_crash_loop_window(Duration::from_secs(u64::from(window_secs)))
+}
+
 /// Return whether the cached previous-launch state indicates a crash. Returns `-1` when the
 /// coordinator has not been configured yet.
 #[unsafe(no_mangle)]
@@ -102,6 +322,216 @@ pub extern "C" fn capture_bitdrift_crash_did_crash_last_launch() -> i8 {
   i8::from(previous_crash_state().is_some_and(|state| state.did_crash))
 }
 
+/// Return everything cached about the previous launch as a null-terminated JSON document, or null
+/// when the coordinator has not been configured yet. The document holds the crash details,
+/// including call stack frames, the annotations, breadcrumbs and crash history, and how the
+/// previous launch ended. New crash kinds are added to it as new `kind` values rather than new
+/// getters, so hosts should ignore kinds and fields they don't know.
+///
+/// The caller owns the returned buffer and must release it with
+/// `capture_bitdrift_crash_free_previous_state_json`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_previous_state_json() -> *mut c_char {
+  previous_crash_state().map_or(null_mut(), previous_state_json)
+}
+
+/// Release a document returned by `capture_bitdrift_crash_previous_state_json`. Passing null is a
+/// no-op.
+///
+/// # Safety
+/// `json` must be null or a pointer returned by `capture_bitdrift_crash_previous_state_json` that
+/// has not been released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_free_previous_state_json(json: *mut c_char) {
+  if !json.is_null() {
+    drop(unsafe { CString::from_raw(json) });
+  }
+}
+
+fn symbolication_stack(state: &PreviousCrashState) -> Option<SymbolicationStack> {
+  state.details.call_stack().map(SymbolicationStack::new)
+}
+
+fn owned_c_string(value: String) -> *mut c_char {
+  CString::new(value).map_or(null_mut(), CString::into_raw)
+}
+
+/// Return the previous launch's crashing call stack as a null-terminated JSON document ready for
+/// symbolication: every image the stack runs through is listed once with its ID, binary name and
+/// load address, and each frame refers to its image by index along with its offset from the image's
+/// load address. Returns null when the previous launch didn't crash with a native call stack or the
+/// coordinator has not been configured yet.
+///
+/// The caller owns the returned buffer and must release it with
+/// `capture_bitdrift_crash_free_symbolication_json`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_previous_symbolication_json() -> *mut c_char {
+  previous_crash_state().map_or(null_mut(), symbolication_json)
+}
+
+/// Same as `capture_bitdrift_crash_previous_symbolication_json`, for the crash history entry at
+/// `index`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_symbolication_json(index: u32) -> *mut c_char {
+  crash_history_entry(index).map_or(null_mut(), symbolication_json)
+}
+
+fn symbolication_json(state: &PreviousCrashState) -> *mut c_char {
+  symbolication_stack(state).map_or(null_mut(), |stack| {
+    owned_c_string(stack.to_json().to_string())
+  })
+}
+
+/// Return the previous launch's crashing call stack as input for a symbolization tool, as a
+/// null-terminated string. `format` `0` produces one `"<binary>" <offset>` line per frame, which
+/// `llvm-symbolizer` reads from stdin, and `1` produces one `atos` command per image. Frames that
+/// didn't resolve to an image with a known binary are left out. Returns null for an unknown
+/// `format`, when the previous launch didn't crash with a native call stack, or when the
+/// coordinator has not been configured yet.
+///
+/// The caller owns the returned buffer and must release it with
+/// `capture_bitdrift_crash_free_symbolizer_input`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_previous_symbolizer_input(format: u8) -> *mut c_char {
+  previous_crash_state().map_or(null_mut(), |state| symbolizer_input(state, format))
+}
+
+/// Same as `capture_bitdrift_crash_previous_symbolizer_input`, for the crash history entry at
+/// `index`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_symbolizer_input(
+  index: u32,
+  format: u8,
+) -> *mut c_char {
+  crash_history_entry(index).map_or(null_mut(), |state| symbolizer_input(state, format))
+}
+
+fn symbolizer_input(state: &PreviousCrashState, format: u8) -> *mut c_char {
+  let Some(format) = SymbolizerFormat::from_u8(format) else {
+    log::debug!("unknown symbolizer input format {format}");
+    return null_mut();
+  };
+  symbolication_stack(state).map_or(null_mut(), |stack| {
+    owned_c_string(stack.symbolizer_input(format))
+  })
+}
+
+/// Release a document returned by one of the `_symbolication_json` functions. Passing null is a
+/// no-op.
+///
+/// # Safety
+/// `json` must be null or a pointer returned by one of the `_symbolication_json` functions that has
+/// not been released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_free_symbolication_json(json: *mut c_char) {
+  unsafe { free_owned_c_string(json) };
+}
+
+/// Release a buffer returned by one of the `_symbolizer_input` functions. Passing null is a no-op.
+///
+/// # Safety
+/// `input` must be null or a pointer returned by one of the `_symbolizer_input` functions that has
+/// not been released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_free_symbolizer_input(input: *mut c_char) {
+  unsafe { free_owned_c_string(input) };
+}
+
+// Safety: `value` must be null or a pointer returned by `owned_c_string` that has not been
+// released yet.
+unsafe fn free_owned_c_string(value: *mut c_char) {
+  if !value.is_null() {
+    drop(unsafe { CString::from_raw(value) });
+  }
+}
+
+/// App and device details attached to the report written by
+/// `capture_bitdrift_crash_write_previous_crash_report`. Every field may be null when the host
+/// doesn't know it.
+#[repr(C)]
+pub struct CrashReportMetadata {
+  pub app_id: *const c_char,
+  pub app_version: *const c_char,
+  /// The bundle version on Apple platforms.
+  pub build_number: *const c_char,
+  pub os_brand: *const c_char,
+  pub os_version: *const c_char,
+  pub manufacturer: *const c_char,
+  pub model: *const c_char,
+}
+
+impl CrashReportMetadata {
+  // Safety: every non-null field must be a valid, null-terminated C string that outlives the
+  // returned metadata.
+  unsafe fn to_report_metadata(&self) -> report::ReportMetadata<'_> {
+    let field = |value: *const c_char| {
+      (!value.is_null())
+        .then(|| unsafe { CStr::from_ptr(value) }.to_str().ok())
+        .flatten()
+    };
+    report::ReportMetadata {
+      app_id: field(self.app_id),
+      app_version: field(self.app_version),
+      build_number: field(self.build_number),
+      os_brand: field(self.os_brand),
+      os_version: field(self.os_version),
+      manufacturer: field(self.manufacturer),
+      model: field(self.model),
+    }
+  }
+}
+
+/// Convert the previous launch's crash into an issue report and write it into `report_directory`,
+/// the SDK's pending report directory, so it's uploaded with the other reports. Returns `true` only
+/// when a report was written; `false` when the previous launch didn't crash, the coordinator has
+/// not been configured yet, or writing fails.
+///
+/// # Safety
+/// `report_directory` and `sdk_version` must be valid, null-terminated C strings for the duration
+/// of the call. `metadata` must be null or point to a valid `CrashReportMetadata` whose non-null
+/// fields are valid, null-terminated C strings for the duration of the call. Fields that are not
+/// valid UTF-8 are left out of the report.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_write_previous_crash_report(
+  report_directory: *const c_char,
+  sdk_version: *const c_char,
+  metadata: *const CrashReportMetadata,
+) -> bool {
+  if report_directory.is_null() || sdk_version.is_null() {
+    log::debug!("capture_bitdrift_crash_write_previous_crash_report called with null argument");
+    return false;
+  }
+
+  let Some(previous_state) = previous_crash_state() else {
+    return false;
+  };
+  let (Ok(report_directory), Ok(sdk_version)) = (
+    unsafe { CStr::from_ptr(report_directory) }.to_str(),
+    unsafe { CStr::from_ptr(sdk_version) }.to_str(),
+  ) else {
+    log::debug!(
+      "capture_bitdrift_crash_write_previous_crash_report called with non-UTF-8 argument"
+    );
+    return false;
+  };
+  let metadata = unsafe { metadata.as_ref() }
+    .map(|metadata| unsafe { metadata.to_report_metadata() })
+    .unwrap_or_default();
+
+  match report::write_report(
+    previous_state,
+    Path::new(report_directory),
+    sdk_version,
+    &metadata,
+  ) {
+    Ok(path) => path.is_some(),
+    Err(error) => {
+      log::warn!("failed to write previous crash report: {error:#}");
+      false
+    },
+  }
+}
+
 /// Return the cached previous-launch crash timestamp, or `0` when no crash state is available.
 #[unsafe(no_mangle)]
 pub extern "C" fn capture_bitdrift_crash_cached_timestamp() -> u64 {
@@ -126,11 +556,7 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_name() -> *const c_char
     return null();
   };
 
-  if exception.name[0] == 0 {
-    return null();
-  }
-
-  exception.name.as_ptr().cast::<c_char>()
+  c_string_or_null(exception.name.as_ref())
 }
 
 /// Return the cached previous-launch exception reason as a pointer into process-owned storage, or
@@ -145,11 +571,7 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_reason() -> *const c_cha
     return null();
   };
 
-  if exception.reason[0] == 0 {
-    return null();
-  }
-
-  exception.reason.as_ptr().cast::<c_char>()
+  c_string_or_null(exception.reason.as_ref())
 }
 
 /// Return the number of cached previous-launch `NSException` call stack frames, or `0` when no
@@ -164,7 +586,7 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_call_stack_frame_count()
     return 0;
   };
 
-  exception.call_stack.frame_count
+  u16::try_from(exception.call_stack.frames.len()).unwrap_or(u16::MAX)
 }
 
 /// Return a pointer to cached previous-launch `NSException` return addresses, or null when no
@@ -180,7 +602,7 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_call_stack_return_addres
     return null();
   };
 
-  if exception.call_stack.frame_count == 0 {
+  if exception.call_stack.return_addresses.is_empty() {
     return null();
   }
 
@@ -201,12 +623,11 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_call_stack_image_load_ad
     return 0;
   };
 
-  let index = usize::from(frame_index);
-  if index >= usize::from(exception.call_stack.frame_count) {
-    return 0;
-  }
-
-  exception.call_stack.frames[index].image_load_address
+  exception
+    .call_stack
+    .frames
+    .get(usize::from(frame_index))
+    .map_or(0, |frame| frame.image_load_address)
 }
 
 /// Return the cached previous-launch `NSException` binary name for the requested frame, or null
@@ -223,17 +644,11 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_call_stack_binary_name_a
     return null();
   };
 
-  let index = usize::from(frame_index);
-  if index >= usize::from(exception.call_stack.frame_count) {
-    return null();
-  }
-
-  let frame = &exception.call_stack.frames[index];
-  if frame.binary_name[0] == 0 {
-    return null();
-  }
-
-  frame.binary_name.as_ptr().cast::<c_char>()
+  exception
+    .call_stack
+    .frames
+    .get(usize::from(frame_index))
+    .map_or(null(), |frame| c_string_or_null(frame.binary_name.as_ref()))
 }
 
 /// Return the cached previous-launch `NSException` image id for the requested frame, or null when
@@ -250,15 +665,377 @@ pub extern "C" fn capture_bitdrift_crash_last_exception_call_stack_image_id_at(
     return null();
   };
 
-  let index = usize::from(frame_index);
-  if index >= usize::from(exception.call_stack.frame_count) {
-    return null();
+  exception
+    .call_stack
+    .frames
+    .get(usize::from(frame_index))
+    .map_or(null(), |frame| c_string_or_null(frame.image_id.as_ref()))
+}
+
+/// Return the cached previous-launch fatal signal number, or `0` when the previous crash was not a
+/// signal.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_signal_number() -> i32 {
+  previous_crash_state()
+    .and_then(previous_signal)
+    .map_or(0, |signal| signal.signal)
+}
+
+/// Return the cached previous-launch fatal signal `si_code`, or `0` when the previous crash was not
+/// a signal.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_signal_code() -> i32 {
+  previous_crash_state()
+    .and_then(previous_signal)
+    .map_or(0, |signal| signal.code)
+}
+
+/// Return the cached previous-launch fatal signal fault address, or `0` when the previous crash was
+/// not a signal.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_signal_fault_address() -> u64 {
+  previous_crash_state()
+    .and_then(previous_signal)
+    .map_or(0, |signal| signal.fault_address)
+}
+
+/// Return the cached previous-launch id of the thread that received the fatal signal, or `0` when
+/// the previous crash was not a signal.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_signal_thread_id() -> u64 {
+  previous_crash_state()
+    .and_then(previous_signal)
+    .map_or(0, |signal| signal.thread_id)
+}
+
+/// Return the cached previous-launch Rust panic message as a pointer into process-owned storage,
+/// or null when no panic message is available.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_panic_message() -> *const c_char {
+  previous_crash_state()
+    .and_then(previous_rust_panic)
+    .map_or(null(), |panic| c_string_or_null(panic.message.as_ref()))
+}
+
+/// Return the cached previous-launch Rust panic source file as a pointer into process-owned
+/// storage, or null when no panic location is available.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_panic_file() -> *const c_char {
+  previous_crash_state()
+    .and_then(previous_rust_panic)
+    .map_or(null(), |panic| c_string_or_null(panic.file.as_ref()))
+}
+
+/// Return the cached previous-launch Rust panic source line, or `0` when no panic location is
+/// available.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_panic_line() -> u32 {
+  previous_crash_state()
+    .and_then(previous_rust_panic)
+    .map_or(0, |panic| panic.line)
+}
+
+/// Return the cached previous-launch Rust panic source column, or `0` when no panic location is
+/// available.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_panic_column() -> u32 {
+  previous_crash_state()
+    .and_then(previous_rust_panic)
+    .map_or(0, |panic| panic.column)
+}
+
+/// Return the cached previous-launch name of the panicking thread as a pointer into process-owned
+/// storage, or null when the thread was unnamed.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_panic_thread_name() -> *const c_char {
+  previous_crash_state()
+    .and_then(previous_rust_panic)
+    .map_or(null(), |panic| c_string_or_null(panic.thread_name.as_ref()))
+}
+
+/// Return the cached previous-launch demangled C++ exception type as a pointer into process-owned
+/// storage, or null when the previous crash wasn't an uncaught C++ exception or `std::terminate`
+/// was called without one.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_cxx_exception_type() -> *const c_char {
+  previous_crash_state()
+    .and_then(previous_cxx_exception)
+    .map_or(null(), |exception| {
+      c_string_or_null(exception.type_name.as_ref())
+    })
+}
+
+/// Return the cached previous-launch C++ exception's `what()` as a pointer into process-owned
+/// storage, or null when the exception didn't derive from `std::exception`.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_cxx_exception_what() -> *const c_char {
+  previous_crash_state()
+    .and_then(previous_cxx_exception)
+    .map_or(null(), |exception| {
+      c_string_or_null(exception.what.as_ref())
+    })
+}
+
+/// Tell the crash reporter whether the app is in the foreground, so that a crash can be attributed
+/// to the state the app was in. Hosts should call this on every lifecycle transition.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_set_foreground(foreground: bool) {
+  process_state::set_foreground(foreground);
+}
+
+/// Return how long the previous launch had been running when it crashed, in milliseconds since
+/// `capture_bitdrift_crash_configure`. Returns `0` when no process state was captured.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_process_uptime_ms() -> u64 {
+  previous_process_state().map_or(0, |process_state| process_state.uptime_ms)
+}
+
+/// Return the previous launch's resident memory at crash time, in bytes. Returns `0` when no
+/// process state was captured or the value couldn't be read.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_process_resident_bytes() -> u64 {
+  previous_process_state().map_or(0, |process_state| process_state.resident_bytes)
+}
+
+/// Return the previous launch's thread count at crash time. Returns `0` when no process state was
+/// captured or the value couldn't be read.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_process_thread_count() -> u32 {
+  previous_process_state().map_or(0, |process_state| process_state.thread_count)
+}
+
+/// Return the previous launch's open file descriptor count at crash time. Returns `0` when no
+/// process state was captured or the value couldn't be read.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_process_open_fd_count() -> u32 {
+  previous_process_state().map_or(0, |process_state| process_state.open_fd_count)
+}
+
+/// Return whether the previous launch was in the foreground when it crashed: `1` for foreground,
+/// `2` for background and `0` when unknown.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_process_app_state() -> u8 {
+  previous_process_state().map_or(0, |process_state| process_state.app_state.into())
+}
+
+/// # Safety
+///
+/// `callback` must follow the async-signal-safety contract documented on `CrashCallback`, and
+/// `context` must stay valid for as long as the callback is registered.
+///
+/// Register a callback that is invoked whenever a crash is recorded, after the crash itself is
+/// written and before the record is committed. It receives a scratch region of up to
+/// `CRASH_CALLBACK_SCRATCH_CAPACITY` bytes inside the crash record to fill with host state, such as
+/// the current request id, and returns how many bytes it wrote. Those bytes are reported as the
+/// next launch's host data. Passing a null callback unregisters it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_set_crash_callback(
+  callback: Option<CrashCallback>,
+  context: *mut c_void,
+) {
+  unsafe {
+    crash_callback::set_crash_callback(callback, context);
   }
+}
+
+/// Return a pointer to the bytes the crash callback wrote when the previous launch crashed, or null
+/// when it wrote none. `capture_bitdrift_crash_last_host_data_len` returns their length.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_host_data() -> *const u8 {
+  previous_crash_state()
+    .and_then(|state| state.host_data.as_ref())
+    .map_or(null(), Vec::as_ptr)
+}
+
+/// Return the number of bytes the crash callback wrote when the previous launch crashed.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_host_data_len() -> u32 {
+  previous_crash_state()
+    .and_then(|state| state.host_data.as_ref())
+    .map_or(0, |host_data| {
+      u32::try_from(host_data.len()).unwrap_or(u32::MAX)
+    })
+}
+
+/// Return the number of committed crashes held in the crash history, including the previous
+/// launch's own crash. Entries are ordered newest first.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_count() -> u32 {
+  previous_crash_state().map_or(0, |state| {
+    u32::try_from(state.history.len()).unwrap_or(u32::MAX)
+  })
+}
+
+/// Return the timestamp of the crash history entry at `index`, or `0` when the index is out of
+/// range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_timestamp_at(index: u32) -> u64 {
+  crash_history_entry(index).map_or(0, |entry| entry.timestamp_secs)
+}
+
+/// Return the process id of the crash history entry at `index`, or `0` when the index is out of
+/// range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_pid_at(index: u32) -> u32 {
+  crash_history_entry(index).map_or(0, |entry| entry.pid)
+}
+
+/// Return the crash kind of the crash history entry at `index`, or `0` when the index is out of
+/// range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_history_kind_at(index: u32) -> u8 {
+  crash_history_entry(index).map_or(0, |entry| entry.kind as u8)
+}
+
+/// # Safety
+///
+/// `message` must be null or point to a valid null-terminated C string for the duration of the
+/// call.
+///
+/// Append a breadcrumb to the current run's breadcrumb ring. `level` is one of the
+/// `BreadcrumbLevel` values (0 = trace through 4 = error). Returns `false` if the level is unknown
+/// or the message is null or not valid UTF-8. Breadcrumbs appended before
+/// `capture_bitdrift_crash_configure` are dropped.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_add_breadcrumb(
+  level: u8,
+  message: *const c_char,
+) -> bool {
+  let Some(level) = BreadcrumbLevel::from_u8(level) else {
+    return false;
+  };
+  if message.is_null() {
+    return false;
+  }
+
+  let Ok(message) = unsafe { CStr::from_ptr(message) }.to_str() else {
+    return false;
+  };
+
+  breadcrumbs::record_breadcrumb(level, message);
+  true
+}
+
+/// Return the number of breadcrumbs recorded before the previous launch's crash. Breadcrumbs are
+/// ordered oldest first.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_breadcrumb_count() -> u32 {
+  previous_crash_state().map_or(0, |state| {
+    u32::try_from(state.breadcrumbs.len()).unwrap_or(u32::MAX)
+  })
+}
+
+/// Return the timestamp, in milliseconds since the Unix epoch, of the previous launch's breadcrumb
+/// at `index`, or `0` when the index is out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_breadcrumb_timestamp_ms_at(index: u32) -> u64 {
+  previous_breadcrumb(index).map_or(0, |breadcrumb| breadcrumb.timestamp_ms)
+}
+
+/// Return the level of the previous launch's breadcrumb at `index`, or `0` when the index is out
+/// of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_breadcrumb_level_at(index: u32) -> u8 {
+  previous_breadcrumb(index).map_or(0, |breadcrumb| breadcrumb.level as u8)
+}
 
-  let frame = &exception.call_stack.frames[index];
-  if frame.image_id[0] == 0 {
+/// Return the message of the previous launch's breadcrumb at `index` as a pointer into
+/// process-owned storage, or null when the index is out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_breadcrumb_message_at(index: u32) -> *const c_char {
+  previous_breadcrumb(index).map_or(null(), |breadcrumb| breadcrumb.message.as_ptr())
+}
+
+/// # Safety
+///
+/// `key` and `value` must be null or point to valid null-terminated C strings for the duration of
+/// the call.
+///
+/// Set the annotation `key` to `value` for the current run, replacing any value previously set for
+/// the key. Keys must be between 1 and 32 bytes long and values longer than 128 bytes are
+/// truncated. Returns `false` if either string is null or not valid UTF-8, if the key is invalid,
+/// if every annotation slot is in use, or if `capture_bitdrift_crash_configure` has not run yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_set_annotation(
+  key: *const c_char,
+  value: *const c_char,
+) -> bool {
+  if key.is_null() || value.is_null() {
+    return false;
+  }
+
+  let (Ok(key), Ok(value)) = (
+    unsafe { CStr::from_ptr(key) }.to_str(),
+    unsafe { CStr::from_ptr(value) }.to_str(),
+  ) else {
+    return false;
+  };
+
+  annotations::set_annotation(key, value)
+}
+
+/// # Safety
+///
+/// `key` must be null or point to a valid null-terminated C string for the duration of the call.
+///
+/// Remove the annotation `key` from the current run. Returns `false` if no annotation is set for
+/// the key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_remove_annotation(key: *const c_char) -> bool {
+  if key.is_null() {
+    return false;
+  }
+
+  let Ok(key) = unsafe { CStr::from_ptr(key) }.to_str() else {
+    return false;
+  };
+
+  annotations::remove_annotation(key)
+}
+
+/// Return the number of annotations that were set when the previous launch crashed.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_annotation_count() -> u32 {
+  previous_crash_state().map_or(0, |state| {
+    u32::try_from(state.annotations.len()).unwrap_or(u32::MAX)
+  })
+}
+
+/// Return the key of the previous launch's annotation at `index` as a pointer into process-owned
+/// storage, or null when the index is out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_annotation_key_at(index: u32) -> *const c_char {
+  previous_annotation(index).map_or(null(), |annotation| annotation.key.as_ptr())
+}
+
+/// Return the value of the previous launch's annotation at `index` as a pointer into process-owned
+/// storage, or null when the index is out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn capture_bitdrift_crash_last_annotation_value_at(index: u32) -> *const c_char {
+  previous_annotation(index).map_or(null(), |annotation| annotation.value.as_ptr())
+}
+
+/// # Safety
+///
+/// `key` must be null or point to a valid null-terminated C string for the duration of the call.
+///
+/// Return the value the previous launch had set for the annotation `key` as a pointer into
+/// process-owned storage, or null when the key was not set.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn capture_bitdrift_crash_last_annotation_value(
+  key: *const c_char,
+) -> *const c_char {
+  if key.is_null() {
     return null();
   }
 
-  frame.image_id.as_ptr().cast::<c_char>()
+  let key = unsafe { CStr::from_ptr(key) };
+  previous_crash_state()
+    .and_then(|state| {
+      state
+        .annotations
+        .iter()
+        .find(|annotation| annotation.key.as_c_str() == key)
+    })
+    .map_or(null(), |annotation| annotation.value.as_ptr())
 }
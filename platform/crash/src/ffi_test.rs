@@ -7,57 +7,288 @@
 
 #![allow(clippy::unwrap_used)]
 
-use super::previous_nsexception;
+use super::{
+  c_string_or_null,
+  capture_bitdrift_crash_free_previous_state_json,
+  capture_bitdrift_crash_free_symbolication_json,
+  capture_bitdrift_crash_free_symbolizer_input,
+  previous_nsexception,
+  previous_rust_panic,
+  previous_signal,
+  previous_state_json,
+  symbolication_json,
+  symbolizer_input,
+};
 use crate::previous::{
-  NSExceptionCallStack,
+  CallStack,
   NSExceptionCrashInfo,
-  NSExceptionStackFrame,
   PreviousCrashDetails,
   PreviousCrashState,
+  RustPanicCrashInfo,
+  SignalCrashInfo,
+  StackFrame,
 };
-use crate::schema;
+use crate::symbolication::SymbolizerFormat;
+use std::ffi::CStr;
 
 #[test]
 fn previous_nsexception_returns_exception_details() {
-  let mut return_addresses = [0; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize];
-  return_addresses[.. 2].copy_from_slice(&[0x1234, 0x5678]);
   let state = PreviousCrashState {
     did_crash: true,
     details: PreviousCrashDetails::NSException(Box::new(NSExceptionCrashInfo {
-      name: [0; schema::NS_EXCEPTION_NAME_CAPACITY],
-      reason: [0; schema::NS_EXCEPTION_REASON_CAPACITY],
-      call_stack: NSExceptionCallStack {
-        frame_count: 2,
-        return_addresses,
-        frames: {
-          let mut frames = std::array::from_fn(|_| NSExceptionStackFrame::default());
-          frames[0].return_address = 0x1234;
-          frames[0].image_load_address = 0x1000;
-          frames[0].binary_name[.. 6].copy_from_slice(b"MyApp\0");
-          frames[0].image_id[.. schema::NS_EXCEPTION_IMAGE_ID_CAPACITY]
-            .copy_from_slice(b"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0\0");
-          frames[1].return_address = 0x5678;
-          frames
-        },
+      name: None,
+      reason: None,
+      call_stack: CallStack {
+        return_addresses: vec![0x1234, 0x5678],
+        frames: vec![
+          StackFrame {
+            return_address: 0x1234,
+            image_load_address: 0x1000,
+            binary_name: Some(c"MyApp".to_owned()),
+            image_id: Some(c"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0".to_owned()),
+          },
+          StackFrame {
+            return_address: 0x5678,
+            ..StackFrame::default()
+          },
+        ],
       },
     })),
     ..PreviousCrashState::default()
   };
 
   let exception = previous_nsexception(&state).unwrap();
-  assert_eq!(2, exception.call_stack.frame_count);
+  assert_eq!(2, exception.call_stack.frames.len());
   assert_eq!(
     &[0x1234, 0x5678],
-    &exception.call_stack.return_addresses[.. 2]
+    exception.call_stack.return_addresses.as_slice()
   );
   assert_eq!(0x1000, exception.call_stack.frames[0].image_load_address);
   assert_eq!(
-    &exception.call_stack.frames[0].binary_name[.. 6],
-    b"MyApp\0"
+    exception.call_stack.frames[0].binary_name.as_deref(),
+    Some(c"MyApp")
   );
+  assert!(c_string_or_null(exception.name.as_ref()).is_null());
 }
 
 #[test]
 fn previous_nsexception_returns_none_for_non_exception_state() {
   assert!(previous_nsexception(&PreviousCrashState::default()).is_none());
 }
+
+#[test]
+fn previous_signal_returns_signal_details() {
+  let state = PreviousCrashState {
+    did_crash: true,
+    details: PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: 11,
+      code: 1,
+      fault_address: 0xdead_beef,
+      thread_id: 42,
+      ..SignalCrashInfo::default()
+    }),
+    ..PreviousCrashState::default()
+  };
+
+  let signal = previous_signal(&state).unwrap();
+  assert_eq!(11, signal.signal);
+  assert_eq!(0xdead_beef, signal.fault_address);
+  assert!(previous_nsexception(&state).is_none());
+}
+
+#[test]
+fn previous_rust_panic_returns_panic_details() {
+  let panic = RustPanicCrashInfo {
+    message: Some(c"boom".to_owned()),
+    line: 12,
+    ..RustPanicCrashInfo::default()
+  };
+  let state = PreviousCrashState {
+    did_crash: true,
+    details: PreviousCrashDetails::RustPanic(Box::new(panic)),
+    ..PreviousCrashState::default()
+  };
+
+  let panic = previous_rust_panic(&state).unwrap();
+  assert_eq!(12, panic.line);
+  assert!(!c_string_or_null(panic.message.as_ref()).is_null());
+  assert!(c_string_or_null(panic.thread_name.as_ref()).is_null());
+  assert!(previous_signal(&state).is_none());
+}
+
+#[test]
+fn previous_state_json_round_trips_through_c_string() {
+  let state = PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: 1_700_000_000,
+    details: PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: 11,
+      ..SignalCrashInfo::default()
+    }),
+    ..PreviousCrashState::default()
+  };
+
+  let json = previous_state_json(&state);
+  assert!(!json.is_null());
+  let document: serde_json::Value =
+    serde_json::from_slice(unsafe { CStr::from_ptr(json) }.to_bytes()).unwrap();
+  assert_eq!(document["timestamp_secs"], 1_700_000_000);
+  assert_eq!(document["details"]["signal"], 11);
+
+  unsafe {
+    capture_bitdrift_crash_free_previous_state_json(json);
+    capture_bitdrift_crash_free_previous_state_json(std::ptr::null_mut());
+  }
+}
+
+fn signal_with_native_stack() -> PreviousCrashState {
+  PreviousCrashState {
+    did_crash: true,
+    details: PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: 11,
+      call_stack: CallStack {
+        return_addresses: vec![0x1010],
+        frames: vec![StackFrame {
+          return_address: 0x1010,
+          image_load_address: 0x1000,
+          binary_name: Some(c"libapp.so".to_owned()),
+          image_id: None,
+        }],
+      },
+      ..SignalCrashInfo::default()
+    }),
+    ..PreviousCrashState::default()
+  }
+}
+
+#[test]
+fn symbolication_json_requires_native_stack() {
+  let panic = PreviousCrashState {
+    did_crash: true,
+    details: PreviousCrashDetails::RustPanic(Box::default()),
+    ..PreviousCrashState::default()
+  };
+
+  let json = symbolication_json(&signal_with_native_stack());
+  let document: serde_json::Value =
+    serde_json::from_slice(unsafe { CStr::from_ptr(json) }.to_bytes()).unwrap();
+  assert_eq!(document["frames"].as_array().unwrap().len(), 1);
+  assert!(symbolication_json(&panic).is_null());
+
+  unsafe {
+    capture_bitdrift_crash_free_symbolication_json(json);
+    capture_bitdrift_crash_free_symbolication_json(std::ptr::null_mut());
+  }
+}
+
+#[test]
+fn symbolizer_input_requires_known_format_and_native_stack() {
+  let signal = signal_with_native_stack();
+  let panic = PreviousCrashState {
+    did_crash: true,
+    details: PreviousCrashDetails::RustPanic(Box::default()),
+    ..PreviousCrashState::default()
+  };
+
+  let input = symbolizer_input(&signal, SymbolizerFormat::LlvmSymbolizer as u8);
+  assert_eq!(
+    unsafe { CStr::from_ptr(input) }.to_bytes(),
+    b"\"libapp.so\" 0x10\n"
+  );
+  assert!(symbolizer_input(&signal, 7).is_null());
+  assert!(symbolizer_input(&panic, SymbolizerFormat::LlvmSymbolizer as u8).is_null());
+
+  unsafe {
+    capture_bitdrift_crash_free_symbolizer_input(input);
+    capture_bitdrift_crash_free_symbolizer_input(std::ptr::null_mut());
+  }
+}
+
+#[cfg(target_os = "linux")]
+const CHILD_STATE_PATH_ENV: &str = "BD_CRASH_FFI_TEST_STATE_PATH";
+#[cfg(target_os = "linux")]
+const CHILD_LAUNCH_ENV: &str = "BD_CRASH_FFI_TEST_LAUNCH";
+#[cfg(target_os = "linux")]
+const CHILD_PREVIOUS_TERMINATION_ENV: &str = "BD_CRASH_FFI_TEST_PREVIOUS_TERMINATION";
+
+// Only does anything when spawned by `previous_launch_is_classified_on_the_next_launch`. Each spawn
+// is one launch of a host that configures and starts the crash reporter the way the Android
+// bindings do, checks how it reads back the previous launch, and then crashes, exits after marking
+// a clean shutdown, or exits without one.
+#[cfg(target_os = "linux")]
+#[test]
+fn crash_reporter_child_process() {
+  use super::capture_bitdrift_crash_last_termination_kind;
+  use crate::previous::TerminationKind;
+
+  let (Some(path), Ok(launch), Ok(previous)) = (
+    std::env::var_os(CHILD_STATE_PATH_ENV),
+    std::env::var(CHILD_LAUNCH_ENV),
+    std::env::var(CHILD_PREVIOUS_TERMINATION_ENV),
+  ) else {
+    return;
+  };
+  assert_eq!(super::did_crash_last_launch(), None);
+  assert!(super::configure(std::path::Path::new(&path), None));
+  assert!(super::start());
+
+  let previous = match previous.as_str() {
+    "unknown" => TerminationKind::Unknown,
+    "clean_exit" => TerminationKind::CleanExit,
+    "crash" => TerminationKind::Crash,
+    _ => TerminationKind::UncleanTermination,
+  };
+  assert_eq!(
+    capture_bitdrift_crash_last_termination_kind(),
+    previous as u8
+  );
+  assert_eq!(
+    super::did_crash_last_launch(),
+    Some(previous == TerminationKind::Crash)
+  );
+
+  if launch == "crash" {
+    std::hint::black_box(unsafe { std::ptr::read_volatile(std::ptr::null::<u64>()) });
+  } else if launch == "clean_exit" {
+    super::mark_clean_shutdown();
+  }
+}
+
+#[cfg(target_os = "linux")]
+fn run_child_launch(
+  path: &std::path::Path,
+  previous: &str,
+  launch: &str,
+) -> std::process::ExitStatus {
+  std::process::Command::new(std::env::current_exe().unwrap())
+    .args([
+      "--exact",
+      "ffi::tests::crash_reporter_child_process",
+      "--nocapture",
+    ])
+    .env(CHILD_STATE_PATH_ENV, path)
+    .env(CHILD_PREVIOUS_TERMINATION_ENV, previous)
+    .env(CHILD_LAUNCH_ENV, launch)
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .status()
+    .unwrap()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn previous_launch_is_classified_on_the_next_launch() {
+  use std::os::unix::process::ExitStatusExt as _;
+
+  let directory = tempfile::tempdir().unwrap();
+  let path = directory.path().join("crash_state");
+
+  assert_eq!(
+    run_child_launch(&path, "unknown", "crash").signal(),
+    Some(libc::SIGSEGV)
+  );
+  // Exiting without marking a clean shutdown looks like the process was killed.
+  assert!(run_child_launch(&path, "crash", "exit").success());
+  assert!(run_child_launch(&path, "unclean_termination", "clean_exit").success());
+  assert!(run_child_launch(&path, "clean_exit", "exit").success());
+}
@@ -0,0 +1,286 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+//! Offline decoding of crash state files, used by the `bd-crash-inspect` binary to debug state
+//! files sent in from the field.
+
+#[cfg(test)]
+#[path = "./inspect_test.rs"]
+mod tests;
+
+use crate::export::{crash_json, hex};
+use crate::previous::{self, RecordValidation};
+use crate::schema::{
+  self,
+  CrashHistoryHeader,
+  CrashKind,
+  CrashRecord,
+  CrashRecordHeader,
+  DoubleBufferHeader,
+  DoubleBufferSlot,
+  LegacyCrashRecord,
+  RecordState,
+};
+use serde_json::{Value, json};
+use std::mem::{offset_of, size_of};
+use std::ptr::read_unaligned;
+
+/// Decodes a crash state file into JSON. Every record slot is listed with its header, timestamp,
+/// pid and the reasons the crash reporter would reject it, if any. The payload of a rejected
+/// record is only decoded when `raw` is set, and only as far as its structure allows.
+#[must_use]
+pub fn inspect_state_file(bytes: &[u8], raw: bool) -> Value {
+  let header = read_pod::<CrashHistoryHeader>(bytes).unwrap_or_default();
+  if header.magic == schema::DOUBLE_BUFFER_MAGIC {
+    return inspect_double_buffered_file(bytes, raw);
+  }
+  if header.magic != schema::HISTORY_MAGIC {
+    // Files written before the history ring was introduced hold a single record at offset 0.
+    return json!({
+      "layout": "single_record",
+      "records": [inspect_record(bytes, raw)],
+    });
+  }
+
+  let slot_len = header.slot_len as usize;
+  let mut records = Vec::new();
+  for index in 0 .. header.capacity {
+    let Some(slot) = slot_bytes(bytes, index, slot_len) else {
+      break;
+    };
+
+    let mut record = inspect_record(slot, raw);
+    record["slot"] = json!(index);
+    record["is_head"] = json!(index == header.head);
+    records.push(record);
+  }
+
+  json!({
+    "layout": "history",
+    "history": {
+      "magic": hex(header.magic),
+      "version": header.version,
+      "capacity": header.capacity,
+      "head": header.head,
+      "slot_len": header.slot_len,
+    },
+    "records": records,
+  })
+}
+
+fn inspect_double_buffered_file(bytes: &[u8], raw: bool) -> Value {
+  let header = read_pod::<DoubleBufferHeader>(bytes).unwrap_or_default();
+  let generation = |index: u32, field_offset: usize| {
+    bytes
+      .get(schema::double_buffer_slot_offset(index) + field_offset ..)
+      .and_then(read_pod::<u64>)
+  };
+
+  let mut slots = Vec::new();
+  for index in 0 .. schema::DOUBLE_BUFFER_SLOT_COUNT {
+    let record_offset =
+      schema::double_buffer_slot_offset(index) + offset_of!(DoubleBufferSlot, record);
+    let Some(record_bytes) = bytes.get(record_offset ..) else {
+      break;
+    };
+
+    let generation_start = generation(index, offset_of!(DoubleBufferSlot, generation));
+    let generation_end = generation(index, offset_of!(DoubleBufferSlot, generation_end));
+    let mut record = inspect_record(record_bytes, raw);
+    record["slot"] = json!(index);
+    record["generation"] = json!(generation_start);
+    record["generation_end"] = json!(generation_end);
+    // Slots whose priming didn't complete are ignored by the crash reporter.
+    record["slot_valid"] =
+      json!(generation_start.is_some_and(|start| start != 0 && Some(start) == generation_end));
+    slots.push(record);
+  }
+
+  json!({
+    "layout": "double_buffered",
+    "double_buffer": {
+      "magic": hex(header.magic),
+      "version": header.version,
+      "slot_len": header.slot_len,
+    },
+    "records": slots,
+  })
+}
+
+fn slot_bytes(bytes: &[u8], index: u32, slot_len: usize) -> Option<&[u8]> {
+  if slot_len == 0 {
+    return None;
+  }
+
+  let start = (index as usize)
+    .checked_mul(slot_len)?
+    .checked_add(size_of::<CrashHistoryHeader>())?;
+  if start >= bytes.len() {
+    return None;
+  }
+
+  // The last slot of a truncated file is inspected as far as it goes.
+  bytes.get(start .. bytes.len().min(start.saturating_add(slot_len)))
+}
+
+fn inspect_record(bytes: &[u8], raw: bool) -> Value {
+  let Some(header) = read_pod::<CrashRecordHeader>(bytes) else {
+    return json!({
+      "accepted": false,
+      "rejections": [format!(
+        "truncated record: {} of {} header bytes",
+        bytes.len(),
+        size_of::<CrashRecordHeader>()
+      )],
+      "payload": null,
+    });
+  };
+
+  let checksummed_len = record_layout(header.version).map(|(len, _)| len);
+  let computed_crc32 = checksummed_len
+    .and_then(|len| bytes.get(.. len))
+    .map(schema::compute_checksum);
+  let rejections = rejections(bytes, &header);
+  let validation = if raw {
+    RecordValidation::Lenient
+  } else {
+    RecordValidation::Strict
+  };
+  let state = previous::read_record(bytes, validation);
+  let timestamp_secs = bytes
+    .get(offset_of!(CrashRecord, timestamp_secs) ..)
+    .and_then(read_pod::<u64>);
+  let pid = bytes
+    .get(offset_of!(CrashRecord, pid) ..)
+    .and_then(read_pod::<u32>);
+
+  // The legacy layout shares the current layout's prefix, so the timestamp and pid are at the same
+  // offsets in both versions.
+  json!({
+    "header": {
+      "magic": hex(header.magic),
+      "version": header.version,
+      "state": record_state_name(header.record_state),
+      "kind": crash_kind_name(header.crash_kind),
+      "crc32": hex(header.crc32),
+      "computed_crc32": computed_crc32.map(hex),
+      "crc_match": computed_crc32.map(|crc32| crc32 == header.crc32),
+    },
+    "timestamp_secs": timestamp_secs,
+    "pid": pid,
+    "accepted": rejections.is_empty(),
+    "rejections": rejections,
+    "payload": state.did_crash.then(|| crash_json(&state)),
+  })
+}
+
+// Explains why the crash reporter would not report the record, in the order it checks.
+fn rejections(bytes: &[u8], header: &CrashRecordHeader) -> Vec<String> {
+  let mut rejections = Vec::new();
+  if *header == CrashRecordHeader::default() {
+    rejections.push("slot is empty".to_string());
+    return rejections;
+  }
+
+  if header.magic != schema::MAGIC {
+    rejections.push(format!("unexpected magic {}", hex(header.magic)));
+    return rejections;
+  }
+
+  let Some((len, crash_kinds)) = record_layout(header.version) else {
+    rejections.push(format!("unsupported version {}", header.version));
+    return rejections;
+  };
+
+  let Some(checksummed_bytes) = bytes.get(.. len) else {
+    rejections.push(format!("truncated record: {} of {len} bytes", bytes.len()));
+    return rejections;
+  };
+
+  if header.record_state != RecordState::Committed {
+    rejections.push(format!(
+      "record is {} rather than Committed",
+      record_state_name(header.record_state)
+    ));
+  }
+
+  let computed_crc32 = schema::compute_checksum(checksummed_bytes);
+  if header.crc32 != computed_crc32 {
+    rejections.push(format!(
+      "crc32 mismatch: stored {}, computed {}",
+      hex(header.crc32),
+      hex(computed_crc32)
+    ));
+  }
+
+  if !crash_kinds.iter().any(|kind| header.crash_kind == *kind) {
+    rejections.push(format!(
+      "crash kind {} is unknown to version {}",
+      header.crash_kind, header.version
+    ));
+  }
+
+  if header.version == schema::VERSION {
+    let arena_len = read_pod::<u32>(&bytes[offset_of!(CrashRecord, arena_len) ..])
+      .map_or(0, |arena_len| arena_len as usize);
+    if arena_len > schema::ARENA_CAPACITY {
+      rejections.push(format!(
+        "arena_len {arena_len} exceeds the arena capacity of {}",
+        schema::ARENA_CAPACITY
+      ));
+    } else if bytes.len() < schema::CRASH_RECORD_FIXED_LEN + arena_len {
+      rejections.push(format!(
+        "truncated arena: {} of {} bytes",
+        bytes.len().saturating_sub(schema::CRASH_RECORD_FIXED_LEN),
+        arena_len
+      ));
+    }
+  }
+
+  rejections
+}
+
+// Returns the checksummed length and the crash kinds of the layout used by `version`.
+const fn record_layout(version: u32) -> Option<(usize, &'static [CrashKind])> {
+  match version {
+    schema::VERSION => Some((schema::CRASH_RECORD_FIXED_LEN, &schema::CRASH_KINDS)),
+    schema::LEGACY_VERSION => Some((size_of::<LegacyCrashRecord>(), &schema::LEGACY_CRASH_KINDS)),
+    _ => None,
+  }
+}
+
+fn record_state_name(value: u8) -> String {
+  [
+    RecordState::Empty,
+    RecordState::Writing,
+    RecordState::Committed,
+  ]
+  .into_iter()
+  .find(|state| value == *state)
+  .map_or_else(|| format!("Unknown({value})"), |state| format!("{state:?}"))
+}
+
+fn crash_kind_name(value: u8) -> String {
+  [
+    CrashKind::None,
+    CrashKind::NSException,
+    CrashKind::Signal,
+    CrashKind::RustPanic,
+    CrashKind::CxxException,
+  ]
+  .into_iter()
+  .find(|kind| value == *kind)
+  .map_or_else(|| format!("Unknown({value})"), |kind| format!("{kind:?}"))
+}
+
+const fn read_pod<T: Copy>(bytes: &[u8]) -> Option<T> {
+  if bytes.len() < size_of::<T>() {
+    return None;
+  }
+
+  Some(unsafe { read_unaligned(bytes.as_ptr().cast::<T>()) })
+}
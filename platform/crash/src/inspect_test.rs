@@ -0,0 +1,233 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::inspect_state_file;
+use crate::previous::{
+  Annotation,
+  CrashKind,
+  PreviousCrashDetails,
+  PreviousCrashState,
+  SignalCrashInfo,
+};
+use crate::schema::{
+  self,
+  CrashHistoryHeader,
+  CrashRecord,
+  DoubleBufferHeader,
+  DoubleBufferSlot,
+  RecordState,
+};
+use crate::writer::rewrite_previous_state;
+use serde_json::json;
+use std::ffi::CString;
+use std::mem::{offset_of, size_of};
+use std::ptr::write_unaligned;
+use std::slice::from_raw_parts;
+
+// Encodes a committed signal crash.
+fn signal_record_bytes() -> Vec<u8> {
+  let mut record = Box::<CrashRecord>::default();
+  rewrite_previous_state(
+    &mut record,
+    &PreviousCrashState {
+      did_crash: true,
+      timestamp_secs: 1_700_000_000,
+      pid: 42,
+      kind: CrashKind::Signal,
+      details: PreviousCrashDetails::Signal(SignalCrashInfo {
+        signal: libc::SIGSEGV,
+        code: 1,
+        fault_address: 0xdead,
+        thread_id: 7,
+        ..SignalCrashInfo::default()
+      }),
+      annotations: vec![Annotation {
+        key: CString::new("screen").unwrap_or_default(),
+        value: CString::new("checkout").unwrap_or_default(),
+      }],
+      ..Default::default()
+    },
+  );
+  unsafe { from_raw_parts((&raw const *record).cast::<u8>(), size_of::<CrashRecord>()) }.to_vec()
+}
+
+// Builds a state file holding a committed signal crash in slot 1, the head.
+fn state_file() -> Vec<u8> {
+  let mut bytes = vec![0; schema::history_file_len(schema::CRASH_HISTORY_CAPACITY)];
+  let header = CrashHistoryHeader {
+    magic: schema::HISTORY_MAGIC,
+    version: schema::HISTORY_VERSION,
+    capacity: schema::CRASH_HISTORY_CAPACITY,
+    head: 1,
+    slot_len: u32::try_from(size_of::<CrashRecord>()).unwrap_or_default(),
+  };
+  unsafe {
+    write_unaligned(bytes.as_mut_ptr().cast::<CrashHistoryHeader>(), header);
+  }
+
+  let record_bytes = signal_record_bytes();
+  let offset = schema::history_slot_offset(1);
+  bytes[offset .. offset + record_bytes.len()].copy_from_slice(&record_bytes);
+  bytes
+}
+
+fn set_record_state(bytes: &mut [u8], state: RecordState) {
+  bytes
+    [schema::history_slot_offset(1) + offset_of!(crate::schema::CrashRecordHeader, record_state)] =
+    state.into();
+}
+
+#[test]
+fn dumps_committed_record() {
+  let report = inspect_state_file(&state_file(), false);
+  assert_eq!(report["layout"], "history");
+  assert_eq!(report["history"]["head"], 1);
+
+  let records = report["records"].as_array().cloned().unwrap_or_default();
+  assert_eq!(records.len(), 4);
+  assert_eq!(records[0]["rejections"], json!(["slot is empty"]));
+  assert_eq!(records[0]["payload"], json!(null));
+
+  let record = &records[1];
+  assert_eq!(record["is_head"], true);
+  assert_eq!(record["header"]["state"], "Committed");
+  assert_eq!(record["header"]["kind"], "Signal");
+  assert_eq!(record["header"]["crc_match"], true);
+  assert_eq!(record["timestamp_secs"], 1_700_000_000);
+  assert_eq!(record["pid"], 42);
+  assert_eq!(record["accepted"], true);
+  assert_eq!(record["rejections"], json!([]));
+  assert_eq!(
+    record["payload"]["details"],
+    json!({
+      "signal": libc::SIGSEGV,
+      "code": 1,
+      "fault_address": "0xdead",
+      "thread_id": 7,
+      "frames": [],
+    })
+  );
+  assert_eq!(
+    record["payload"]["annotations"],
+    json!([{ "key": "screen", "value": "checkout" }])
+  );
+}
+
+#[test]
+fn raw_mode_decodes_uncommitted_record() {
+  let mut bytes = state_file();
+  set_record_state(&mut bytes, RecordState::Writing);
+
+  let report = inspect_state_file(&bytes, false);
+  let record = &report["records"][1];
+  assert_eq!(record["accepted"], false);
+  assert_eq!(
+    record["rejections"],
+    json!(["record is Writing rather than Committed"])
+  );
+  assert_eq!(record["payload"], json!(null));
+
+  let report = inspect_state_file(&bytes, true);
+  let record = &report["records"][1];
+  assert_eq!(record["accepted"], false);
+  assert_eq!(record["payload"]["kind"], "Signal");
+  assert_eq!(record["payload"]["details"]["code"], 1);
+}
+
+#[test]
+fn raw_mode_decodes_record_with_crc32_mismatch() {
+  let mut bytes = state_file();
+  bytes[schema::history_slot_offset(1) + offset_of!(CrashRecord, pid)] ^= 0xff;
+
+  let report = inspect_state_file(&bytes, false);
+  let record = &report["records"][1];
+  assert_eq!(record["header"]["crc_match"], false);
+  let rejections = record["rejections"].as_array().cloned().unwrap_or_default();
+  assert_eq!(rejections.len(), 1);
+  assert!(
+    rejections[0]
+      .as_str()
+      .is_some_and(|rejection| rejection.starts_with("crc32 mismatch"))
+  );
+  assert_eq!(record["payload"], json!(null));
+
+  let report = inspect_state_file(&bytes, true);
+  assert_eq!(
+    report["records"][1]["payload"]["details"]["signal"],
+    libc::SIGSEGV
+  );
+}
+
+#[test]
+fn dumps_single_record_file() {
+  let report = inspect_state_file(
+    include_bytes!("../fixtures/records/v1_nsexception.bin"),
+    false,
+  );
+  assert_eq!(report["layout"], "single_record");
+  let record = &report["records"][0];
+  assert_eq!(record["header"]["version"], 1);
+  assert_eq!(record["accepted"], true);
+  assert_eq!(record["payload"]["kind"], "NSException");
+}
+
+#[test]
+fn reports_truncated_file() {
+  let bytes = state_file();
+  let report = inspect_state_file(&bytes[.. schema::history_slot_offset(1) + 16], true);
+  let records = report["records"].as_array().cloned().unwrap_or_default();
+  assert_eq!(records.len(), 2);
+  assert_eq!(records[1]["accepted"], false);
+  assert_eq!(records[1]["payload"], json!(null));
+}
+
+#[test]
+fn dumps_double_buffered_file() {
+  let mut bytes = vec![0; schema::double_buffer_file_len()];
+  let header = DoubleBufferHeader {
+    magic: schema::DOUBLE_BUFFER_MAGIC,
+    version: schema::DOUBLE_BUFFER_VERSION,
+    slot_len: u32::try_from(size_of::<DoubleBufferSlot>()).unwrap_or_default(),
+  };
+  unsafe {
+    write_unaligned(bytes.as_mut_ptr().cast::<DoubleBufferHeader>(), header);
+  }
+  let set_generations = |bytes: &mut [u8], index: u32, start: u64, end: u64| {
+    let offset = schema::double_buffer_slot_offset(index);
+    unsafe {
+      write_unaligned(
+        bytes[offset + offset_of!(DoubleBufferSlot, generation) ..]
+          .as_mut_ptr()
+          .cast::<u64>(),
+        start,
+      );
+      write_unaligned(
+        bytes[offset + offset_of!(DoubleBufferSlot, generation_end) ..]
+          .as_mut_ptr()
+          .cast::<u64>(),
+        end,
+      );
+    }
+  };
+  // Slot 0's priming was interrupted, while slot 1 holds the previous run's crash.
+  set_generations(&mut bytes, 0, 3, 0);
+  set_generations(&mut bytes, 1, 2, 2);
+  let record_bytes = signal_record_bytes();
+  let offset = schema::double_buffer_slot_offset(1) + offset_of!(DoubleBufferSlot, record);
+  bytes[offset .. offset + record_bytes.len()].copy_from_slice(&record_bytes);
+
+  let report = inspect_state_file(&bytes, false);
+  assert_eq!(report["layout"], "double_buffered");
+  let records = report["records"].as_array().cloned().unwrap_or_default();
+  assert_eq!(records.len(), 2);
+  assert_eq!(records[0]["slot_valid"], false);
+  assert_eq!(records[0]["generation"], 3);
+  assert_eq!(records[1]["slot_valid"], true);
+  assert_eq!(records[1]["generation"], 2);
+  assert_eq!(records[1]["accepted"], true);
+  assert_eq!(records[1]["payload"]["kind"], "Signal");
+}
@@ -19,12 +19,40 @@
   clippy::unwrap_used
 )]
 
+mod annotations;
+mod breadcrumbs;
 mod coordinator;
+mod crash_callback;
+mod crash_log;
+mod crash_loop;
+mod export;
 mod ffi;
+pub mod inspect;
 mod monitors;
 mod previous;
+mod process_state;
+mod report;
 mod schema;
+mod session;
 mod store;
+mod symbolication;
 #[cfg(test)]
 mod test_support;
 mod writer;
+
+pub use annotations::{remove_annotation, set_annotation};
+pub use breadcrumbs::{BreadcrumbLevel, record_breadcrumb};
+pub use crash_callback::{CRASH_CALLBACK_SCRATCH_CAPACITY, CrashCallback, set_crash_callback};
+pub use crash_log::{
+  CrashLoggerToken,
+  PreviousLaunchCrash,
+  attach_crash_logger,
+  detach_crash_logger,
+};
+pub use crash_loop::{
+  consecutive_launch_crashes,
+  reset_consecutive_launch_crashes,
+  set_crash_loop_window,
+};
+pub use ffi::{configure, did_crash_last_launch, mark_clean_shutdown, start};
+pub use process_state::set_foreground;
@@ -5,38 +5,51 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
+#[cfg(not(target_vendor = "apple"))]
+mod modules;
 #[cfg(target_vendor = "apple")]
 mod nsexception;
+mod panic;
+#[cfg(not(target_vendor = "apple"))]
+mod signal;
+#[cfg(not(target_vendor = "apple"))]
+mod terminate;
+#[cfg(not(target_vendor = "apple"))]
+mod unwind;
 
 // Abstracts process-global crash monitor lifecycle for the current platform. `install` registers
 // the platform-specific hook, and `uninstall` restores the prior process state when possible.
-// Apple-only for now; widen this cfg when non-Apple monitors are introduced.
-#[cfg(target_vendor = "apple")]
 trait Monitor {
   fn install(&self) -> bool;
   fn uninstall(&self);
 }
 
-// Install every crash monitor supported on the current platform.
+// Install every crash monitor supported on the current platform. Rust panics are captured
+// everywhere.
 #[cfg(target_vendor = "apple")]
 pub(crate) fn install() -> bool {
-  nsexception::NSExceptionMonitor.install()
+  nsexception::NSExceptionMonitor.install() && panic::PanicMonitor.install()
 }
 
-// Non-Apple builds currently have no crash monitor implementation in this crate, so installation
-// is a no-op and reports failure to install any monitor. This will change in the future.
+// Non-Apple builds capture fatal POSIX signals, uncaught C++ exceptions and Rust panics.
 #[cfg(not(target_vendor = "apple"))]
 pub(crate) fn install() -> bool {
-  false
+  signal::SignalMonitor.install()
+    && terminate::TerminateMonitor.install()
+    && panic::PanicMonitor.install()
 }
 
 // Uninstall every crash monitor that may have been registered by `install()`.
 #[cfg(target_vendor = "apple")]
 pub(crate) fn uninstall() {
+  panic::PanicMonitor.uninstall();
   nsexception::NSExceptionMonitor.uninstall();
 }
 
-// Non-Apple builds currently have no crash monitor implementation to tear down.
-// This will change in the future.
+// Uninstall the POSIX signal, terminate and panic monitors registered by `install()`.
 #[cfg(not(target_vendor = "apple"))]
-pub(crate) fn uninstall() {}
+pub(crate) fn uninstall() {
+  panic::PanicMonitor.uninstall();
+  terminate::TerminateMonitor.uninstall();
+  signal::SignalMonitor.uninstall();
+}
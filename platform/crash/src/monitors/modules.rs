@@ -0,0 +1,346 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./modules_test.rs"]
+mod tests;
+
+use crate::writer::StackFrameRecord;
+use libc::{c_int, c_void, dl_phdr_info, size_t};
+use std::ffi::CStr;
+use std::fmt::Write as _;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const NT_GNU_BUILD_ID: u32 = 3;
+
+// How often the loaded images are checked for changes, e.g. after Android's `System.loadLibrary`.
+// A crash in an image loaded since the last refresh records its frames without an image.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+// How long a replaced map is kept before it's freed. Crash handlers resolve a stack against a map
+// within microseconds of loading it, so this only has to outlast a handler that loaded the map just
+// before it was replaced.
+const RETIRED_MAP_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+// The module map resolved against by the crash handlers, which never allocate or walk the image
+// list themselves. Replaced maps are retired rather than freed straight away, since a handler
+// running on another thread may still be reading one.
+static MODULE_MAP: AtomicPtr<ModuleMap> = AtomicPtr::new(null_mut());
+static RETIRED_MAPS: Mutex<Vec<RetiredMap>> = Mutex::new(Vec::new());
+static REFRESHER: Mutex<Option<Refresher>> = Mutex::new(None);
+
+struct ModuleMap {
+  modules: Vec<Module>,
+  // Identifies the set of images the map was built from, see `LoadedImages`.
+  images: LoadedImages,
+}
+
+struct RetiredMap {
+  _map: Box<ModuleMap>,
+  retired_at: Instant,
+}
+
+// A loaded ELF image, as needed to symbolicate a return address on the server.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Module {
+  // Bounds of the image's `PT_LOAD` segments in memory.
+  pub(crate) start: u64,
+  pub(crate) end: u64,
+  // Address the ELF header is mapped at, which is what symbolicators subtract from a frame.
+  pub(crate) load_address: u64,
+  pub(crate) path: String,
+  // Lowercase hex GNU build-id, when the image carries one.
+  pub(crate) image_id: Option<String>,
+}
+
+// Captures the loaded images and keeps the map up to date on a background thread until
+// `stop_refreshing()`. Walking the image list takes the loader lock and allocates, so this happens
+// ahead of time rather than in the crash handlers.
+pub(crate) fn start_refreshing() {
+  refresh();
+  let mut refresher = refresher_lock();
+  if refresher.is_none() {
+    *refresher = Refresher::start(REFRESH_INTERVAL);
+  }
+}
+
+// Stops refreshing the map. The last map stays published.
+pub(crate) fn stop_refreshing() {
+  let refresher = refresher_lock().take();
+  drop(refresher);
+}
+
+// Publishes a new map if images were loaded or unloaded since the current one was captured, and
+// frees the maps that were retired long enough ago.
+pub(crate) fn refresh() {
+  let images = LoadedImages::current();
+  let current = unsafe { MODULE_MAP.load(Ordering::Acquire).as_ref() };
+  if current.is_none_or(|current| current.images != images) {
+    let mut modules = loaded_modules();
+    modules.sort_unstable_by_key(|module| module.start);
+    let map = Box::into_raw(Box::new(ModuleMap { modules, images }));
+    let previous = MODULE_MAP.swap(map, Ordering::AcqRel);
+    if !previous.is_null() {
+      retired_maps_lock().push(RetiredMap {
+        _map: unsafe { Box::from_raw(previous) },
+        retired_at: Instant::now(),
+      });
+    }
+  }
+
+  retired_maps_lock().retain(|map| map.retired_at.elapsed() < RETIRED_MAP_GRACE_PERIOD);
+}
+
+// Finds the image containing `address` in the current map. Async-signal-safe.
+pub(crate) fn resolve(address: u64) -> Option<&'static Module> {
+  let modules = &unsafe { MODULE_MAP.load(Ordering::Acquire).as_ref() }?.modules;
+  let index = modules
+    .partition_point(|module| module.start <= address)
+    .checked_sub(1)?;
+  let module = &modules[index];
+  (address < module.end).then_some(module)
+}
+
+// Describes the frame returning to `return_address` using the module it resolves to.
+// Async-signal-safe.
+pub(crate) fn stack_frame(return_address: u64) -> StackFrameRecord<'static> {
+  let module = resolve(return_address);
+  StackFrameRecord {
+    return_address,
+    image_load_address: module.map_or(0, |module| module.load_address),
+    binary_name: module.map(|module| module.path.as_str()),
+    image_id: module.and_then(|module| module.image_id.as_deref()),
+  }
+}
+
+//
+// LoadedImages
+//
+
+// A cheap fingerprint of the loaded images, taken without allocating, so the refresher only
+// rebuilds the map when an image was loaded or unloaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct LoadedImages {
+  count: usize,
+  // Wrapping sums of the images' base and program header addresses.
+  bases: u64,
+  headers: u64,
+}
+
+impl LoadedImages {
+  fn current() -> Self {
+    let mut images = Self::default();
+    unsafe {
+      libc::dl_iterate_phdr(Some(visit_image), (&raw mut images).cast());
+    }
+    images
+  }
+}
+
+unsafe extern "C" fn visit_image(
+  info: *mut dl_phdr_info,
+  _size: size_t,
+  data: *mut c_void,
+) -> c_int {
+  let (Some(info), Some(images)) = (unsafe { info.as_ref() }, unsafe {
+    data.cast::<LoadedImages>().as_mut()
+  }) else {
+    return 0;
+  };
+  images.count += 1;
+  images.bases = images.bases.wrapping_add(address(info.dlpi_addr));
+  images.headers = images.headers.wrapping_add(info.dlpi_phdr.addr() as u64);
+  0
+}
+
+//
+// Refresher
+//
+
+// Refreshes the module map on a background thread until dropped.
+struct Refresher {
+  stop: Option<Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Refresher {
+  fn start(interval: Duration) -> Option<Self> {
+    let (stop, stopped) = mpsc::channel::<()>();
+    let thread = thread::Builder::new()
+      .name("bd-crash-modules".to_string())
+      .spawn(move || {
+        while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+          refresh();
+        }
+      });
+
+    match thread {
+      Ok(thread) => Some(Self {
+        stop: Some(stop),
+        thread: Some(thread),
+      }),
+      Err(error) => {
+        log::warn!("failed to start bitdrift crash module map thread: {error}");
+        None
+      },
+    }
+  }
+}
+
+impl Drop for Refresher {
+  fn drop(&mut self) {
+    // Disconnecting the channel wakes the thread up immediately.
+    self.stop.take();
+    if let Some(thread) = self.thread.take() {
+      let _ignored = thread.join();
+    }
+  }
+}
+
+fn refresher_lock() -> MutexGuard<'static, Option<Refresher>> {
+  match REFRESHER.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
+
+fn retired_maps_lock() -> MutexGuard<'static, Vec<RetiredMap>> {
+  match RETIRED_MAPS.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
+
+fn loaded_modules() -> Vec<Module> {
+  let mut modules = Vec::new();
+  unsafe {
+    libc::dl_iterate_phdr(Some(visit_module), (&raw mut modules).cast());
+  }
+  modules
+}
+
+unsafe extern "C" fn visit_module(
+  info: *mut dl_phdr_info,
+  _size: size_t,
+  data: *mut c_void,
+) -> c_int {
+  let (Some(info), Some(modules)) = (unsafe { info.as_ref() }, unsafe {
+    data.cast::<Vec<Module>>().as_mut()
+  }) else {
+    return 0;
+  };
+  if let Some(module) = unsafe { module_from_info(info) } {
+    modules.push(module);
+  }
+  0
+}
+
+unsafe fn module_from_info(info: &dl_phdr_info) -> Option<Module> {
+  let base = address(info.dlpi_addr);
+  let headers = unsafe { std::slice::from_raw_parts(info.dlpi_phdr, usize::from(info.dlpi_phnum)) };
+
+  let mut start = u64::MAX;
+  let mut end = 0;
+  let mut load_address = u64::MAX;
+  let mut image_id = None;
+  for header in headers {
+    match header.p_type {
+      libc::PT_LOAD => {
+        let segment_start = base.wrapping_add(address(header.p_vaddr));
+        start = start.min(segment_start);
+        end = end.max(segment_start.wrapping_add(address(header.p_memsz)));
+        load_address = load_address.min(segment_start.wrapping_sub(address(header.p_offset)));
+      },
+      libc::PT_NOTE if image_id.is_none() => {
+        let notes = unsafe {
+          std::slice::from_raw_parts(
+            base.wrapping_add(address(header.p_vaddr)) as *const u8,
+            usize::try_from(header.p_memsz).ok()?,
+          )
+        };
+        image_id = gnu_build_id(notes).map(hex_string);
+      },
+      _ => {},
+    }
+  }
+  if start >= end {
+    return None;
+  }
+
+  // The main executable is reported without a name.
+  let name = unsafe { info.dlpi_name.as_ref() }
+    .map(|name| {
+      unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned()
+    })
+    .unwrap_or_default();
+  let path = if name.is_empty() {
+    std::env::current_exe()
+      .map(|path| path.to_string_lossy().into_owned())
+      .unwrap_or_default()
+  } else {
+    name
+  };
+
+  Some(Module {
+    start,
+    end,
+    load_address,
+    path,
+    image_id,
+  })
+}
+
+// Program header fields are 32 bits wide on 32-bit targets.
+fn address(value: impl Into<u64>) -> u64 {
+  value.into()
+}
+
+// Walks an ELF note segment: each note is a `namesz`, `descsz` and `type` header followed by the
+// name and the descriptor, each padded to 4 bytes.
+fn gnu_build_id(mut notes: &[u8]) -> Option<&[u8]> {
+  const HEADER_LEN: usize = 12;
+
+  while notes.len() >= HEADER_LEN {
+    let word = |offset: usize| {
+      u32::from_ne_bytes([
+        notes[offset],
+        notes[offset + 1],
+        notes[offset + 2],
+        notes[offset + 3],
+      ])
+    };
+    let name_len = usize::try_from(word(0)).ok()?;
+    let desc_len = usize::try_from(word(4)).ok()?;
+    let kind = word(8);
+
+    let name_start = HEADER_LEN;
+    let desc_start = name_start.checked_add(name_len)?.next_multiple_of(4);
+    let next = desc_start.checked_add(desc_len)?.next_multiple_of(4);
+    let desc = notes.get(desc_start .. desc_start.checked_add(desc_len)?)?;
+    if kind == NT_GNU_BUILD_ID && notes.get(name_start .. name_start + name_len) == Some(b"GNU\0") {
+      return Some(desc);
+    }
+    notes = notes.get(next ..).unwrap_or_default();
+  }
+  None
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+  bytes
+    .iter()
+    .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+      let _ = write!(hex, "{byte:02x}");
+      hex
+    })
+}
@@ -0,0 +1,105 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{
+  LoadedImages,
+  MODULE_MAP,
+  ModuleMap,
+  gnu_build_id,
+  hex_string,
+  loaded_modules,
+  refresh,
+  resolve,
+  retired_maps_lock,
+};
+use std::sync::atomic::Ordering;
+
+fn note(name: &[u8], kind: u32, desc: &[u8]) -> Vec<u8> {
+  let mut note = Vec::new();
+  note.extend_from_slice(&u32::try_from(name.len()).unwrap().to_ne_bytes());
+  note.extend_from_slice(&u32::try_from(desc.len()).unwrap().to_ne_bytes());
+  note.extend_from_slice(&kind.to_ne_bytes());
+  note.extend_from_slice(name);
+  note.resize(note.len().next_multiple_of(4), 0);
+  note.extend_from_slice(desc);
+  note.resize(note.len().next_multiple_of(4), 0);
+  note
+}
+
+#[test]
+fn finds_gnu_build_id_among_other_notes() {
+  let mut notes = note(b"GNU\0", 1, &[0; 16]);
+  notes.extend(note(b"Android\0", 3, &[1; 4]));
+  notes.extend(note(b"GNU\0", 3, &[0xab, 0xcd, 0x01]));
+
+  assert_eq!(gnu_build_id(&notes), Some(&[0xab, 0xcd, 0x01][..]));
+  assert_eq!(hex_string(&[0xab, 0xcd, 0x01]), "abcd01");
+}
+
+#[test]
+fn ignores_truncated_notes() {
+  let notes = note(b"GNU\0", 3, &[0xab; 20]);
+
+  assert_eq!(gnu_build_id(&notes[.. notes.len() - 4]), None);
+  assert_eq!(gnu_build_id(&notes[.. 8]), None);
+}
+
+#[test]
+fn resolves_addresses_in_loaded_images() {
+  refresh();
+
+  let address = resolves_addresses_in_loaded_images as fn() as usize as u64;
+  let module = resolve(address).unwrap();
+  assert!(module.start <= address && address < module.end);
+  assert!(module.load_address <= module.start);
+  assert_eq!(
+    module.path,
+    std::env::current_exe().unwrap().to_string_lossy()
+  );
+
+  let libc_module =
+    resolve(libc::getpid as unsafe extern "C" fn() -> libc::pid_t as usize as u64).unwrap();
+  assert!(libc_module.path.contains("libc"));
+  let image_id = libc_module.image_id.as_deref().unwrap();
+  assert!(!image_id.is_empty());
+  assert!(image_id.bytes().all(|byte| byte.is_ascii_hexdigit()));
+
+  assert_eq!(resolve(0), None);
+}
+
+#[test]
+fn refresh_keeps_the_map_while_images_are_unchanged() {
+  refresh();
+  let current = MODULE_MAP.load(Ordering::Acquire);
+
+  refresh();
+  assert_eq!(MODULE_MAP.load(Ordering::Acquire), current);
+}
+
+#[test]
+fn refresh_retires_a_stale_map() {
+  refresh();
+  let mut modules = loaded_modules();
+  modules.sort_unstable_by_key(|module| module.start);
+  let stale = Box::into_raw(Box::new(ModuleMap {
+    modules,
+    images: LoadedImages::default(),
+  }));
+  let previous = MODULE_MAP.swap(stale, Ordering::AcqRel);
+  retired_maps_lock().push(super::RetiredMap {
+    _map: unsafe { Box::from_raw(previous) },
+    retired_at: std::time::Instant::now(),
+  });
+  let retired = retired_maps_lock().len();
+
+  refresh();
+  assert_ne!(MODULE_MAP.load(Ordering::Acquire), stale);
+  assert!(retired_maps_lock().len() > retired);
+  assert!(resolve(libc::getpid as unsafe extern "C" fn() -> libc::pid_t as usize as u64).is_some());
+}
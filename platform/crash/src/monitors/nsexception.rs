@@ -10,7 +10,7 @@
 mod tests;
 
 use crate::monitors::Monitor;
-use crate::writer::{self, NSExceptionFrameRecord};
+use crate::writer::{self, StackFrameRecord};
 use objc2_foundation::{NSArray, NSException, NSNumber};
 use std::ffi::{CStr, c_char, c_int, c_void};
 use std::ptr::{null, null_mut, read_unaligned};
@@ -166,7 +166,7 @@ fn record_exception_snapshot(snapshot: &ExceptionSnapshot) {
   let frames = snapshot
     .frames
     .iter()
-    .map(|frame| NSExceptionFrameRecord {
+    .map(|frame| StackFrameRecord {
       return_address: frame.return_address,
       image_load_address: frame.image_load_address,
       binary_name: frame.binary_name.as_deref(),
@@ -5,7 +5,7 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
-#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic, clippy::unwrap_used)]
 
 use super::{
   ExceptionHandler,
@@ -18,8 +18,9 @@ use super::{
   store_previous_handler,
   try_enter_handler,
 };
-use crate::schema::{self, CrashKind, CrashRecord, RecordState};
-use crate::test_support::test_crash_record_guard;
+use crate::previous::{NSExceptionCrashInfo, PreviousCrashDetails};
+use crate::schema::{CrashKind, CrashRecord, RecordState};
+use crate::test_support::{decode_record, test_crash_record_guard};
 use crate::writer::{CRASH_RECORD, prime_shared_record};
 use objc2_foundation::NSException;
 use std::ptr::{NonNull, null_mut};
@@ -86,6 +87,13 @@ fn current_record() -> &'static CrashRecord {
   unsafe { &*record_ptr }
 }
 
+fn decoded_nsexception(record: &CrashRecord) -> NSExceptionCrashInfo {
+  match decode_record(record).details {
+    PreviousCrashDetails::NSException(exception) => *exception,
+    other => panic!("expected an NSException record, got {other:?}"),
+  }
+}
+
 #[test]
 fn try_enter_handler_rejects_reentrant_entry() {
   let _guard = test_monitor_state_guard();
@@ -134,24 +142,14 @@ fn handle_exception_snapshot_records_snapshot_and_chains_previous() {
   let record = current_record();
   assert_eq!(record.header.record_state, RecordState::Committed);
   assert_eq!(record.header.crash_kind, CrashKind::NSException);
-  assert_eq!(&record.nsexception.name[.. 12], b"NSException\0");
-  assert_eq!(&record.nsexception.reason[.. 11], b"bad reason\0");
-  assert_eq!(record.nsexception.call_stack.frame_count, 2);
-  assert_eq!(
-    record.nsexception.call_stack.frames[0].return_address,
-    0x1234
-  );
+  let exception = decoded_nsexception(record);
+  assert_eq!(exception.name.as_deref(), Some(c"NSException"));
+  assert_eq!(exception.reason.as_deref(), Some(c"bad reason"));
+  assert_eq!(exception.call_stack.return_addresses, vec![0x1234, 0x5678]);
+  assert_eq!(exception.call_stack.frames[0].image_load_address, 0x1000);
   assert_eq!(
-    record.nsexception.call_stack.frames[1].return_address,
-    0x5678
-  );
-  assert_eq!(
-    record.nsexception.call_stack.frames[0].image_load_address,
-    0x1000
-  );
-  assert_eq!(
-    &record.nsexception.call_stack.frames[0].binary_name[.. 6],
-    b"MyApp\0"
+    exception.call_stack.frames[0].binary_name.as_deref(),
+    Some(c"MyApp")
   );
   assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
   assert_eq!(PREVIOUS_LAST_EXCEPTION.load(Ordering::Acquire), exception);
@@ -218,16 +216,9 @@ fn handle_exception_snapshot_records_missing_reason_and_empty_stack() {
 
   let record = current_record();
   assert_eq!(record.header.record_state, RecordState::Committed);
-  assert_eq!(record.nsexception.reason[0], 0);
-  assert_eq!(record.nsexception.call_stack.frame_count, 0);
-  assert!(
-    record
-      .nsexception
-      .call_stack
-      .frames
-      .iter()
-      .all(|frame| frame == &schema::RawNSExceptionStackFrame::default())
-  );
+  let exception = decoded_nsexception(record);
+  assert_eq!(exception.reason, None);
+  assert!(exception.call_stack.frames.is_empty());
 }
 
 #[test]
@@ -0,0 +1,145 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+//! Records Rust panics into the crash record.
+//!
+//! The monitor relies on the SDK building with `panic = "abort"` (see the workspace `Cargo.toml`):
+//! the first panic is then always fatal, so recording it as a crash and ignoring any later ones is
+//! correct. Under `panic = "unwind"` a panic may be caught and the process carry on, yet the record
+//! would still report a crash. Tests build with `panic = "unwind"` regardless of the profile, which
+//! is why this is not a compile-time assertion.
+
+#[cfg(test)]
+#[path = "./panic_test.rs"]
+mod tests;
+
+use crate::monitors::Monitor;
+#[cfg(not(target_vendor = "apple"))]
+use crate::monitors::signal;
+use crate::writer;
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+static IN_HANDLER: AtomicBool = AtomicBool::new(false);
+// Whether our hook records panics. A hook installed by the host after ours may still chain to ours
+// once we're uninstalled, in which case ours only forwards to the hook it replaced.
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static PREVIOUS_HOOK: Mutex<Option<InstalledHook>> = Mutex::new(None);
+
+// The hook ours replaced, along with the address of our own hook so uninstalling can tell whether
+// it's still the current one.
+struct InstalledHook {
+  previous: Arc<PanicHook>,
+  ours: usize,
+}
+
+//
+// PanicMonitor
+//
+
+pub(crate) struct PanicMonitor;
+
+impl Monitor for PanicMonitor {
+  fn install(&self) -> bool {
+    // The standard library keeps a single process-wide panic hook, which is the default stderr
+    // printer unless another component replaced it.
+    //
+    // Install flow:
+    // 1. Take and save the current hook.
+    // 2. Publish our hook, which shares ownership of the saved hook.
+    // 3. When a panic later arrives, record it and then chain to the saved hook so existing
+    //    application behavior (e.g. printing the panic message) is preserved.
+    if cfg!(all(panic = "unwind", not(test))) {
+      log::warn!("the panic monitor expects panic = \"abort\"; caught panics are still recorded");
+    }
+
+    let mut previous_hook = previous_hook_lock();
+    let previous = Arc::new(panic::take_hook());
+    let chained = previous.clone();
+    let hook: PanicHook = Box::new(move |info| {
+      handle_panic(info);
+      chained(info);
+    });
+    *previous_hook = Some(InstalledHook {
+      previous,
+      ours: hook_address(&hook),
+    });
+    RECORDING.store(true, Ordering::SeqCst);
+    panic::set_hook(hook);
+    true
+  }
+
+  fn uninstall(&self) {
+    // Uninstall flow:
+    // 1. Stop recording and clear the re-entrancy flag so a future install starts from a clean
+    //    state.
+    // 2. If our hook is still the current one, drop it, which releases its reference to the saved
+    //    hook, and restore the saved hook. It's unwrapped back into the original box when we hold
+    //    the last reference so repeated install/uninstall cycles don't nest wrappers.
+    // 3. Otherwise the host installed a hook after ours, which is put back as is. Ours stays in its
+    //    chain, but only forwards to the saved hook from now on.
+    RECORDING.store(false, Ordering::SeqCst);
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    let Some(installed) = previous_hook_lock().take() else {
+      return;
+    };
+
+    let current = panic::take_hook();
+    if hook_address(&current) != installed.ours {
+      log::debug!("panic hook was replaced after the panic monitor was installed, leaving it");
+      panic::set_hook(current);
+      return;
+    }
+
+    drop(current);
+    match Arc::try_unwrap(installed.previous) {
+      Ok(previous) => panic::set_hook(previous),
+      Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+    }
+  }
+}
+
+fn handle_panic(info: &PanicHookInfo<'_>) {
+  // Panic hooks run on the panicking thread before unwinding or aborting, so ordinary Rust is
+  // allowed here. The SDK builds with `panic = "abort"`, which makes the first panic fatal, so
+  // only that one is recorded.
+  if !RECORDING.load(Ordering::SeqCst) || !try_enter_handler() {
+    return;
+  }
+
+  let current = thread::current();
+  writer::record_rust_panic(
+    info.payload_as_str(),
+    info
+      .location()
+      .map(|location| (location.file(), location.line(), location.column())),
+    current.name(),
+  );
+  // Under `panic = "abort"` the panic ends in `abort()`, and the resulting `SIGABRT` must not
+  // replace the record.
+  #[cfg(not(target_vendor = "apple"))]
+  signal::skip_recording();
+}
+
+fn hook_address(hook: &PanicHook) -> usize {
+  std::ptr::from_ref(hook.as_ref()).cast::<()>().addr()
+}
+
+fn try_enter_handler() -> bool {
+  !IN_HANDLER.swap(true, Ordering::SeqCst)
+}
+
+fn previous_hook_lock() -> MutexGuard<'static, Option<InstalledHook>> {
+  match PREVIOUS_HOOK.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
@@ -0,0 +1,202 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::panic, clippy::unwrap_used)]
+
+use super::{IN_HANDLER, PanicMonitor, previous_hook_lock, try_enter_handler};
+use crate::monitors::Monitor;
+use crate::previous::PreviousCrashDetails;
+use crate::schema::{CrashKind, CrashRecord, RecordState};
+use crate::test_support::{decode_record, test_crash_record_guard};
+use crate::writer::{CRASH_RECORD, prime_shared_record};
+use std::panic::{self, catch_unwind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static PREVIOUS_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+struct TestMonitorStateGuard;
+
+impl Drop for TestMonitorStateGuard {
+  fn drop(&mut self) {
+    drop(previous_hook_lock().take());
+    drop(panic::take_hook());
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    PREVIOUS_CALL_COUNT.store(0, Ordering::Release);
+  }
+}
+
+// Must be taken while holding `test_crash_record_guard()`, which also serializes every test that
+// installs process-wide monitors.
+fn test_monitor_state_guard() -> TestMonitorStateGuard {
+  IN_HANDLER.store(false, Ordering::SeqCst);
+  PREVIOUS_CALL_COUNT.store(0, Ordering::Release);
+  panic::set_hook(Box::new(|_| {
+    PREVIOUS_CALL_COUNT.fetch_add(1, Ordering::AcqRel);
+  }));
+  TestMonitorStateGuard
+}
+
+fn current_record() -> &'static CrashRecord {
+  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
+  unsafe { &*record_ptr }
+}
+
+#[test]
+fn try_enter_handler_rejects_reentrant_entry() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+
+  assert!(try_enter_handler());
+  assert!(!try_enter_handler());
+}
+
+#[test]
+fn panic_is_recorded_and_chained_to_previous_hook() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  let mut record = CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+  assert!(PanicMonitor.install());
+
+  let line = line!() + 5;
+  let result = thread::Builder::new()
+    .name("panicking-thread".to_string())
+    .spawn(|| {
+      let _ = catch_unwind(|| {
+        panic!("boom {}", 42);
+      });
+    })
+    .unwrap()
+    .join();
+  PanicMonitor.uninstall();
+
+  assert!(result.is_ok());
+  let record = current_record();
+  assert_eq!(record.header.crash_kind, CrashKind::RustPanic);
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  let PreviousCrashDetails::RustPanic(panic) = decode_record(record).details else {
+    panic!("expected a Rust panic record");
+  };
+  assert_eq!(panic.message.as_deref(), Some(c"boom 42"));
+  assert!(panic.file.unwrap().as_bytes().starts_with(b"src/"));
+  assert_eq!(panic.line, line);
+  assert_eq!(panic.thread_name.as_deref(), Some(c"panicking-thread"));
+  assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
+}
+
+#[test]
+fn uninstall_restores_previous_hook() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  let mut record = CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  assert!(PanicMonitor.install());
+  PanicMonitor.uninstall();
+  let _ = catch_unwind(|| {
+    panic!("not recorded");
+  });
+
+  assert_eq!(current_record().header.crash_kind, CrashKind::None);
+  assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
+  assert!(previous_hook_lock().is_none());
+}
+
+static HOST_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn uninstall_keeps_a_hook_installed_after_ours() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  HOST_CALL_COUNT.store(0, Ordering::Release);
+  let mut record = CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  assert!(PanicMonitor.install());
+  let ours = panic::take_hook();
+  panic::set_hook(Box::new(move |info| {
+    HOST_CALL_COUNT.fetch_add(1, Ordering::AcqRel);
+    ours(info);
+  }));
+  PanicMonitor.uninstall();
+  let _ = catch_unwind(|| {
+    panic!("not recorded");
+  });
+
+  // The host's hook is still called and still chains through ours to the original hook, but the
+  // panic is no longer recorded.
+  assert_eq!(HOST_CALL_COUNT.load(Ordering::Acquire), 1);
+  assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
+  assert_eq!(current_record().header.crash_kind, CrashKind::None);
+}
+
+#[cfg(target_os = "linux")]
+const CHILD_STATE_PATH_ENV: &str = "BD_CRASH_PANIC_TEST_STATE_PATH";
+
+// Only does anything when spawned by `aborting_panic_in_child_process_is_recorded`, which it does
+// by panicking in a function that can't unwind. Tests build with `panic = "unwind"`, so this is
+// how the process aborts after the panic hook runs, as it would under `panic = "abort"`.
+#[cfg(target_os = "linux")]
+#[test]
+fn panicking_child_process() {
+  use crate::coordinator::Coordinator;
+  use std::os::unix::ffi::OsStrExt as _;
+
+  let Some(path) = std::env::var_os(CHILD_STATE_PATH_ENV) else {
+    return;
+  };
+  let path = std::ffi::CString::new(path.as_bytes()).unwrap();
+  let coordinator = Coordinator::new(&path, crate::store::StoreKind::History, None).unwrap();
+  assert!(coordinator.start());
+
+  panic_without_unwinding();
+}
+
+#[cfg(target_os = "linux")]
+extern "C" fn panic_without_unwinding() {
+  panic!("aborting");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn aborting_panic_in_child_process_is_recorded() {
+  use std::os::unix::ffi::OsStrExt as _;
+  use std::os::unix::process::ExitStatusExt as _;
+
+  let directory = tempfile::tempdir().unwrap();
+  let path = directory.path().join("crash_state");
+  let status = std::process::Command::new(std::env::current_exe().unwrap())
+    .args([
+      "--exact",
+      "monitors::panic::tests::panicking_child_process",
+      "--nocapture",
+    ])
+    .env(CHILD_STATE_PATH_ENV, &path)
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .status()
+    .unwrap();
+  assert_eq!(status.signal(), Some(libc::SIGABRT));
+
+  // The `SIGABRT` raised by the abort doesn't replace the panic.
+  let path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+  let state = crate::store::open(&path, crate::store::StoreKind::History, None)
+    .unwrap()
+    .previous_crash_state();
+  assert!(state.did_crash);
+  let PreviousCrashDetails::RustPanic(panic) = state.details else {
+    panic!("expected a Rust panic record");
+  };
+  assert_eq!(panic.message.as_deref(), Some(c"aborting"));
+}
@@ -0,0 +1,265 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./signal_test.rs"]
+mod tests;
+
+use crate::monitors::{Monitor, modules, unwind};
+use crate::writer;
+use libc::{c_int, c_void, siginfo_t};
+use std::cell::UnsafeCell;
+use std::mem::zeroed;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+type SigactionHandler = unsafe extern "C" fn(c_int, *mut siginfo_t, *mut c_void);
+type SignalHandler = unsafe extern "C" fn(c_int);
+
+const MONITORED_SIGNALS: [c_int; 6] = [
+  libc::SIGSEGV,
+  libc::SIGBUS,
+  libc::SIGILL,
+  libc::SIGFPE,
+  libc::SIGABRT,
+  libc::SIGTRAP,
+];
+
+// Large enough for the record writer and the chained handlers, which run on this stack when the
+// crash was caused by a stack overflow.
+const ALTERNATE_STACK_SIZE: usize = 64 * 1024;
+
+static IN_HANDLER: AtomicBool = AtomicBool::new(false);
+static ALTERNATE_STACK: AtomicPtr<c_void> = AtomicPtr::new(null_mut());
+static PREVIOUS_ACTIONS: PreviousActions = PreviousActions::new();
+static BACKTRACE: HandlerBacktrace = HandlerBacktrace(UnsafeCell::new(unwind::Backtrace::new()));
+
+// Dispositions that were registered before `install()`, indexed like `MONITORED_SIGNALS`. The
+// table is only written by `install()` and `uninstall()`, which the coordinator serializes, and is
+// read from the signal handler, so it cannot be guarded by a lock.
+struct PreviousActions(UnsafeCell<[libc::sigaction; MONITORED_SIGNALS.len()]>);
+
+unsafe impl Sync for PreviousActions {}
+
+impl PreviousActions {
+  const fn new() -> Self {
+    Self(UnsafeCell::new(unsafe { zeroed() }))
+  }
+
+  fn get(&self, index: usize) -> libc::sigaction {
+    unsafe { (*self.0.get())[index] }
+  }
+
+  fn as_mut_ptr(&self, index: usize) -> *mut libc::sigaction {
+    unsafe { &raw mut (*self.0.get())[index] }
+  }
+
+  fn clear(&self) {
+    unsafe {
+      *self.0.get() = zeroed();
+    }
+  }
+}
+
+// The backtrace of the crashing thread, kept out of the alternate signal stack. Only the thread
+// that wins `try_enter_handler()` ever touches it.
+struct HandlerBacktrace(UnsafeCell<unwind::Backtrace>);
+
+unsafe impl Sync for HandlerBacktrace {}
+
+//
+// SignalMonitor
+//
+
+pub(crate) struct SignalMonitor;
+
+impl Monitor for SignalMonitor {
+  fn install(&self) -> bool {
+    // Install flow:
+    // 1. Make sure the installing thread has an alternate signal stack large enough for the
+    //    handler, so a stack overflow can still be recorded.
+    // 2. Swap in our handler for every monitored signal, saving the previous disposition.
+    // 3. Capture the loaded images and keep refreshing them, since they can't be enumerated from
+    //    the signal handler.
+    // 4. When a fatal signal later arrives, record it along with the interrupted thread's stack and
+    //    then chain to the saved disposition so existing application or system behavior (e.g.
+    //    debuggerd on Android) is preserved.
+    install_alternate_stack();
+    modules::start_refreshing();
+
+    for (index, signal) in MONITORED_SIGNALS.iter().enumerate() {
+      let action = handler_action();
+      let result = unsafe {
+        libc::sigaction(
+          *signal,
+          &raw const action,
+          PREVIOUS_ACTIONS.as_mut_ptr(index),
+        )
+      };
+      if result != 0 {
+        log::warn!("failed to install signal handler for signal {signal}");
+        restore_previous_actions(index);
+        PREVIOUS_ACTIONS.clear();
+        return false;
+      }
+    }
+
+    true
+  }
+
+  fn uninstall(&self) {
+    // Uninstall flow:
+    // 1. Clear the re-entrancy flag so a future install starts from a clean state.
+    // 2. Restore every previously registered disposition.
+    // 3. Drop the saved dispositions once restoration is complete.
+    //
+    // The alternate stack is intentionally kept: it may still be registered for the thread that
+    // called `install()`, and it is reused if the monitor is installed again.
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    restore_previous_actions(MONITORED_SIGNALS.len());
+    PREVIOUS_ACTIONS.clear();
+    modules::stop_refreshing();
+  }
+}
+
+fn handler_action() -> libc::sigaction {
+  let mut action: libc::sigaction = unsafe { zeroed() };
+  action.sa_sigaction = handle_signal as SigactionHandler as usize;
+  action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+  unsafe {
+    libc::sigemptyset(&raw mut action.sa_mask);
+  }
+  action
+}
+
+fn restore_previous_actions(count: usize) {
+  for (index, signal) in MONITORED_SIGNALS[.. count].iter().enumerate() {
+    let previous = PREVIOUS_ACTIONS.get(index);
+    unsafe {
+      libc::sigaction(*signal, &raw const previous, null_mut());
+    }
+  }
+}
+
+// Only the thread that installs the monitor gets this stack. Any other thread that crashes runs
+// the handler on whatever alternate stack it already has, such as the small one Rust std or bionic
+// give every thread, or on its own stack if it has none, in which case a stack overflow on that
+// thread can't be recorded.
+fn install_alternate_stack() {
+  // Rust std and bionic already give every thread an alternate signal stack, but one of only a few
+  // KiB, which the handler overflows. Keep an existing stack only if it's at least as large as
+  // ours, or if the thread is running on it and it can't be replaced.
+  let mut current: libc::stack_t = unsafe { zeroed() };
+  if unsafe { libc::sigaltstack(null(), &raw mut current) } == 0
+    && current.ss_flags & libc::SS_DISABLE == 0
+    && (current.ss_size >= ALTERNATE_STACK_SIZE || current.ss_flags & libc::SS_ONSTACK != 0)
+  {
+    return;
+  }
+
+  let mut stack = ALTERNATE_STACK.load(Ordering::Acquire);
+  if stack.is_null() {
+    stack = unsafe {
+      libc::mmap(
+        null_mut(),
+        ALTERNATE_STACK_SIZE,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0,
+      )
+    };
+    if stack == libc::MAP_FAILED {
+      log::warn!("failed to allocate alternate signal stack");
+      return;
+    }
+    ALTERNATE_STACK.store(stack, Ordering::Release);
+  }
+
+  let alternate = libc::stack_t {
+    ss_sp: stack,
+    ss_flags: 0,
+    ss_size: ALTERNATE_STACK_SIZE,
+  };
+  if unsafe { libc::sigaltstack(&raw const alternate, null_mut()) } != 0 {
+    log::warn!("failed to register alternate signal stack");
+  }
+}
+
+unsafe extern "C" fn handle_signal(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+  // Everything reachable from here must be async-signal-safe. Only the first fatal signal is
+  // recorded; a second one (e.g. from a crash inside a chained handler) is passed straight on.
+  if try_enter_handler()
+    && let Some(info) = unsafe { info.as_ref() }
+  {
+    let backtrace = unsafe { &mut *BACKTRACE.0.get() };
+    unwind::unwind_context(context, backtrace);
+    writer::record_signal(
+      signal,
+      info.si_code,
+      unsafe { info.si_addr() } as u64,
+      current_thread_id(),
+      backtrace
+        .addresses()
+        .iter()
+        .map(|address| modules::stack_frame(*address)),
+    );
+  }
+
+  chain_previous(signal, info, context);
+}
+
+// Lets a fatal signal that follows a crash another monitor already recorded, such as the `SIGABRT`
+// raised once `std::terminate` is done, pass through without replacing that record.
+pub(crate) fn skip_recording() {
+  IN_HANDLER.store(true, Ordering::SeqCst);
+}
+
+fn try_enter_handler() -> bool {
+  !IN_HANDLER.swap(true, Ordering::SeqCst)
+}
+
+fn chain_previous(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+  let Some(index) = MONITORED_SIGNALS
+    .iter()
+    .position(|monitored| *monitored == signal)
+  else {
+    return;
+  };
+
+  let previous = PREVIOUS_ACTIONS.get(index);
+  match previous.sa_sigaction {
+    libc::SIG_DFL | libc::SIG_IGN => {
+      // Fall back to the default disposition and re-raise. The signal is blocked while this
+      // handler runs, so it is delivered as soon as we return and terminates the process the way
+      // it would have without us. Ignoring a fatal signal isn't meaningful for a real fault, so
+      // `SIG_IGN` is treated the same way.
+      let mut default: libc::sigaction = unsafe { zeroed() };
+      default.sa_sigaction = libc::SIG_DFL;
+      unsafe {
+        libc::sigaction(signal, &raw const default, null_mut());
+        libc::raise(signal);
+      }
+    },
+    handler if previous.sa_flags & libc::SA_SIGINFO != 0 => unsafe {
+      std::mem::transmute::<usize, SigactionHandler>(handler)(signal, info, context);
+    },
+    handler => unsafe {
+      std::mem::transmute::<usize, SignalHandler>(handler)(signal);
+    },
+  }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn current_thread_id() -> u64 {
+  u64::try_from(unsafe { libc::syscall(libc::SYS_gettid) }).unwrap_or(0)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn current_thread_id() -> u64 {
+  unsafe { libc::pthread_self() as u64 }
+}
@@ -0,0 +1,242 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::panic, clippy::unwrap_used)]
+
+use super::{
+  IN_HANDLER,
+  PREVIOUS_ACTIONS,
+  SigactionHandler,
+  SignalMonitor,
+  current_thread_id,
+  handle_signal,
+  try_enter_handler,
+};
+use crate::monitors::Monitor;
+use crate::previous::PreviousCrashDetails;
+use crate::schema::{CrashKind, CrashRecord, RecordState};
+use crate::test_support::{decode_record, test_crash_record_guard};
+use crate::writer::{CRASH_RECORD, prime_shared_record};
+use libc::{c_int, c_void, siginfo_t};
+use std::mem::zeroed;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+static PREVIOUS_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+static PREVIOUS_LAST_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+struct TestMonitorStateGuard {
+  original: libc::sigaction,
+}
+
+impl Drop for TestMonitorStateGuard {
+  fn drop(&mut self) {
+    unsafe {
+      libc::sigaction(libc::SIGTRAP, &raw const self.original, null_mut());
+    }
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    PREVIOUS_ACTIONS.clear();
+    PREVIOUS_CALL_COUNT.store(0, Ordering::Release);
+    PREVIOUS_LAST_SIGNAL.store(0, Ordering::Release);
+  }
+}
+
+// Must be taken while holding `test_crash_record_guard()`, which also serializes every test that
+// installs process-wide monitors.
+fn test_monitor_state_guard() -> TestMonitorStateGuard {
+  let mut original: libc::sigaction = unsafe { zeroed() };
+  unsafe {
+    libc::sigaction(libc::SIGTRAP, std::ptr::null(), &raw mut original);
+  }
+  IN_HANDLER.store(false, Ordering::SeqCst);
+  PREVIOUS_ACTIONS.clear();
+  PREVIOUS_CALL_COUNT.store(0, Ordering::Release);
+  PREVIOUS_LAST_SIGNAL.store(0, Ordering::Release);
+  TestMonitorStateGuard { original }
+}
+
+unsafe extern "C" fn fake_previous_handler(
+  signal: c_int,
+  _info: *mut siginfo_t,
+  _context: *mut c_void,
+) {
+  PREVIOUS_CALL_COUNT.fetch_add(1, Ordering::AcqRel);
+  PREVIOUS_LAST_SIGNAL.store(signal, Ordering::Release);
+}
+
+fn install_fake_previous_handler() {
+  let mut action: libc::sigaction = unsafe { zeroed() };
+  action.sa_sigaction = fake_previous_handler as SigactionHandler as usize;
+  action.sa_flags = libc::SA_SIGINFO;
+  unsafe {
+    libc::sigemptyset(&raw mut action.sa_mask);
+    libc::sigaction(libc::SIGTRAP, &raw const action, null_mut());
+  }
+}
+
+fn current_trap_handler() -> usize {
+  let mut action: libc::sigaction = unsafe { zeroed() };
+  unsafe {
+    libc::sigaction(libc::SIGTRAP, std::ptr::null(), &raw mut action);
+  }
+  action.sa_sigaction
+}
+
+#[test]
+fn try_enter_handler_rejects_reentrant_entry() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+
+  assert!(try_enter_handler());
+  assert!(!try_enter_handler());
+}
+
+#[test]
+fn install_replaces_and_uninstall_restores_previous_handler() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  install_fake_previous_handler();
+
+  assert!(SignalMonitor.install());
+  assert_eq!(
+    current_trap_handler(),
+    handle_signal as SigactionHandler as usize
+  );
+
+  SignalMonitor.uninstall();
+  assert_eq!(
+    current_trap_handler(),
+    fake_previous_handler as SigactionHandler as usize
+  );
+}
+
+#[test]
+fn raised_signal_is_recorded_and_chained_to_previous_handler() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  let mut record = CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+  install_fake_previous_handler();
+  assert!(SignalMonitor.install());
+
+  unsafe {
+    libc::raise(libc::SIGTRAP);
+  }
+  SignalMonitor.uninstall();
+
+  let record = unsafe { &*CRASH_RECORD.load(Ordering::Acquire) };
+  assert_eq!(record.header.crash_kind, CrashKind::Signal);
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  let PreviousCrashDetails::Signal(signal) = decode_record(record).details else {
+    panic!("expected a signal record");
+  };
+  assert_eq!(signal.signal, libc::SIGTRAP);
+  assert_eq!(signal.thread_id, current_thread_id());
+  assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
+  assert_eq!(PREVIOUS_LAST_SIGNAL.load(Ordering::Acquire), libc::SIGTRAP);
+}
+
+#[test]
+fn reentrant_signal_is_chained_without_recording() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+  let mut record = CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+  install_fake_previous_handler();
+  assert!(SignalMonitor.install());
+  assert!(try_enter_handler());
+
+  unsafe {
+    libc::raise(libc::SIGTRAP);
+  }
+  SignalMonitor.uninstall();
+
+  let record = unsafe { &*CRASH_RECORD.load(Ordering::Acquire) };
+  assert_eq!(record.header.crash_kind, CrashKind::None);
+  assert_eq!(record.header.record_state, RecordState::Empty);
+  assert_eq!(PREVIOUS_CALL_COUNT.load(Ordering::Acquire), 1);
+}
+
+#[cfg(target_os = "linux")]
+const CHILD_STATE_PATH_ENV: &str = "BD_CRASH_SIGNAL_TEST_STATE_PATH";
+
+// Only does anything when spawned by `crash_in_child_process_is_unwound`, which it does by
+// crashing the process.
+#[cfg(target_os = "linux")]
+#[test]
+fn crashing_child_process() {
+  use crate::coordinator::Coordinator;
+  use std::os::unix::ffi::OsStrExt as _;
+
+  let Some(path) = std::env::var_os(CHILD_STATE_PATH_ENV) else {
+    return;
+  };
+  let path = std::ffi::CString::new(path.as_bytes()).unwrap();
+  let coordinator = Coordinator::new(&path, crate::store::StoreKind::History, None).unwrap();
+  assert!(coordinator.start());
+
+  crash_with_null_read(&mut 0);
+}
+
+#[cfg(target_os = "linux")]
+#[inline(never)]
+fn crash_with_null_read(depth: &mut u32) {
+  *depth += 1;
+  if *depth < 3 {
+    crash_with_null_read(depth);
+    return;
+  }
+  let value = unsafe { std::ptr::read_volatile(std::ptr::null::<u64>()) };
+  *depth += u32::try_from(value).unwrap_or_default();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn crash_in_child_process_is_unwound() {
+  use std::os::unix::ffi::OsStrExt as _;
+  use std::os::unix::process::ExitStatusExt as _;
+
+  let directory = tempfile::tempdir().unwrap();
+  let path = directory.path().join("crash_state");
+  let status = std::process::Command::new(std::env::current_exe().unwrap())
+    .args([
+      "--exact",
+      "monitors::signal::tests::crashing_child_process",
+      "--nocapture",
+    ])
+    .env(CHILD_STATE_PATH_ENV, &path)
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .status()
+    .unwrap();
+  assert_eq!(status.signal(), Some(libc::SIGSEGV));
+
+  let path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+  let state = crate::store::open(&path, crate::store::StoreKind::History, None)
+    .unwrap()
+    .previous_crash_state();
+  assert!(state.did_crash);
+  let PreviousCrashDetails::Signal(signal) = state.details else {
+    panic!("expected a signal record");
+  };
+  assert_eq!(signal.signal, libc::SIGSEGV);
+  assert_eq!(signal.fault_address, 0);
+
+  // The faulting instruction is always recorded and resolves to the test binary. How many callers
+  // follow depends on whether the test binary keeps frame pointers.
+  let frame = &signal.call_stack.frames[0];
+  let executable = std::env::current_exe().unwrap();
+  assert_eq!(
+    frame.binary_name.as_deref().unwrap().to_bytes(),
+    executable.as_os_str().as_bytes()
+  );
+  assert!(frame.image_load_address != 0 && frame.image_load_address <= frame.return_address);
+}
@@ -0,0 +1,46 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+// The parts of the terminate monitor that have to be written in C++: swapping the terminate handler
+// and inspecting the exception that is being handled. Everything else lives in `terminate.rs`.
+
+#include <cxxabi.h>
+#include <exception>
+#include <typeinfo>
+
+extern "C" {
+
+std::terminate_handler bd_crash_set_terminate_handler(std::terminate_handler handler) noexcept {
+  return std::set_terminate(handler);
+}
+
+// Returns the mangled type name of the exception currently being handled, or null when
+// `std::terminate` was called without one.
+const char* bd_crash_current_exception_type() noexcept {
+  const std::type_info* type = abi::__cxa_current_exception_type();
+  return type == nullptr ? nullptr : type->name();
+}
+
+// Returns `what()` of the exception currently being handled when it derives from `std::exception`,
+// or null otherwise. The string is owned by the exception, which the runtime keeps alive until the
+// terminate handler returns.
+const char* bd_crash_current_exception_what() noexcept {
+  std::exception_ptr exception = std::current_exception();
+  if (!exception) {
+    return nullptr;
+  }
+
+  try {
+    std::rethrow_exception(exception);
+  } catch (const std::exception& e) {
+    return e.what();
+  } catch (...) {
+  }
+  return nullptr;
+}
+
+}
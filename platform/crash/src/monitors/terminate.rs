@@ -0,0 +1,148 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./terminate_test.rs"]
+mod tests;
+
+use crate::monitors::{Monitor, modules, signal, unwind};
+use crate::writer;
+use libc::{c_char, c_int};
+use std::ffi::CStr;
+use std::ptr::{fn_addr_eq, null_mut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+type TerminateHandler = unsafe extern "C" fn();
+
+static IN_HANDLER: AtomicBool = AtomicBool::new(false);
+static PREVIOUS_HANDLER: Mutex<Option<TerminateHandler>> = Mutex::new(None);
+
+unsafe extern "C" {
+  // Implemented in `terminate.cc`.
+  fn bd_crash_set_terminate_handler(handler: Option<TerminateHandler>) -> Option<TerminateHandler>;
+  fn bd_crash_current_exception_type() -> *const c_char;
+  fn bd_crash_current_exception_what() -> *const c_char;
+
+  // Part of the Itanium C++ ABI, provided by the C++ runtime.
+  fn __cxa_demangle(
+    mangled_name: *const c_char,
+    output_buffer: *mut c_char,
+    length: *mut usize,
+    status: *mut c_int,
+  ) -> *mut c_char;
+}
+
+//
+// TerminateMonitor
+//
+
+pub(crate) struct TerminateMonitor;
+
+impl Monitor for TerminateMonitor {
+  fn install(&self) -> bool {
+    // The C++ runtime keeps a single process-wide terminate handler, which aborts the process
+    // unless another component replaced it. An uncaught exception, an exception escaping a
+    // `noexcept` function and explicit calls all end up in it. On Android every library may carry
+    // its own C++ runtime, and only terminations in the copy libcapture links statically reach
+    // this handler.
+    //
+    // Install flow:
+    // 1. Swap in our handler, saving the one it replaces.
+    // 2. When `std::terminate` is later called, record the exception being handled along with the
+    //    terminating thread's stack and then chain to the saved handler, which ends the process.
+    let previous = unsafe { bd_crash_set_terminate_handler(Some(handle_terminate)) };
+    // Installing twice must not chain our handler to itself.
+    if !previous.is_some_and(|previous| fn_addr_eq(previous, handle_terminate as TerminateHandler))
+    {
+      *previous_handler_lock() = previous;
+    }
+    true
+  }
+
+  fn uninstall(&self) {
+    // Uninstall flow:
+    // 1. Clear the re-entrancy flag so a future install starts from a clean state.
+    // 2. Restore the saved handler. A null handler makes the runtime use its default one.
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    let previous = previous_handler_lock().take();
+    unsafe {
+      bd_crash_set_terminate_handler(previous);
+    }
+  }
+}
+
+unsafe extern "C" fn handle_terminate() {
+  // Terminate handlers run on the terminating thread before the process is aborted, so ordinary
+  // Rust is allowed here. Only the first call is recorded.
+  if try_enter_handler() {
+    record_current_exception();
+    // The handler chain ends in `abort()`, and the resulting `SIGABRT` must not replace the record.
+    signal::skip_recording();
+  }
+
+  chain_previous();
+}
+
+fn record_current_exception() {
+  let backtrace = unwind::current_backtrace();
+  let type_name = current_exception_type();
+  let what = unsafe { c_str(bd_crash_current_exception_what()) }.map(CStr::to_string_lossy);
+  writer::record_cxx_exception(
+    type_name.as_deref(),
+    what.as_deref(),
+    backtrace
+      .addresses()
+      .iter()
+      .map(|address| modules::stack_frame(*address)),
+  );
+}
+
+// Returns the demangled type of the exception being handled, falling back to the mangled name when
+// it can't be demangled.
+fn current_exception_type() -> Option<String> {
+  let mangled = unsafe { c_str(bd_crash_current_exception_type()) }?;
+  Some(demangle(mangled).unwrap_or_else(|| mangled.to_string_lossy().into_owned()))
+}
+
+fn demangle(mangled: &CStr) -> Option<String> {
+  let mut status = 0;
+  let demangled =
+    unsafe { __cxa_demangle(mangled.as_ptr(), null_mut(), null_mut(), &raw mut status) };
+  let name = unsafe { c_str(demangled) }.map(|name| name.to_string_lossy().into_owned());
+  unsafe {
+    libc::free(demangled.cast());
+  }
+  name.filter(|_| status == 0)
+}
+
+unsafe fn c_str<'a>(value: *const c_char) -> Option<&'a CStr> {
+  (!value.is_null()).then(|| unsafe { CStr::from_ptr(value) })
+}
+
+fn chain_previous() -> ! {
+  let previous = *previous_handler_lock();
+  if let Some(previous) = previous {
+    unsafe {
+      previous();
+    }
+  }
+
+  // Terminate handlers must not return.
+  unsafe { libc::abort() }
+}
+
+fn try_enter_handler() -> bool {
+  !IN_HANDLER.swap(true, Ordering::SeqCst)
+}
+
+fn previous_handler_lock() -> MutexGuard<'static, Option<TerminateHandler>> {
+  match PREVIOUS_HANDLER.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
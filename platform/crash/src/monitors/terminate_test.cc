@@ -0,0 +1,43 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+// Throws C++ exceptions for `terminate_test.rs`. The functions are `noexcept`, so an exception
+// leaving them calls `std::terminate` instead of unwinding into Rust.
+
+#include <stdexcept>
+
+namespace bd_crash_test {
+
+struct NotAnException {
+  int code;
+};
+
+[[gnu::noinline]] void throw_runtime_error() {
+  throw std::runtime_error("the widget is broken");
+}
+
+[[gnu::noinline]] void throw_custom_type() {
+  throw NotAnException{42};
+}
+
+} // namespace bd_crash_test
+
+extern "C" {
+
+void bd_crash_test_throw_runtime_error() noexcept {
+  bd_crash_test::throw_runtime_error();
+}
+
+void bd_crash_test_throw_custom_type() noexcept {
+  bd_crash_test::throw_custom_type();
+}
+
+void bd_crash_test_terminate() noexcept {
+  std::terminate();
+}
+
+}
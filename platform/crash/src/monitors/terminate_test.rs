@@ -0,0 +1,212 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::panic, clippy::unwrap_used)]
+
+use super::{
+  IN_HANDLER,
+  TerminateHandler,
+  TerminateMonitor,
+  bd_crash_set_terminate_handler,
+  demangle,
+  handle_terminate,
+  previous_handler_lock,
+  try_enter_handler,
+};
+use crate::monitors::Monitor;
+#[cfg(target_os = "linux")]
+use crate::previous::{CxxExceptionCrashInfo, PreviousCrashDetails};
+use crate::test_support::test_crash_record_guard;
+use std::ptr::fn_addr_eq;
+use std::sync::atomic::Ordering;
+
+#[cfg(target_os = "linux")]
+#[link(name = "bd_crash_terminate_test", kind = "static")]
+unsafe extern "C" {
+  // Implemented in `terminate_test.cc`.
+  fn bd_crash_test_throw_runtime_error();
+  fn bd_crash_test_throw_custom_type();
+  fn bd_crash_test_terminate();
+}
+
+#[cfg(target_os = "linux")]
+const CHILD_STATE_PATH_ENV: &str = "BD_CRASH_TERMINATE_TEST_STATE_PATH";
+#[cfg(target_os = "linux")]
+const CHILD_FIXTURE_ENV: &str = "BD_CRASH_TERMINATE_TEST_FIXTURE";
+
+struct TestMonitorStateGuard {
+  original: Option<TerminateHandler>,
+}
+
+impl Drop for TestMonitorStateGuard {
+  fn drop(&mut self) {
+    unsafe {
+      bd_crash_set_terminate_handler(self.original);
+    }
+    IN_HANDLER.store(false, Ordering::SeqCst);
+    *previous_handler_lock() = None;
+  }
+}
+
+// Must be taken while holding `test_crash_record_guard()`, which also serializes every test that
+// installs process-wide monitors.
+fn test_monitor_state_guard() -> TestMonitorStateGuard {
+  let original = unsafe { bd_crash_set_terminate_handler(Some(fake_previous_handler)) };
+  IN_HANDLER.store(false, Ordering::SeqCst);
+  *previous_handler_lock() = None;
+  TestMonitorStateGuard { original }
+}
+
+unsafe extern "C" fn fake_previous_handler() {}
+
+fn current_handler() -> Option<TerminateHandler> {
+  unsafe {
+    let current = bd_crash_set_terminate_handler(None);
+    bd_crash_set_terminate_handler(current);
+    current
+  }
+}
+
+fn is_handler(handler: Option<TerminateHandler>, expected: TerminateHandler) -> bool {
+  handler.is_some_and(|handler| fn_addr_eq(handler, expected))
+}
+
+#[test]
+fn try_enter_handler_rejects_reentrant_entry() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+
+  assert!(try_enter_handler());
+  assert!(!try_enter_handler());
+}
+
+#[test]
+fn install_replaces_and_uninstall_restores_previous_handler() {
+  let _record_guard = test_crash_record_guard();
+  let _guard = test_monitor_state_guard();
+
+  assert!(TerminateMonitor.install());
+  assert!(is_handler(current_handler(), handle_terminate));
+  // A second install keeps chaining to the original handler rather than to itself.
+  assert!(TerminateMonitor.install());
+
+  TerminateMonitor.uninstall();
+  assert!(is_handler(current_handler(), fake_previous_handler));
+}
+
+#[test]
+fn demangles_type_names() {
+  assert_eq!(
+    demangle(c"St13runtime_error").as_deref(),
+    Some("std::runtime_error")
+  );
+  assert_eq!(
+    demangle(c"N13bd_crash_test14NotAnExceptionE").as_deref(),
+    Some("bd_crash_test::NotAnException")
+  );
+  assert_eq!(demangle(c"not a mangled name!"), None);
+}
+
+// Only does anything when spawned by `terminate_in_child_process`, which it does by terminating
+// the process.
+#[cfg(target_os = "linux")]
+#[test]
+fn terminating_child_process() {
+  use crate::coordinator::Coordinator;
+  use std::os::unix::ffi::OsStrExt as _;
+
+  let (Some(path), Some(fixture)) = (
+    std::env::var_os(CHILD_STATE_PATH_ENV),
+    std::env::var(CHILD_FIXTURE_ENV).ok(),
+  ) else {
+    return;
+  };
+  let path = std::ffi::CString::new(path.as_bytes()).unwrap();
+  let coordinator = Coordinator::new(&path, crate::store::StoreKind::History, None).unwrap();
+  assert!(coordinator.start());
+
+  unsafe {
+    match fixture.as_str() {
+      "runtime_error" => bd_crash_test_throw_runtime_error(),
+      "custom_type" => bd_crash_test_throw_custom_type(),
+      _ => bd_crash_test_terminate(),
+    }
+  }
+}
+
+#[cfg(target_os = "linux")]
+fn terminate_in_child_process(fixture: &str) -> CxxExceptionCrashInfo {
+  use std::os::unix::ffi::OsStrExt as _;
+  use std::os::unix::process::ExitStatusExt as _;
+
+  let directory = tempfile::tempdir().unwrap();
+  let path = directory.path().join("crash_state");
+  let status = std::process::Command::new(std::env::current_exe().unwrap())
+    .args([
+      "--exact",
+      "monitors::terminate::tests::terminating_child_process",
+      "--nocapture",
+    ])
+    .env(CHILD_STATE_PATH_ENV, &path)
+    .env(CHILD_FIXTURE_ENV, fixture)
+    .stdout(std::process::Stdio::null())
+    .stderr(std::process::Stdio::null())
+    .status()
+    .unwrap();
+  assert_eq!(status.signal(), Some(libc::SIGABRT));
+
+  // The `SIGABRT` that ends the process must not replace the terminate record.
+  let path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+  let state = crate::store::open(&path, crate::store::StoreKind::History, None)
+    .unwrap()
+    .previous_crash_state();
+  assert!(state.did_crash);
+  let PreviousCrashDetails::CxxException(exception) = state.details else {
+    panic!("expected a C++ exception record, got {:?}", state.kind);
+  };
+  *exception
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn uncaught_std_exception_is_recorded() {
+  use std::os::unix::ffi::OsStrExt as _;
+
+  let exception = terminate_in_child_process("runtime_error");
+
+  assert_eq!(exception.type_name.as_deref(), Some(c"std::runtime_error"));
+  assert_eq!(exception.what.as_deref(), Some(c"the widget is broken"));
+  // The innermost frame is in the crash reporter itself and resolves to the test binary.
+  let frame = &exception.call_stack.frames[0];
+  let executable = std::env::current_exe().unwrap();
+  assert_eq!(
+    frame.binary_name.as_deref().unwrap().to_bytes(),
+    executable.as_os_str().as_bytes()
+  );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn uncaught_exception_of_other_type_is_recorded_without_what() {
+  let exception = terminate_in_child_process("custom_type");
+
+  assert_eq!(
+    exception.type_name.as_deref(),
+    Some(c"bd_crash_test::NotAnException")
+  );
+  assert_eq!(exception.what, None);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn terminate_without_exception_is_recorded() {
+  let exception = terminate_in_child_process("terminate");
+
+  assert_eq!(exception.type_name, None);
+  assert_eq!(exception.what, None);
+  assert!(!exception.call_stack.frames.is_empty());
+}
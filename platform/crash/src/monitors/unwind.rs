@@ -0,0 +1,230 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./unwind_test.rs"]
+mod tests;
+
+use crate::schema;
+use libc::c_void;
+use std::mem::size_of;
+
+pub(crate) const MAX_FRAMES: usize = schema::MAX_CALL_STACK_FRAMES as usize;
+
+// Frame pointers above this distance from the stack pointer are assumed to be garbage rather than
+// a frame on a very deep stack.
+const MAX_STACK_SPAN: u64 = 64 * 1024 * 1024;
+
+// aarch64 return addresses may carry a pointer authentication code in their upper bits.
+const ADDRESS_MASK: u64 = if cfg!(target_arch = "aarch64") {
+  0x0000_ffff_ffff_ffff
+} else {
+  u64::MAX
+};
+
+// The registers an unwind starts from.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Registers {
+  pub(crate) pc: u64,
+  pub(crate) fp: u64,
+  pub(crate) sp: u64,
+  // The link register on architectures that have one. It holds the caller's return address when
+  // the crash happened before the crashing function saved it into a frame record.
+  pub(crate) lr: Option<u64>,
+}
+
+// Return addresses of a captured stack, innermost first. Backed by a fixed buffer so it can be
+// filled from a signal handler, where it's kept in static storage rather than on the small
+// alternate signal stack.
+pub(crate) struct Backtrace {
+  addresses: [u64; MAX_FRAMES],
+  len: usize,
+}
+
+impl Backtrace {
+  pub(crate) const fn new() -> Self {
+    Self {
+      addresses: [0; MAX_FRAMES],
+      len: 0,
+    }
+  }
+
+  pub(crate) fn addresses(&self) -> &[u64] {
+    &self.addresses[.. self.len]
+  }
+
+  fn push(&mut self, address: u64) -> bool {
+    let Some(slot) = self.addresses.get_mut(self.len) else {
+      return false;
+    };
+    *slot = address & ADDRESS_MASK;
+    self.len += 1;
+    true
+  }
+}
+
+// Unwinds the interrupted thread from the `ucontext_t` passed to a `SA_SIGINFO` handler into
+// `backtrace`. Async-signal-safe; yields no frames on architectures without a register reader.
+pub(crate) fn unwind_context(context: *const c_void, backtrace: &mut Backtrace) {
+  backtrace.len = 0;
+  if let Some(registers) = unsafe { context_registers(context) } {
+    walk_into(registers, backtrace);
+  }
+}
+
+// Unwinds the calling thread, for crashes that are reported outside of a signal handler. The
+// innermost frames belong to the unwinder itself.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub(crate) fn current_backtrace() -> Backtrace {
+  walk(current_registers())
+}
+
+// Without a register reader there is nothing to unwind from.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) const fn current_backtrace() -> Backtrace {
+  Backtrace::new()
+}
+
+// Follows the frame pointer chain. Every frame record is the caller's frame pointer followed by the
+// return address, and the chain must move strictly up the stack. Memory is read through
+// `process_vm_readv` so a corrupt chain ends the walk instead of faulting inside the handler.
+pub(crate) fn walk(registers: Registers) -> Backtrace {
+  let mut backtrace = Backtrace::new();
+  walk_into(registers, &mut backtrace);
+  backtrace
+}
+
+fn walk_into(registers: Registers, backtrace: &mut Backtrace) {
+  backtrace.len = 0;
+  if registers.pc == 0 || !backtrace.push(registers.pc) {
+    return;
+  }
+
+  let mut fp = registers.fp;
+  let mut pending_lr = registers.lr.filter(|lr| *lr != 0);
+  while fp != 0
+    && fp.is_multiple_of(size_of::<u64>() as u64)
+    && fp >= registers.sp
+    && fp - registers.sp <= MAX_STACK_SPAN
+  {
+    let Some([next_fp, return_address]) = read_frame_record(fp) else {
+      break;
+    };
+
+    // If the link register matches the first saved return address the crashing function had
+    // already set up its frame, so it's the same frame and is only recorded once.
+    if let Some(lr) = pending_lr.take()
+      && lr & ADDRESS_MASK != return_address & ADDRESS_MASK
+      && !backtrace.push(lr)
+    {
+      return;
+    }
+    if return_address == 0 || !backtrace.push(return_address) || next_fp <= fp {
+      break;
+    }
+    fp = next_fp;
+  }
+
+  if let Some(lr) = pending_lr {
+    backtrace.push(lr);
+  }
+}
+
+fn read_frame_record(fp: u64) -> Option<[u64; 2]> {
+  let mut record = [0_u64; 2];
+  let local = libc::iovec {
+    iov_base: record.as_mut_ptr().cast(),
+    iov_len: size_of::<[u64; 2]>(),
+  };
+  let remote = libc::iovec {
+    iov_base: fp as *mut c_void,
+    iov_len: size_of::<[u64; 2]>(),
+  };
+  let read =
+    unsafe { libc::process_vm_readv(libc::getpid(), &raw const local, 1, &raw const remote, 1, 0) };
+  (usize::try_from(read).ok() == Some(size_of::<[u64; 2]>())).then_some(record)
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn context_registers(context: *const c_void) -> Option<Registers> {
+  let context = unsafe { context.cast::<libc::ucontext_t>().as_ref() }?;
+  let register = |index: libc::c_int| {
+    usize::try_from(index)
+      .ok()
+      .and_then(|index| context.uc_mcontext.gregs.get(index))
+      .map_or(0, |value| value.cast_unsigned())
+  };
+  Some(Registers {
+    pc: register(libc::REG_RIP),
+    fp: register(libc::REG_RBP),
+    sp: register(libc::REG_RSP),
+    lr: None,
+  })
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn context_registers(context: *const c_void) -> Option<Registers> {
+  let context = unsafe { context.cast::<libc::ucontext_t>().as_ref() }?;
+  let registers = &context.uc_mcontext;
+  Some(Registers {
+    pc: registers.pc,
+    fp: registers.regs[29],
+    sp: registers.sp,
+    lr: Some(registers.regs[30]),
+  })
+}
+
+#[cfg(target_arch = "x86_64")]
+fn current_registers() -> Registers {
+  let (pc, fp, sp): (u64, u64, u64);
+  unsafe {
+    std::arch::asm!(
+      "lea {pc}, [rip]",
+      "mov {fp}, rbp",
+      "mov {sp}, rsp",
+      pc = out(reg) pc,
+      fp = out(reg) fp,
+      sp = out(reg) sp,
+      options(nomem, nostack, preserves_flags),
+    );
+  }
+  Registers {
+    pc,
+    fp,
+    sp,
+    lr: None,
+  }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn current_registers() -> Registers {
+  let (pc, fp, sp, lr): (u64, u64, u64, u64);
+  unsafe {
+    std::arch::asm!(
+      "adr {pc}, .",
+      "mov {fp}, x29",
+      "mov {sp}, sp",
+      "mov {lr}, x30",
+      pc = out(reg) pc,
+      fp = out(reg) fp,
+      sp = out(reg) sp,
+      lr = out(reg) lr,
+      options(nomem, nostack, preserves_flags),
+    );
+  }
+  Registers {
+    pc,
+    fp,
+    sp,
+    lr: Some(lr),
+  }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const unsafe fn context_registers(_context: *const c_void) -> Option<Registers> {
+  None
+}
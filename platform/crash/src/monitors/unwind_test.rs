@@ -0,0 +1,105 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{MAX_FRAMES, Registers, walk};
+
+// A fake stack holding a chain of three frame records, so the walk doesn't depend on how the test
+// binary itself was compiled.
+struct FakeStack {
+  words: Box<[u64; 8]>,
+}
+
+impl FakeStack {
+  fn new() -> Self {
+    let mut stack = Self {
+      words: Box::new([0; 8]),
+    };
+    let (second, third) = (stack.address(4), stack.address(6));
+    stack.words[2 .. 4].copy_from_slice(&[second, 0x2000]);
+    stack.words[4 .. 6].copy_from_slice(&[third, 0x3000]);
+    stack.words[6 .. 8].copy_from_slice(&[0, 0x4000]);
+    stack
+  }
+
+  fn address(&self, index: usize) -> u64 {
+    (&raw const self.words[index]) as u64
+  }
+
+  fn registers(&self, lr: Option<u64>) -> Registers {
+    Registers {
+      pc: 0x1000,
+      fp: self.address(2),
+      sp: self.address(0),
+      lr,
+    }
+  }
+}
+
+#[test]
+fn walks_frame_pointer_chain() {
+  let stack = FakeStack::new();
+
+  let backtrace = walk(stack.registers(None));
+  assert_eq!(backtrace.addresses(), [0x1000, 0x2000, 0x3000, 0x4000]);
+}
+
+#[test]
+fn records_link_register_once() {
+  let stack = FakeStack::new();
+
+  let backtrace = walk(stack.registers(Some(0x2000)));
+  assert_eq!(backtrace.addresses(), [0x1000, 0x2000, 0x3000, 0x4000]);
+
+  let backtrace = walk(stack.registers(Some(0x1800)));
+  assert_eq!(
+    backtrace.addresses(),
+    [0x1000, 0x1800, 0x2000, 0x3000, 0x4000]
+  );
+}
+
+#[test]
+fn stops_at_invalid_frame_pointers() {
+  let mut stack = FakeStack::new();
+
+  // A record pointing back down the stack ends the walk after its return address.
+  stack.words[4] = stack.address(2);
+  assert_eq!(
+    walk(stack.registers(None)).addresses(),
+    [0x1000, 0x2000, 0x3000]
+  );
+
+  // Frame pointers below the stack pointer or unreadable ones aren't followed.
+  let mut registers = stack.registers(Some(0x1800));
+  registers.sp = stack.address(4);
+  assert_eq!(walk(registers).addresses(), [0x1000, 0x1800]);
+
+  let registers = Registers {
+    pc: 0x1000,
+    fp: 0x10,
+    sp: 0,
+    lr: None,
+  };
+  assert_eq!(walk(registers).addresses(), [0x1000]);
+}
+
+#[test]
+fn caps_frames() {
+  let mut words = vec![0_u64; MAX_FRAMES * 2 + 8];
+  let base = words.as_ptr() as u64;
+  for index in (0 .. words.len() - 2).step_by(2) {
+    words[index] = base + (index as u64 + 2) * 8;
+    words[index + 1] = 0x2000 + index as u64;
+  }
+
+  let backtrace = walk(Registers {
+    pc: 0x1000,
+    fp: base,
+    sp: base,
+    lr: None,
+  });
+  assert_eq!(backtrace.addresses().len(), MAX_FRAMES);
+}
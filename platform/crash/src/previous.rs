@@ -9,54 +9,51 @@
 #[path = "./previous_test.rs"]
 mod tests;
 
+use crate::breadcrumbs::BreadcrumbLevel;
 use crate::schema::{
   self,
+  ArenaAnnotation,
+  ArenaBreadcrumb,
+  ArenaEntryHeader,
+  ArenaPanicLocation,
+  ArenaProcessState,
+  ArenaSignal,
+  ArenaStackFrame,
+  ArenaTag,
   CrashRecord,
+  CrashRecordHeader,
+  LegacyCrashRecord,
   RawNSExceptionCallStack,
   RawNSExceptionPayload,
-  RawNSExceptionStackFrame,
 };
-pub(crate) use schema::CrashKind;
-use std::mem::size_of;
-use std::ptr::read_unaligned;
+pub(crate) use schema::{AppState, CrashKind};
+use std::ffi::CString;
+use std::mem::{offset_of, size_of};
+use std::ptr::{copy_nonoverlapping, read_unaligned};
 
 //
-// NSExceptionCallStack
+// CallStack
 //
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) struct NSExceptionStackFrame {
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StackFrame {
   pub(crate) return_address: u64,
   pub(crate) image_load_address: u64,
-  pub(crate) binary_name: [u8; schema::NS_EXCEPTION_BINARY_NAME_CAPACITY],
-  pub(crate) image_id: [u8; schema::NS_EXCEPTION_IMAGE_ID_CAPACITY],
+  pub(crate) binary_name: Option<CString>,
+  pub(crate) image_id: Option<CString>,
 }
 
-impl Default for NSExceptionStackFrame {
-  fn default() -> Self {
-    Self {
-      return_address: 0,
-      image_load_address: 0,
-      binary_name: [0; schema::NS_EXCEPTION_BINARY_NAME_CAPACITY],
-      image_id: [0; schema::NS_EXCEPTION_IMAGE_ID_CAPACITY],
-    }
-  }
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CallStack {
+  // Kept alongside `frames` so the return addresses can be handed out as one contiguous array.
+  pub(crate) return_addresses: Vec<u64>,
+  pub(crate) frames: Vec<StackFrame>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) struct NSExceptionCallStack {
-  pub(crate) frame_count: u16,
-  pub(crate) return_addresses: [u64; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize],
-  pub(crate) frames: [NSExceptionStackFrame; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize],
-}
-
-impl Default for NSExceptionCallStack {
-  fn default() -> Self {
-    Self {
-      frame_count: 0,
-      return_addresses: [0; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize],
-      frames: std::array::from_fn(|_| NSExceptionStackFrame::default()),
-    }
+impl CallStack {
+  fn push(&mut self, frame: StackFrame) {
+    self.return_addresses.push(frame.return_address);
+    self.frames.push(frame);
   }
 }
 
@@ -64,21 +61,114 @@ impl Default for NSExceptionCallStack {
 // NSExceptionCrashInfo
 //
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub(crate) struct NSExceptionCrashInfo {
-  pub(crate) name: [u8; schema::NS_EXCEPTION_NAME_CAPACITY],
-  pub(crate) reason: [u8; schema::NS_EXCEPTION_REASON_CAPACITY],
-  pub(crate) call_stack: NSExceptionCallStack,
+  pub(crate) name: Option<CString>,
+  pub(crate) reason: Option<CString>,
+  pub(crate) call_stack: CallStack,
 }
 
-impl Default for NSExceptionCrashInfo {
-  fn default() -> Self {
-    Self {
-      name: [0; schema::NS_EXCEPTION_NAME_CAPACITY],
-      reason: [0; schema::NS_EXCEPTION_REASON_CAPACITY],
-      call_stack: NSExceptionCallStack::default(),
-    }
-  }
+//
+// SignalCrashInfo
+//
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SignalCrashInfo {
+  pub(crate) signal: i32,
+  pub(crate) code: i32,
+  pub(crate) fault_address: u64,
+  pub(crate) thread_id: u64,
+  // The crashing thread's stack, innermost frame first. Empty for records written before signal
+  // crashes were unwound.
+  pub(crate) call_stack: CallStack,
+}
+
+//
+// RustPanicCrashInfo
+//
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RustPanicCrashInfo {
+  pub(crate) message: Option<CString>,
+  pub(crate) file: Option<CString>,
+  pub(crate) line: u32,
+  pub(crate) column: u32,
+  pub(crate) thread_name: Option<CString>,
+}
+
+//
+// CxxExceptionCrashInfo
+//
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CxxExceptionCrashInfo {
+  // Demangled type of the uncaught exception. Missing when `std::terminate` was called without
+  // one.
+  pub(crate) type_name: Option<CString>,
+  // `what()` of the exception, when it derives from `std::exception`.
+  pub(crate) what: Option<CString>,
+  // The terminating thread's stack, innermost frame first.
+  pub(crate) call_stack: CallStack,
+}
+
+//
+// Breadcrumb
+//
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Breadcrumb {
+  pub(crate) timestamp_ms: u64,
+  pub(crate) level: BreadcrumbLevel,
+  pub(crate) message: CString,
+}
+
+//
+// Annotation
+//
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Annotation {
+  pub(crate) key: CString,
+  pub(crate) value: CString,
+}
+
+//
+// ProcessState
+//
+
+// The state of the process when it crashed. Counters that couldn't be read are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ProcessState {
+  // Time since the coordinator was created.
+  pub(crate) uptime_ms: u64,
+  pub(crate) resident_bytes: u64,
+  pub(crate) thread_count: u32,
+  pub(crate) open_fd_count: u32,
+  pub(crate) app_state: AppState,
+}
+
+//
+// PreviousTermination
+//
+
+// How the previous run ended. Values are exposed over the C ABI, so they must not be renumbered.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TerminationKind {
+  // The file holds no session state, e.g. on first launch or after an upgrade.
+  #[default]
+  Unknown            = 0,
+  CleanExit          = 1,
+  Crash              = 2,
+  // The run was still marked as running but left no crash record behind.
+  UncleanTermination = 3,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PreviousTermination {
+  pub(crate) kind: TerminationKind,
+  pub(crate) started_at_secs: u64,
+  pub(crate) last_heartbeat_secs: u64,
 }
 
 //
@@ -90,6 +180,21 @@ pub(crate) enum PreviousCrashDetails {
   #[default]
   None,
   NSException(Box<NSExceptionCrashInfo>),
+  Signal(SignalCrashInfo),
+  RustPanic(Box<RustPanicCrashInfo>),
+  CxxException(Box<CxxExceptionCrashInfo>),
+}
+
+impl PreviousCrashDetails {
+  // The crashing thread's native call stack. Rust panics only record their location.
+  pub(crate) fn call_stack(&self) -> Option<&CallStack> {
+    match self {
+      Self::NSException(exception) => Some(&exception.call_stack),
+      Self::Signal(signal) => Some(&signal.call_stack),
+      Self::CxxException(exception) => Some(&exception.call_stack),
+      Self::RustPanic(_) | Self::None => None,
+    }
+  }
 }
 
 //
@@ -103,119 +208,533 @@ pub(crate) struct PreviousCrashState {
   pub(crate) pid: u32,
   pub(crate) kind: CrashKind,
   pub(crate) details: PreviousCrashDetails,
+  // The annotations that were set when the crash happened.
+  pub(crate) annotations: Vec<Annotation>,
+  // The breadcrumbs recorded before the crash, oldest first.
+  pub(crate) breadcrumbs: Vec<Breadcrumb>,
+  // The state of the process at crash time. Missing for records written before it was captured.
+  pub(crate) process_state: Option<ProcessState>,
+  // Opaque bytes written by the host's crash callback, if one was registered and wrote any.
+  pub(crate) host_data: Option<Vec<u8>>,
+  // How the previous run ended. Only populated by the store; history entries keep the default.
+  pub(crate) termination: PreviousTermination,
+  // How many launches in a row, up to and including the previous one, crashed shortly after
+  // starting. Only populated by the store.
+  pub(crate) consecutive_launch_crashes: u32,
+  // Every committed crash still held in the crash history ring, newest first, including the
+  // previous launch's own crash. Only populated by the store; entries never carry nested history.
+  pub(crate) history: Vec<Self>,
+}
+
+//
+// RecordValidation
+//
+
+// How strictly a record is checked before it's decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RecordValidation {
+  // The acceptance policy used for crash reporting.
+  Strict,
+  // Also decodes records that are uncommitted, fail their checksum or hold an unknown crash kind.
+  // Only used for offline inspection; the record must still be structurally readable.
+  Lenient,
 }
 
 pub(crate) fn read_previous_state_from_bytes(bytes: &[u8]) -> PreviousCrashState {
-  if bytes.len() < size_of::<CrashRecord>() {
+  read_record(bytes, RecordValidation::Strict)
+}
+
+pub(crate) fn read_record(bytes: &[u8], validation: RecordValidation) -> PreviousCrashState {
+  if bytes.len() < size_of::<CrashRecordHeader>() {
     return PreviousCrashState::default();
   }
 
   // This function defines the acceptance policy for a persisted crash record. The record is
   // mmap-backed, so reads on a later launch must tolerate truncated or partially-corrupt contents
-  // instead of assuming the bytes were produced by a clean shutdown.
-  let raw: CrashRecord = unsafe { read_unaligned(bytes.as_ptr().cast::<CrashRecord>()) };
-  if raw.header.magic != schema::MAGIC {
+  // instead of assuming the bytes were produced by a clean shutdown. Records may also have been
+  // written by an older SDK, so the header's version selects how the rest is decoded.
+  let header: CrashRecordHeader =
+    unsafe { read_unaligned(bytes.as_ptr().cast::<CrashRecordHeader>()) };
+  if header.magic != schema::MAGIC {
     log::debug!("ignoring crash record with unexpected magic");
     return PreviousCrashState::default();
   }
 
-  if raw.header.version != schema::VERSION {
+  match header.version {
+    schema::VERSION => read_arena_record(bytes, &header, validation),
+    schema::LEGACY_VERSION => read_legacy_record(bytes, &header, validation),
+    version => {
+      log::debug!("ignoring crash record with unsupported version {version}");
+      PreviousCrashState::default()
+    },
+  }
+}
+
+fn read_arena_record(
+  bytes: &[u8],
+  header: &CrashRecordHeader,
+  validation: RecordValidation,
+) -> PreviousCrashState {
+  if bytes.len() < schema::CRASH_RECORD_FIXED_LEN {
     log::debug!(
-      "ignoring crash record with unsupported version {}",
-      raw.header.version
+      "ignoring truncated crash record ({} of {} bytes)",
+      bytes.len(),
+      schema::CRASH_RECORD_FIXED_LEN
     );
     return PreviousCrashState::default();
   }
 
-  if raw.header.record_state != schema::RecordState::Committed {
+  // The record checksum covers the fixed fields, including `arena_len`, so the arena bounds can be
+  // trusted once it matches. Entries are validated individually below.
+  let fixed_bytes = &bytes[.. schema::CRASH_RECORD_FIXED_LEN];
+  if validation == RecordValidation::Strict
+    && (!is_committed(header)
+      || !has_valid_checksum(header, fixed_bytes)
+      || !has_known_crash_kind(header, &schema::CRASH_KINDS))
+  {
+    return PreviousCrashState::default();
+  }
+
+  let timestamp_secs = unsafe {
+    read_unaligned(
+      bytes[offset_of!(CrashRecord, timestamp_secs) ..]
+        .as_ptr()
+        .cast(),
+    )
+  };
+  let pid = unsafe { read_unaligned(bytes[offset_of!(CrashRecord, pid) ..].as_ptr().cast()) };
+  let arena_len: u32 =
+    unsafe { read_unaligned(bytes[offset_of!(CrashRecord, arena_len) ..].as_ptr().cast()) };
+  let arena_len = arena_len as usize;
+  if arena_len > schema::ARENA_CAPACITY {
+    log::debug!("ignoring crash record with arena_len={arena_len} beyond the arena");
+    return PreviousCrashState::default();
+  }
+
+  let Some(arena) =
+    bytes.get(schema::CRASH_RECORD_FIXED_LEN .. schema::CRASH_RECORD_FIXED_LEN + arena_len)
+  else {
     log::debug!(
-      "ignoring crash record because record_state={} is not committed",
-      raw.header.record_state
+      "ignoring truncated crash record ({} bytes, arena needs {arena_len})",
+      bytes.len()
     );
     return PreviousCrashState::default();
+  };
+
+  let entries = ArenaEntries { arena, offset: 0 };
+  let annotations = parse_annotation_entries(ArenaEntries { arena, offset: 0 });
+  let breadcrumbs = parse_breadcrumb_entries(ArenaEntries { arena, offset: 0 });
+  let process_state = parse_process_state_entry(ArenaEntries { arena, offset: 0 });
+  let host_data = parse_host_data_entry(ArenaEntries { arena, offset: 0 });
+  let (kind, details) = match header.crash_kind {
+    kind if kind == CrashKind::NSException => (
+      CrashKind::NSException,
+      PreviousCrashDetails::NSException(Box::new(parse_nsexception_entries(entries))),
+    ),
+    kind if kind == CrashKind::Signal => (
+      CrashKind::Signal,
+      PreviousCrashDetails::Signal(parse_signal_entries(entries)),
+    ),
+    kind if kind == CrashKind::RustPanic => (
+      CrashKind::RustPanic,
+      PreviousCrashDetails::RustPanic(Box::new(parse_rust_panic_entries(entries))),
+    ),
+    kind if kind == CrashKind::CxxException => (
+      CrashKind::CxxException,
+      PreviousCrashDetails::CxxException(Box::new(parse_cxx_exception_entries(entries))),
+    ),
+    // Only reachable with lenient validation.
+    _ => (CrashKind::None, PreviousCrashDetails::None),
+  };
+
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs,
+    pid,
+    kind,
+    details,
+    annotations,
+    breadcrumbs,
+    process_state,
+    host_data,
+    termination: PreviousTermination::default(),
+    consecutive_launch_crashes: 0,
+    history: Vec::new(),
   }
+}
 
-  let expected_crc32 = schema::compute_record_checksum(&raw);
-  if raw.header.crc32 != expected_crc32 {
+fn read_legacy_record(
+  bytes: &[u8],
+  header: &CrashRecordHeader,
+  validation: RecordValidation,
+) -> PreviousCrashState {
+  let Some(record_bytes) = bytes.get(.. size_of::<LegacyCrashRecord>()) else {
     log::debug!(
-      "ignoring crash record with crc32 mismatch (expected {expected_crc32}, got {})",
-      raw.header.crc32
+      "ignoring truncated crash record (version {}, {} of {} bytes)",
+      header.version,
+      bytes.len(),
+      size_of::<LegacyCrashRecord>()
     );
     return PreviousCrashState::default();
+  };
+
+  if validation == RecordValidation::Strict
+    && (!is_committed(header)
+      || !has_valid_checksum(header, record_bytes)
+      || !has_known_crash_kind(header, &schema::LEGACY_CRASH_KINDS))
+  {
+    return PreviousCrashState::default();
   }
 
-  match raw.header.crash_kind {
-    kind if kind == CrashKind::NSException => PreviousCrashState {
-      did_crash: true,
-      timestamp_secs: raw.timestamp_secs,
-      pid: raw.pid,
-      kind: CrashKind::NSException,
-      details: PreviousCrashDetails::NSException(Box::new(parse_nsexception(&raw.nsexception))),
-    },
-    other => {
-      log::debug!("ignoring crash record with unknown crash_kind={other}");
-      PreviousCrashState::default()
-    },
+  // The legacy record is large, so it's copied to the heap rather than read onto the stack.
+  let mut raw = Box::<LegacyCrashRecord>::default();
+  unsafe {
+    copy_nonoverlapping(
+      record_bytes.as_ptr(),
+      (&raw mut *raw).cast::<u8>(),
+      record_bytes.len(),
+    );
+  }
+
+  let (kind, details) = if raw.header.crash_kind == CrashKind::NSException {
+    (
+      CrashKind::NSException,
+      PreviousCrashDetails::NSException(Box::new(parse_nsexception(&raw.nsexception))),
+    )
+  } else {
+    // Only reachable with lenient validation.
+    (CrashKind::None, PreviousCrashDetails::None)
+  };
+
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: raw.timestamp_secs,
+    pid: raw.pid,
+    kind,
+    details,
+    annotations: Vec::new(),
+    breadcrumbs: Vec::new(),
+    process_state: None,
+    host_data: None,
+    termination: PreviousTermination::default(),
+    consecutive_launch_crashes: 0,
+    history: Vec::new(),
   }
 }
 
-fn parse_nsexception(raw: &RawNSExceptionPayload) -> NSExceptionCrashInfo {
-  // Convert the persisted payload into a safer in-memory representation before exposing it to the
-  // rest of the crate. In particular, sanitize stored C strings so malformed persisted bytes are
-  // treated as absent data instead of being forwarded as unterminated strings.
-  let mut name = raw.name;
-  let mut reason = raw.reason;
-  sanitize_c_string_bytes(&mut name);
-  sanitize_c_string_bytes(&mut reason);
+fn is_committed(header: &CrashRecordHeader) -> bool {
+  if header.record_state != schema::RecordState::Committed {
+    log::debug!(
+      "ignoring crash record because record_state={} is not committed",
+      header.record_state
+    );
+    return false;
+  }
 
-  NSExceptionCrashInfo {
-    name,
-    reason,
-    call_stack: parse_nsexception_call_stack(&raw.call_stack),
+  true
+}
+
+fn has_valid_checksum(header: &CrashRecordHeader, record_bytes: &[u8]) -> bool {
+  let expected_crc32 = schema::compute_checksum(record_bytes);
+  if header.crc32 != expected_crc32 {
+    log::debug!(
+      "ignoring crash record with crc32 mismatch (expected {expected_crc32}, got {})",
+      header.crc32
+    );
+    return false;
   }
+
+  true
 }
 
-fn sanitize_c_string_bytes<const N: usize>(bytes: &mut [u8; N]) {
-  // Persisted strings are expected to be null-terminated. If a terminator is missing, treat the
-  // full field as invalid rather than guessing where a truncated or corrupt string should end.
-  if !bytes.contains(&0) {
-    bytes.fill(0);
+fn has_known_crash_kind(header: &CrashRecordHeader, crash_kinds: &[CrashKind]) -> bool {
+  if !crash_kinds.iter().any(|kind| header.crash_kind == *kind) {
+    log::debug!(
+      "ignoring crash record with crash_kind={} unknown to version {}",
+      header.crash_kind,
+      header.version
+    );
+    return false;
   }
+
+  true
 }
 
-fn parse_nsexception_call_stack(raw: &RawNSExceptionCallStack) -> NSExceptionCallStack {
-  let frame_count = raw
-    .frame_count
-    .min(schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES);
-  let mut return_addresses = [0; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize];
-  let mut frames = std::array::from_fn(|_| NSExceptionStackFrame::default());
+//
+// ArenaEntries
+//
+
+// Walks the tag-length-value entries of a record arena. Iteration stops at the first entry whose
+// header or value would extend past the arena, since nothing after it can be located reliably.
+// Entries whose checksum doesn't match are skipped.
+struct ArenaEntries<'a> {
+  arena: &'a [u8],
+  offset: usize,
+}
+
+impl<'a> Iterator for ArenaEntries<'a> {
+  type Item = (u16, &'a [u8]);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if self.offset >= self.arena.len() {
+        return None;
+      }
+
+      let value_start = self.offset + size_of::<ArenaEntryHeader>();
+      let Some(header_bytes) = self.arena.get(self.offset .. value_start) else {
+        log::debug!(
+          "truncated crash record arena entry header at {}",
+          self.offset
+        );
+        self.offset = self.arena.len();
+        return None;
+      };
+      let header: ArenaEntryHeader =
+        unsafe { read_unaligned(header_bytes.as_ptr().cast::<ArenaEntryHeader>()) };
+      let Some(value) = value_start
+        .checked_add(header.len as usize)
+        .and_then(|end| self.arena.get(value_start .. end))
+      else {
+        log::debug!(
+          "crash record arena entry at {} overruns the arena (len {})",
+          self.offset,
+          header.len
+        );
+        self.offset = self.arena.len();
+        return None;
+      };
+      self.offset = schema::align_arena_offset(value_start + value.len());
+
+      let expected_crc32 = schema::compute_entry_checksum(header.tag, header.len, &[value]);
+      if header.crc32 != expected_crc32 {
+        log::debug!(
+          "skipping crash record arena entry with tag {} and crc32 mismatch",
+          header.tag
+        );
+        continue;
+      }
+
+      return Some((header.tag, value));
+    }
+  }
+}
+
+fn parse_nsexception_entries(entries: ArenaEntries<'_>) -> NSExceptionCrashInfo {
+  let mut info = NSExceptionCrashInfo::default();
+  for (tag, value) in entries {
+    match tag {
+      tag if tag == ArenaTag::NSExceptionName => info.name = c_string(value),
+      tag if tag == ArenaTag::NSExceptionReason => info.reason = c_string(value),
+      tag if tag == ArenaTag::StackFrame => {
+        if let Some(frame) = parse_stack_frame_entry(value) {
+          info.call_stack.push(frame);
+        }
+      },
+      // Tags added by newer builds, or belonging to other crash kinds.
+      _ => {},
+    }
+  }
+  info
+}
+
+fn parse_cxx_exception_entries(entries: ArenaEntries<'_>) -> CxxExceptionCrashInfo {
+  let mut info = CxxExceptionCrashInfo::default();
+  for (tag, value) in entries {
+    match tag {
+      tag if tag == ArenaTag::CxxExceptionType => info.type_name = c_string(value),
+      tag if tag == ArenaTag::CxxExceptionWhat => info.what = c_string(value),
+      tag if tag == ArenaTag::StackFrame => {
+        if let Some(frame) = parse_stack_frame_entry(value) {
+          info.call_stack.push(frame);
+        }
+      },
+      // Tags added by newer builds, or belonging to other crash kinds.
+      _ => {},
+    }
+  }
+  info
+}
+
+fn parse_stack_frame_entry(value: &[u8]) -> Option<StackFrame> {
+  let fixed_len = size_of::<ArenaStackFrame>();
+  let frame: ArenaStackFrame = read_pod(value)?;
+  let binary_name_end = fixed_len + usize::from(frame.binary_name_len);
+  let image_id_end = binary_name_end + usize::from(frame.image_id_len);
+  if image_id_end > value.len() {
+    log::debug!("skipping crash record stack frame with out of bounds strings");
+    return None;
+  }
+
+  Some(StackFrame {
+    return_address: frame.return_address,
+    image_load_address: frame.image_load_address,
+    binary_name: c_string(&value[fixed_len .. binary_name_end]),
+    image_id: c_string(&value[binary_name_end .. image_id_end]),
+  })
+}
+
+fn parse_signal_entries(entries: ArenaEntries<'_>) -> SignalCrashInfo {
+  let mut info = SignalCrashInfo::default();
+  let mut found_signal = false;
+  for (tag, value) in entries {
+    match tag {
+      tag if tag == ArenaTag::Signal && !found_signal => {
+        if let Some(signal) = read_pod::<ArenaSignal>(value) {
+          found_signal = true;
+          info.signal = signal.signal;
+          info.code = signal.code;
+          info.fault_address = signal.fault_address;
+          info.thread_id = signal.thread_id;
+        }
+      },
+      tag if tag == ArenaTag::StackFrame => {
+        if let Some(frame) = parse_stack_frame_entry(value) {
+          info.call_stack.push(frame);
+        }
+      },
+      // Tags added by newer builds, or belonging to other crash kinds.
+      _ => {},
+    }
+  }
+  info
+}
+
+fn parse_rust_panic_entries(entries: ArenaEntries<'_>) -> RustPanicCrashInfo {
+  let mut info = RustPanicCrashInfo::default();
+  for (tag, value) in entries {
+    match tag {
+      tag if tag == ArenaTag::PanicMessage => info.message = c_string(value),
+      tag if tag == ArenaTag::PanicFile => info.file = c_string(value),
+      tag if tag == ArenaTag::PanicLocation => {
+        if let Some(location) = read_pod::<ArenaPanicLocation>(value) {
+          info.line = location.line;
+          info.column = location.column;
+        }
+      },
+      tag if tag == ArenaTag::PanicThreadName => info.thread_name = c_string(value),
+      _ => {},
+    }
+  }
+  info
+}
+
+fn parse_annotation_entries(entries: ArenaEntries<'_>) -> Vec<Annotation> {
+  entries
+    .filter(|(tag, _)| *tag == ArenaTag::Annotation)
+    .filter_map(|(_, value)| {
+      let fixed_len = size_of::<ArenaAnnotation>();
+      let annotation: ArenaAnnotation = read_pod(value)?;
+      let key_end = fixed_len + usize::from(annotation.key_len);
+      let value_end = key_end + usize::from(annotation.value_len);
+      if value_end > value.len() {
+        log::debug!("skipping crash record annotation with out of bounds strings");
+        return None;
+      }
+
+      // Annotations always have a key, but may have been set to an empty value.
+      Some(Annotation {
+        key: c_string(&value[fixed_len .. key_end])?,
+        value: c_string(&value[key_end .. value_end]).unwrap_or_default(),
+      })
+    })
+    .collect()
+}
+
+fn parse_process_state_entry(mut entries: ArenaEntries<'_>) -> Option<ProcessState> {
+  entries
+    .find(|(tag, _)| *tag == ArenaTag::ProcessState)
+    .and_then(|(_, value)| read_pod::<ArenaProcessState>(value))
+    .map(|process_state| ProcessState {
+      uptime_ms: process_state.uptime_ms,
+      resident_bytes: process_state.resident_bytes,
+      thread_count: process_state.thread_count,
+      open_fd_count: process_state.open_fd_count,
+      app_state: AppState::from_u8(process_state.app_state),
+    })
+}
+
+fn parse_host_data_entry(mut entries: ArenaEntries<'_>) -> Option<Vec<u8>> {
+  entries
+    .find(|(tag, _)| *tag == ArenaTag::HostData)
+    .map(|(_, value)| value.to_vec())
+}
 
-  for (index, raw_frame) in raw.frames[.. usize::from(frame_count)].iter().enumerate() {
-    let mut binary_name = raw_frame.binary_name;
-    let mut image_id = raw_frame.image_id;
-    sanitize_c_string_bytes(&mut binary_name);
-    sanitize_c_string_bytes(&mut image_id);
+fn parse_breadcrumb_entries(entries: ArenaEntries<'_>) -> Vec<Breadcrumb> {
+  // The writer stores breadcrumbs newest first so the oldest are dropped when the arena is full.
+  let mut breadcrumbs = entries
+    .filter(|(tag, _)| *tag == ArenaTag::Breadcrumb)
+    .filter_map(|(_, value)| {
+      let breadcrumb: ArenaBreadcrumb = read_pod(value)?;
+      let Some(level) = BreadcrumbLevel::from_u8(breadcrumb.level) else {
+        log::debug!(
+          "skipping breadcrumb with unknown level {}",
+          breadcrumb.level
+        );
+        return None;
+      };
+      Some(Breadcrumb {
+        timestamp_ms: breadcrumb.timestamp_ms,
+        level,
+        message: c_string(&value[size_of::<ArenaBreadcrumb>() ..]).unwrap_or_default(),
+      })
+    })
+    .collect::<Vec<_>>();
+  breadcrumbs.reverse();
+  breadcrumbs
+}
 
-    return_addresses[index] = raw_frame.return_address;
-    frames[index] = parse_nsexception_stack_frame(raw_frame, binary_name, image_id);
+fn read_pod<T: Copy>(value: &[u8]) -> Option<T> {
+  // Values may grow new trailing fields in later builds, so only the known prefix is required.
+  if value.len() < size_of::<T>() {
+    log::debug!("skipping undersized crash record arena entry");
+    return None;
   }
 
-  NSExceptionCallStack {
-    frame_count,
-    return_addresses,
-    frames,
+  Some(unsafe { read_unaligned(value.as_ptr().cast::<T>()) })
+}
+
+fn c_string(bytes: &[u8]) -> Option<CString> {
+  // Strings are handed out as C strings, so treat values with interior nulls as absent rather than
+  // silently cutting them short.
+  if bytes.is_empty() {
+    return None;
   }
+
+  CString::new(bytes).ok()
 }
 
-const fn parse_nsexception_stack_frame(
-  raw: &RawNSExceptionStackFrame,
-  binary_name: [u8; schema::NS_EXCEPTION_BINARY_NAME_CAPACITY],
-  image_id: [u8; schema::NS_EXCEPTION_IMAGE_ID_CAPACITY],
-) -> NSExceptionStackFrame {
-  NSExceptionStackFrame {
-    return_address: raw.return_address,
-    image_load_address: raw.image_load_address,
-    binary_name,
-    image_id,
+//
+// Legacy payload
+//
+
+fn parse_nsexception(raw: &RawNSExceptionPayload) -> NSExceptionCrashInfo {
+  NSExceptionCrashInfo {
+    name: legacy_c_string(&raw.name),
+    reason: legacy_c_string(&raw.reason),
+    call_stack: parse_nsexception_call_stack(&raw.call_stack),
+  }
+}
+
+fn legacy_c_string(bytes: &[u8]) -> Option<CString> {
+  // Persisted strings are expected to be null-terminated. If a terminator is missing, treat the
+  // full field as invalid rather than guessing where a truncated or corrupt string should end.
+  let len = bytes.iter().position(|byte| *byte == 0)?;
+  c_string(&bytes[.. len])
+}
+
+fn parse_nsexception_call_stack(raw: &RawNSExceptionCallStack) -> CallStack {
+  let frame_count = raw
+    .frame_count
+    .min(schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES);
+  let mut call_stack = CallStack::default();
+  for raw_frame in &raw.frames[.. usize::from(frame_count)] {
+    call_stack.push(StackFrame {
+      return_address: raw_frame.return_address,
+      image_load_address: raw_frame.image_load_address,
+      binary_name: legacy_c_string(&raw_frame.binary_name),
+      image_id: legacy_c_string(&raw_frame.image_id),
+    });
   }
+  call_stack
 }
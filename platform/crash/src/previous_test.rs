@@ -5,44 +5,171 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
-#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic, clippy::unwrap_used)]
 
 use super::{
+  Annotation,
+  Breadcrumb,
+  CallStack,
   CrashKind,
-  NSExceptionCallStack,
-  NSExceptionStackFrame,
+  CxxExceptionCrashInfo,
+  NSExceptionCrashInfo,
   PreviousCrashDetails,
   PreviousCrashState,
+  PreviousTermination,
+  RustPanicCrashInfo,
+  SignalCrashInfo,
+  StackFrame,
   read_previous_state_from_bytes,
 };
-use crate::schema::{self, CrashRecord, CrashRecordHeader, RecordState};
-use std::mem::size_of;
+use crate::breadcrumbs::BreadcrumbLevel;
+use crate::schema::{
+  self,
+  ArenaEntryHeader,
+  ArenaTag,
+  CrashRecord,
+  CrashRecordHeader,
+  LegacyCrashRecord,
+  RecordState,
+};
+use std::ffi::CString;
+use std::mem::{offset_of, size_of};
 use std::slice::from_raw_parts;
 
+// Frozen byte images of committed records, each written by the SDK build that introduced the
+// layout version in its name. Released versions must never be regenerated: they guard the promise
+// that a crash persisted by an older SDK is still reported after an upgrade.
+const RECORD_FIXTURES: &[(u32, CrashKind, &[u8])] = &[
+  (
+    1,
+    CrashKind::NSException,
+    include_bytes!("../fixtures/records/v1_nsexception.bin"),
+  ),
+  (
+    4,
+    CrashKind::NSException,
+    include_bytes!("../fixtures/records/v4_nsexception.bin"),
+  ),
+  (
+    4,
+    CrashKind::Signal,
+    include_bytes!("../fixtures/records/v4_signal.bin"),
+  ),
+  (
+    4,
+    CrashKind::RustPanic,
+    include_bytes!("../fixtures/records/v4_rust_panic.bin"),
+  ),
+  (
+    4,
+    CrashKind::CxxException,
+    include_bytes!("../fixtures/records/v4_cxx_exception.bin"),
+  ),
+];
+
+fn record_fixtures(kind: CrashKind) -> impl Iterator<Item = (u32, &'static [u8])> {
+  RECORD_FIXTURES
+    .iter()
+    .filter(move |(_, fixture_kind, _)| *fixture_kind == kind)
+    .map(|(version, _, bytes)| (*version, *bytes))
+}
+
+fn c_string(value: &str) -> Option<CString> {
+  Some(CString::new(value).unwrap())
+}
+
 fn crash_record_bytes(record: &CrashRecord) -> &[u8] {
   unsafe { from_raw_parts((&raw const *record).cast::<u8>(), size_of::<CrashRecord>()) }
 }
 
-fn committed_nsexception_record() -> CrashRecord {
-  CrashRecord {
-    header: CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION,
-      record_state: RecordState::Committed.into(),
-      crash_kind: CrashKind::NSException.into(),
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    timestamp_secs: 99,
-    pid: 7,
-    ..CrashRecord::default()
-  }
+fn committed_record(kind: CrashKind) -> Box<CrashRecord> {
+  let mut record = Box::<CrashRecord>::default();
+  record.header = CrashRecordHeader {
+    magic: schema::MAGIC,
+    version: schema::VERSION,
+    record_state: RecordState::Committed.into(),
+    crash_kind: kind.into(),
+    reserved: [0; 2],
+    crc32: 0,
+  };
+  record.timestamp_secs = 99;
+  record.pid = 7;
+  record
+}
+
+// Appends an entry the same way the writer does, returning its offset in the arena.
+fn push_entry(record: &mut CrashRecord, tag: u16, value: &[u8]) -> usize {
+  let start = record.arena_len as usize;
+  let len = u32::try_from(value.len()).unwrap();
+  let header = ArenaEntryHeader {
+    tag,
+    reserved: 0,
+    len,
+    crc32: schema::compute_entry_checksum(tag, len, &[value]),
+  };
+  let header_bytes = unsafe {
+    from_raw_parts(
+      (&raw const header).cast::<u8>(),
+      size_of::<ArenaEntryHeader>(),
+    )
+  };
+  let value_start = start + header_bytes.len();
+  record.arena[start .. value_start].copy_from_slice(header_bytes);
+  record.arena[value_start .. value_start + value.len()].copy_from_slice(value);
+  record.arena_len = u32::try_from(schema::align_arena_offset(value_start + value.len())).unwrap();
+  start
+}
+
+fn stack_frame_value(return_address: u64, binary_name: &[u8], image_id: &[u8]) -> Vec<u8> {
+  let mut value = Vec::new();
+  value.extend_from_slice(&return_address.to_ne_bytes());
+  value.extend_from_slice(&0x1000_u64.to_ne_bytes());
+  value.extend_from_slice(&u16::try_from(binary_name.len()).unwrap().to_ne_bytes());
+  value.extend_from_slice(&u16::try_from(image_id.len()).unwrap().to_ne_bytes());
+  value.extend_from_slice(&[0; 4]);
+  value.extend_from_slice(binary_name);
+  value.extend_from_slice(image_id);
+  value
 }
 
 fn finalize_crc32(record: &mut CrashRecord) {
   record.header.crc32 = schema::compute_record_checksum(record);
 }
 
+fn decoded_nsexception(record: &CrashRecord) -> NSExceptionCrashInfo {
+  match read_previous_state_from_bytes(crash_record_bytes(record)).details {
+    PreviousCrashDetails::NSException(exception) => *exception,
+    other => panic!("expected an NSException record, got {other:?}"),
+  }
+}
+
+fn committed_legacy_record(kind: CrashKind) -> Box<LegacyCrashRecord> {
+  let mut record = Box::<LegacyCrashRecord>::default();
+  record.header = CrashRecordHeader {
+    magic: schema::MAGIC,
+    version: schema::LEGACY_VERSION,
+    record_state: RecordState::Committed.into(),
+    crash_kind: kind.into(),
+    reserved: [0; 2],
+    crc32: 0,
+  };
+  record
+}
+
+// Returns the record's bytes with the legacy checksum, which covers the whole record.
+fn legacy_record_bytes(record: &LegacyCrashRecord) -> Vec<u8> {
+  let mut bytes = unsafe {
+    from_raw_parts(
+      (&raw const *record).cast::<u8>(),
+      size_of::<LegacyCrashRecord>(),
+    )
+  }
+  .to_vec();
+  let crc32 = schema::compute_checksum(&bytes);
+  bytes[offset_of!(CrashRecordHeader, crc32) ..][.. 4].copy_from_slice(&crc32.to_ne_bytes());
+  bytes
+}
+
 #[test]
 fn ignores_incomplete_bytes() {
   assert_eq!(
@@ -53,17 +180,9 @@ fn ignores_incomplete_bytes() {
 
 #[test]
 fn ignores_record_with_unexpected_magic() {
-  let raw = CrashRecord {
-    header: CrashRecordHeader {
-      magic: 0,
-      version: schema::VERSION,
-      record_state: RecordState::Committed.into(),
-      crash_kind: CrashKind::NSException.into(),
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    ..CrashRecord::default()
-  };
+  let mut raw = committed_record(CrashKind::NSException);
+  raw.header.magic = 0;
+  finalize_crc32(&mut raw);
 
   assert_eq!(
     read_previous_state_from_bytes(crash_record_bytes(&raw)),
@@ -73,17 +192,9 @@ fn ignores_record_with_unexpected_magic() {
 
 #[test]
 fn ignores_record_with_unsupported_version() {
-  let raw = CrashRecord {
-    header: CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION + 1,
-      record_state: RecordState::Committed.into(),
-      crash_kind: CrashKind::NSException.into(),
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    ..CrashRecord::default()
-  };
+  let mut raw = committed_record(CrashKind::NSException);
+  raw.header.version = schema::VERSION + 1;
+  finalize_crc32(&mut raw);
 
   assert_eq!(
     read_previous_state_from_bytes(crash_record_bytes(&raw)),
@@ -93,17 +204,9 @@ fn ignores_record_with_unsupported_version() {
 
 #[test]
 fn ignores_uncommitted_records() {
-  let raw = CrashRecord {
-    header: CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION,
-      record_state: RecordState::Writing.into(),
-      crash_kind: CrashKind::NSException.into(),
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    ..CrashRecord::default()
-  };
+  let mut raw = committed_record(CrashKind::NSException);
+  raw.header.record_state = RecordState::Writing.into();
+  finalize_crc32(&mut raw);
 
   assert_eq!(
     read_previous_state_from_bytes(crash_record_bytes(&raw)),
@@ -113,10 +216,9 @@ fn ignores_uncommitted_records() {
 
 #[test]
 fn ignores_record_with_crc32_mismatch() {
-  let mut raw = committed_nsexception_record();
-  raw.nsexception.name[.. 12].copy_from_slice(b"NSException\0");
+  let mut raw = committed_record(CrashKind::NSException);
   finalize_crc32(&mut raw);
-  raw.nsexception.name[0] = b'X';
+  raw.timestamp_secs += 1;
 
   assert_eq!(
     read_previous_state_from_bytes(crash_record_bytes(&raw)),
@@ -126,17 +228,9 @@ fn ignores_record_with_crc32_mismatch() {
 
 #[test]
 fn ignores_unknown_crash_kind() {
-  let raw = CrashRecord {
-    header: CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION,
-      record_state: RecordState::Committed.into(),
-      crash_kind: 255,
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    ..CrashRecord::default()
-  };
+  let mut raw = committed_record(CrashKind::NSException);
+  raw.header.crash_kind = 255;
+  finalize_crc32(&mut raw);
 
   assert_eq!(
     read_previous_state_from_bytes(crash_record_bytes(&raw)),
@@ -146,16 +240,19 @@ fn ignores_unknown_crash_kind() {
 
 #[test]
 fn reads_committed_nsexception() {
-  let mut raw = committed_nsexception_record();
-  raw.nsexception.name[.. 12].copy_from_slice(b"NSException\0");
-  raw.nsexception.reason[.. 12].copy_from_slice(b"bad reason!\0");
-  raw.nsexception.call_stack.frame_count = 2;
-  raw.nsexception.call_stack.frames[0].return_address = 10;
-  raw.nsexception.call_stack.frames[1].return_address = 11;
-  raw.nsexception.call_stack.frames[0].image_load_address = 0x1000;
-  raw.nsexception.call_stack.frames[0].binary_name[.. 6].copy_from_slice(b"MyApp\0");
-  raw.nsexception.call_stack.frames[0].image_id[.. schema::NS_EXCEPTION_IMAGE_ID_CAPACITY]
-    .copy_from_slice(b"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0\0");
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  push_entry(&mut raw, ArenaTag::NSExceptionReason.into(), b"bad reason!");
+  push_entry(
+    &mut raw,
+    ArenaTag::StackFrame.into(),
+    &stack_frame_value(10, b"MyApp", b"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0"),
+  );
+  push_entry(
+    &mut raw,
+    ArenaTag::StackFrame.into(),
+    &stack_frame_value(11, b"", b""),
+  );
   finalize_crc32(&mut raw);
 
   let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
@@ -164,111 +261,451 @@ fn reads_committed_nsexception() {
   assert_eq!(previous.timestamp_secs, 99);
   assert_eq!(previous.pid, 7);
   assert_eq!(previous.kind, CrashKind::NSException);
-  assert!(matches!(
-    &previous.details,
-    PreviousCrashDetails::NSException(_)
-  ));
-  let exception = match previous.details {
-    PreviousCrashDetails::NSException(exception) => exception,
-    PreviousCrashDetails::None => return,
+  let PreviousCrashDetails::NSException(exception) = previous.details else {
+    panic!("expected an NSException record");
   };
-  assert_eq!(&exception.name[.. 12], b"NSException\0");
-  assert_eq!(&exception.reason[.. 12], b"bad reason!\0");
+  assert_eq!(exception.name, c_string("NSException"));
+  assert_eq!(exception.reason, c_string("bad reason!"));
   assert_eq!(
     exception.call_stack,
-    NSExceptionCallStack {
-      frame_count: 2,
-      return_addresses: {
-        let mut return_addresses = [0; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize];
-        return_addresses[.. 2].copy_from_slice(&[10, 11]);
-        return_addresses
-      },
-      frames: {
-        let mut frames = std::array::from_fn(|_| NSExceptionStackFrame::default());
-        frames[0] = NSExceptionStackFrame {
+    CallStack {
+      return_addresses: vec![10, 11],
+      frames: vec![
+        StackFrame {
           return_address: 10,
           image_load_address: 0x1000,
-          binary_name: {
-            let mut binary_name = [0; schema::NS_EXCEPTION_BINARY_NAME_CAPACITY];
-            binary_name[.. 6].copy_from_slice(b"MyApp\0");
-            binary_name
-          },
-          image_id: {
-            let mut image_id = [0; schema::NS_EXCEPTION_IMAGE_ID_CAPACITY];
-            image_id[.. schema::NS_EXCEPTION_IMAGE_ID_CAPACITY]
-              .copy_from_slice(b"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0\0");
-            image_id
-          },
-        };
-        frames[1].return_address = 11;
-        frames
-      },
+          binary_name: c_string("MyApp"),
+          image_id: c_string("BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0"),
+        },
+        StackFrame {
+          return_address: 11,
+          image_load_address: 0x1000,
+          binary_name: None,
+          image_id: None,
+        },
+      ],
     }
   );
 }
 
 #[test]
-fn clamps_nsexception_frame_count_to_capacity() {
-  let mut raw = committed_nsexception_record();
-  raw.nsexception.call_stack.frame_count = u16::MAX;
+fn skips_arena_entry_with_crc32_mismatch() {
+  let mut raw = committed_record(CrashKind::NSException);
+  let name_offset = push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  push_entry(&mut raw, ArenaTag::NSExceptionReason.into(), b"bad reason!");
+  finalize_crc32(&mut raw);
+  raw.arena[name_offset + size_of::<ArenaEntryHeader>()] = b'X';
+
+  let exception = decoded_nsexception(&raw);
+
+  assert_eq!(exception.name, None);
+  assert_eq!(exception.reason, c_string("bad reason!"));
+}
+
+#[test]
+fn stops_at_arena_entry_that_overruns_arena() {
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  let reason_offset = push_entry(&mut raw, ArenaTag::NSExceptionReason.into(), b"bad reason!");
+  let len_offset = reason_offset + offset_of!(ArenaEntryHeader, len);
+  raw.arena[len_offset .. len_offset + 4].copy_from_slice(&u32::MAX.to_ne_bytes());
+  finalize_crc32(&mut raw);
+
+  let exception = decoded_nsexception(&raw);
+
+  assert_eq!(exception.name, c_string("NSException"));
+  assert_eq!(exception.reason, None);
+}
+
+#[test]
+fn ignores_arena_entries_past_arena_len() {
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  let arena_len = raw.arena_len;
+  push_entry(&mut raw, ArenaTag::NSExceptionReason.into(), b"bad reason!");
+  raw.arena_len = arena_len;
+  finalize_crc32(&mut raw);
+
+  assert_eq!(decoded_nsexception(&raw).reason, None);
+}
+
+#[test]
+fn skips_unknown_arena_tags() {
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, u16::MAX, b"from a newer build");
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  finalize_crc32(&mut raw);
+
+  assert_eq!(decoded_nsexception(&raw).name, c_string("NSException"));
+}
+
+#[test]
+fn skips_stack_frame_with_out_of_bounds_strings() {
+  let mut raw = committed_record(CrashKind::NSException);
+  let mut value = stack_frame_value(10, b"MyApp", b"");
+  value.truncate(value.len() - 1);
+  push_entry(&mut raw, ArenaTag::StackFrame.into(), &value);
+  push_entry(
+    &mut raw,
+    ArenaTag::StackFrame.into(),
+    &stack_frame_value(11, b"MyApp", b""),
+  );
+  finalize_crc32(&mut raw);
+
+  assert_eq!(
+    decoded_nsexception(&raw).call_stack.return_addresses,
+    vec![11]
+  );
+}
+
+#[test]
+fn treats_strings_with_interior_nulls_as_absent() {
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NS\0Exception");
+  finalize_crc32(&mut raw);
+
+  assert_eq!(decoded_nsexception(&raw).name, None);
+}
+
+#[test]
+fn ignores_record_with_arena_len_beyond_capacity() {
+  let mut raw = committed_record(CrashKind::NSException);
+  raw.arena_len = u32::try_from(schema::ARENA_CAPACITY + 4).unwrap();
+  finalize_crc32(&mut raw);
+
+  assert_eq!(
+    read_previous_state_from_bytes(crash_record_bytes(&raw)),
+    PreviousCrashState::default()
+  );
+}
+
+#[test]
+fn ignores_record_with_truncated_arena() {
+  let mut raw = committed_record(CrashKind::NSException);
+  push_entry(&mut raw, ArenaTag::NSExceptionName.into(), b"NSException");
+  finalize_crc32(&mut raw);
+  let len = schema::CRASH_RECORD_FIXED_LEN + raw.arena_len as usize;
+
+  assert!(read_previous_state_from_bytes(&crash_record_bytes(&raw)[.. len]).did_crash);
+  assert_eq!(
+    read_previous_state_from_bytes(&crash_record_bytes(&raw)[.. len - 1]),
+    PreviousCrashState::default()
+  );
+}
+
+#[test]
+fn reads_committed_signal() {
+  let mut raw = committed_record(CrashKind::Signal);
+  let mut value = Vec::new();
+  value.extend_from_slice(&11_i32.to_ne_bytes());
+  value.extend_from_slice(&1_i32.to_ne_bytes());
+  value.extend_from_slice(&0xdead_beef_u64.to_ne_bytes());
+  value.extend_from_slice(&42_u64.to_ne_bytes());
+  push_entry(&mut raw, ArenaTag::Signal.into(), &value);
   finalize_crc32(&mut raw);
 
   let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
 
-  assert!(matches!(
-    &previous.details,
-    PreviousCrashDetails::NSException(_)
-  ));
-  let exception = match previous.details {
-    PreviousCrashDetails::NSException(exception) => exception,
-    PreviousCrashDetails::None => return,
-  };
   assert_eq!(
-    exception.call_stack.frame_count,
-    schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES
+    previous,
+    PreviousCrashState {
+      did_crash: true,
+      timestamp_secs: 99,
+      pid: 7,
+      kind: CrashKind::Signal,
+      details: PreviousCrashDetails::Signal(SignalCrashInfo {
+        signal: 11,
+        code: 1,
+        fault_address: 0xdead_beef,
+        thread_id: 42,
+        ..SignalCrashInfo::default()
+      }),
+      annotations: Vec::new(),
+      breadcrumbs: Vec::new(),
+      process_state: None,
+      host_data: None,
+      termination: PreviousTermination::default(),
+      consecutive_launch_crashes: 0,
+      history: Vec::new(),
+    }
   );
 }
 
 #[test]
-fn clears_unterminated_nsexception_strings() {
-  let mut raw = committed_nsexception_record();
-  raw.nsexception.name.fill(b'A');
-  raw.nsexception.reason.fill(b'B');
+fn reads_committed_rust_panic() {
+  let mut raw = committed_record(CrashKind::RustPanic);
+  push_entry(&mut raw, ArenaTag::PanicMessage.into(), b"boom");
+  push_entry(&mut raw, ArenaTag::PanicFile.into(), b"src/lib.rs");
+  let mut location = Vec::new();
+  location.extend_from_slice(&12_u32.to_ne_bytes());
+  location.extend_from_slice(&5_u32.to_ne_bytes());
+  push_entry(&mut raw, ArenaTag::PanicLocation.into(), &location);
+  finalize_crc32(&mut raw);
+
+  let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
+
+  assert!(previous.did_crash);
+  assert_eq!(previous.kind, CrashKind::RustPanic);
+  assert_eq!(
+    previous.details,
+    PreviousCrashDetails::RustPanic(Box::new(RustPanicCrashInfo {
+      message: c_string("boom"),
+      file: c_string("src/lib.rs"),
+      line: 12,
+      column: 5,
+      thread_name: None,
+    }))
+  );
+}
+
+#[test]
+fn reads_breadcrumbs_oldest_first() {
+  let mut raw = committed_record(CrashKind::Signal);
+  for (timestamp_ms, level, message) in [
+    (3_u64, 4_u8, b"newest".as_slice()),
+    (2, 200, b"unknown level"),
+    (1, 2, b"oldest"),
+  ] {
+    let mut value = Vec::new();
+    value.extend_from_slice(&timestamp_ms.to_ne_bytes());
+    value.push(level);
+    value.extend_from_slice(&[0; 7]);
+    value.extend_from_slice(message);
+    push_entry(&mut raw, ArenaTag::Breadcrumb.into(), &value);
+  }
   finalize_crc32(&mut raw);
 
   let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
 
-  assert!(matches!(
-    &previous.details,
-    PreviousCrashDetails::NSException(_)
-  ));
-  let exception = match previous.details {
-    PreviousCrashDetails::NSException(exception) => exception,
-    PreviousCrashDetails::None => return,
+  assert_eq!(
+    previous.breadcrumbs,
+    vec![
+      Breadcrumb {
+        timestamp_ms: 1,
+        level: BreadcrumbLevel::Info,
+        message: c"oldest".to_owned(),
+      },
+      Breadcrumb {
+        timestamp_ms: 3,
+        level: BreadcrumbLevel::Error,
+        message: c"newest".to_owned(),
+      },
+    ]
+  );
+}
+
+#[test]
+fn skips_annotations_with_out_of_bounds_or_missing_keys() {
+  let mut raw = committed_record(CrashKind::Signal);
+  for (key_len, value_len, bytes) in [
+    (6_u16, 8_u16, b"screencheckout".as_slice()),
+    (6, 200, b"screencheckout"),
+    (0, 5, b"value"),
+  ] {
+    let mut value = Vec::new();
+    value.extend_from_slice(&key_len.to_ne_bytes());
+    value.extend_from_slice(&value_len.to_ne_bytes());
+    value.extend_from_slice(bytes);
+    push_entry(&mut raw, ArenaTag::Annotation.into(), &value);
+  }
+  finalize_crc32(&mut raw);
+
+  let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
+
+  assert_eq!(
+    previous.annotations,
+    vec![Annotation {
+      key: c"screen".to_owned(),
+      value: c"checkout".to_owned(),
+    }]
+  );
+}
+
+#[test]
+fn clamps_legacy_nsexception_frame_count_to_capacity() {
+  let mut raw = committed_legacy_record(CrashKind::NSException);
+  raw.nsexception.call_stack.frame_count = u16::MAX;
+
+  let previous = read_previous_state_from_bytes(&legacy_record_bytes(&raw));
+
+  let PreviousCrashDetails::NSException(exception) = previous.details else {
+    panic!("expected an NSException record");
   };
-  assert_eq!(exception.name[0], 0);
-  assert_eq!(exception.reason[0], 0);
+  assert_eq!(
+    exception.call_stack.frames.len(),
+    usize::from(schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES)
+  );
 }
 
 #[test]
-fn clears_unterminated_frame_metadata_strings() {
-  let mut raw = committed_nsexception_record();
+fn clears_unterminated_legacy_strings() {
+  let mut raw = committed_legacy_record(CrashKind::NSException);
+  raw.nsexception.name.fill(b'A');
+  raw.nsexception.reason.fill(b'B');
   raw.nsexception.call_stack.frame_count = 1;
   raw.nsexception.call_stack.frames[0].return_address = 10;
   raw.nsexception.call_stack.frames[0].binary_name.fill(b'A');
   raw.nsexception.call_stack.frames[0].image_id.fill(b'B');
-  finalize_crc32(&mut raw);
 
-  let previous = read_previous_state_from_bytes(crash_record_bytes(&raw));
+  let previous = read_previous_state_from_bytes(&legacy_record_bytes(&raw));
 
-  assert!(matches!(
-    &previous.details,
-    PreviousCrashDetails::NSException(_)
-  ));
-  let exception = match previous.details {
-    PreviousCrashDetails::NSException(exception) => exception,
-    PreviousCrashDetails::None => return,
+  let PreviousCrashDetails::NSException(exception) = previous.details else {
+    panic!("expected an NSException record");
   };
-  assert_eq!(exception.call_stack.frames[0].binary_name[0], 0);
-  assert_eq!(exception.call_stack.frames[0].image_id[0], 0);
+  assert_eq!(exception.name, None);
+  assert_eq!(exception.reason, None);
+  assert_eq!(
+    exception.call_stack.frames,
+    vec![StackFrame {
+      return_address: 10,
+      ..StackFrame::default()
+    }]
+  );
+}
+
+#[test]
+fn record_fixtures_cover_every_supported_layout() {
+  for kind in &schema::LEGACY_CRASH_KINDS {
+    let fixture = RECORD_FIXTURES.iter().find(|(version, fixture_kind, _)| {
+      *version == schema::LEGACY_VERSION && fixture_kind == kind
+    });
+    assert!(
+      fixture.is_some_and(|(_, _, bytes)| bytes.len() == size_of::<LegacyCrashRecord>()),
+      "missing or mis-sized fixture for version {} kind {kind:?}",
+      schema::LEGACY_VERSION
+    );
+  }
+  for kind in &schema::CRASH_KINDS {
+    assert!(
+      RECORD_FIXTURES
+        .iter()
+        .any(|(version, fixture_kind, _)| *version == schema::VERSION && fixture_kind == kind),
+      "missing fixture for version {} kind {kind:?}",
+      schema::VERSION
+    );
+  }
+}
+
+#[test]
+fn reads_nsexception_fixtures_from_every_version() {
+  for (version, bytes) in record_fixtures(CrashKind::NSException) {
+    let previous = read_previous_state_from_bytes(bytes);
+
+    assert!(previous.did_crash, "version {version}");
+    assert_eq!(previous.timestamp_secs, 1_700_000_000);
+    assert_eq!(previous.pid, 4242);
+    let PreviousCrashDetails::NSException(exception) = previous.details else {
+      panic!("version {version} did not decode as an NSException");
+    };
+    assert_eq!(exception.name, c_string("NSInvalidArgumentException"));
+    assert_eq!(exception.reason, c_string("fixture reason"));
+    assert_eq!(
+      exception.call_stack.return_addresses,
+      vec![0x1_0000_1234, 0x1_8000_5678]
+    );
+    let frame = &exception.call_stack.frames[0];
+    assert_eq!(frame.image_load_address, 0x1_0000_0000);
+    assert_eq!(frame.binary_name, c_string("MyApp"));
+    assert_eq!(
+      frame.image_id,
+      c_string("BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0")
+    );
+  }
+}
+
+#[test]
+fn reads_signal_fixtures_from_every_version() {
+  for (version, bytes) in record_fixtures(CrashKind::Signal) {
+    let previous = read_previous_state_from_bytes(bytes);
+
+    assert_eq!(previous.kind, CrashKind::Signal, "version {version}");
+    assert_eq!(
+      previous.details,
+      PreviousCrashDetails::Signal(SignalCrashInfo {
+        signal: 11,
+        code: 1,
+        fault_address: 0xdead_beef,
+        thread_id: 4243,
+        ..SignalCrashInfo::default()
+      })
+    );
+  }
+}
+
+#[test]
+fn reads_rust_panic_fixtures_from_every_version() {
+  for (version, bytes) in record_fixtures(CrashKind::RustPanic) {
+    let previous = read_previous_state_from_bytes(bytes);
+
+    assert_eq!(previous.kind, CrashKind::RustPanic, "version {version}");
+    assert_eq!(
+      previous.details,
+      PreviousCrashDetails::RustPanic(Box::new(RustPanicCrashInfo {
+        message: c_string("fixture panic"),
+        file: c_string("src/lib.rs"),
+        line: 12,
+        column: 5,
+        thread_name: c_string("main"),
+      }))
+    );
+  }
+}
+
+#[test]
+fn reads_cxx_exception_fixtures_from_every_version() {
+  for (version, bytes) in record_fixtures(CrashKind::CxxException) {
+    let previous = read_previous_state_from_bytes(bytes);
+
+    assert_eq!(previous.kind, CrashKind::CxxException, "version {version}");
+    let PreviousCrashDetails::CxxException(exception) = previous.details else {
+      panic!("version {version} did not decode as a C++ exception");
+    };
+    assert_eq!(
+      *exception,
+      CxxExceptionCrashInfo {
+        type_name: c_string("std::runtime_error"),
+        what: c_string("fixture what"),
+        call_stack: CallStack {
+          return_addresses: vec![0x7f00_0000_1234, 0x7f00_8000_5678],
+          frames: vec![
+            StackFrame {
+              return_address: 0x7f00_0000_1234,
+              image_load_address: 0x7f00_0000_0000,
+              binary_name: c_string("/data/app/lib/arm64/libapp.so"),
+              image_id: c_string("0123456789abcdef0123456789abcdef01234567"),
+            },
+            StackFrame {
+              return_address: 0x7f00_8000_5678,
+              image_load_address: 0x7f00_8000_0000,
+              binary_name: c_string("/system/lib64/libc++.so"),
+              image_id: None,
+            },
+          ],
+        },
+      }
+    );
+  }
+}
+
+#[test]
+fn ignores_older_record_with_crash_kind_it_could_not_hold() {
+  let mut bytes = RECORD_FIXTURES[0].2.to_vec();
+  bytes[offset_of!(CrashRecordHeader, crash_kind)] = CrashKind::Signal.into();
+  let crc32 = schema::compute_checksum(&bytes);
+  bytes[offset_of!(CrashRecordHeader, crc32) ..][.. 4].copy_from_slice(&crc32.to_ne_bytes());
+
+  assert_eq!(
+    read_previous_state_from_bytes(&bytes),
+    PreviousCrashState::default()
+  );
+}
+
+#[test]
+fn ignores_truncated_older_record() {
+  let bytes = RECORD_FIXTURES[0].2;
+
+  assert_eq!(
+    read_previous_state_from_bytes(&bytes[.. bytes.len() - 1]),
+    PreviousCrashState::default()
+  );
 }
@@ -0,0 +1,290 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./process_state_test.rs"]
+mod tests;
+
+use crate::schema::{AppState, ArenaProcessState};
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+
+// Monotonic time at which the coordinator was created, in milliseconds.
+static LAUNCHED_AT_MS: AtomicU64 = AtomicU64::new(0);
+// Looked up ahead of time since `sysconf` isn't async-signal-safe.
+static PAGE_SIZE: AtomicU64 = AtomicU64::new(0);
+static APP_STATE: AtomicU8 = AtomicU8::new(AppState::Unknown as u8);
+
+/// Tells the crash reporter whether the app is in the foreground, so a crash record can say which
+/// state the app crashed in. Until this is called the state is reported as unknown.
+pub fn set_foreground(foreground: bool) {
+  let state = if foreground {
+    AppState::Foreground
+  } else {
+    AppState::Background
+  };
+  APP_STATE.store(state.into(), Ordering::Relaxed);
+}
+
+pub(crate) fn mark_launched() {
+  LAUNCHED_AT_MS.store(monotonic_ms(), Ordering::Relaxed);
+  let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+  PAGE_SIZE.store(u64::try_from(page_size).unwrap_or(0), Ordering::Relaxed);
+}
+
+// Captures the state of the process at crash time. Async-signal-safe: counters are read with raw
+// system calls into static buffers, since this runs on the small alternate signal stack.
+pub(crate) fn snapshot() -> ArenaProcessState {
+  let launched_at_ms = LAUNCHED_AT_MS.load(Ordering::Relaxed);
+  let counters = process_counters();
+  ArenaProcessState {
+    uptime_ms: if launched_at_ms == 0 {
+      0
+    } else {
+      monotonic_ms().saturating_sub(launched_at_ms)
+    },
+    resident_bytes: counters.resident_bytes,
+    thread_count: counters.thread_count,
+    open_fd_count: counters.open_fd_count,
+    app_state: APP_STATE.load(Ordering::Relaxed),
+    reserved: [0; 7],
+  }
+}
+
+fn monotonic_ms() -> u64 {
+  let mut now = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &raw mut now) } != 0 {
+    return 0;
+  }
+  let secs = u64::try_from(now.tv_sec).unwrap_or(0);
+  let nanos = u64::try_from(now.tv_nsec).unwrap_or(0);
+  secs.saturating_mul(1_000).saturating_add(nanos / 1_000_000)
+}
+
+// Scratch space that's kept out of the stack. A crash record is only written once, so snapshots
+// never overlap outside of tests; if they do, the later one goes without the scratch space.
+#[cfg_attr(
+  not(any(target_os = "linux", target_os = "android", target_vendor = "apple")),
+  allow(dead_code)
+)]
+struct Scratch<T> {
+  in_use: AtomicBool,
+  value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for Scratch<T> {}
+
+#[cfg_attr(
+  not(any(target_os = "linux", target_os = "android", target_vendor = "apple")),
+  allow(dead_code)
+)]
+impl<T> Scratch<T> {
+  const fn new(value: T) -> Self {
+    Self {
+      in_use: AtomicBool::new(false),
+      value: UnsafeCell::new(value),
+    }
+  }
+
+  fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    if self.in_use.swap(true, Ordering::Acquire) {
+      return None;
+    }
+    let result = f(unsafe { &mut *self.value.get() });
+    self.in_use.store(false, Ordering::Release);
+    Some(result)
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Counters {
+  resident_bytes: u64,
+  thread_count: u32,
+  open_fd_count: u32,
+}
+
+// Aligned for the `linux_dirent64` records, and also used to read `/proc/self/stat`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C, align(8))]
+struct DirentBuffer([u8; 1024]);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static SCRATCH: Scratch<DirentBuffer> = Scratch::new(DirentBuffer([0; 1024]));
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn process_counters() -> Counters {
+  SCRATCH
+    .with(|buffer| {
+      let (thread_count, resident_pages) = read_file(c"/proc/self/stat", &mut buffer.0)
+        .and_then(parse_stat)
+        .unwrap_or_default();
+      Counters {
+        resident_bytes: resident_pages.saturating_mul(PAGE_SIZE.load(Ordering::Relaxed)),
+        thread_count,
+        open_fd_count: count_open_fds(buffer),
+      }
+    })
+    .unwrap_or_default()
+}
+
+// Extracts `num_threads` and `rss` (in pages) from `/proc/<pid>/stat`. The command name is wrapped
+// in parentheses and may contain spaces, so fields are counted from the last closing parenthesis.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_stat(stat: &[u8]) -> Option<(u32, u64)> {
+  let comm_end = stat.iter().rposition(|byte| *byte == b')')?;
+  let mut fields = stat[comm_end + 1 ..]
+    .split(u8::is_ascii_whitespace)
+    .filter(|field| !field.is_empty());
+  // `state` is the first field after the command name, and `num_threads` is the 18th.
+  let thread_count = parse_decimal(fields.nth(17)?)?;
+  let resident_pages = parse_decimal(fields.nth(3)?)?;
+  Some((u32::try_from(thread_count).ok()?, resident_pages))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_decimal(field: &[u8]) -> Option<u64> {
+  if field.is_empty() {
+    return None;
+  }
+  field.iter().try_fold(0_u64, |value, byte| {
+    if !byte.is_ascii_digit() {
+      return None;
+    }
+    value.checked_mul(10)?.checked_add(u64::from(byte - b'0'))
+  })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_file<'a>(path: &std::ffi::CStr, buffer: &'a mut [u8]) -> Option<&'a [u8]> {
+  let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC) };
+  if fd < 0 {
+    return None;
+  }
+  let mut len = 0;
+  while len < buffer.len() {
+    let read = unsafe { libc::read(fd, buffer[len ..].as_mut_ptr().cast(), buffer.len() - len) };
+    match usize::try_from(read) {
+      Ok(0) | Err(_) => break,
+      Ok(read) => len += read,
+    }
+  }
+  unsafe {
+    libc::close(fd);
+  }
+  Some(&buffer[.. len])
+}
+
+// Counts the entries of `/proc/self/fd` with `getdents64`, since `opendir` allocates.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn count_open_fds(buffer: &mut DirentBuffer) -> u32 {
+  // `linux_dirent64`: inode, offset, record length, type, then the null-terminated name.
+  const RECORD_LEN_OFFSET: usize = 16;
+  const NAME_OFFSET: usize = 19;
+
+  let fd = unsafe {
+    libc::open(
+      c"/proc/self/fd".as_ptr(),
+      libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+    )
+  };
+  if fd < 0 {
+    return 0;
+  }
+
+  let mut count = 0_u32;
+  loop {
+    let read = unsafe {
+      libc::syscall(
+        libc::SYS_getdents64,
+        fd,
+        buffer.0.as_mut_ptr(),
+        buffer.0.len(),
+      )
+    };
+    let Ok(read) = usize::try_from(read) else {
+      break;
+    };
+    if read == 0 {
+      break;
+    }
+
+    let mut offset = 0;
+    while offset + NAME_OFFSET < read {
+      let record_len = usize::from(u16::from_ne_bytes([
+        buffer.0[offset + RECORD_LEN_OFFSET],
+        buffer.0[offset + RECORD_LEN_OFFSET + 1],
+      ]));
+      if record_len == 0 {
+        break;
+      }
+      let name = &buffer.0[offset + NAME_OFFSET ..];
+      if !name.starts_with(b".\0") && !name.starts_with(b"..\0") {
+        count += 1;
+      }
+      offset += record_len;
+    }
+  }
+  unsafe {
+    libc::close(fd);
+  }
+
+  // The descriptor used to list the directory is listed as well.
+  count.saturating_sub(1)
+}
+
+// Processes with more descriptors than fit are reported with the buffer's capacity.
+#[cfg(target_vendor = "apple")]
+static SCRATCH: Scratch<[libc::proc_fdinfo; 512]> = Scratch::new(unsafe { std::mem::zeroed() });
+
+#[cfg(target_vendor = "apple")]
+fn process_counters() -> Counters {
+  use std::mem::{size_of, size_of_val, zeroed};
+
+  let pid = unsafe { libc::getpid() };
+  let mut counters = Counters::default();
+
+  let mut task_info: libc::proc_taskinfo = unsafe { zeroed() };
+  let task_info_len = libc::c_int::try_from(size_of::<libc::proc_taskinfo>()).unwrap_or(0);
+  let read = unsafe {
+    libc::proc_pidinfo(
+      pid,
+      libc::PROC_PIDTASKINFO,
+      0,
+      (&raw mut task_info).cast(),
+      task_info_len,
+    )
+  };
+  if read == task_info_len {
+    counters.resident_bytes = task_info.pti_resident_size;
+    counters.thread_count = u32::try_from(task_info.pti_threadnum).unwrap_or(0);
+  }
+
+  let read = SCRATCH
+    .with(|fds| unsafe {
+      libc::proc_pidinfo(
+        pid,
+        libc::PROC_PIDLISTFDS,
+        0,
+        fds.as_mut_ptr().cast(),
+        libc::c_int::try_from(size_of_val(fds)).unwrap_or(0),
+      )
+    })
+    .unwrap_or(0);
+  counters.open_fd_count = usize::try_from(read)
+    .ok()
+    .and_then(|read| u32::try_from(read / size_of::<libc::proc_fdinfo>()).ok())
+    .unwrap_or(0);
+  counters
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+fn process_counters() -> Counters {
+  Counters::default()
+}
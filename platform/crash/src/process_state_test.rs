@@ -0,0 +1,54 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{mark_launched, set_foreground, snapshot};
+use crate::schema::AppState;
+use crate::test_support::test_crash_record_guard;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn parses_stat_with_spaces_in_command_name() {
+  let stat = b"1234 (my app) S 1 1234 1234 0 -1 4194560 500 0 0 0 10 5 0 0 20 0 7 0 100 \
+               123456789 2048 18446744073709551615 1 1 0 0 0 0 0 4096 0 0 0 17 3 0 0 0 0 0\n";
+
+  assert_eq!(super::parse_stat(stat), Some((7, 2048)));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn rejects_truncated_stat() {
+  assert_eq!(super::parse_stat(b"1234 (app) S 1 1234"), None);
+  assert_eq!(super::parse_stat(b""), None);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+#[test]
+fn snapshot_reads_process_counters() {
+  // Serializes with the tests that write crash records, which share the snapshot's scratch space.
+  let _guard = test_crash_record_guard();
+  mark_launched();
+  let _file = std::fs::File::open("/dev/null").unwrap();
+
+  let process_state = snapshot();
+  assert!(process_state.thread_count >= 1);
+  assert!(process_state.resident_bytes > 0);
+  // stdin, stdout and stderr may be closed under some test harnesses, but the file above is not.
+  assert!(process_state.open_fd_count >= 1);
+  assert!(process_state.uptime_ms < 60_000);
+}
+
+#[test]
+fn snapshot_reports_foreground_state() {
+  let _guard = test_crash_record_guard();
+  set_foreground(true);
+  assert_eq!(snapshot().app_state, u8::from(AppState::Foreground));
+
+  set_foreground(false);
+  assert_eq!(snapshot().app_state, u8::from(AppState::Background));
+}
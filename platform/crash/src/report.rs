@@ -0,0 +1,406 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./report_test.rs"]
+mod tests;
+
+use crate::previous::{CallStack, PreviousCrashDetails, PreviousCrashState, RustPanicCrashInfo};
+use bd_proto::flatbuffers::report::bitdrift_public::fbs::issue_reporting::v_1::{
+  AppBuildNumber,
+  AppBuildNumberArgs,
+  AppMetrics,
+  AppMetricsArgs,
+  Architecture,
+  BinaryImage,
+  BinaryImageArgs,
+  DeviceMetrics,
+  DeviceMetricsArgs,
+  Error,
+  ErrorArgs,
+  Frame,
+  FrameArgs,
+  FrameType,
+  OSBuild,
+  OSBuildArgs,
+  Platform,
+  Report,
+  ReportArgs,
+  ReportType,
+  SDKInfo,
+  SDKInfoArgs,
+  SourceFile,
+  SourceFileArgs,
+  Timestamp,
+};
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Vector, WIPOffset};
+use std::ffi::CStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SDK_ID: &str = "io.bitdrift.capture-crash-reporter";
+
+//
+// Report conversion
+//
+
+// Details about the app and device that only the host knows, attached to the report the same way
+// the host's own issue reports carry them. Any of them may be missing.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ReportMetadata<'a> {
+  pub(crate) app_id: Option<&'a str>,
+  pub(crate) app_version: Option<&'a str>,
+  // The bundle version on Apple platforms.
+  pub(crate) build_number: Option<&'a str>,
+  pub(crate) os_brand: Option<&'a str>,
+  pub(crate) os_version: Option<&'a str>,
+  pub(crate) manufacturer: Option<&'a str>,
+  pub(crate) model: Option<&'a str>,
+}
+
+// Serializes the crash held by `state` into an `issue_reporting::v_1` report, or returns `None`
+// when the previous launch didn't crash.
+pub(crate) fn build_report(
+  state: &PreviousCrashState,
+  sdk_version: &str,
+  metadata: &ReportMetadata<'_>,
+) -> Option<Vec<u8>> {
+  if !state.did_crash {
+    return None;
+  }
+
+  let mut builder = FlatBufferBuilder::new();
+  let timestamp = Timestamp::new(state.timestamp_secs, 0);
+  let sdk = SDKInfoArgs {
+    id: Some(builder.create_string(SDK_ID)),
+    version: Some(builder.create_string(sdk_version)),
+  };
+  let sdk = SDKInfo::create(&mut builder, &sdk);
+  let app_metrics = build_app_metrics(&mut builder, metadata);
+  let app_metrics = AppMetrics::create(&mut builder, &app_metrics);
+  let device_metrics = build_device_metrics(&mut builder, metadata, &timestamp);
+  let device_metrics = DeviceMetrics::create(&mut builder, &device_metrics);
+
+  let description = error_description(&state.details)?;
+  let (error, binary_images) = match &state.details {
+    PreviousCrashDetails::NSException(exception) => {
+      build_error(&mut builder, &description, Some(&exception.call_stack))
+    },
+    // Records written before signal stacks were unwound have no frames.
+    PreviousCrashDetails::Signal(signal) => build_error(
+      &mut builder,
+      &description,
+      Some(&signal.call_stack).filter(|call_stack| !call_stack.frames.is_empty()),
+    ),
+    PreviousCrashDetails::RustPanic(panic) => (
+      build_rust_panic(&mut builder, &description, panic),
+      Vec::new(),
+    ),
+    PreviousCrashDetails::CxxException(exception) => {
+      build_error(&mut builder, &description, Some(&exception.call_stack))
+    },
+    PreviousCrashDetails::None => return None,
+  };
+  let errors = builder.create_vector(&[error]);
+  let binary_images = (!binary_images.is_empty()).then(|| builder.create_vector(&binary_images));
+
+  let report = Report::create(
+    &mut builder,
+    &ReportArgs {
+      sdk: Some(sdk),
+      type_: ReportType::NativeCrash,
+      app_metrics: Some(app_metrics),
+      device_metrics: Some(device_metrics),
+      errors: Some(errors),
+      binary_images,
+      ..Default::default()
+    },
+  );
+  builder.finish(report, None);
+  Some(builder.finished_data().to_vec())
+}
+
+// Writes the report for `state` into `directory`, the SDK's pending report directory, and returns
+// the path of the new file. The report is written under a temporary name first and then renamed,
+// so the report processor never picks up a partially written file. The file name is derived from
+// the crash so converting the same crash twice replaces the first report.
+pub(crate) fn write_report(
+  state: &PreviousCrashState,
+  directory: &Path,
+  sdk_version: &str,
+  metadata: &ReportMetadata<'_>,
+) -> anyhow::Result<Option<PathBuf>> {
+  let Some(report) = build_report(state, sdk_version, metadata) else {
+    return Ok(None);
+  };
+
+  fs::create_dir_all(directory)?;
+  let file_name = format!(
+    "{}_native_crash_{}.cap",
+    state.timestamp_secs.saturating_mul(1_000),
+    state.pid
+  );
+  let path = directory.join(&file_name);
+  let temporary_path = directory.join(format!(".{file_name}.tmp"));
+  fs::write(&temporary_path, report)?;
+  fs::rename(&temporary_path, &path)?;
+  log::debug!("wrote previous crash report to {}", path.display());
+  Ok(Some(path))
+}
+
+fn build_app_metrics<'fbb>(
+  builder: &mut FlatBufferBuilder<'fbb>,
+  metadata: &ReportMetadata<'_>,
+) -> AppMetricsArgs<'fbb> {
+  let build_number = metadata.build_number.map(|build_number| {
+    let cf_bundle_version = Some(builder.create_string(build_number));
+    AppBuildNumber::create(
+      builder,
+      &AppBuildNumberArgs {
+        cf_bundle_version,
+        ..Default::default()
+      },
+    )
+  });
+  AppMetricsArgs {
+    app_id: metadata.app_id.map(|app_id| builder.create_string(app_id)),
+    version: metadata
+      .app_version
+      .map(|app_version| builder.create_string(app_version)),
+    build_number,
+    ..Default::default()
+  }
+}
+
+fn build_device_metrics<'fbb>(
+  builder: &mut FlatBufferBuilder<'fbb>,
+  metadata: &ReportMetadata<'_>,
+  timestamp: &'fbb Timestamp,
+) -> DeviceMetricsArgs<'fbb> {
+  let os_build = OSBuildArgs {
+    brand: metadata.os_brand.map(|brand| builder.create_string(brand)),
+    version: metadata
+      .os_version
+      .map(|version| builder.create_string(version)),
+    ..Default::default()
+  };
+  DeviceMetricsArgs {
+    manufacturer: metadata
+      .manufacturer
+      .map(|manufacturer| builder.create_string(manufacturer)),
+    model: metadata.model.map(|model| builder.create_string(model)),
+    os_build: Some(OSBuild::create(builder, &os_build)),
+    time: Some(timestamp),
+    platform: current_platform(),
+    arch: current_architecture(),
+    ..Default::default()
+  }
+}
+
+// How a crash is named in reports and logs.
+pub(crate) struct ErrorDescription {
+  pub(crate) name: String,
+  pub(crate) reason: Option<String>,
+}
+
+// Describes the crash held by `details`, or returns `None` when there is no crash.
+pub(crate) fn error_description(details: &PreviousCrashDetails) -> Option<ErrorDescription> {
+  let (name, reason) = match details {
+    PreviousCrashDetails::NSException(exception) => (
+      exception
+        .name
+        .as_deref()
+        .map_or_else(|| "NSException".to_string(), lossy),
+      exception.reason.as_deref().map(lossy),
+    ),
+    PreviousCrashDetails::Signal(signal) => (
+      signal_name(signal.signal),
+      Some(format!(
+        "code {} at {:#x} on thread {}",
+        signal.code, signal.fault_address, signal.thread_id
+      )),
+    ),
+    PreviousCrashDetails::RustPanic(panic) => (
+      "Rust panic".to_string(),
+      panic.message.as_deref().map(lossy),
+    ),
+    PreviousCrashDetails::CxxException(exception) => (
+      exception
+        .type_name
+        .as_deref()
+        .map_or_else(|| "std::terminate".to_string(), lossy),
+      exception.what.as_deref().map(lossy),
+    ),
+    PreviousCrashDetails::None => return None,
+  };
+  Some(ErrorDescription { name, reason })
+}
+
+fn build_error<'fbb>(
+  builder: &mut FlatBufferBuilder<'fbb>,
+  description: &ErrorDescription,
+  call_stack: Option<&CallStack>,
+) -> (WIPOffset<Error<'fbb>>, Vec<WIPOffset<BinaryImage<'fbb>>>) {
+  let (stack_trace, binary_images) = call_stack.map_or((None, Vec::new()), |call_stack| {
+    let (stack_trace, binary_images) = build_call_stack(builder, call_stack);
+    (Some(stack_trace), binary_images)
+  });
+  let name = builder.create_string(&description.name);
+  let reason = description
+    .reason
+    .as_deref()
+    .map(|reason| builder.create_string(reason));
+  let error = Error::create(
+    builder,
+    &ErrorArgs {
+      name: Some(name),
+      reason,
+      stack_trace,
+      ..Default::default()
+    },
+  );
+  (error, binary_images)
+}
+
+// Builds the frames of `call_stack` along with every distinct image they resolve to, so the frames
+// can be symbolicated against those images.
+fn build_call_stack<'fbb>(
+  builder: &mut FlatBufferBuilder<'fbb>,
+  call_stack: &CallStack,
+) -> (
+  WIPOffset<Vector<'fbb, ForwardsUOffset<Frame<'fbb>>>>,
+  Vec<WIPOffset<BinaryImage<'fbb>>>,
+) {
+  let frames = call_stack
+    .frames
+    .iter()
+    .map(|frame| {
+      let image_id = frame
+        .image_id
+        .as_deref()
+        .map(|image_id| builder.create_string(&lossy(image_id)));
+      Frame::create(
+        builder,
+        &FrameArgs {
+          type_: FrameType::DWARF,
+          frame_address: frame.return_address,
+          image_id,
+          ..Default::default()
+        },
+      )
+    })
+    .collect::<Vec<_>>();
+  let stack_trace = builder.create_vector(&frames);
+
+  let mut seen_images = Vec::new();
+  let mut binary_images = Vec::new();
+  for frame in &call_stack.frames {
+    let Some(image_id) = frame.image_id.as_deref() else {
+      continue;
+    };
+    if seen_images.contains(&image_id) {
+      continue;
+    }
+    seen_images.push(image_id);
+
+    let id = builder.create_string(&lossy(image_id));
+    let path = frame
+      .binary_name
+      .as_deref()
+      .map(|binary_name| builder.create_string(&lossy(binary_name)));
+    binary_images.push(BinaryImage::create(
+      builder,
+      &BinaryImageArgs {
+        id: Some(id),
+        path,
+        load_address: frame.image_load_address,
+      },
+    ));
+  }
+  (stack_trace, binary_images)
+}
+
+fn build_rust_panic<'fbb>(
+  builder: &mut FlatBufferBuilder<'fbb>,
+  description: &ErrorDescription,
+  panic: &RustPanicCrashInfo,
+) -> WIPOffset<Error<'fbb>> {
+  // The panic location is the only frame that is known, and it's already symbolicated.
+  let stack_trace = panic.file.as_deref().map(|file| {
+    let path = builder.create_string(&lossy(file));
+    let source_file = SourceFile::create(
+      builder,
+      &SourceFileArgs {
+        path: Some(path),
+        line: i64::from(panic.line),
+        column: i64::from(panic.column),
+      },
+    );
+    let frame = Frame::create(
+      builder,
+      &FrameArgs {
+        type_: FrameType::Unknown,
+        source_file: Some(source_file),
+        ..Default::default()
+      },
+    );
+    builder.create_vector(&[frame])
+  });
+
+  let name = builder.create_string(&description.name);
+  let reason = description
+    .reason
+    .as_deref()
+    .map(|reason| builder.create_string(reason));
+  Error::create(
+    builder,
+    &ErrorArgs {
+      name: Some(name),
+      reason,
+      stack_trace,
+      ..Default::default()
+    },
+  )
+}
+
+fn signal_name(signal: i32) -> String {
+  match signal {
+    libc::SIGSEGV => "SIGSEGV".to_string(),
+    libc::SIGBUS => "SIGBUS".to_string(),
+    libc::SIGILL => "SIGILL".to_string(),
+    libc::SIGFPE => "SIGFPE".to_string(),
+    libc::SIGABRT => "SIGABRT".to_string(),
+    libc::SIGTRAP => "SIGTRAP".to_string(),
+    signal => format!("signal {signal}"),
+  }
+}
+
+fn lossy(value: &CStr) -> String {
+  value.to_string_lossy().into_owned()
+}
+
+const fn current_platform() -> Platform {
+  if cfg!(target_os = "ios") {
+    Platform::iOS
+  } else if cfg!(target_os = "macos") {
+    Platform::macOS
+  } else if cfg!(target_os = "android") {
+    Platform::Android
+  } else {
+    Platform::Unknown
+  }
+}
+
+const fn current_architecture() -> Architecture {
+  if cfg!(target_arch = "aarch64") {
+    Architecture::arm64
+  } else if cfg!(target_arch = "x86_64") {
+    Architecture::x86_64
+  } else {
+    Architecture::Unknown
+  }
+}
@@ -0,0 +1,307 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{ReportMetadata, build_report, write_report};
+use crate::previous::{
+  CallStack,
+  CrashKind,
+  CxxExceptionCrashInfo,
+  NSExceptionCrashInfo,
+  PreviousCrashDetails,
+  PreviousCrashState,
+  RustPanicCrashInfo,
+  SignalCrashInfo,
+  StackFrame,
+};
+use bd_proto::flatbuffers::report::bitdrift_public::fbs::issue_reporting::v_1::{
+  FrameType,
+  Report,
+  ReportType,
+};
+use std::ffi::CString;
+use std::fs;
+
+fn c_string(value: &str) -> Option<CString> {
+  Some(CString::new(value).unwrap())
+}
+
+fn crashed_state(kind: CrashKind, details: PreviousCrashDetails) -> PreviousCrashState {
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: 1_700_000_000,
+    pid: 42,
+    kind,
+    details,
+    ..Default::default()
+  }
+}
+
+fn stack_frame(return_address: u64, binary_name: &str, image_id: &str) -> StackFrame {
+  StackFrame {
+    return_address,
+    image_load_address: return_address & !0xfff,
+    binary_name: c_string(binary_name),
+    image_id: c_string(image_id),
+  }
+}
+
+#[test]
+fn does_not_build_report_without_crash() {
+  assert!(
+    build_report(
+      &PreviousCrashState::default(),
+      "1.0.0",
+      &ReportMetadata::default()
+    )
+    .is_none()
+  );
+}
+
+#[test]
+fn builds_nsexception_report() {
+  let frames = vec![
+    stack_frame(0x1000_1234, "App", "app-uuid"),
+    stack_frame(0x2000_5678, "Foundation", "foundation-uuid"),
+    stack_frame(0x1000_4321, "App", "app-uuid"),
+  ];
+  let state = crashed_state(
+    CrashKind::NSException,
+    PreviousCrashDetails::NSException(Box::new(NSExceptionCrashInfo {
+      name: c_string("NSInvalidArgumentException"),
+      reason: c_string("unrecognized selector"),
+      call_stack: CallStack {
+        return_addresses: frames.iter().map(|frame| frame.return_address).collect(),
+        frames,
+      },
+    })),
+  );
+
+  let bytes = build_report(&state, "1.2.3", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  assert_eq!(report.type_(), ReportType::NativeCrash);
+  assert_eq!(report.sdk().unwrap().version(), Some("1.2.3"));
+  assert_eq!(
+    report.device_metrics().unwrap().time().unwrap().seconds(),
+    1_700_000_000
+  );
+
+  let errors = report.errors().unwrap();
+  assert_eq!(errors.len(), 1);
+  let error = errors.get(0);
+  assert_eq!(error.name(), Some("NSInvalidArgumentException"));
+  assert_eq!(error.reason(), Some("unrecognized selector"));
+
+  let stack_trace = error.stack_trace().unwrap();
+  assert_eq!(stack_trace.len(), 3);
+  assert_eq!(stack_trace.get(1).type_(), FrameType::DWARF);
+  assert_eq!(stack_trace.get(1).frame_address(), 0x2000_5678);
+  assert_eq!(stack_trace.get(1).image_id(), Some("foundation-uuid"));
+
+  let binary_images = report.binary_images().unwrap();
+  assert_eq!(binary_images.len(), 2);
+  assert_eq!(binary_images.get(0).id(), Some("app-uuid"));
+  assert_eq!(binary_images.get(0).path(), Some("App"));
+  assert_eq!(binary_images.get(0).load_address(), 0x1000_1000);
+  assert_eq!(binary_images.get(1).id(), Some("foundation-uuid"));
+}
+
+#[test]
+fn report_carries_app_and_device_metadata() {
+  let state = crashed_state(
+    CrashKind::Signal,
+    PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: libc::SIGSEGV,
+      ..SignalCrashInfo::default()
+    }),
+  );
+  let metadata = ReportMetadata {
+    app_id: Some("io.bitdrift.example"),
+    app_version: Some("2.4.0"),
+    build_number: Some("240"),
+    os_brand: Some("iOS"),
+    os_version: Some("17.2"),
+    manufacturer: Some("Apple"),
+    model: Some("iPhone15,2"),
+  };
+
+  let bytes = build_report(&state, "1.0.0", &metadata).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  let app_metrics = report.app_metrics().unwrap();
+  assert_eq!(app_metrics.app_id(), Some("io.bitdrift.example"));
+  assert_eq!(app_metrics.version(), Some("2.4.0"));
+  assert_eq!(
+    app_metrics.build_number().unwrap().cf_bundle_version(),
+    Some("240")
+  );
+  let device_metrics = report.device_metrics().unwrap();
+  assert_eq!(device_metrics.manufacturer(), Some("Apple"));
+  assert_eq!(device_metrics.model(), Some("iPhone15,2"));
+  let os_build = device_metrics.os_build().unwrap();
+  assert_eq!(os_build.brand(), Some("iOS"));
+  assert_eq!(os_build.version(), Some("17.2"));
+
+  // Metadata the host doesn't know is left out.
+  let bytes = build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  assert_eq!(report.app_metrics().unwrap().app_id(), None);
+  assert!(report.app_metrics().unwrap().build_number().is_none());
+  assert_eq!(report.device_metrics().unwrap().model(), None);
+}
+
+#[test]
+fn builds_signal_report() {
+  let state = crashed_state(
+    CrashKind::Signal,
+    PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: libc::SIGSEGV,
+      code: 1,
+      fault_address: 0xdead,
+      thread_id: 7,
+      ..SignalCrashInfo::default()
+    }),
+  );
+
+  let bytes = build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  let error = report.errors().unwrap().get(0);
+  assert_eq!(error.name(), Some("SIGSEGV"));
+  assert_eq!(error.reason(), Some("code 1 at 0xdead on thread 7"));
+  assert!(error.stack_trace().is_none());
+  assert!(report.binary_images().is_none());
+}
+
+#[test]
+fn builds_signal_report_with_frames() {
+  let frames = vec![
+    stack_frame(0x7000_1234, "/system/lib64/libapp.so", "0a1b2c"),
+    stack_frame(
+      0x7100_5678,
+      "/apex/com.android.runtime/lib64/bionic/libc.so",
+      "3d4e5f",
+    ),
+  ];
+  let state = crashed_state(
+    CrashKind::Signal,
+    PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: libc::SIGSEGV,
+      call_stack: CallStack {
+        return_addresses: frames.iter().map(|frame| frame.return_address).collect(),
+        frames,
+      },
+      ..SignalCrashInfo::default()
+    }),
+  );
+
+  let bytes = build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  let stack_trace = report.errors().unwrap().get(0).stack_trace().unwrap();
+  assert_eq!(stack_trace.len(), 2);
+  assert_eq!(stack_trace.get(0).frame_address(), 0x7000_1234);
+  assert_eq!(stack_trace.get(0).image_id(), Some("0a1b2c"));
+
+  let binary_images = report.binary_images().unwrap();
+  assert_eq!(binary_images.len(), 2);
+  assert_eq!(binary_images.get(0).path(), Some("/system/lib64/libapp.so"));
+  assert_eq!(binary_images.get(0).load_address(), 0x7000_1000);
+}
+
+#[test]
+fn builds_cxx_exception_report() {
+  let frames = vec![stack_frame(
+    0x7000_1234,
+    "/system/lib64/libapp.so",
+    "0a1b2c",
+  )];
+  let state = crashed_state(
+    CrashKind::CxxException,
+    PreviousCrashDetails::CxxException(Box::new(CxxExceptionCrashInfo {
+      type_name: c_string("std::runtime_error"),
+      what: c_string("the widget is broken"),
+      call_stack: CallStack {
+        return_addresses: frames.iter().map(|frame| frame.return_address).collect(),
+        frames,
+      },
+    })),
+  );
+
+  let bytes = build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  let error = report.errors().unwrap().get(0);
+  assert_eq!(error.name(), Some("std::runtime_error"));
+  assert_eq!(error.reason(), Some("the widget is broken"));
+  assert_eq!(error.stack_trace().unwrap().len(), 1);
+  assert_eq!(report.binary_images().unwrap().len(), 1);
+}
+
+#[test]
+fn builds_rust_panic_report() {
+  let state = crashed_state(
+    CrashKind::RustPanic,
+    PreviousCrashDetails::RustPanic(Box::new(RustPanicCrashInfo {
+      message: c_string("index out of bounds"),
+      file: c_string("src/lib.rs"),
+      line: 12,
+      column: 5,
+      thread_name: c_string("main"),
+    })),
+  );
+
+  let bytes = build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap();
+  let report = flatbuffers::root::<Report<'_>>(&bytes).unwrap();
+  let error = report.errors().unwrap().get(0);
+  assert_eq!(error.name(), Some("Rust panic"));
+  assert_eq!(error.reason(), Some("index out of bounds"));
+
+  let source_file = error.stack_trace().unwrap().get(0).source_file().unwrap();
+  assert_eq!(source_file.path(), Some("src/lib.rs"));
+  assert_eq!(source_file.line(), 12);
+  assert_eq!(source_file.column(), 5);
+}
+
+#[test]
+fn writes_report_into_directory() {
+  let directory = tempfile::tempdir().unwrap();
+  let report_directory = directory.path().join("reports/new");
+  let state = crashed_state(
+    CrashKind::Signal,
+    PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: libc::SIGABRT,
+      ..Default::default()
+    }),
+  );
+
+  let path = write_report(
+    &state,
+    &report_directory,
+    "1.0.0",
+    &ReportMetadata::default(),
+  )
+  .unwrap()
+  .unwrap();
+  assert_eq!(
+    path.file_name().unwrap().to_str(),
+    Some("1700000000000_native_crash_42.cap")
+  );
+  assert_eq!(
+    fs::read(&path).unwrap(),
+    build_report(&state, "1.0.0", &ReportMetadata::default()).unwrap()
+  );
+  assert_eq!(fs::read_dir(&report_directory).unwrap().count(), 1);
+
+  assert!(
+    write_report(
+      &PreviousCrashState::default(),
+      &report_directory,
+      "1.0.0",
+      &ReportMetadata::default()
+    )
+    .unwrap()
+    .is_none()
+  );
+}
@@ -5,15 +5,31 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
-use std::mem::size_of;
+use std::mem::{offset_of, size_of};
 
 pub(crate) const MAGIC: u64 = u64::from_be_bytes(*b"BDCRASH\0");
-pub(crate) const VERSION: u32 = 1;
-pub(crate) const NS_EXCEPTION_NAME_CAPACITY: usize = 128;
-pub(crate) const NS_EXCEPTION_REASON_CAPACITY: usize = 1024;
-pub(crate) const NS_EXCEPTION_BINARY_NAME_CAPACITY: usize = 256;
-pub(crate) const NS_EXCEPTION_IMAGE_ID_CAPACITY: usize = 37;
-pub(crate) const MAX_NS_EXCEPTION_CALL_STACK_FRAMES: u16 = 128;
+pub(crate) const VERSION: u32 = 4;
+pub(crate) const HISTORY_MAGIC: u64 = u64::from_be_bytes(*b"BDCRHIST");
+pub(crate) const HISTORY_VERSION: u32 = 2;
+pub(crate) const CRASH_HISTORY_CAPACITY: u32 = 4;
+pub(crate) const ARENA_CAPACITY: usize = 16 * 1024;
+pub(crate) const ARENA_ENTRY_ALIGNMENT: usize = 4;
+pub(crate) const MAX_ARENA_STRING_LEN: usize = 4 * 1024;
+pub(crate) const MAX_FRAME_STRING_LEN: usize = 255;
+pub(crate) const MAX_CALL_STACK_FRAMES: u16 = 128;
+pub(crate) const BREADCRUMB_MAGIC: u64 = u64::from_be_bytes(*b"BDCRUMBS");
+pub(crate) const BREADCRUMB_CAPACITY: u32 = 50;
+pub(crate) const BREADCRUMB_MESSAGE_CAPACITY: usize = 104;
+pub(crate) const ANNOTATION_MAGIC: u64 = u64::from_be_bytes(*b"BDANNOTS");
+pub(crate) const ANNOTATION_CAPACITY: u32 = 16;
+pub(crate) const ANNOTATION_KEY_CAPACITY: usize = 32;
+pub(crate) const ANNOTATION_VALUE_CAPACITY: usize = 128;
+pub(crate) const SESSION_MAGIC: u64 = u64::from_be_bytes(*b"BDSESSON");
+pub(crate) const CRASH_LOOP_MAGIC: u64 = u64::from_be_bytes(*b"BDCRLOOP");
+pub(crate) const REPORTED_CRASH_MAGIC: u64 = u64::from_be_bytes(*b"BDCRRPTD");
+pub(crate) const DOUBLE_BUFFER_MAGIC: u64 = u64::from_be_bytes(*b"BDCRABUF");
+pub(crate) const DOUBLE_BUFFER_VERSION: u32 = 1;
+pub(crate) const DOUBLE_BUFFER_SLOT_COUNT: u32 = 2;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -40,8 +56,12 @@ impl PartialEq<RecordState> for u8 {
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub(crate) enum CrashKind {
   #[default]
-  None        = 0,
-  NSException = 1,
+  None         = 0,
+  NSException  = 1,
+  Signal       = 2,
+  RustPanic    = 3,
+  // An uncaught C++ exception, or any other call to `std::terminate`.
+  CxxException = 4,
 }
 
 impl From<CrashKind> for u8 {
@@ -56,6 +76,14 @@ impl PartialEq<CrashKind> for u8 {
   }
 }
 
+// Crash kinds that records with the current `VERSION` can hold.
+pub(crate) const CRASH_KINDS: [CrashKind; 4] = [
+  CrashKind::NSException,
+  CrashKind::Signal,
+  CrashKind::RustPanic,
+  CrashKind::CxxException,
+];
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub(crate) struct CrashRecordHeader {
@@ -67,6 +95,337 @@ pub(crate) struct CrashRecordHeader {
   pub(crate) crc32: u32,
 }
 
+//
+// CrashRecord
+//
+
+// A fixed header followed by a bump-allocated arena of tag-length-value entries. Each entry is an
+// `ArenaEntryHeader` followed by `len` value bytes, padded to `ARENA_ENTRY_ALIGNMENT`, and
+// `arena_len` is the number of arena bytes in use.
+//
+// The record checksum covers the fixed fields, while every entry carries its own checksum so a
+// damaged entry only loses itself. Readers skip tags they don't know, so new fields and crash kinds
+// are added as new tags without changing this layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CrashRecord {
+  pub(crate) header: CrashRecordHeader,
+  pub(crate) timestamp_secs: u64,
+  pub(crate) pid: u32,
+  pub(crate) arena_len: u32,
+  pub(crate) arena: [u8; ARENA_CAPACITY],
+}
+
+impl Default for CrashRecord {
+  fn default() -> Self {
+    Self {
+      header: CrashRecordHeader::default(),
+      timestamp_secs: 0,
+      pid: 0,
+      arena_len: 0,
+      arena: [0; ARENA_CAPACITY],
+    }
+  }
+}
+
+pub(crate) const CRASH_RECORD_FIXED_LEN: usize = offset_of!(CrashRecord, arena);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaEntryHeader {
+  pub(crate) tag: u16,
+  pub(crate) reserved: u16,
+  pub(crate) len: u32,
+  pub(crate) crc32: u32,
+}
+
+// Tags are persisted, so existing values must never be reused for a different meaning.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ArenaTag {
+  // UTF-8 bytes, not null-terminated.
+  NSExceptionName   = 1,
+  // UTF-8 bytes, not null-terminated.
+  NSExceptionReason = 2,
+  // `ArenaStackFrame`, then `binary_name_len` and `image_id_len` UTF-8 bytes.
+  StackFrame        = 3,
+  // `ArenaSignal`.
+  Signal            = 4,
+  // UTF-8 bytes, not null-terminated.
+  PanicMessage      = 5,
+  // UTF-8 bytes, not null-terminated.
+  PanicFile         = 6,
+  // `ArenaPanicLocation`.
+  PanicLocation     = 7,
+  // UTF-8 bytes, not null-terminated.
+  PanicThreadName   = 8,
+  // `ArenaBreadcrumb`, then the message as UTF-8 bytes.
+  Breadcrumb        = 9,
+  // `ArenaAnnotation`, then `key_len` and `value_len` UTF-8 bytes.
+  Annotation        = 10,
+  // `ArenaProcessState`.
+  ProcessState      = 11,
+  // Demangled type name as UTF-8 bytes, not null-terminated.
+  CxxExceptionType  = 12,
+  // `what()` as UTF-8 bytes, not null-terminated.
+  CxxExceptionWhat  = 13,
+  // Opaque bytes written by the host's crash callback.
+  HostData          = 14,
+}
+
+impl From<ArenaTag> for u16 {
+  fn from(tag: ArenaTag) -> Self {
+    tag as Self
+  }
+}
+
+impl PartialEq<ArenaTag> for u16 {
+  fn eq(&self, other: &ArenaTag) -> bool {
+    *self == *other as Self
+  }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaStackFrame {
+  pub(crate) return_address: u64,
+  pub(crate) image_load_address: u64,
+  pub(crate) binary_name_len: u16,
+  pub(crate) image_id_len: u16,
+  pub(crate) reserved: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaSignal {
+  pub(crate) signal: i32,
+  pub(crate) code: i32,
+  pub(crate) fault_address: u64,
+  pub(crate) thread_id: u64,
+}
+
+// A snapshot of the process taken when the crash was recorded. Counters that couldn't be read are
+// left at 0.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaProcessState {
+  pub(crate) uptime_ms: u64,
+  pub(crate) resident_bytes: u64,
+  pub(crate) thread_count: u32,
+  pub(crate) open_fd_count: u32,
+  // `AppState`.
+  pub(crate) app_state: u8,
+  pub(crate) reserved: [u8; 7],
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum AppState {
+  #[default]
+  Unknown    = 0,
+  Foreground = 1,
+  Background = 2,
+}
+
+impl AppState {
+  // Values written by a later build that this one doesn't know about are reported as unknown.
+  pub(crate) const fn from_u8(value: u8) -> Self {
+    match value {
+      1 => Self::Foreground,
+      2 => Self::Background,
+      _ => Self::Unknown,
+    }
+  }
+}
+
+impl From<AppState> for u8 {
+  fn from(state: AppState) -> Self {
+    state as Self
+  }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaPanicLocation {
+  pub(crate) line: u32,
+  pub(crate) column: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaBreadcrumb {
+  pub(crate) timestamp_ms: u64,
+  pub(crate) level: u8,
+  pub(crate) reserved: [u8; 7],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ArenaAnnotation {
+  pub(crate) key_len: u16,
+  pub(crate) value_len: u16,
+}
+
+pub(crate) const fn align_arena_offset(offset: usize) -> usize {
+  offset.next_multiple_of(ARENA_ENTRY_ALIGNMENT)
+}
+
+// Checksums an entry's tag, length and value. The value is passed in pieces so writers never have
+// to assemble it in a temporary buffer.
+pub(crate) fn compute_entry_checksum(tag: u16, len: u32, parts: &[&[u8]]) -> u32 {
+  let mut hasher = crc32fast::Hasher::new();
+  hasher.update(&tag.to_ne_bytes());
+  hasher.update(&len.to_ne_bytes());
+  for part in parts {
+    hasher.update(part);
+  }
+  hasher.finalize()
+}
+
+// Prefix of the crash state file. The file holds `capacity` slots of `slot_len` bytes after this
+// header, and `head` is the slot owned by the most recent run. Each slot carries its own checksum,
+// so the header itself is validated structurally instead.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CrashHistoryHeader {
+  pub(crate) magic: u64,
+  pub(crate) version: u32,
+  pub(crate) capacity: u32,
+  pub(crate) head: u32,
+  pub(crate) slot_len: u32,
+}
+
+pub(crate) const fn history_slot_offset(index: u32) -> usize {
+  // `CrashHistoryHeader` is a multiple of `CrashRecord`'s alignment, so every slot stays aligned
+  // when the mapping itself is page-aligned.
+  size_of::<CrashHistoryHeader>() + index as usize * size_of::<CrashRecord>()
+}
+
+// The breadcrumb ring follows the last slot. Its offset is a multiple of 8 like every slot offset.
+pub(crate) const fn breadcrumb_ring_offset(capacity: u32) -> usize {
+  history_slot_offset(capacity)
+}
+
+pub(crate) const fn annotation_table_offset(capacity: u32) -> usize {
+  breadcrumb_ring_offset(capacity) + size_of::<BreadcrumbRing>()
+}
+
+pub(crate) const fn session_state_offset(capacity: u32) -> usize {
+  annotation_table_offset(capacity) + size_of::<AnnotationTable>()
+}
+
+pub(crate) const fn crash_loop_state_offset(capacity: u32) -> usize {
+  session_state_offset(capacity) + size_of::<SessionState>()
+}
+
+pub(crate) const fn reported_crash_state_offset(capacity: u32) -> usize {
+  crash_loop_state_offset(capacity) + size_of::<CrashLoopState>()
+}
+
+pub(crate) const fn history_file_len(capacity: u32) -> usize {
+  reported_crash_state_offset(capacity) + size_of::<ReportedCrashState>()
+}
+
+//
+// BreadcrumbRing
+//
+
+// Breadcrumbs appended by the current run. The ring is reset on every launch and is never read back
+// directly: when a crash is committed, the writer copies its entries into the record arena so they
+// are covered by the record's commit protocol.
+//
+// Appends are lock-free. `next` hands out a monotonically increasing index, and each entry is
+// guarded by a sequence number that is odd while the entry is being written and `2 * (index + 1)`
+// once it is complete, so a snapshot can detect torn or concurrently overwritten entries.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BreadcrumbRing {
+  pub(crate) magic: u64,
+  pub(crate) capacity: u32,
+  pub(crate) reserved: u32,
+  pub(crate) next: u64,
+  pub(crate) entries: [RawBreadcrumb; BREADCRUMB_CAPACITY as usize],
+}
+
+impl Default for BreadcrumbRing {
+  fn default() -> Self {
+    Self {
+      magic: 0,
+      capacity: 0,
+      reserved: 0,
+      next: 0,
+      entries: [RawBreadcrumb::default(); BREADCRUMB_CAPACITY as usize],
+    }
+  }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RawBreadcrumb {
+  pub(crate) sequence: u64,
+  pub(crate) timestamp_ms: u64,
+  pub(crate) level: u8,
+  pub(crate) message_len: u8,
+  pub(crate) reserved: [u8; 6],
+  pub(crate) message: [u8; BREADCRUMB_MESSAGE_CAPACITY],
+}
+
+impl Default for RawBreadcrumb {
+  fn default() -> Self {
+    Self {
+      sequence: 0,
+      timestamp_ms: 0,
+      level: 0,
+      message_len: 0,
+      reserved: [0; 6],
+      message: [0; BREADCRUMB_MESSAGE_CAPACITY],
+    }
+  }
+}
+
+pub(crate) fn compute_record_checksum(record: &CrashRecord) -> u32 {
+  let bytes = unsafe {
+    std::slice::from_raw_parts((&raw const *record).cast::<u8>(), CRASH_RECORD_FIXED_LEN)
+  };
+  compute_checksum(bytes)
+}
+
+// Computes the record checksum over `bytes`, which must be exactly the checksummed prefix of the
+// record: the fixed fields for the current layout, or the full record for the legacy layout.
+pub(crate) fn compute_checksum(bytes: &[u8]) -> u32 {
+  // `record_state` is committed via a separate volatile write after this checksum is computed, so
+  // it's masked out here along with `crc32` itself to keep the checksum reproducible on read.
+  //
+  // This runs inside signal handlers on a small alternate stack, so hash the record in place
+  // instead of copying it to mask those fields.
+  let record_state_offset = offset_of!(CrashRecordHeader, record_state);
+  let crc32_offset = offset_of!(CrashRecordHeader, crc32);
+
+  let mut hasher = crc32fast::Hasher::new();
+  hasher.update(&bytes[.. record_state_offset]);
+  hasher.update(&[0; size_of::<u8>()]);
+  hasher.update(&bytes[record_state_offset + size_of::<u8>() .. crc32_offset]);
+  hasher.update(&[0; size_of::<u32>()]);
+  hasher.update(&bytes[crc32_offset + size_of::<u32>() ..]);
+  hasher.finalize()
+}
+
+//
+// LegacyCrashRecord
+//
+
+// Version 1, the only layout released before the arena, stored the NSException payload in
+// fixed-size arrays and checksummed the whole record. It is only read, never written, so a crash
+// persisted by an SDK from before the upgrade is still reported.
+
+pub(crate) const LEGACY_VERSION: u32 = 1;
+pub(crate) const LEGACY_CRASH_KINDS: [CrashKind; 1] = [CrashKind::NSException];
+pub(crate) const NS_EXCEPTION_NAME_CAPACITY: usize = 128;
+pub(crate) const NS_EXCEPTION_REASON_CAPACITY: usize = 1024;
+pub(crate) const NS_EXCEPTION_BINARY_NAME_CAPACITY: usize = 256;
+pub(crate) const NS_EXCEPTION_IMAGE_ID_CAPACITY: usize = 37;
+pub(crate) const MAX_NS_EXCEPTION_CALL_STACK_FRAMES: u16 = 128;
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct RawNSExceptionStackFrame {
@@ -126,7 +485,7 @@ impl Default for RawNSExceptionPayload {
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub(crate) struct CrashRecord {
+pub(crate) struct LegacyCrashRecord {
   pub(crate) header: CrashRecordHeader,
   pub(crate) timestamp_secs: u64,
   pub(crate) pid: u32,
@@ -134,14 +493,187 @@ pub(crate) struct CrashRecord {
   pub(crate) nsexception: RawNSExceptionPayload,
 }
 
-pub(crate) fn compute_record_checksum(record: &CrashRecord) -> u32 {
-  // `record_state` is committed via a separate volatile write after this checksum is computed, so
-  // it's masked out here along with `crc32` itself to keep the checksum reproducible on read.
-  let mut masked = *record;
-  masked.header.record_state = 0;
-  masked.header.crc32 = 0;
-  let bytes = unsafe {
-    std::slice::from_raw_parts((&raw const masked).cast::<u8>(), size_of::<CrashRecord>())
-  };
-  crc32fast::hash(bytes)
+//
+// AnnotationTable
+//
+
+// Host-provided key/value annotations for the current run. Like the breadcrumb ring, the table is
+// reset on every launch and copied into the record arena when a crash is committed.
+//
+// Writers are serialized by the annotations module, but the crash-time snapshot runs in signal
+// handlers and can't take that lock. Each entry is therefore guarded by a sequence number that is
+// odd while the entry is being changed. Free entries have a `key_len` of zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct AnnotationTable {
+  pub(crate) magic: u64,
+  pub(crate) capacity: u32,
+  pub(crate) reserved: u32,
+  pub(crate) entries: [RawAnnotation; ANNOTATION_CAPACITY as usize],
+}
+
+impl Default for AnnotationTable {
+  fn default() -> Self {
+    Self {
+      magic: 0,
+      capacity: 0,
+      reserved: 0,
+      entries: [RawAnnotation::default(); ANNOTATION_CAPACITY as usize],
+    }
+  }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RawAnnotation {
+  pub(crate) sequence: u64,
+  pub(crate) key_len: u8,
+  pub(crate) value_len: u8,
+  pub(crate) reserved: [u8; 6],
+  pub(crate) key: [u8; ANNOTATION_KEY_CAPACITY],
+  pub(crate) value: [u8; ANNOTATION_VALUE_CAPACITY],
+}
+
+impl Default for RawAnnotation {
+  fn default() -> Self {
+    Self {
+      sequence: 0,
+      key_len: 0,
+      value_len: 0,
+      reserved: [0; 6],
+      key: [0; ANNOTATION_KEY_CAPACITY],
+      value: [0; ANNOTATION_VALUE_CAPACITY],
+    }
+  }
+}
+
+//
+// SessionState
+//
+
+// Tracks whether the run that owns the file is still alive. The marker is set to `Running` when a
+// run starts and only moves to `CleanExit` on an explicit shutdown, so a run that is killed without
+// any monitor firing (OOM, watchdog, `SIGKILL`) leaves `Running` behind. The heartbeat is refreshed
+// periodically to bound when such a run was last seen alive.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SessionState {
+  pub(crate) magic: u64,
+  pub(crate) marker: u32,
+  pub(crate) pid: u32,
+  pub(crate) started_at_secs: u64,
+  pub(crate) last_heartbeat_secs: u64,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SessionMarker {
+  #[default]
+  None      = 0,
+  Running   = 1,
+  CleanExit = 2,
+}
+
+impl From<SessionMarker> for u32 {
+  fn from(marker: SessionMarker) -> Self {
+    marker as Self
+  }
+}
+
+impl PartialEq<SessionMarker> for u32 {
+  fn eq(&self, other: &SessionMarker) -> bool {
+    *self == *other as Self
+  }
+}
+
+//
+// CrashLoopState
+//
+
+// Counts consecutive launches that crashed shortly after the crash monitors were started. Unlike
+// the other regions it carries over between runs: each launch derives its count from the previous
+// run's state, and the window is kept until the host configures a different one.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CrashLoopState {
+  pub(crate) magic: u64,
+  // A crash is counted when it happens at most this long after `started_at_secs`.
+  pub(crate) window_secs: u32,
+  // Consecutive early crashes up to and including the previous launch.
+  pub(crate) consecutive_crashes: u32,
+  // When the monitors were started in the run that owns the state, or 0 if they never were.
+  pub(crate) started_at_secs: u64,
+  // Set once the host deems the run stable, after which a crash no longer counts as early.
+  pub(crate) stable: u32,
+  pub(crate) reserved: u32,
+}
+
+//
+// ReportedCrashState
+//
+
+// Identifies the last crash that was handed to an attached logger, so it's only logged once. Like
+// `CrashLoopState` it carries over between runs, but it lives outside of every record slot: a run
+// that dies before its own slot is primed leaves the previous crash in place, and the next launch
+// must still know it was logged.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ReportedCrashState {
+  pub(crate) magic: u64,
+  pub(crate) timestamp_secs: u64,
+  pub(crate) pid: u32,
+  pub(crate) crash_kind: u8,
+  pub(crate) reserved: [u8; 3],
+}
+
+//
+// DoubleBufferHeader
+//
+
+// Prefix of a double-buffered crash state file, the alternative to the history ring. The file holds
+// `DOUBLE_BUFFER_SLOT_COUNT` slots of `slot_len` bytes after this header, followed by the current
+// run's `BreadcrumbRing` and `AnnotationTable`. There is no head: the valid slot with the newest
+// generation belongs to the most recent run, and each run takes over the other slot. The file ends
+// with the `ReportedCrashState`, which no run ever primes.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DoubleBufferHeader {
+  pub(crate) magic: u64,
+  pub(crate) version: u32,
+  pub(crate) slot_len: u32,
+}
+
+// Everything a run leaves behind for the next one. The generation is stamped at both ends of the
+// slot once it's fully primed, and a slot is only valid while both stamps match and are non-zero,
+// so a slot whose priming was interrupted or torn anywhere in between is ignored.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DoubleBufferSlot {
+  pub(crate) generation: u64,
+  pub(crate) record: CrashRecord,
+  pub(crate) session_state: SessionState,
+  pub(crate) crash_loop_state: CrashLoopState,
+  pub(crate) generation_end: u64,
+}
+
+pub(crate) const fn double_buffer_slot_offset(index: u32) -> usize {
+  // Like the history ring's, the header is a multiple of `CrashRecord`'s alignment, so both slots
+  // stay aligned when the mapping itself is page-aligned.
+  size_of::<DoubleBufferHeader>() + index as usize * size_of::<DoubleBufferSlot>()
+}
+
+pub(crate) const fn double_buffer_breadcrumb_ring_offset() -> usize {
+  double_buffer_slot_offset(DOUBLE_BUFFER_SLOT_COUNT)
+}
+
+pub(crate) const fn double_buffer_annotation_table_offset() -> usize {
+  double_buffer_breadcrumb_ring_offset() + size_of::<BreadcrumbRing>()
+}
+
+pub(crate) const fn double_buffer_reported_crash_state_offset() -> usize {
+  double_buffer_annotation_table_offset() + size_of::<AnnotationTable>()
+}
+
+pub(crate) const fn double_buffer_file_len() -> usize {
+  double_buffer_reported_crash_state_offset() + size_of::<ReportedCrashState>()
 }
@@ -0,0 +1,145 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./session_test.rs"]
+mod tests;
+
+use crate::previous::{PreviousTermination, TerminationKind};
+use crate::schema::{self, SessionMarker, SessionState};
+use std::process::id;
+use std::ptr::{addr_of_mut, null_mut};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(crate) static SESSION_STATE: AtomicPtr<SessionState> = AtomicPtr::new(null_mut());
+
+// How often a running session refreshes its heartbeat. This bounds how far the last heartbeat
+// reported for an unclean termination may lag behind the actual time of death.
+pub(crate) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub(crate) unsafe fn prime_session_state(state_ptr: *mut SessionState) {
+  let now = current_timestamp_secs();
+  let state = unsafe { &mut *state_ptr };
+  state.magic = schema::SESSION_MAGIC;
+  state.pid = id();
+  state.started_at_secs = now;
+  unsafe {
+    addr_of_mut!(state.last_heartbeat_secs).write_volatile(now);
+    addr_of_mut!(state.marker).write_volatile(SessionMarker::Running.into());
+  }
+  SESSION_STATE.store(state_ptr, Ordering::Release);
+}
+
+pub(crate) fn mark_running() {
+  update_session(|state| unsafe {
+    addr_of_mut!((*state).last_heartbeat_secs).write_volatile(current_timestamp_secs());
+    addr_of_mut!((*state).marker).write_volatile(SessionMarker::Running.into());
+  });
+}
+
+pub(crate) fn mark_clean_exit() {
+  update_session(|state| unsafe {
+    addr_of_mut!((*state).last_heartbeat_secs).write_volatile(current_timestamp_secs());
+    addr_of_mut!((*state).marker).write_volatile(SessionMarker::CleanExit.into());
+  });
+}
+
+pub(crate) fn refresh_heartbeat() {
+  update_session(|state| unsafe {
+    addr_of_mut!((*state).last_heartbeat_secs).write_volatile(current_timestamp_secs());
+  });
+}
+
+// Classifies how the run that wrote `state` ended. A committed crash record always wins since the
+// crash handlers never get to clear the running marker.
+pub(crate) fn classify_previous_session(
+  state: &SessionState,
+  did_crash: bool,
+) -> PreviousTermination {
+  if state.magic != schema::SESSION_MAGIC {
+    return PreviousTermination::default();
+  }
+
+  let kind = if did_crash {
+    TerminationKind::Crash
+  } else if state.marker == SessionMarker::Running {
+    TerminationKind::UncleanTermination
+  } else if state.marker == SessionMarker::CleanExit {
+    TerminationKind::CleanExit
+  } else {
+    TerminationKind::Unknown
+  };
+
+  PreviousTermination {
+    kind,
+    started_at_secs: state.started_at_secs,
+    last_heartbeat_secs: state.last_heartbeat_secs,
+  }
+}
+
+fn update_session(update: impl FnOnce(*mut SessionState)) {
+  let state = SESSION_STATE.load(Ordering::Acquire);
+  if !state.is_null() {
+    update(state);
+  }
+}
+
+pub(crate) fn current_timestamp_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |duration| duration.as_secs())
+}
+
+//
+// Heartbeat
+//
+
+// Refreshes the session heartbeat on a background thread until dropped. Dropping joins the thread,
+// so the heartbeat never writes into a mapping that has already been unmapped.
+pub(crate) struct Heartbeat {
+  stop: Option<Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+  pub(crate) fn start(interval: Duration) -> Self {
+    let (stop, stopped) = mpsc::channel::<()>();
+    let thread = thread::Builder::new()
+      .name("bd-crash-heartbeat".to_string())
+      .spawn(move || {
+        while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+          refresh_heartbeat();
+        }
+      });
+
+    let thread = match thread {
+      Ok(thread) => Some(thread),
+      Err(error) => {
+        log::warn!("failed to start bitdrift crash heartbeat thread: {error}");
+        None
+      },
+    };
+
+    Self {
+      stop: Some(stop),
+      thread,
+    }
+  }
+}
+
+impl Drop for Heartbeat {
+  fn drop(&mut self) {
+    // Disconnecting the channel wakes the thread up immediately.
+    self.stop.take();
+    if let Some(thread) = self.thread.take() {
+      let _ignored = thread.join();
+    }
+  }
+}
@@ -0,0 +1,100 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{
+  Heartbeat,
+  classify_previous_session,
+  mark_clean_exit,
+  mark_running,
+  prime_session_state,
+};
+use crate::previous::{PreviousTermination, TerminationKind};
+use crate::schema::{self, SessionMarker, SessionState};
+use crate::test_support::test_crash_record_guard;
+use std::thread;
+use std::time::Duration;
+
+fn session_state(marker: SessionMarker) -> SessionState {
+  SessionState {
+    magic: schema::SESSION_MAGIC,
+    marker: marker.into(),
+    pid: 1,
+    started_at_secs: 100,
+    last_heartbeat_secs: 160,
+  }
+}
+
+#[test]
+fn classifies_previous_session() {
+  let expected = |kind| PreviousTermination {
+    kind,
+    started_at_secs: 100,
+    last_heartbeat_secs: 160,
+  };
+
+  assert_eq!(
+    classify_previous_session(&session_state(SessionMarker::Running), false),
+    expected(TerminationKind::UncleanTermination)
+  );
+  assert_eq!(
+    classify_previous_session(&session_state(SessionMarker::CleanExit), false),
+    expected(TerminationKind::CleanExit)
+  );
+  assert_eq!(
+    classify_previous_session(&session_state(SessionMarker::Running), true),
+    expected(TerminationKind::Crash)
+  );
+  assert_eq!(
+    classify_previous_session(&SessionState::default(), true),
+    PreviousTermination::default()
+  );
+}
+
+#[test]
+fn session_moves_between_running_and_clean_exit() {
+  let _guard = test_crash_record_guard();
+  let mut state = SessionState::default();
+  unsafe {
+    prime_session_state(&raw mut state);
+  }
+
+  assert_eq!(state.magic, schema::SESSION_MAGIC);
+  assert_eq!(state.marker, SessionMarker::Running);
+  assert!(state.started_at_secs > 0);
+  assert_eq!(state.last_heartbeat_secs, state.started_at_secs);
+
+  mark_clean_exit();
+  assert_eq!(state.marker, SessionMarker::CleanExit);
+
+  mark_running();
+  assert_eq!(state.marker, SessionMarker::Running);
+}
+
+#[test]
+fn heartbeat_refreshes_until_dropped() {
+  let _guard = test_crash_record_guard();
+  let mut state = SessionState::default();
+  unsafe {
+    prime_session_state(&raw mut state);
+  }
+  let state_ptr = &raw mut state;
+  unsafe {
+    (*state_ptr).last_heartbeat_secs = 0;
+  }
+
+  let heartbeat = Heartbeat::start(Duration::from_millis(1));
+  while unsafe { (&raw const (*state_ptr).last_heartbeat_secs).read_volatile() } == 0 {
+    thread::sleep(Duration::from_millis(1));
+  }
+  drop(heartbeat);
+
+  unsafe {
+    (*state_ptr).last_heartbeat_secs = 0;
+  }
+  thread::sleep(Duration::from_millis(10));
+  assert_eq!(unsafe { (*state_ptr).last_heartbeat_secs }, 0);
+}
@@ -9,16 +9,37 @@
 #[path = "./store_test.rs"]
 mod tests;
 
+mod double_buffered;
+
 use crate::previous::{self, PreviousCrashState};
-use crate::schema::CrashRecord;
-use crate::writer;
+use crate::schema::{
+  self,
+  AnnotationTable,
+  BreadcrumbRing,
+  CrashHistoryHeader,
+  CrashLoopState,
+  CrashRecord,
+  ReportedCrashState,
+  SessionState,
+};
+use crate::{annotations, breadcrumbs, crash_log, crash_loop, session, writer};
 use anyhow::{Result, anyhow};
+use double_buffered::DoubleBufferedCrashStateStore;
 use memmap2::{MmapMut, MmapOptions};
 use std::ffi::{CStr, OsStr};
-use std::fs::{OpenOptions, create_dir_all};
+use std::fs::{self, File, OpenOptions, TryLockError, create_dir_all};
 use std::mem::{align_of, size_of};
 use std::os::unix::ffi::OsStrExt as _;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::ptr::{addr_of_mut, read_unaligned, write_unaligned};
+use std::time::{Duration, SystemTime};
+
+// Separates the state path from the process name in the name of a named process's state file.
+const PROCESS_FILE_SEPARATOR: &str = ".proc.";
+
+// Named state files that haven't been opened for this long are deleted, so files of processes an
+// app no longer runs don't accumulate.
+const STALE_PROCESS_FILE_AGE: Duration = Duration::from_hours(30 * 24);
 
 //
 // CrashStateStore
@@ -29,56 +50,92 @@ pub(crate) trait CrashStateStore: Send + Sync {
   fn prepare_current_run(&mut self) -> Result<()>;
 }
 
-pub(crate) fn open(path: &CStr) -> Result<Box<dyn CrashStateStore>> {
-  Ok(Box::new(MmapCrashStateStore::open(path)?))
+// Opens the store backing `path`. `process_name` identifies the process among the ones sharing
+// `path`: the app's main process passes `None` and uses `path` itself, while every other process
+// passes a name of its own, such as an extension's bundle identifier or an Android process name,
+// and uses a sibling file named after it.
+pub(crate) fn open(
+  path: &CStr,
+  kind: StoreKind,
+  process_name: Option<&str>,
+) -> Result<Box<dyn CrashStateStore>> {
+  Ok(match kind {
+    StoreKind::History => Box::new(MmapCrashStateStore::open(path, process_name)?),
+    StoreKind::DoubleBuffered => Box::new(DoubleBufferedCrashStateStore::open(path, process_name)?),
+  })
+}
+
+//
+// StoreKind
+//
+
+// The layout of the crash state file. Values are exposed over the C ABI, so they must not be
+// renumbered.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum StoreKind {
+  // A ring holding the last `CRASH_HISTORY_CAPACITY` crashes.
+  #[default]
+  History        = 0,
+  // Two slots used in turns, so the current run never writes to the previous run's slot. Only the
+  // last two runs are kept.
+  DoubleBuffered = 1,
+}
+
+impl StoreKind {
+  pub(crate) const fn from_u8(value: u8) -> Option<Self> {
+    match value {
+      0 => Some(Self::History),
+      1 => Some(Self::DoubleBuffered),
+      _ => None,
+    }
+  }
 }
 
 //
 // MmapCrashStateStore
 //
 
+// Persists a fixed-capacity ring of `CrashRecord` slots behind a `CrashHistoryHeader`, followed by
+// the current run's `BreadcrumbRing`, `AnnotationTable` and `SessionState`, and the
+// `CrashLoopState` and `ReportedCrashState` carried across runs. The current run always
+// writes into the `head` slot. A run only moves `head` forward when the slot it inherits holds a
+// committed crash, so clean launches reuse their slot and the ring keeps the last
+// `CRASH_HISTORY_CAPACITY` crashes even if the host never reads them in between.
+//
+// The backing file is exclusively locked for the store's lifetime, so a second process opening the
+// same file fails instead of overwriting the records of the first.
 struct MmapCrashStateStore {
   mapping: MmapMut,
   previous_crash_state: PreviousCrashState,
+  // Holds the advisory lock, which is released when the file is closed.
+  _file: File,
 }
 
 impl MmapCrashStateStore {
-  fn open(path: &CStr) -> Result<Self> {
-    let path = Path::new(OsStr::from_bytes(path.to_bytes()));
-
-    if let Some(parent) = path.parent() {
-      create_dir_all(parent)?;
-    }
-
-    let file = OpenOptions::new()
-      .create(true)
-      .read(true)
-      .write(true)
-      .truncate(false)
-      .open(path)?;
-    let desired_len = size_of::<CrashRecord>() as u64;
-    let current_len = file.metadata()?.len();
-
-    // The on-disk record may come from an older or newer build. Extend short files so the current
-    // schema fits, but do not truncate larger files since callers should still be able to inspect
-    // the prefix that matches the current record layout.
-    if current_len < desired_len {
-      file.set_len(desired_len)?;
-    }
-
-    let mapping = unsafe {
-      MmapOptions::new()
-        .len(size_of::<CrashRecord>())
-        .map_mut(&file)?
-    };
-    let previous_crash_state = previous::read_previous_state_from_bytes(mapping.as_ref());
+  fn open(path: &CStr, process_name: Option<&str>) -> Result<Self> {
+    let base_path = Path::new(OsStr::from_bytes(path.to_bytes()));
+    let (path, file, mut mapping) = map_state_file(
+      base_path,
+      process_name,
+      schema::history_file_len(schema::CRASH_HISTORY_CAPACITY),
+    )?;
+    let mut previous_crash_state = load_history(&mut mapping);
+    merge_sibling_history(&mut previous_crash_state, base_path, &path);
     log::debug!("opened crash state store at {}", path.display());
 
     Ok(Self {
       mapping,
       previous_crash_state,
+      _file: file,
     })
   }
+
+  #[allow(clippy::cast_ptr_alignment)]
+  fn header_mut(&mut self) -> &mut CrashHistoryHeader {
+    // `mmap` returns page-aligned memory, so the header at offset 0 is sufficiently aligned.
+    unsafe { &mut *self.mapping.as_mut_ptr().cast::<CrashHistoryHeader>() }
+  }
 }
 
 impl CrashStateStore for MmapCrashStateStore {
@@ -88,18 +145,405 @@ impl CrashStateStore for MmapCrashStateStore {
 
   #[allow(clippy::cast_ptr_alignment)]
   fn prepare_current_run(&mut self) -> Result<()> {
-    // `mmap` returns page-aligned memory, and this mapping starts at file offset 0, so the start
-    // of the mapping is sufficiently aligned for `CrashRecord`.
-    let record_ptr = self.mapping.as_mut_ptr().cast::<CrashRecord>();
+    // Keep the previous run's slot if it recorded a crash and move on to the next (oldest) slot.
+    // Otherwise the previous slot holds nothing worth keeping and is reused.
+    let mut head = self.header_mut().head;
+    if self.previous_crash_state.did_crash {
+      head = (head + 1) % schema::CRASH_HISTORY_CAPACITY;
+      unsafe {
+        addr_of_mut!(self.header_mut().head).write_volatile(head);
+      }
+    }
+
+    // The slot offsets are multiples of `CrashRecord`'s alignment from the page-aligned start of
+    // the mapping, so every slot is sufficiently aligned for `CrashRecord`.
+    let record_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::history_slot_offset(head))
+        .cast::<CrashRecord>()
+    };
     if record_ptr.is_null() {
       return Err(anyhow!("crash state mapping returned a null pointer"));
     }
     debug_assert_eq!((record_ptr as usize) % align_of::<CrashRecord>(), 0);
 
+    let ring_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::breadcrumb_ring_offset(
+          schema::CRASH_HISTORY_CAPACITY,
+        ))
+        .cast::<BreadcrumbRing>()
+    };
+    debug_assert_eq!((ring_ptr as usize) % align_of::<BreadcrumbRing>(), 0);
+
+    let table_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::annotation_table_offset(
+          schema::CRASH_HISTORY_CAPACITY,
+        ))
+        .cast::<AnnotationTable>()
+    };
+    debug_assert_eq!((table_ptr as usize) % align_of::<AnnotationTable>(), 0);
+
+    let session_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::session_state_offset(schema::CRASH_HISTORY_CAPACITY))
+        .cast::<SessionState>()
+    };
+    debug_assert_eq!((session_ptr as usize) % align_of::<SessionState>(), 0);
+
+    let crash_loop_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::crash_loop_state_offset(
+          schema::CRASH_HISTORY_CAPACITY,
+        ))
+        .cast::<CrashLoopState>()
+    };
+    debug_assert_eq!((crash_loop_ptr as usize) % align_of::<CrashLoopState>(), 0);
+
+    let reported_crash_ptr = unsafe {
+      self
+        .mapping
+        .as_mut_ptr()
+        .add(schema::reported_crash_state_offset(
+          schema::CRASH_HISTORY_CAPACITY,
+        ))
+        .cast::<ReportedCrashState>()
+    };
+    debug_assert_eq!(
+      (reported_crash_ptr as usize) % align_of::<ReportedCrashState>(),
+      0
+    );
+
     unsafe {
+      crash_log::prime_reported_crash_state(reported_crash_ptr);
+      crash_loop::prime_crash_loop_state(
+        crash_loop_ptr,
+        self.previous_crash_state.consecutive_launch_crashes,
+      );
+      session::prime_session_state(session_ptr);
+      annotations::prime_annotation_table(table_ptr);
+      breadcrumbs::prime_breadcrumb_ring(ring_ptr);
       writer::prime_shared_record(record_ptr);
     }
 
     Ok(())
   }
 }
+
+// Locks the state file of the process named `process_name`, and maps it with room for at least
+// `len` bytes.
+fn map_state_file(
+  base_path: &Path,
+  process_name: Option<&str>,
+  len: usize,
+) -> Result<(PathBuf, File, MmapMut)> {
+  if let Some(parent) = base_path.parent() {
+    create_dir_all(parent)?;
+  }
+
+  let (path, file) = lock_state_file(&state_file_path(base_path, process_name)?)?;
+  // Opening the file counts as using it, see `prune_stale_process_files`.
+  if let Err(error) = file.set_modified(SystemTime::now()) {
+    log::debug!(
+      "failed to touch crash state file {}: {error}",
+      path.display()
+    );
+  }
+  prune_stale_process_files(base_path, &path);
+
+  let desired_len = len as u64;
+  let current_len = file.metadata()?.len();
+
+  // The on-disk state may come from an older or newer build, or from a store with another layout.
+  // Extend short files so the current layout fits, but do not truncate larger files since their
+  // contents are still inspected by the store.
+  if current_len < desired_len {
+    file.set_len(desired_len)?;
+  }
+
+  let mapping = unsafe { MmapOptions::new().map_mut(&file)? };
+  Ok((path, file, mapping))
+}
+
+// Apps with extensions, or Android apps with `:remote` processes, may run several processes that
+// share a state path. Each process names its own file, so a process always reads back the records
+// it wrote during its previous launch, and no two processes ever prime or overwrite the same
+// records. Two live processes using the same name would, so the second one fails instead.
+fn lock_state_file(path: &Path) -> Result<(PathBuf, File)> {
+  let file = OpenOptions::new()
+    .create(true)
+    .read(true)
+    .write(true)
+    .truncate(false)
+    .open(path)?;
+  match file.try_lock() {
+    Ok(()) => Ok((path.to_path_buf(), file)),
+    Err(TryLockError::WouldBlock) => Err(anyhow!(
+      "crash state file {} is in use by another process; processes sharing a state path must be \
+       configured with distinct process names",
+      path.display()
+    )),
+    Err(TryLockError::Error(error)) => {
+      // Some filesystems don't support locking. Fall back to the unlocked file, which is how the
+      // store behaved before locking was introduced.
+      log::warn!(
+        "failed to lock crash state file {}, using it unlocked: {error}",
+        path.display()
+      );
+      Ok((path.to_path_buf(), file))
+    },
+  }
+}
+
+// The state file of the process named `process_name`: the state path itself for the main process,
+// or a sibling named `<state path>.proc.<process name>` otherwise. Names are restricted to
+// characters that are safe in a file name, which covers bundle identifiers and Android process
+// names once the `:` separating the process suffix is replaced.
+fn state_file_path(base_path: &Path, process_name: Option<&str>) -> Result<PathBuf> {
+  let Some(process_name) = process_name else {
+    return Ok(base_path.to_path_buf());
+  };
+  if !is_valid_process_name(process_name.as_bytes()) {
+    return Err(anyhow!(
+      "invalid crash reporter process name {process_name:?}: only ASCII letters, digits, '.', '-' \
+       and '_' are allowed"
+    ));
+  }
+
+  let mut path = base_path.as_os_str().to_owned();
+  path.push(PROCESS_FILE_SEPARATOR);
+  path.push(process_name);
+  Ok(PathBuf::from(path))
+}
+
+fn is_valid_process_name(name: &[u8]) -> bool {
+  !name.is_empty()
+    && name
+      .iter()
+      .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'-' | b'_'))
+}
+
+// The state files of every process that shares `base_path`: the state path itself followed by the
+// files of named processes. Only names `state_file_path` can produce are matched, so unrelated
+// files that happen to share the prefix, such as backups, are left alone.
+fn state_file_paths(base_path: &Path) -> Vec<PathBuf> {
+  std::iter::once(base_path.to_path_buf())
+    .chain(process_file_paths(base_path))
+    .collect()
+}
+
+fn process_file_paths(base_path: &Path) -> Vec<PathBuf> {
+  let (Some(parent), Some(file_name)) = (base_path.parent(), base_path.file_name()) else {
+    return Vec::new();
+  };
+  let mut prefix = file_name.to_owned();
+  prefix.push(PROCESS_FILE_SEPARATOR);
+
+  fs::read_dir(parent)
+    .map(|entries| {
+      entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+          path.file_name().is_some_and(|name| {
+            name
+              .as_bytes()
+              .strip_prefix(prefix.as_bytes())
+              .is_some_and(is_valid_process_name)
+          })
+        })
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+// Deletes the files of named processes that haven't opened them for `STALE_PROCESS_FILE_AGE`, such
+// as processes a previous version of the app ran. A file is only deleted while holding its lock, so
+// the file of a live process is never removed.
+fn prune_stale_process_files(base_path: &Path, own_path: &Path) {
+  for path in process_file_paths(base_path)
+    .into_iter()
+    .filter(|path| path != own_path)
+  {
+    let Ok(file) = OpenOptions::new().read(true).write(true).open(&path) else {
+      continue;
+    };
+    let is_stale = file
+      .metadata()
+      .and_then(|metadata| metadata.modified())
+      .ok()
+      .and_then(|modified| modified.elapsed().ok())
+      .is_some_and(|age| age >= STALE_PROCESS_FILE_AGE);
+    if !is_stale || file.try_lock().is_err() {
+      continue;
+    }
+
+    match fs::remove_file(&path) {
+      Ok(()) => log::debug!("removed stale crash state file {}", path.display()),
+      Err(error) => log::debug!(
+        "failed to remove stale crash state file {}: {error}",
+        path.display()
+      ),
+    }
+  }
+}
+
+// Adds the crashes recorded by the other processes sharing the state path to `state`'s history,
+// which stays ordered newest first. The previous launch itself is only described by this process's
+// own file. Sibling files may be in use by a live process, so they're only ever read.
+fn merge_sibling_history(state: &mut PreviousCrashState, base_path: &Path, own_path: &Path) {
+  let mut merged = false;
+  for path in state_file_paths(base_path)
+    .into_iter()
+    .filter(|path| path != own_path)
+  {
+    // The state file itself is missing when only named processes have run.
+    let Ok(bytes) = fs::read(&path) else {
+      continue;
+    };
+    let history = committed_crashes(&bytes);
+    log::debug!(
+      "read {} crashes from sibling crash state file {}",
+      history.len(),
+      path.display()
+    );
+    merged |= !history.is_empty();
+    state.history.extend(history);
+  }
+
+  if merged {
+    state
+      .history
+      .sort_by_key(|entry| std::cmp::Reverse(entry.timestamp_secs));
+  }
+}
+
+fn load_history(mapping: &mut [u8]) -> PreviousCrashState {
+  // Decode the ring written by a previous run and return the state of the most recent slot, with
+  // every committed slot attached as history. Files that don't hold a ring with the current
+  // geometry (pre-ring single-record files, or rings from builds with a different capacity or slot
+  // layout) are converted in place, carrying over only their most recent record.
+  let header = read_history_header(mapping);
+  let session_state = read_session_state(mapping);
+  let crash_loop_state = read_crash_loop_state(mapping);
+  if !has_current_geometry(&header) {
+    let carried_over = most_recent_foreign_record(mapping, &header);
+    reset_history(mapping, &carried_over);
+  }
+
+  let head = read_history_header(mapping).head;
+  let mut previous_crash_state =
+    previous::read_previous_state_from_bytes(&mapping[schema::history_slot_offset(head) ..]);
+  previous_crash_state.history = committed_history(mapping, head);
+  previous_crash_state.termination =
+    session::classify_previous_session(&session_state, previous_crash_state.did_crash);
+  previous_crash_state.consecutive_launch_crashes =
+    crash_loop::count_consecutive_crashes(&crash_loop_state, &previous_crash_state);
+  previous_crash_state
+}
+
+const fn has_current_geometry(header: &CrashHistoryHeader) -> bool {
+  header.magic == schema::HISTORY_MAGIC
+    && header.version == schema::HISTORY_VERSION
+    && header.capacity == schema::CRASH_HISTORY_CAPACITY
+    && header.head < schema::CRASH_HISTORY_CAPACITY
+    && header.slot_len as usize == size_of::<CrashRecord>()
+}
+
+// Every committed crash in a state file of either layout, newest first.
+fn committed_crashes(bytes: &[u8]) -> Vec<PreviousCrashState> {
+  let header = read_history_header(bytes);
+  if has_current_geometry(&header) {
+    committed_history(bytes, header.head)
+  } else {
+    double_buffered::committed_crashes(bytes)
+  }
+}
+
+// Every committed slot of a ring with the current geometry, newest first.
+fn committed_history(bytes: &[u8], head: u32) -> Vec<PreviousCrashState> {
+  (0 .. schema::CRASH_HISTORY_CAPACITY)
+    .map(|age| (head + schema::CRASH_HISTORY_CAPACITY - age) % schema::CRASH_HISTORY_CAPACITY)
+    .filter_map(|index| bytes.get(schema::history_slot_offset(index) ..))
+    .map(previous::read_previous_state_from_bytes)
+    .filter(|state| state.did_crash)
+    .collect()
+}
+
+fn read_history_header(bytes: &[u8]) -> CrashHistoryHeader {
+  if bytes.len() < size_of::<CrashHistoryHeader>() {
+    return CrashHistoryHeader::default();
+  }
+
+  unsafe { read_unaligned(bytes.as_ptr().cast::<CrashHistoryHeader>()) }
+}
+
+fn read_session_state(bytes: &[u8]) -> SessionState {
+  // The session state is read before a foreign ring is reset. In files from builds that predate it,
+  // or that use a different ring geometry, the magic won't match and the session is unknown.
+  bytes
+    .get(schema::session_state_offset(schema::CRASH_HISTORY_CAPACITY) ..)
+    .filter(|bytes| bytes.len() >= size_of::<SessionState>())
+    .map(|bytes| unsafe { read_unaligned(bytes.as_ptr().cast::<SessionState>()) })
+    .unwrap_or_default()
+}
+
+fn read_crash_loop_state(bytes: &[u8]) -> CrashLoopState {
+  bytes
+    .get(schema::crash_loop_state_offset(schema::CRASH_HISTORY_CAPACITY) ..)
+    .filter(|bytes| bytes.len() >= size_of::<CrashLoopState>())
+    .map(|bytes| unsafe { read_unaligned(bytes.as_ptr().cast::<CrashLoopState>()) })
+    .unwrap_or_default()
+}
+
+fn most_recent_foreign_record(bytes: &[u8], header: &CrashHistoryHeader) -> PreviousCrashState {
+  let offset = if header.magic == schema::HISTORY_MAGIC {
+    (header.head as usize)
+      .checked_mul(header.slot_len as usize)
+      .and_then(|offset| offset.checked_add(size_of::<CrashHistoryHeader>()))
+  } else {
+    // Files written before the history ring was introduced hold a single record at offset 0.
+    Some(0)
+  };
+
+  offset
+    .and_then(|offset| bytes.get(offset ..))
+    .map(previous::read_previous_state_from_bytes)
+    .unwrap_or_default()
+}
+
+#[allow(clippy::cast_ptr_alignment)]
+fn reset_history(mapping: &mut [u8], carried_over: &PreviousCrashState) {
+  mapping.fill(0);
+  let header = CrashHistoryHeader {
+    magic: schema::HISTORY_MAGIC,
+    version: schema::HISTORY_VERSION,
+    capacity: schema::CRASH_HISTORY_CAPACITY,
+    head: 0,
+    slot_len: u32::try_from(size_of::<CrashRecord>()).unwrap_or(0),
+  };
+  unsafe {
+    write_unaligned(mapping.as_mut_ptr().cast::<CrashHistoryHeader>(), header);
+  }
+
+  if carried_over.did_crash {
+    // The carried over record may use an older layout, so it's re-encoded rather than copied.
+    let record = unsafe {
+      &mut *mapping
+        .as_mut_ptr()
+        .add(schema::history_slot_offset(0))
+        .cast::<CrashRecord>()
+    };
+    writer::rewrite_previous_state(record, carried_over);
+  }
+}
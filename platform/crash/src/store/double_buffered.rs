@@ -0,0 +1,281 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./double_buffered_test.rs"]
+mod tests;
+
+use super::{
+  CrashStateStore,
+  map_state_file,
+  merge_sibling_history,
+  most_recent_foreign_record,
+  read_history_header,
+};
+use crate::previous::{self, PreviousCrashState};
+use crate::schema::{
+  self,
+  AnnotationTable,
+  BreadcrumbRing,
+  CrashLoopState,
+  DoubleBufferHeader,
+  DoubleBufferSlot,
+  ReportedCrashState,
+  SessionState,
+};
+use crate::{annotations, breadcrumbs, crash_log, crash_loop, session, writer};
+use anyhow::{Result, anyhow};
+use memmap2::MmapMut;
+use std::cmp::Reverse;
+use std::ffi::{CStr, OsStr};
+use std::fs::File;
+use std::mem::{align_of, offset_of, size_of};
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::ptr::{addr_of_mut, read_unaligned, write_unaligned};
+use std::sync::atomic::{Ordering, fence};
+
+//
+// DoubleBufferedCrashStateStore
+//
+
+// Persists two `DoubleBufferSlot`s behind a `DoubleBufferHeader`, followed by the current run's
+// `BreadcrumbRing` and `AnnotationTable`, and the `ReportedCrashState` carried across runs. Each
+// run primes the slot the previous run didn't use and only then stamps it with the next
+// generation, so the previous run's slot is never written while the current run is alive: neither
+// a crash during priming nor a torn write can lose its record.
+// In exchange, only the run before the previous one is kept as history.
+pub(super) struct DoubleBufferedCrashStateStore {
+  mapping: MmapMut,
+  previous_crash_state: PreviousCrashState,
+  // The slot and generation of the most recent run, if any slot is valid.
+  previous_slot: Option<(u32, u64)>,
+  // Holds the advisory lock, which is released when the file is closed.
+  _file: File,
+}
+
+impl DoubleBufferedCrashStateStore {
+  pub(super) fn open(path: &CStr, process_name: Option<&str>) -> Result<Self> {
+    let base_path = Path::new(OsStr::from_bytes(path.to_bytes()));
+    let (path, file, mut mapping) =
+      map_state_file(base_path, process_name, schema::double_buffer_file_len())?;
+    let (mut previous_crash_state, previous_slot) = load_slots(&mut mapping);
+    merge_sibling_history(&mut previous_crash_state, base_path, &path);
+    log::debug!(
+      "opened double-buffered crash state store at {}",
+      path.display()
+    );
+
+    Ok(Self {
+      mapping,
+      previous_crash_state,
+      previous_slot,
+      _file: file,
+    })
+  }
+
+  fn region<T>(&mut self, offset: usize) -> Result<*mut T> {
+    // Every region offset is a multiple of 8 from the page-aligned start of the mapping.
+    let ptr = unsafe { self.mapping.as_mut_ptr().add(offset).cast::<T>() };
+    if ptr.is_null() {
+      return Err(anyhow!("crash state mapping returned a null pointer"));
+    }
+    debug_assert_eq!((ptr as usize) % align_of::<T>(), 0);
+    Ok(ptr)
+  }
+}
+
+impl CrashStateStore for DoubleBufferedCrashStateStore {
+  fn previous_crash_state(&self) -> PreviousCrashState {
+    self.previous_crash_state.clone()
+  }
+
+  fn prepare_current_run(&mut self) -> Result<()> {
+    let (current, generation) = self.previous_slot.map_or((0, 1), |(index, generation)| {
+      (
+        (index + 1) % schema::DOUBLE_BUFFER_SLOT_COUNT,
+        generation.saturating_add(1),
+      )
+    });
+    let previous_crash_loop_state = self.previous_slot.and_then(|(index, _)| {
+      read_at::<CrashLoopState>(&self.mapping, slot_field_offset(index, CRASH_LOOP_OFFSET))
+    });
+
+    let slot_ptr = self.region::<DoubleBufferSlot>(schema::double_buffer_slot_offset(current))?;
+    let ring_ptr = self.region::<BreadcrumbRing>(schema::double_buffer_breadcrumb_ring_offset())?;
+    let table_ptr =
+      self.region::<AnnotationTable>(schema::double_buffer_annotation_table_offset())?;
+    let reported_crash_ptr =
+      self.region::<ReportedCrashState>(schema::double_buffer_reported_crash_state_offset())?;
+
+    // Priming flow:
+    // 1. Invalidate the slot, so a run that dies while priming it leaves it ignored.
+    // 2. Prime the slot's contents and the current run's regions.
+    // 3. Stamp the slot with the next generation at both ends, which makes it the newest valid
+    //    slot.
+    //
+    // The reported crash state sits outside of both slots and is never rewritten while priming.
+    unsafe {
+      crash_log::prime_reported_crash_state(reported_crash_ptr);
+
+      addr_of_mut!((*slot_ptr).generation).write_volatile(0);
+      addr_of_mut!((*slot_ptr).generation_end).write_volatile(0);
+      fence(Ordering::Release);
+
+      // The crash loop state is primed over the state it replaces to keep the configured window,
+      // which lives in the previous run's slot.
+      if let Some(crash_loop_state) = previous_crash_loop_state {
+        addr_of_mut!((*slot_ptr).crash_loop_state).write(crash_loop_state);
+      }
+      crash_loop::prime_crash_loop_state(
+        addr_of_mut!((*slot_ptr).crash_loop_state),
+        self.previous_crash_state.consecutive_launch_crashes,
+      );
+      session::prime_session_state(addr_of_mut!((*slot_ptr).session_state));
+      annotations::prime_annotation_table(table_ptr);
+      breadcrumbs::prime_breadcrumb_ring(ring_ptr);
+      writer::prime_shared_record(addr_of_mut!((*slot_ptr).record));
+
+      fence(Ordering::Release);
+      addr_of_mut!((*slot_ptr).generation_end).write_volatile(generation);
+      addr_of_mut!((*slot_ptr).generation).write_volatile(generation);
+    }
+
+    Ok(())
+  }
+}
+
+const RECORD_OFFSET: usize = offset_of!(DoubleBufferSlot, record);
+const SESSION_STATE_OFFSET: usize = offset_of!(DoubleBufferSlot, session_state);
+const CRASH_LOOP_OFFSET: usize = offset_of!(DoubleBufferSlot, crash_loop_state);
+
+fn load_slots(mapping: &mut [u8]) -> (PreviousCrashState, Option<(u32, u64)>) {
+  // Files that don't hold the current double-buffered layout (history rings, pre-ring single-record
+  // files, or an older layout) are converted in place, carrying over only their most recent record.
+  if !has_current_layout(mapping) {
+    let carried_over = most_recent_foreign_record(mapping, &read_history_header(mapping));
+    reset_slots(mapping, &carried_over);
+  }
+
+  read_previous_run(mapping)
+}
+
+// Decodes the state left behind by the most recent run, along with which slot holds it and its
+// generation. Only reads, so it can also be used on a copy of the file.
+fn read_previous_run(bytes: &[u8]) -> (PreviousCrashState, Option<(u32, u64)>) {
+  let slots = valid_slots_newest_first(bytes);
+  let Some(&(index, generation)) = slots.first() else {
+    return (PreviousCrashState::default(), None);
+  };
+
+  let session_state =
+    read_at::<SessionState>(bytes, slot_field_offset(index, SESSION_STATE_OFFSET))
+      .unwrap_or_default();
+  let crash_loop_state =
+    read_at::<CrashLoopState>(bytes, slot_field_offset(index, CRASH_LOOP_OFFSET))
+      .unwrap_or_default();
+
+  let mut previous_crash_state = read_slot_record(bytes, index);
+  previous_crash_state.history = slot_crashes(bytes, &slots);
+  previous_crash_state.termination =
+    session::classify_previous_session(&session_state, previous_crash_state.did_crash);
+  previous_crash_state.consecutive_launch_crashes =
+    crash_loop::count_consecutive_crashes(&crash_loop_state, &previous_crash_state);
+  (previous_crash_state, Some((index, generation)))
+}
+
+// Every committed crash in a double-buffered state file, newest first. Files with another layout
+// hold none.
+pub(super) fn committed_crashes(bytes: &[u8]) -> Vec<PreviousCrashState> {
+  if !has_current_layout(bytes) {
+    return Vec::new();
+  }
+
+  slot_crashes(bytes, &valid_slots_newest_first(bytes))
+}
+
+fn slot_crashes(bytes: &[u8], slots: &[(u32, u64)]) -> Vec<PreviousCrashState> {
+  slots
+    .iter()
+    .map(|(index, _)| read_slot_record(bytes, *index))
+    .filter(|state| state.did_crash)
+    .collect()
+}
+
+fn read_slot_record(bytes: &[u8], index: u32) -> PreviousCrashState {
+  bytes
+    .get(slot_field_offset(index, RECORD_OFFSET) ..)
+    .map(previous::read_previous_state_from_bytes)
+    .unwrap_or_default()
+}
+
+fn valid_slots_newest_first(bytes: &[u8]) -> Vec<(u32, u64)> {
+  let mut slots = (0 .. schema::DOUBLE_BUFFER_SLOT_COUNT)
+    .filter_map(|index| slot_generation(bytes, index).map(|generation| (index, generation)))
+    .collect::<Vec<_>>();
+  slots.sort_by_key(|(_, generation)| Reverse(*generation));
+  slots
+}
+
+// Returns the slot's generation, or `None` if the slot was never primed or its priming didn't
+// complete.
+fn slot_generation(bytes: &[u8], index: u32) -> Option<u64> {
+  let generation = read_at::<u64>(
+    bytes,
+    slot_field_offset(index, offset_of!(DoubleBufferSlot, generation)),
+  )?;
+  let generation_end = read_at::<u64>(
+    bytes,
+    slot_field_offset(index, offset_of!(DoubleBufferSlot, generation_end)),
+  )?;
+  (generation != 0 && generation == generation_end).then_some(generation)
+}
+
+fn has_current_layout(bytes: &[u8]) -> bool {
+  read_at::<DoubleBufferHeader>(bytes, 0).is_some_and(|header| {
+    header.magic == schema::DOUBLE_BUFFER_MAGIC
+      && header.version == schema::DOUBLE_BUFFER_VERSION
+      && header.slot_len as usize == size_of::<DoubleBufferSlot>()
+  })
+}
+
+const fn slot_field_offset(index: u32, field_offset: usize) -> usize {
+  schema::double_buffer_slot_offset(index) + field_offset
+}
+
+fn read_at<T: Copy>(bytes: &[u8], offset: usize) -> Option<T> {
+  bytes
+    .get(offset .. offset.checked_add(size_of::<T>())?)
+    .map(|bytes| unsafe { read_unaligned(bytes.as_ptr().cast::<T>()) })
+}
+
+#[allow(clippy::cast_ptr_alignment)]
+fn reset_slots(mapping: &mut [u8], carried_over: &PreviousCrashState) {
+  mapping.fill(0);
+  let header = DoubleBufferHeader {
+    magic: schema::DOUBLE_BUFFER_MAGIC,
+    version: schema::DOUBLE_BUFFER_VERSION,
+    slot_len: u32::try_from(size_of::<DoubleBufferSlot>()).unwrap_or(0),
+  };
+  unsafe {
+    write_unaligned(mapping.as_mut_ptr().cast::<DoubleBufferHeader>(), header);
+  }
+
+  if carried_over.did_crash {
+    // The carried over record may use an older layout, so it's re-encoded rather than copied. The
+    // slot then becomes the previous run's, as if this layout had written it.
+    let slot = unsafe {
+      &mut *mapping
+        .as_mut_ptr()
+        .add(schema::double_buffer_slot_offset(0))
+        .cast::<DoubleBufferSlot>()
+    };
+    writer::rewrite_previous_state(&mut slot.record, carried_over);
+    slot.generation = 1;
+    slot.generation_end = 1;
+  }
+}
@@ -0,0 +1,189 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{committed_crashes, read_previous_run};
+use crate::previous::{PreviousCrashDetails, PreviousCrashState};
+use crate::schema;
+use crate::store::{StoreKind, open};
+use crate::test_support::test_crash_record_guard;
+use crate::writer::record_signal;
+use anyhow::Result;
+use std::ffi::CString;
+use std::fs::read;
+use std::path::Path;
+
+// Simulates one launch with the given store layout: opens the store, prepares the current run and
+// optionally records a signal crash before the process goes away. Returns the previous crash state
+// seen by that launch.
+fn launch(
+  path: &CString,
+  kind: StoreKind,
+  crash_signal: Option<i32>,
+) -> Result<PreviousCrashState> {
+  let mut store = open(path, kind, None)?;
+  let previous = store.previous_crash_state();
+  store.prepare_current_run()?;
+  if let Some(signal) = crash_signal {
+    record_signal(signal, 0, 0, 0, []);
+  }
+  Ok(previous)
+}
+
+fn state_path(directory: &Path) -> Result<CString> {
+  Ok(CString::new(
+    directory.join("state.bin").to_string_lossy().as_bytes(),
+  )?)
+}
+
+fn signal_number(state: &PreviousCrashState) -> Option<i32> {
+  match &state.details {
+    PreviousCrashDetails::Signal(signal) => Some(signal.signal),
+    _ => None,
+  }
+}
+
+fn history_signals(state: &PreviousCrashState) -> Vec<Option<i32>> {
+  state.history.iter().map(signal_number).collect()
+}
+
+#[test]
+fn previous_run_crash_is_read_from_its_slot() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  let first = launch(&path, StoreKind::DoubleBuffered, Some(11))?;
+  let second = launch(&path, StoreKind::DoubleBuffered, Some(6))?;
+  let third = launch(&path, StoreKind::DoubleBuffered, None)?;
+  let fourth = launch(&path, StoreKind::DoubleBuffered, None)?;
+
+  assert!(!first.did_crash);
+  assert_eq!(signal_number(&second), Some(11));
+  assert_eq!(signal_number(&third), Some(6));
+  assert_eq!(history_signals(&third), vec![Some(6), Some(11)]);
+  // Only the last two runs are kept, and the clean run took over the slot of the first crash.
+  assert!(!fourth.did_crash);
+  assert_eq!(history_signals(&fourth), vec![Some(6)]);
+  Ok(())
+}
+
+#[test]
+fn current_run_leaves_previous_slot_untouched() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+  let file_path = tempdir.path().join("state.bin");
+
+  launch(&path, StoreKind::DoubleBuffered, Some(11))?;
+  let before = read(&file_path)?;
+  let previous_slot = schema::double_buffer_slot_offset(0) .. schema::double_buffer_slot_offset(1);
+
+  let mut store = open(&path, StoreKind::DoubleBuffered, None)?;
+  store.prepare_current_run()?;
+  record_signal(6, 0, 0, 0, []);
+  let after = read(&file_path)?;
+  drop(store);
+
+  assert_eq!(after[previous_slot.clone()], before[previous_slot]);
+  Ok(())
+}
+
+#[test]
+fn torn_priming_never_loses_previous_crash() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+  let file_path = tempdir.path().join("state.bin");
+
+  // Slot 0 holds an older crash and slot 1 the previous run's crash. Priming the next run rewrites
+  // slot 0.
+  launch(&path, StoreKind::DoubleBuffered, Some(6))?;
+  launch(&path, StoreKind::DoubleBuffered, Some(11))?;
+  let before = read(&file_path)?;
+  let older_crash = committed_crashes(&before)[1].clone();
+  let previous_crash = committed_crashes(&before)[0].clone();
+  assert_eq!(signal_number(&previous_crash), Some(11));
+
+  launch(&path, StoreKind::DoubleBuffered, None)?;
+  let after = read(&file_path)?;
+  assert_eq!(before.len(), after.len());
+
+  // Simulates a write that only made it to disk up to `offset`, in either direction: the new file
+  // contents before the offset and the old ones after it, and the other way around.
+  let mut new_then_old = before.clone();
+  let mut old_then_new = after.clone();
+  for offset in 0 ..= before.len() {
+    if offset > 0 {
+      new_then_old[offset - 1] = after[offset - 1];
+      old_then_new[offset - 1] = before[offset - 1];
+    }
+
+    for torn in [&new_then_old, &old_then_new] {
+      let (state, _) = read_previous_run(torn);
+      // The previous run's crash is either still the newest run or, once the new slot is complete,
+      // the newest crash in the history. Nothing else is ever reported in its place.
+      if state.did_crash {
+        assert_eq!(
+          state.details, previous_crash.details,
+          "torn at offset {offset}"
+        );
+      }
+      assert_eq!(
+        state.history.first(),
+        Some(&previous_crash),
+        "torn at offset {offset}"
+      );
+      assert!(
+        state.history[1 ..]
+          .iter()
+          .all(|crash| *crash == older_crash),
+        "torn at offset {offset}"
+      );
+    }
+  }
+  Ok(())
+}
+
+#[test]
+fn converts_history_file_carrying_over_previous_crash() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  launch(&path, StoreKind::History, Some(11))?;
+  let converted = launch(&path, StoreKind::DoubleBuffered, None)?;
+  let next = launch(&path, StoreKind::DoubleBuffered, None)?;
+
+  assert_eq!(signal_number(&converted), Some(11));
+  assert_eq!(history_signals(&converted), vec![Some(11)]);
+  assert!(!next.did_crash);
+  assert_eq!(history_signals(&next), vec![Some(11)]);
+  Ok(())
+}
+
+#[test]
+fn sibling_history_includes_double_buffered_crashes() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = state_path(tempdir.path())?;
+
+  // The main process keeps the state file locked while an extension crashes.
+  let mut main = open(&path, StoreKind::DoubleBuffered, None)?;
+  main.prepare_current_run()?;
+  let mut extension = open(&path, StoreKind::DoubleBuffered, Some("extension"))?;
+  extension.prepare_current_run()?;
+  record_signal(6, 0, 0, 0, []);
+  drop(extension);
+  drop(main);
+
+  let previous = launch(&path, StoreKind::DoubleBuffered, None)?;
+  assert!(!previous.did_crash);
+  assert_eq!(history_signals(&previous), vec![Some(6)]);
+  Ok(())
+}
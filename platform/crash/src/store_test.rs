@@ -7,30 +7,83 @@
 
 #![allow(clippy::unwrap_used)]
 
-use super::open;
+use super::{StoreKind, open};
+use crate::annotations::set_annotation;
+use crate::breadcrumbs::{BreadcrumbLevel, record_breadcrumb};
 use crate::previous::{
+  CallStack,
   CrashKind,
-  NSExceptionCallStack,
   NSExceptionCrashInfo,
-  NSExceptionStackFrame,
   PreviousCrashDetails,
   PreviousCrashState,
+  PreviousTermination,
+  SignalCrashInfo,
+  StackFrame,
+  TerminationKind,
 };
-use crate::schema::{self, CrashRecord, CrashRecordHeader, RecordState};
+use crate::schema::{self, CrashRecord, RecordState};
 use crate::test_support::test_crash_record_guard;
-use crate::writer::CRASH_RECORD;
+use crate::writer::{CRASH_RECORD, record_signal, rewrite_previous_state};
+use crate::{crash_loop, session};
 use anyhow::Result;
 use std::ffi::CString;
-use std::fs::write;
+use std::fs::{File, copy, write};
 use std::mem::size_of;
 use std::process::id;
 use std::slice::from_raw_parts;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
 
 fn crash_record_bytes(record: &CrashRecord) -> &[u8] {
   unsafe { from_raw_parts((&raw const *record).cast::<u8>(), size_of::<CrashRecord>()) }
 }
 
+// Simulates one launch: opens the store, prepares the current run and optionally records a signal
+// crash before the process goes away. Returns the previous crash state seen by that launch.
+fn launch(path: &CString, crash_signal: Option<i32>) -> Result<PreviousCrashState> {
+  let mut store = open(path, StoreKind::History, None)?;
+  let previous = store.previous_crash_state();
+  store.prepare_current_run()?;
+  if let Some(signal) = crash_signal {
+    record_signal(signal, 0, 0, 0, []);
+  }
+  Ok(previous)
+}
+
+// Same as `launch`, for a process named `process_name`.
+fn launch_named(path: &CString, process_name: &str, crash_signal: Option<i32>) -> Result<()> {
+  let mut store = open(path, StoreKind::History, Some(process_name))?;
+  store.prepare_current_run()?;
+  if let Some(signal) = crash_signal {
+    record_signal(signal, 0, 0, 0, []);
+  }
+  Ok(())
+}
+
+fn signal_crash(timestamp_secs: u64, signal: i32) -> PreviousCrashState {
+  PreviousCrashState {
+    did_crash: true,
+    timestamp_secs,
+    kind: CrashKind::Signal,
+    details: PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal,
+      ..SignalCrashInfo::default()
+    }),
+    ..PreviousCrashState::default()
+  }
+}
+
+fn history_signals(state: &PreviousCrashState) -> Vec<i32> {
+  state
+    .history
+    .iter()
+    .map(|entry| match &entry.details {
+      PreviousCrashDetails::Signal(signal) => signal.signal,
+      _ => 0,
+    })
+    .collect()
+}
+
 #[test]
 fn open_creates_store_for_empty_file() -> Result<()> {
   let _guard = test_crash_record_guard();
@@ -43,7 +96,7 @@ fn open_creates_store_for_empty_file() -> Result<()> {
       .as_bytes(),
   )?;
 
-  let store = open(&path)?;
+  let store = open(&path, StoreKind::History, None)?;
 
   assert_eq!(store.previous_crash_state(), PreviousCrashState::default());
   Ok(())
@@ -54,65 +107,48 @@ fn open_reads_previous_nsexception_state() -> Result<()> {
   let _guard = test_crash_record_guard();
   let tempdir = tempfile::tempdir()?;
   let path = tempdir.path().join("state.bin");
-
-  {
-    let mut record = CrashRecord {
-      header: CrashRecordHeader {
-        magic: schema::MAGIC,
-        version: schema::VERSION,
-        record_state: RecordState::Committed.into(),
-        crash_kind: CrashKind::NSException.into(),
-        reserved: [0; 2],
-        crc32: 0,
+  let expected = PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: 123,
+    pid: 0,
+    kind: CrashKind::NSException,
+    details: PreviousCrashDetails::NSException(Box::new(NSExceptionCrashInfo {
+      name: Some(c"NSException".to_owned()),
+      reason: Some(c"bad reason!".to_owned()),
+      call_stack: CallStack {
+        return_addresses: vec![21, 34],
+        frames: vec![
+          StackFrame {
+            return_address: 21,
+            ..StackFrame::default()
+          },
+          StackFrame {
+            return_address: 34,
+            ..StackFrame::default()
+          },
+        ],
       },
-      timestamp_secs: 123,
-      ..CrashRecord::default()
-    };
-    record.nsexception.name[.. 12].copy_from_slice(b"NSException\0");
-    record.nsexception.reason[.. 12].copy_from_slice(b"bad reason!\0");
-    record.nsexception.call_stack.frame_count = 2;
-    record.nsexception.call_stack.frames[0].return_address = 21;
-    record.nsexception.call_stack.frames[1].return_address = 34;
-    record.header.crc32 = schema::compute_record_checksum(&record);
-    write(&path, crash_record_bytes(&record))?;
-  }
+    })),
+    annotations: Vec::new(),
+    breadcrumbs: Vec::new(),
+    process_state: None,
+    host_data: None,
+    termination: PreviousTermination::default(),
+    consecutive_launch_crashes: 0,
+    history: Vec::new(),
+  };
+  let mut record = Box::<CrashRecord>::default();
+  rewrite_previous_state(&mut record, &expected);
+  write(&path, crash_record_bytes(&record))?;
 
   let path = CString::new(path.to_string_lossy().as_bytes())?;
-  let store = open(&path)?;
+  let store = open(&path, StoreKind::History, None)?;
 
   assert_eq!(
     store.previous_crash_state(),
     PreviousCrashState {
-      did_crash: true,
-      timestamp_secs: 123,
-      pid: 0,
-      kind: CrashKind::NSException,
-      details: PreviousCrashDetails::NSException(Box::new(NSExceptionCrashInfo {
-        name: {
-          let mut name = [0; schema::NS_EXCEPTION_NAME_CAPACITY];
-          name[.. 12].copy_from_slice(b"NSException\0");
-          name
-        },
-        reason: {
-          let mut reason = [0; schema::NS_EXCEPTION_REASON_CAPACITY];
-          reason[.. 12].copy_from_slice(b"bad reason!\0");
-          reason
-        },
-        call_stack: NSExceptionCallStack {
-          frame_count: 2,
-          return_addresses: {
-            let mut return_addresses = [0; schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize];
-            return_addresses[.. 2].copy_from_slice(&[21, 34]);
-            return_addresses
-          },
-          frames: {
-            let mut frames = std::array::from_fn(|_| NSExceptionStackFrame::default());
-            frames[0].return_address = 21;
-            frames[1].return_address = 34;
-            frames
-          },
-        },
-      })),
+      history: vec![expected.clone()],
+      ..expected
     }
   );
   Ok(())
@@ -123,23 +159,21 @@ fn open_prepares_empty_record_for_current_run_after_reading_previous_state() ->
   let _guard = test_crash_record_guard();
   let tempdir = tempfile::tempdir()?;
   let path = tempdir.path().join("state.bin");
-  let mut record = CrashRecord {
-    header: CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION,
-      record_state: RecordState::Committed.into(),
-      crash_kind: CrashKind::NSException.into(),
-      reserved: [0; 2],
-      crc32: 0,
+  let mut record = Box::<CrashRecord>::default();
+  rewrite_previous_state(
+    &mut record,
+    &PreviousCrashState {
+      did_crash: true,
+      timestamp_secs: 456,
+      kind: CrashKind::NSException,
+      details: PreviousCrashDetails::NSException(Box::default()),
+      ..PreviousCrashState::default()
     },
-    timestamp_secs: 456,
-    ..CrashRecord::default()
-  };
-  record.header.crc32 = schema::compute_record_checksum(&record);
+  );
   write(&path, crash_record_bytes(&record))?;
 
   let path = CString::new(path.to_string_lossy().as_bytes())?;
-  let mut store = open(&path)?;
+  let mut store = open(&path, StoreKind::History, None)?;
 
   assert!(store.previous_crash_state().did_crash);
   assert_eq!(store.previous_crash_state().timestamp_secs, 456);
@@ -152,6 +186,424 @@ fn open_prepares_empty_record_for_current_run_after_reading_previous_state() ->
   assert_eq!(current_record.header.record_state, RecordState::Empty);
   assert_eq!(current_record.header.crash_kind, CrashKind::None);
   assert_eq!(current_record.timestamp_secs, 0);
+  assert_eq!(current_record.arena_len, 0);
   assert_eq!(current_record.pid, id());
   Ok(())
 }
+
+#[test]
+fn crash_history_keeps_crashes_from_consecutive_launches() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  launch(&path, Some(11))?;
+  let second = launch(&path, Some(6))?;
+  let third = launch(&path, None)?;
+
+  assert!(second.did_crash);
+  assert_eq!(history_signals(&second), vec![11]);
+  assert!(third.did_crash);
+  assert_eq!(history_signals(&third), vec![6, 11]);
+  assert!(matches!(
+    third.details,
+    PreviousCrashDetails::Signal(ref signal) if signal.signal == 6
+  ));
+  Ok(())
+}
+
+#[test]
+fn clean_launches_reuse_their_slot() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  launch(&path, Some(11))?;
+  for _ in 0 .. schema::CRASH_HISTORY_CAPACITY * 2 {
+    launch(&path, None)?;
+  }
+  let previous = launch(&path, None)?;
+
+  assert!(!previous.did_crash);
+  assert_eq!(history_signals(&previous), vec![11]);
+  Ok(())
+}
+
+#[test]
+fn crash_history_drops_oldest_crash_when_full() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  let capacity = i32::try_from(schema::CRASH_HISTORY_CAPACITY)?;
+  for signal in 1 ..= capacity + 1 {
+    launch(&path, Some(signal))?;
+  }
+  let previous = launch(&path, None)?;
+
+  assert_eq!(
+    history_signals(&previous),
+    (2 ..= capacity + 1).rev().collect::<Vec<_>>()
+  );
+  Ok(())
+}
+
+#[test]
+fn breadcrumbs_are_reported_with_the_next_launch_crash() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  {
+    let mut store = open(&path, StoreKind::History, None)?;
+    store.prepare_current_run()?;
+    record_breadcrumb(BreadcrumbLevel::Info, "from a clean run");
+  }
+  {
+    let mut store = open(&path, StoreKind::History, None)?;
+    store.prepare_current_run()?;
+    record_breadcrumb(BreadcrumbLevel::Error, "before crash");
+    record_signal(11, 0, 0, 0, []);
+  }
+  let previous = launch(&path, None)?;
+
+  let messages = previous
+    .breadcrumbs
+    .iter()
+    .map(|breadcrumb| breadcrumb.message.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(messages, vec![c"before crash".to_owned()]);
+  assert_eq!(previous.history[0].breadcrumbs, previous.breadcrumbs);
+  Ok(())
+}
+
+#[test]
+fn annotations_are_reported_with_the_next_launch_crash() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  {
+    let mut store = open(&path, StoreKind::History, None)?;
+    store.prepare_current_run()?;
+    assert!(set_annotation("build_flavor", "from a clean run"));
+  }
+  {
+    let mut store = open(&path, StoreKind::History, None)?;
+    store.prepare_current_run()?;
+    assert!(set_annotation("screen", "checkout"));
+    record_signal(11, 0, 0, 0, []);
+  }
+  let previous = launch(&path, None)?;
+
+  let annotations = previous
+    .annotations
+    .iter()
+    .map(|annotation| (annotation.key.clone(), annotation.value.clone()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    annotations,
+    vec![(c"screen".to_owned(), c"checkout".to_owned())]
+  );
+  assert_eq!(previous.history[0].annotations, previous.annotations);
+  Ok(())
+}
+
+#[test]
+fn previous_run_is_classified_by_how_it_ended() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  // The first launch has nothing to classify. It is then killed without a clean shutdown.
+  assert_eq!(
+    launch(&path, None)?.termination.kind,
+    TerminationKind::Unknown
+  );
+
+  let previous = {
+    let mut store = open(&path, StoreKind::History, None)?;
+    store.prepare_current_run()?;
+    session::mark_clean_exit();
+    store.previous_crash_state()
+  };
+  assert_eq!(
+    previous.termination.kind,
+    TerminationKind::UncleanTermination
+  );
+  assert!(previous.termination.last_heartbeat_secs > 0);
+
+  assert_eq!(
+    launch(&path, Some(11))?.termination.kind,
+    TerminationKind::CleanExit
+  );
+  assert_eq!(
+    launch(&path, None)?.termination.kind,
+    TerminationKind::Crash
+  );
+  Ok(())
+}
+
+// Simulates a launch that starts the monitors and crashes right away. Returns the consecutive
+// launch crashes seen by that launch.
+fn launch_and_crash_early(path: &CString, mark_stable: bool) -> Result<u32> {
+  let mut store = open(path, StoreKind::History, None)?;
+  store.prepare_current_run()?;
+  crash_loop::mark_started();
+  let consecutive = crash_loop::consecutive_launch_crashes();
+  if mark_stable {
+    crash_loop::reset_consecutive_launch_crashes();
+    assert_eq!(crash_loop::consecutive_launch_crashes(), 0);
+  }
+  record_signal(11, 0, 0, 0, []);
+  Ok(consecutive)
+}
+
+#[test]
+fn consecutive_early_crashes_are_counted_until_a_launch_is_stable() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  assert_eq!(launch_and_crash_early(&path, false)?, 0);
+  assert_eq!(launch_and_crash_early(&path, false)?, 1);
+  assert_eq!(launch_and_crash_early(&path, true)?, 2);
+
+  // The stable launch's crash doesn't count, so the streak starts over.
+  let previous = launch(&path, None)?;
+  assert!(previous.did_crash);
+  assert_eq!(previous.consecutive_launch_crashes, 0);
+
+  // A launch that never started the monitors doesn't count either.
+  launch(&path, Some(11))?;
+  assert_eq!(launch(&path, None)?.consecutive_launch_crashes, 0);
+  Ok(())
+}
+
+#[test]
+fn open_converts_single_record_file_into_history() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = tempdir.path().join("state.bin");
+  let mut record = Box::<CrashRecord>::default();
+  rewrite_previous_state(&mut record, &signal_crash(456, 11));
+  write(&path, crash_record_bytes(&record))?;
+  let path = CString::new(path.to_string_lossy().as_bytes())?;
+
+  let first = launch(&path, Some(6))?;
+  let second = launch(&path, None)?;
+
+  assert_eq!(first.timestamp_secs, 456);
+  assert_eq!(history_signals(&first), vec![11]);
+  assert_eq!(history_signals(&second), vec![6, 11]);
+  Ok(())
+}
+
+#[test]
+fn open_converts_version_1_state_file_into_history() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = tempdir.path().join("state.bin");
+  // Released SDKs wrote a single version 1 record at the start of the file.
+  write(
+    &path,
+    include_bytes!("../fixtures/records/v1_nsexception.bin"),
+  )?;
+  let path = CString::new(path.to_string_lossy().as_bytes())?;
+
+  let first = launch(&path, Some(6))?;
+  let second = launch(&path, None)?;
+
+  assert_eq!(first.timestamp_secs, 1_700_000_000);
+  assert_eq!(first.pid, 4242);
+  assert_eq!(first.kind, CrashKind::NSException);
+  assert_eq!(history_signals(&second), vec![6, 0]);
+  assert_eq!(second.history[1], first.history[0]);
+  Ok(())
+}
+
+#[test]
+fn processes_sharing_a_state_path_use_files_named_after_them() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  // The main process keeps the state file locked while an extension crashes.
+  let mut main = open(&path, StoreKind::History, None)?;
+  main.prepare_current_run()?;
+  let mut extension = open(&path, StoreKind::History, Some("io.bitdrift.app.widget"))?;
+  extension.prepare_current_run()?;
+  record_signal(6, 0, 0, 0, []);
+  drop(extension);
+  drop(main);
+  assert!(
+    tempdir
+      .path()
+      .join("state.bin.proc.io.bitdrift.app.widget")
+      .exists()
+  );
+
+  // Each process reads back its own previous launch, whichever process starts first, and the
+  // history includes the crashes recorded by the other.
+  let extension =
+    open(&path, StoreKind::History, Some("io.bitdrift.app.widget"))?.previous_crash_state();
+  assert!(extension.did_crash);
+  assert_eq!(history_signals(&extension), vec![6]);
+
+  let main = open(&path, StoreKind::History, None)?.previous_crash_state();
+  assert!(!main.did_crash);
+  assert_eq!(history_signals(&main), vec![6]);
+  Ok(())
+}
+
+#[test]
+fn only_files_named_after_a_process_are_read_as_siblings() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  launch_named(&path, "widget", Some(6))?;
+  let widget = tempdir.path().join("state.bin.proc.widget");
+  for name in [
+    "state.bin.backup",
+    "state.bin.widget",
+    "state.bin.proc.",
+    "state.bin.proc.a:b",
+  ] {
+    copy(&widget, tempdir.path().join(name))?;
+  }
+
+  let main = open(&path, StoreKind::History, None)?.previous_crash_state();
+  assert_eq!(history_signals(&main), vec![6]);
+  Ok(())
+}
+
+#[test]
+fn stale_process_files_are_removed_unless_in_use() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+  let long_ago = SystemTime::now() - Duration::from_hours(31 * 24);
+
+  launch_named(&path, "stale", Some(6))?;
+  launch_named(&path, "recent", Some(11))?;
+  let live = open(&path, StoreKind::History, Some("live"))?;
+  for name in ["stale", "live"] {
+    File::options()
+      .write(true)
+      .open(tempdir.path().join(format!("state.bin.proc.{name}")))?
+      .set_modified(long_ago)?;
+  }
+
+  let main = open(&path, StoreKind::History, None)?.previous_crash_state();
+  assert_eq!(history_signals(&main), vec![11]);
+  assert!(!tempdir.path().join("state.bin.proc.stale").exists());
+  assert!(tempdir.path().join("state.bin.proc.recent").exists());
+  assert!(tempdir.path().join("state.bin.proc.live").exists());
+  drop(live);
+  Ok(())
+}
+
+#[test]
+fn open_fails_when_another_process_uses_the_same_name() -> Result<()> {
+  let _guard = test_crash_record_guard();
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  let _main = open(&path, StoreKind::History, None)?;
+  let _extension = open(&path, StoreKind::History, Some("extension"))?;
+
+  for process_name in [None, Some("extension")] {
+    let error = open(&path, StoreKind::History, process_name)
+      .err()
+      .map(|error| error.to_string());
+    assert!(error.is_some_and(|error| error.contains("distinct process names")));
+  }
+  Ok(())
+}
+
+#[test]
+fn open_rejects_process_names_that_are_not_file_names() -> Result<()> {
+  let tempdir = tempfile::tempdir()?;
+  let path = CString::new(
+    tempdir
+      .path()
+      .join("state.bin")
+      .to_string_lossy()
+      .as_bytes(),
+  )?;
+
+  for process_name in ["", "../state.bin", "com.example:remote"] {
+    let error = open(&path, StoreKind::History, Some(process_name))
+      .err()
+      .map(|error| error.to_string());
+    assert!(error.is_some_and(|error| error.contains("invalid crash reporter process name")));
+  }
+  Ok(())
+}
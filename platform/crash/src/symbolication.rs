@@ -0,0 +1,181 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./symbolication_test.rs"]
+mod tests;
+
+use crate::export::hex;
+use crate::previous::CallStack;
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+
+// Version of the symbolication document, bumped like the export format's.
+pub(crate) const SYMBOLICATION_FORMAT_VERSION: u32 = 1;
+
+//
+// SymbolicationStack
+//
+
+// A call stack rearranged the way symbolication tools consume it: every image the stack runs
+// through is listed once, and each frame refers to its image by index along with its offset from
+// the image's load address. Offsets are computed from the return addresses as captured, so callers
+// that want the call site rather than the return site subtract one themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SymbolicationStack {
+  pub(crate) images: Vec<SymbolicationImage>,
+  pub(crate) frames: Vec<SymbolicationFrame>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SymbolicationImage {
+  pub(crate) image_id: Option<String>,
+  pub(crate) name: Option<String>,
+  pub(crate) load_address: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SymbolicationFrame {
+  pub(crate) address: u64,
+  // The index into `images` and the offset from that image's load address, or `None` when the
+  // frame didn't resolve to any image.
+  pub(crate) image: Option<(usize, u64)>,
+}
+
+// The text formats `symbolizer_input` produces. Values are exposed over the C ABI, so they must not
+// be renumbered.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SymbolizerFormat {
+  // One `"<binary>" <offset>` line per frame, as read by `llvm-symbolizer` from stdin.
+  LlvmSymbolizer = 0,
+  // One `atos -o <binary> -l <load address> <addresses>` command per image.
+  Atos           = 1,
+}
+
+impl SymbolizerFormat {
+  pub(crate) const fn from_u8(value: u8) -> Option<Self> {
+    match value {
+      0 => Some(Self::LlvmSymbolizer),
+      1 => Some(Self::Atos),
+      _ => None,
+    }
+  }
+}
+
+impl SymbolicationStack {
+  pub(crate) fn new(call_stack: &CallStack) -> Self {
+    let mut stack = Self::default();
+    for frame in &call_stack.frames {
+      let image_id = frame
+        .image_id
+        .as_ref()
+        .map(|id| id.to_string_lossy().into_owned());
+      let name = frame
+        .binary_name
+        .as_ref()
+        .map(|name| name.to_string_lossy().into_owned());
+      // Frames the module map couldn't place carry neither an image ID nor a load address.
+      let image = frame
+        .return_address
+        .checked_sub(frame.image_load_address)
+        .filter(|_| image_id.is_some() || frame.image_load_address != 0)
+        .map(|offset| {
+          let index = stack.image_index(SymbolicationImage {
+            image_id,
+            name,
+            load_address: frame.image_load_address,
+          });
+          (index, offset)
+        });
+      stack.frames.push(SymbolicationFrame {
+        address: frame.return_address,
+        image,
+      });
+    }
+    stack
+  }
+
+  // Returns the index of `image`, adding it unless an image with the same ID, or the same name and
+  // load address when it has no ID, is already listed.
+  fn image_index(&mut self, image: SymbolicationImage) -> usize {
+    let existing = self.images.iter().position(|listed| {
+      if image.image_id.is_some() {
+        listed.image_id == image.image_id
+      } else {
+        listed.image_id.is_none()
+          && listed.name == image.name
+          && listed.load_address == image.load_address
+      }
+    });
+    existing.unwrap_or_else(|| {
+      self.images.push(image);
+      self.images.len() - 1
+    })
+  }
+
+  pub(crate) fn to_json(&self) -> Value {
+    json!({
+      "format_version": SYMBOLICATION_FORMAT_VERSION,
+      "images": self.images.iter().map(|image| json!({
+        "image_id": image.image_id,
+        "name": image.name,
+        "load_address": hex(image.load_address),
+      })).collect::<Vec<_>>(),
+      "frames": self.frames.iter().map(|frame| json!({
+        "address": hex(frame.address),
+        "image_index": frame.image.map(|(index, _)| index),
+        "offset": frame.image.map(|(_, offset)| hex(offset)),
+      })).collect::<Vec<_>>(),
+    })
+  }
+
+  // Renders the stack as input for a symbolization tool. Frames that didn't resolve to an image
+  // with a known binary are left out, since neither tool can do anything with them.
+  pub(crate) fn symbolizer_input(&self, format: SymbolizerFormat) -> String {
+    let mut input = String::new();
+    match format {
+      SymbolizerFormat::LlvmSymbolizer => {
+        for (name, offset) in self.resolved_frames() {
+          let _ = writeln!(input, "\"{name}\" {offset:#x}");
+        }
+      },
+      SymbolizerFormat::Atos => {
+        for (index, image) in self.images.iter().enumerate() {
+          let Some(name) = &image.name else {
+            continue;
+          };
+          let addresses = self
+            .frames
+            .iter()
+            .filter(|frame| frame.image.is_some_and(|(image, _)| image == index))
+            .fold(String::new(), |mut addresses, frame| {
+              let _ = write!(addresses, " {:#x}", frame.address);
+              addresses
+            });
+          let _ = writeln!(
+            input,
+            "atos -o '{}' -l {:#x}{addresses}",
+            name.replace('\'', r"'\''"),
+            image.load_address
+          );
+        }
+      },
+    }
+    input
+  }
+
+  // Every frame that resolved to an image with a known binary, as the binary's name along with the
+  // frame's offset.
+  fn resolved_frames(&self) -> impl Iterator<Item = (&str, u64)> {
+    self.frames.iter().filter_map(|frame| {
+      let (index, offset) = frame.image?;
+      let name = self.images.get(index)?.name.as_deref()?;
+      Some((name, offset))
+    })
+  }
+}
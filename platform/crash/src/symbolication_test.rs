@@ -0,0 +1,170 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{SymbolicationFrame, SymbolicationImage, SymbolicationStack, SymbolizerFormat};
+use crate::previous::{CallStack, StackFrame};
+use serde_json::json;
+use std::ffi::CString;
+
+fn frame(
+  return_address: u64,
+  image_load_address: u64,
+  binary_name: Option<&str>,
+  image_id: Option<&str>,
+) -> StackFrame {
+  StackFrame {
+    return_address,
+    image_load_address,
+    binary_name: binary_name.map(|name| CString::new(name).unwrap_or_default()),
+    image_id: image_id.map(|id| CString::new(id).unwrap_or_default()),
+  }
+}
+
+fn call_stack(frames: Vec<StackFrame>) -> CallStack {
+  CallStack {
+    return_addresses: frames.iter().map(|frame| frame.return_address).collect(),
+    frames,
+  }
+}
+
+fn sample_stack() -> SymbolicationStack {
+  SymbolicationStack::new(&call_stack(vec![
+    frame(0x1_0040, 0x1_0000, Some("/app/App"), Some("APP-UUID")),
+    frame(
+      0x7_0120,
+      0x7_0000,
+      Some("/usr/lib/libc.dylib"),
+      Some("LIBC-UUID"),
+    ),
+    frame(0xdead, 0, None, None),
+    frame(0x1_0400, 0x1_0000, Some("/app/App"), Some("APP-UUID")),
+  ]))
+}
+
+#[test]
+fn frames_refer_to_unique_images_by_offset() {
+  let stack = sample_stack();
+
+  assert_eq!(
+    stack.images,
+    vec![
+      SymbolicationImage {
+        image_id: Some("APP-UUID".to_string()),
+        name: Some("/app/App".to_string()),
+        load_address: 0x1_0000,
+      },
+      SymbolicationImage {
+        image_id: Some("LIBC-UUID".to_string()),
+        name: Some("/usr/lib/libc.dylib".to_string()),
+        load_address: 0x7_0000,
+      },
+    ]
+  );
+  assert_eq!(
+    stack.frames,
+    vec![
+      SymbolicationFrame {
+        address: 0x1_0040,
+        image: Some((0, 0x40)),
+      },
+      SymbolicationFrame {
+        address: 0x7_0120,
+        image: Some((1, 0x120)),
+      },
+      SymbolicationFrame {
+        address: 0xdead,
+        image: None,
+      },
+      SymbolicationFrame {
+        address: 0x1_0400,
+        image: Some((0, 0x400)),
+      },
+    ]
+  );
+}
+
+#[test]
+fn images_without_id_are_told_apart_by_name_and_load_address() {
+  let stack = SymbolicationStack::new(&call_stack(vec![
+    frame(0x2010, 0x2000, Some("libfoo.so"), None),
+    frame(0x2020, 0x2000, Some("libfoo.so"), None),
+    frame(0x9010, 0x9000, Some("libfoo.so"), None),
+    // A return address below its image's load address can't be placed.
+    frame(0x10, 0x2000, Some("libfoo.so"), None),
+  ]));
+
+  assert_eq!(stack.images.len(), 2);
+  assert_eq!(
+    stack
+      .frames
+      .iter()
+      .map(|frame| frame.image)
+      .collect::<Vec<_>>(),
+    vec![Some((0, 0x10)), Some((0, 0x20)), Some((1, 0x10)), None]
+  );
+}
+
+#[test]
+fn llvm_symbolizer_input_lists_resolved_frames() {
+  assert_eq!(
+    sample_stack().symbolizer_input(SymbolizerFormat::LlvmSymbolizer),
+    "\"/app/App\" 0x40\n\"/usr/lib/libc.dylib\" 0x120\n\"/app/App\" 0x400\n"
+  );
+}
+
+#[test]
+fn atos_input_has_one_command_per_image() {
+  assert_eq!(
+    sample_stack().symbolizer_input(SymbolizerFormat::Atos),
+    "atos -o '/app/App' -l 0x10000 0x10040 0x10400\natos -o '/usr/lib/libc.dylib' -l 0x70000 \
+     0x70120\n"
+  );
+
+  let quoted = SymbolicationStack::new(&call_stack(vec![frame(
+    0x1010,
+    0x1000,
+    Some("/My App's.app/App"),
+    None,
+  )]));
+  assert_eq!(
+    quoted.symbolizer_input(SymbolizerFormat::Atos),
+    "atos -o '/My App'\\''s.app/App' -l 0x1000 0x1010\n"
+  );
+}
+
+#[test]
+fn json_document_holds_images_and_frames() {
+  assert_eq!(
+    sample_stack().to_json(),
+    json!({
+      "format_version": 1,
+      "images": [
+        {"image_id": "APP-UUID", "name": "/app/App", "load_address": "0x10000"},
+        {"image_id": "LIBC-UUID", "name": "/usr/lib/libc.dylib", "load_address": "0x70000"},
+      ],
+      "frames": [
+        {"address": "0x10040", "image_index": 0, "offset": "0x40"},
+        {"address": "0x70120", "image_index": 1, "offset": "0x120"},
+        {"address": "0xdead", "image_index": null, "offset": null},
+        {"address": "0x10400", "image_index": 0, "offset": "0x400"},
+      ],
+    })
+  );
+}
+
+#[test]
+fn symbolizer_formats_round_trip_through_their_values() {
+  assert_eq!(
+    SymbolizerFormat::from_u8(SymbolizerFormat::LlvmSymbolizer as u8),
+    Some(SymbolizerFormat::LlvmSymbolizer)
+  );
+  assert_eq!(
+    SymbolizerFormat::from_u8(SymbolizerFormat::Atos as u8),
+    Some(SymbolizerFormat::Atos)
+  );
+  assert_eq!(SymbolizerFormat::from_u8(2), None);
+}
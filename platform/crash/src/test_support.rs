@@ -5,8 +5,18 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
+use crate::annotations::ANNOTATION_TABLE;
+use crate::breadcrumbs::BREADCRUMB_RING;
+use crate::crash_callback::set_crash_callback;
+use crate::crash_log::{self, REPORTED_CRASH_STATE};
+use crate::crash_loop::CRASH_LOOP_STATE;
+use crate::previous::{PreviousCrashState, read_previous_state_from_bytes};
+use crate::schema::CrashRecord;
+use crate::session::SESSION_STATE;
 use crate::writer::CRASH_RECORD;
+use std::mem::size_of;
 use std::ptr::null_mut;
+use std::slice::from_raw_parts;
 use std::sync::atomic::Ordering;
 use std::sync::{Mutex, MutexGuard};
 
@@ -19,6 +29,16 @@ pub(crate) struct TestCrashRecordGuard {
 impl Drop for TestCrashRecordGuard {
   fn drop(&mut self) {
     CRASH_RECORD.store(null_mut(), Ordering::Release);
+    ANNOTATION_TABLE.store(null_mut(), Ordering::Release);
+    BREADCRUMB_RING.store(null_mut(), Ordering::Release);
+    SESSION_STATE.store(null_mut(), Ordering::Release);
+    CRASH_LOOP_STATE.store(null_mut(), Ordering::Release);
+    REPORTED_CRASH_STATE.store(null_mut(), Ordering::Release);
+    unsafe {
+      set_crash_callback(None, null_mut());
+    }
+    crash_log::detach_any_crash_logger();
+    crash_log::set_previous_crash(&PreviousCrashState::default());
   }
 }
 
@@ -28,5 +48,22 @@ pub(crate) fn test_crash_record_guard() -> TestCrashRecordGuard {
     Err(poisoned) => poisoned.into_inner(),
   };
   CRASH_RECORD.store(null_mut(), Ordering::Release);
+  ANNOTATION_TABLE.store(null_mut(), Ordering::Release);
+  BREADCRUMB_RING.store(null_mut(), Ordering::Release);
+  SESSION_STATE.store(null_mut(), Ordering::Release);
+  CRASH_LOOP_STATE.store(null_mut(), Ordering::Release);
+  REPORTED_CRASH_STATE.store(null_mut(), Ordering::Release);
+  unsafe {
+    set_crash_callback(None, null_mut());
+  }
+  crash_log::detach_any_crash_logger();
+  crash_log::set_previous_crash(&PreviousCrashState::default());
   TestCrashRecordGuard { _guard: guard }
 }
+
+// Decodes `record` the same way the next launch would read it back from the crash state file.
+pub(crate) fn decode_record(record: &CrashRecord) -> PreviousCrashState {
+  let bytes =
+    unsafe { from_raw_parts((&raw const *record).cast::<u8>(), size_of::<CrashRecord>()) };
+  read_previous_state_from_bytes(bytes)
+}
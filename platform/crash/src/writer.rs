@@ -9,7 +9,24 @@
 #[path = "./writer_test.rs"]
 mod tests;
 
-use crate::schema::{self, CrashKind, CrashRecord, RecordState};
+use crate::previous::{CallStack, PreviousCrashDetails, PreviousCrashState};
+use crate::schema::{
+  self,
+  ArenaAnnotation,
+  ArenaBreadcrumb,
+  ArenaEntryHeader,
+  ArenaPanicLocation,
+  ArenaProcessState,
+  ArenaSignal,
+  ArenaStackFrame,
+  ArenaTag,
+  CrashKind,
+  CrashRecord,
+  RecordState,
+};
+use crate::{annotations, breadcrumbs, crash_callback, process_state};
+use std::ffi::CStr;
+use std::mem::size_of;
 use std::process::id;
 use std::ptr::{addr_of_mut, null_mut};
 use std::sync::atomic::{AtomicPtr, Ordering, fence};
@@ -21,8 +38,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 pub(crate) static CRASH_RECORD: AtomicPtr<CrashRecord> = AtomicPtr::new(null_mut());
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub(crate) struct NSExceptionFrameRecord<'a> {
+// A stack frame as captured at crash time, before it's encoded into the record.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StackFrameRecord<'a> {
   pub(crate) return_address: u64,
   pub(crate) image_load_address: u64,
   pub(crate) binary_name: Option<&'a str>,
@@ -32,28 +50,28 @@ pub(crate) struct NSExceptionFrameRecord<'a> {
 pub(crate) unsafe fn prime_shared_record(record_ptr: *mut CrashRecord) {
   // Initialize the backed record for the current run before any monitor can publish crash data into
   // it. This binds the shared pointer to live storage and resets the record to a known empty state.
+  // Fields are assigned in place since the record is too large to build on the stack.
   let record = unsafe { &mut *record_ptr };
-  *record = CrashRecord {
-    header: schema::CrashRecordHeader {
-      magic: schema::MAGIC,
-      version: schema::VERSION,
-      record_state: RecordState::Empty.into(),
-      crash_kind: CrashKind::None.into(),
-      reserved: [0; 2],
-      crc32: 0,
-    },
-    timestamp_secs: 0,
-    pid: id(),
-    reserved: [0; 4],
-    nsexception: schema::RawNSExceptionPayload::default(),
+  record.header = schema::CrashRecordHeader {
+    magic: schema::MAGIC,
+    version: schema::VERSION,
+    record_state: RecordState::Empty.into(),
+    crash_kind: CrashKind::None.into(),
+    reserved: [0; 2],
+    crc32: 0,
   };
+  record.timestamp_secs = 0;
+  record.pid = id();
+  record.arena_len = 0;
+  record.arena.fill(0);
   CRASH_RECORD.store(record_ptr, Ordering::Release);
 }
 
+#[cfg_attr(not(target_vendor = "apple"), allow(dead_code))]
 pub(crate) fn record_nsexception(
   name: &str,
   reason: Option<&str>,
-  frames: &[NSExceptionFrameRecord<'_>],
+  frames: &[StackFrameRecord<'_>],
 ) {
   let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
   if record_ptr.is_null() {
@@ -64,42 +82,230 @@ pub(crate) fn record_nsexception(
   mark_record_writing(record);
   record.timestamp_secs = current_timestamp_secs();
   record.pid = id();
+  record.arena_len = 0;
 
-  write_string(name, &mut record.nsexception.name);
-  if let Some(reason) = reason {
-    write_string(reason, &mut record.nsexception.reason);
-  } else {
-    record.nsexception.reason.fill(0);
-  }
-  let frame_count =
-    u16::try_from(frames.len()).unwrap_or(schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES);
-  record.nsexception.call_stack.frame_count =
-    frame_count.min(schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES);
-  let copy_len = usize::from(record.nsexception.call_stack.frame_count);
-  record
-    .nsexception
-    .call_stack
-    .frames
-    .fill(schema::RawNSExceptionStackFrame::default());
-  for (target, frame) in record.nsexception.call_stack.frames[.. copy_len]
-    .iter_mut()
-    .zip(&frames[.. copy_len])
-  {
-    target.return_address = frame.return_address;
-    target.image_load_address = frame.image_load_address;
-    if let Some(binary_name) = frame.binary_name {
-      write_string(binary_name, &mut target.binary_name);
-    }
-    if let Some(image_id) = frame.image_id {
-      write_string(image_id, &mut target.image_id);
-    }
-  }
+  // Entries are appended in order of importance: once the arena is full the remaining frames are
+  // dropped, but the name, reason and process state always fit.
+  append_string(record, ArenaTag::NSExceptionName, Some(name.as_bytes()));
+  append_string(
+    record,
+    ArenaTag::NSExceptionReason,
+    reason.map(str::as_bytes),
+  );
+  append_process_state(record, &process_state::snapshot());
+  append_call_stack(record, frames.iter().copied());
+  append_annotations(record);
+  append_breadcrumbs(record);
+  append_host_data(record);
   record.header.crash_kind = CrashKind::NSException.into();
   // Always mark as "Committed" after every other field is updated, so the next
   // launch never treats a partial write as a valid crash record
   commit_record(record);
 }
 
+pub(crate) fn record_signal<'a>(
+  signal: i32,
+  code: i32,
+  fault_address: u64,
+  thread_id: u64,
+  frames: impl IntoIterator<Item = StackFrameRecord<'a>>,
+) {
+  // Called from a signal handler, so this must stay async-signal-safe: no allocation, no locks and
+  // no large stack temporaries.
+  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
+  if record_ptr.is_null() {
+    return;
+  }
+
+  let record = unsafe { &mut *record_ptr };
+  mark_record_writing(record);
+  record.timestamp_secs = current_timestamp_secs();
+  record.pid = id();
+  record.arena_len = 0;
+  append_signal(
+    record,
+    &ArenaSignal {
+      signal,
+      code,
+      fault_address,
+      thread_id,
+    },
+  );
+  append_process_state(record, &process_state::snapshot());
+  append_call_stack(record, frames);
+  append_annotations(record);
+  append_breadcrumbs(record);
+  append_host_data(record);
+  record.header.crash_kind = CrashKind::Signal.into();
+  commit_record(record);
+}
+
+pub(crate) fn record_rust_panic(
+  message: Option<&str>,
+  location: Option<(&str, u32, u32)>,
+  thread_name: Option<&str>,
+) {
+  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
+  if record_ptr.is_null() {
+    return;
+  }
+
+  let record = unsafe { &mut *record_ptr };
+  mark_record_writing(record);
+  record.timestamp_secs = current_timestamp_secs();
+  record.pid = id();
+  record.arena_len = 0;
+  append_rust_panic(
+    record,
+    message.map(str::as_bytes),
+    location.map(|(file, line, column)| (file.as_bytes(), line, column)),
+    thread_name.map(str::as_bytes),
+  );
+  append_process_state(record, &process_state::snapshot());
+  append_annotations(record);
+  append_breadcrumbs(record);
+  append_host_data(record);
+  record.header.crash_kind = CrashKind::RustPanic.into();
+  commit_record(record);
+}
+
+#[cfg_attr(target_vendor = "apple", allow(dead_code))]
+pub(crate) fn record_cxx_exception<'a>(
+  type_name: Option<&str>,
+  what: Option<&str>,
+  frames: impl IntoIterator<Item = StackFrameRecord<'a>>,
+) {
+  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
+  if record_ptr.is_null() {
+    return;
+  }
+
+  let record = unsafe { &mut *record_ptr };
+  mark_record_writing(record);
+  record.timestamp_secs = current_timestamp_secs();
+  record.pid = id();
+  record.arena_len = 0;
+  append_string(
+    record,
+    ArenaTag::CxxExceptionType,
+    type_name.map(str::as_bytes),
+  );
+  append_string(record, ArenaTag::CxxExceptionWhat, what.map(str::as_bytes));
+  append_process_state(record, &process_state::snapshot());
+  append_call_stack(record, frames);
+  append_annotations(record);
+  append_breadcrumbs(record);
+  append_host_data(record);
+  record.header.crash_kind = CrashKind::CxxException.into();
+  commit_record(record);
+}
+
+// Re-encodes a crash decoded from another layout into `record`, keeping its original timestamp and
+// pid. Used when the store migrates a crash state file written by an older build.
+pub(crate) fn rewrite_previous_state(record: &mut CrashRecord, state: &PreviousCrashState) {
+  mark_record_writing(record);
+  record.header.magic = schema::MAGIC;
+  record.header.version = schema::VERSION;
+  record.timestamp_secs = state.timestamp_secs;
+  record.pid = state.pid;
+  record.arena_len = 0;
+
+  match &state.details {
+    PreviousCrashDetails::None => {
+      record.header.crash_kind = CrashKind::None.into();
+      unsafe {
+        addr_of_mut!(record.header.record_state).write_volatile(RecordState::Empty.into());
+      }
+      return;
+    },
+    PreviousCrashDetails::NSException(exception) => {
+      append_string(
+        record,
+        ArenaTag::NSExceptionName,
+        exception.name.as_deref().map(CStr::to_bytes),
+      );
+      append_string(
+        record,
+        ArenaTag::NSExceptionReason,
+        exception.reason.as_deref().map(CStr::to_bytes),
+      );
+      append_decoded_call_stack(record, &exception.call_stack);
+    },
+    PreviousCrashDetails::Signal(signal) => {
+      append_signal(
+        record,
+        &ArenaSignal {
+          signal: signal.signal,
+          code: signal.code,
+          fault_address: signal.fault_address,
+          thread_id: signal.thread_id,
+        },
+      );
+      append_decoded_call_stack(record, &signal.call_stack);
+    },
+    PreviousCrashDetails::RustPanic(panic) => {
+      append_rust_panic(
+        record,
+        panic.message.as_deref().map(CStr::to_bytes),
+        panic
+          .file
+          .as_deref()
+          .map(|file| (file.to_bytes(), panic.line, panic.column)),
+        panic.thread_name.as_deref().map(CStr::to_bytes),
+      );
+    },
+    PreviousCrashDetails::CxxException(exception) => {
+      append_string(
+        record,
+        ArenaTag::CxxExceptionType,
+        exception.type_name.as_deref().map(CStr::to_bytes),
+      );
+      append_string(
+        record,
+        ArenaTag::CxxExceptionWhat,
+        exception.what.as_deref().map(CStr::to_bytes),
+      );
+      append_decoded_call_stack(record, &exception.call_stack);
+    },
+  }
+  if let Some(process_state) = &state.process_state {
+    append_process_state(
+      record,
+      &ArenaProcessState {
+        uptime_ms: process_state.uptime_ms,
+        resident_bytes: process_state.resident_bytes,
+        thread_count: process_state.thread_count,
+        open_fd_count: process_state.open_fd_count,
+        app_state: process_state.app_state.into(),
+        reserved: [0; 7],
+      },
+    );
+  }
+  for annotation in &state.annotations {
+    append_annotation(
+      record,
+      annotation.key.to_bytes(),
+      annotation.value.to_bytes(),
+    );
+  }
+  for breadcrumb in state.breadcrumbs.iter().rev() {
+    let appended = append_breadcrumb(
+      record,
+      breadcrumb.timestamp_ms,
+      breadcrumb.level as u8,
+      breadcrumb.message.to_bytes(),
+    );
+    if !appended {
+      break;
+    }
+  }
+  if let Some(host_data) = &state.host_data {
+    append_entry(record, ArenaTag::HostData, &[host_data]);
+  }
+  record.header.crash_kind = state.kind.into();
+  commit_record(record);
+}
+
 fn mark_record_writing(record: &mut CrashRecord) {
   // The mapped record is shared with a future process, so state transitions must be emitted as
   // observable writes instead of relying on compiler-visible ordinary stores.
@@ -124,11 +330,247 @@ fn current_timestamp_secs() -> u64 {
     .map_or(0, |duration| duration.as_secs())
 }
 
-fn write_string<const N: usize>(value: &str, target: &mut [u8; N]) {
-  // Leave one byte for the null terminator so persisted strings always remain safe for later
-  // C-string readers, even when the input has to be truncated
-  target.fill(0);
-  let bytes = value.as_bytes();
-  let copy_len = bytes.len().min(target.len().saturating_sub(1));
-  target[.. copy_len].copy_from_slice(&bytes[.. copy_len]);
+//
+// Arena entries
+//
+
+// Everything below may run inside a signal handler: entries are written straight into the mapped
+// arena, and values are passed as borrowed pieces instead of being assembled in a buffer.
+
+fn append_string(record: &mut CrashRecord, tag: ArenaTag, value: Option<&[u8]>) {
+  // Absent and empty strings are both represented by a missing entry.
+  let Some(value) = value.filter(|value| !value.is_empty()) else {
+    return;
+  };
+
+  append_entry(
+    record,
+    tag,
+    &[truncate_utf8(value, schema::MAX_ARENA_STRING_LEN)],
+  );
+}
+
+// Appends frames innermost first until the arena is full, so the frames closest to the crash are
+// the ones that are kept.
+fn append_call_stack<'a>(
+  record: &mut CrashRecord,
+  frames: impl IntoIterator<Item = StackFrameRecord<'a>>,
+) {
+  for frame in frames
+    .into_iter()
+    .take(usize::from(schema::MAX_CALL_STACK_FRAMES))
+  {
+    let appended = append_stack_frame(
+      record,
+      frame.return_address,
+      frame.image_load_address,
+      frame.binary_name.map_or(&[], str::as_bytes),
+      frame.image_id.map_or(&[], str::as_bytes),
+    );
+    if !appended {
+      break;
+    }
+  }
+}
+
+fn append_decoded_call_stack(record: &mut CrashRecord, call_stack: &CallStack) {
+  for frame in &call_stack.frames {
+    let appended = append_stack_frame(
+      record,
+      frame.return_address,
+      frame.image_load_address,
+      frame.binary_name.as_deref().map_or(&[], CStr::to_bytes),
+      frame.image_id.as_deref().map_or(&[], CStr::to_bytes),
+    );
+    if !appended {
+      break;
+    }
+  }
+}
+
+fn append_stack_frame(
+  record: &mut CrashRecord,
+  return_address: u64,
+  image_load_address: u64,
+  binary_name: &[u8],
+  image_id: &[u8],
+) -> bool {
+  let binary_name = truncate_utf8(binary_name, schema::MAX_FRAME_STRING_LEN);
+  let image_id = truncate_utf8(image_id, schema::MAX_FRAME_STRING_LEN);
+  let frame = ArenaStackFrame {
+    return_address,
+    image_load_address,
+    binary_name_len: u16::try_from(binary_name.len()).unwrap_or(0),
+    image_id_len: u16::try_from(image_id.len()).unwrap_or(0),
+    reserved: [0; 4],
+  };
+  append_entry(
+    record,
+    ArenaTag::StackFrame,
+    &[pod_bytes(&frame), binary_name, image_id],
+  )
+}
+
+fn append_signal(record: &mut CrashRecord, signal: &ArenaSignal) {
+  append_entry(record, ArenaTag::Signal, &[pod_bytes(signal)]);
+}
+
+fn append_process_state(record: &mut CrashRecord, process_state: &ArenaProcessState) {
+  append_entry(record, ArenaTag::ProcessState, &[pod_bytes(process_state)]);
+}
+
+// Lets the host's crash callback write its data straight into the arena, right where the entry's
+// value goes, so nothing has to be copied out of a buffer on the signal handler's stack. Runs after
+// every core entry is appended, so the callback only gets the space they left over and can't crowd
+// out the call stack.
+fn append_host_data(record: &mut CrashRecord) {
+  let value_start = record.arena_len as usize + size_of::<ArenaEntryHeader>();
+  let value_end = value_start
+    .saturating_add(crash_callback::CRASH_CALLBACK_SCRATCH_CAPACITY)
+    .min(schema::ARENA_CAPACITY);
+  let Some(scratch) = record.arena.get_mut(value_start .. value_end) else {
+    return;
+  };
+
+  if let Some(len) = crash_callback::invoke(scratch) {
+    finish_entry(record, ArenaTag::HostData, len);
+  }
+}
+
+fn append_rust_panic(
+  record: &mut CrashRecord,
+  message: Option<&[u8]>,
+  location: Option<(&[u8], u32, u32)>,
+  thread_name: Option<&[u8]>,
+) {
+  append_string(record, ArenaTag::PanicMessage, message);
+  if let Some((file, line, column)) = location {
+    append_string(record, ArenaTag::PanicFile, Some(file));
+    append_entry(
+      record,
+      ArenaTag::PanicLocation,
+      &[pod_bytes(&ArenaPanicLocation { line, column })],
+    );
+  }
+  append_string(record, ArenaTag::PanicThreadName, thread_name);
+}
+
+fn append_annotations(record: &mut CrashRecord) {
+  // Annotations go before breadcrumbs: there are few of them and they describe the state of the
+  // app at the time of the crash, so they are worth more than the oldest breadcrumbs.
+  annotations::for_each_annotation(|annotation| {
+    append_annotation(
+      record,
+      &annotation.key[.. usize::from(annotation.key_len)],
+      &annotation.value[.. usize::from(annotation.value_len)],
+    )
+  });
+}
+
+fn append_annotation(record: &mut CrashRecord, key: &[u8], value: &[u8]) -> bool {
+  let key = truncate_utf8(key, schema::ANNOTATION_KEY_CAPACITY);
+  let value = truncate_utf8(value, schema::ANNOTATION_VALUE_CAPACITY);
+  let annotation = ArenaAnnotation {
+    key_len: u16::try_from(key.len()).unwrap_or(0),
+    value_len: u16::try_from(value.len()).unwrap_or(0),
+  };
+  append_entry(
+    record,
+    ArenaTag::Annotation,
+    &[pod_bytes(&annotation), key, value],
+  )
+}
+
+fn append_breadcrumbs(record: &mut CrashRecord) {
+  // Breadcrumbs are appended after the crash payload and newest first, so when the arena runs out
+  // of space it's the oldest breadcrumbs that are dropped.
+  breadcrumbs::for_each_breadcrumb_newest_first(|breadcrumb| {
+    let message_len = usize::from(breadcrumb.message_len).min(schema::BREADCRUMB_MESSAGE_CAPACITY);
+    append_breadcrumb(
+      record,
+      breadcrumb.timestamp_ms,
+      breadcrumb.level,
+      &breadcrumb.message[.. message_len],
+    )
+  });
+}
+
+fn append_breadcrumb(
+  record: &mut CrashRecord,
+  timestamp_ms: u64,
+  level: u8,
+  message: &[u8],
+) -> bool {
+  let breadcrumb = ArenaBreadcrumb {
+    timestamp_ms,
+    level,
+    reserved: [0; 7],
+  };
+  append_entry(
+    record,
+    ArenaTag::Breadcrumb,
+    &[pod_bytes(&breadcrumb), message],
+  )
+}
+
+fn append_entry(record: &mut CrashRecord, tag: ArenaTag, parts: &[&[u8]]) -> bool {
+  // Bump-allocates one entry at `arena_len`. Entries that don't fit in the remaining space are
+  // dropped whole so a reader never sees a partial value.
+  let value_len = parts.iter().map(|part| part.len()).sum::<usize>();
+  let value_start = record.arena_len as usize + size_of::<ArenaEntryHeader>();
+  if value_start
+    .checked_add(value_len)
+    .is_none_or(|end| end > schema::ARENA_CAPACITY)
+  {
+    return false;
+  }
+
+  let mut offset = value_start;
+  for part in parts {
+    record.arena[offset .. offset + part.len()].copy_from_slice(part);
+    offset += part.len();
+  }
+  finish_entry(record, tag, value_len)
+}
+
+// Completes the entry whose `value_len` value bytes were already written after the space reserved
+// for its header at `arena_len`: writes the header and checksum, then bumps `arena_len` past it.
+fn finish_entry(record: &mut CrashRecord, tag: ArenaTag, value_len: usize) -> bool {
+  let start = record.arena_len as usize;
+  let value_start = start + size_of::<ArenaEntryHeader>();
+  let end = value_start + value_len;
+  let Ok(len) = u32::try_from(value_len) else {
+    return false;
+  };
+
+  let header = ArenaEntryHeader {
+    tag: tag.into(),
+    reserved: 0,
+    len,
+    crc32: schema::compute_entry_checksum(tag.into(), len, &[&record.arena[value_start .. end]]),
+  };
+  record.arena[start .. value_start].copy_from_slice(pod_bytes(&header));
+
+  let aligned_end = schema::align_arena_offset(end).min(schema::ARENA_CAPACITY);
+  record.arena[end .. aligned_end].fill(0);
+  record.arena_len = u32::try_from(aligned_end).unwrap_or(0);
+  true
+}
+
+fn truncate_utf8(value: &[u8], max_len: usize) -> &[u8] {
+  // Cut on a character boundary when the value is valid UTF-8 so truncated strings still decode.
+  if value.len() <= max_len {
+    return value;
+  }
+
+  let mut len = max_len;
+  while len > 0 && (value[len] & 0b1100_0000) == 0b1000_0000 {
+    len -= 1;
+  }
+  &value[.. len]
+}
+
+const fn pod_bytes<T: Copy>(value: &T) -> &[u8] {
+  // Only used with the `repr(C)` arena structs, which have no padding.
+  unsafe { std::slice::from_raw_parts((&raw const *value).cast::<u8>(), size_of::<T>()) }
 }
@@ -5,19 +5,47 @@
 // LICENSE file or at:
 // https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
 
-#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic, clippy::unwrap_used)]
 
-use super::{CRASH_RECORD, NSExceptionFrameRecord, prime_shared_record, record_nsexception};
+use super::{
+  CRASH_RECORD,
+  StackFrameRecord,
+  prime_shared_record,
+  record_cxx_exception,
+  record_nsexception,
+  record_rust_panic,
+  record_signal,
+  rewrite_previous_state,
+};
+use crate::annotations::{prime_annotation_table, set_annotation};
+use crate::breadcrumbs::{BreadcrumbLevel, prime_breadcrumb_ring, record_breadcrumb};
+use crate::crash_callback::set_crash_callback;
+use crate::previous::{
+  Annotation,
+  AppState,
+  Breadcrumb,
+  CallStack,
+  NSExceptionCrashInfo,
+  PreviousCrashDetails,
+  PreviousCrashState,
+  PreviousTermination,
+  ProcessState,
+  RustPanicCrashInfo,
+  SignalCrashInfo,
+  StackFrame,
+};
 use crate::schema::{self, CrashKind, RecordState};
-use crate::test_support::test_crash_record_guard;
+use crate::test_support::{decode_record, test_crash_record_guard};
+use std::ffi::{CString, c_void};
+use std::ptr::{copy_nonoverlapping, null_mut};
 use std::sync::atomic::Ordering;
 
-fn frame_records(return_addresses: &[u64]) -> Vec<NSExceptionFrameRecord<'static>> {
+fn frame_records(return_addresses: &[u64]) -> Vec<StackFrameRecord<'static>> {
   return_addresses
     .iter()
-    .map(|return_address| NSExceptionFrameRecord {
+    .map(|return_address| StackFrameRecord {
       return_address: *return_address,
-      ..NSExceptionFrameRecord::default()
+      ..StackFrameRecord::default()
     })
     .collect()
 }
@@ -35,6 +63,25 @@ fn prime_shared_record_initializes_empty_record() {
   assert_eq!(record.header.crash_kind, CrashKind::None);
 }
 
+fn current_record() -> &'static schema::CrashRecord {
+  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
+  unsafe { &*record_ptr }
+}
+
+fn decoded_nsexception() -> NSExceptionCrashInfo {
+  match decode_record(current_record()).details {
+    PreviousCrashDetails::NSException(exception) => *exception,
+    other => panic!("expected an NSException record, got {other:?}"),
+  }
+}
+
+fn decoded_rust_panic() -> RustPanicCrashInfo {
+  match decode_record(current_record()).details {
+    PreviousCrashDetails::RustPanic(panic) => *panic,
+    other => panic!("expected a Rust panic record, got {other:?}"),
+  }
+}
+
 #[test]
 fn record_nsexception_commits_after_payload() {
   let _guard = test_crash_record_guard();
@@ -49,14 +96,14 @@ fn record_nsexception_commits_after_payload() {
     &frame_records(&[1, 2, 3]),
   );
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
+  let record = current_record();
   assert_eq!(record.header.crash_kind, CrashKind::NSException);
   assert_eq!(record.header.record_state, RecordState::Committed);
-  assert_eq!(&record.nsexception.name[.. 12], b"NSException\0");
-  assert_eq!(&record.nsexception.reason[.. 11], b"bad reason\0");
-  assert_eq!(record.nsexception.call_stack.frame_count, 3);
   assert_eq!(record.header.crc32, schema::compute_record_checksum(record));
+  let exception = decoded_nsexception();
+  assert_eq!(exception.name.as_deref(), Some(c"NSException"));
+  assert_eq!(exception.reason.as_deref(), Some(c"bad reason"));
+  assert_eq!(exception.call_stack.return_addresses, vec![1, 2, 3]);
 }
 
 #[test]
@@ -70,104 +117,112 @@ fn record_nsexception_clears_reason_when_absent() {
   record_nsexception("NSException", Some("bad reason"), &[]);
   record_nsexception("NSException", None, &[]);
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
-  assert_eq!(record.nsexception.reason[0], 0);
+  assert_eq!(decoded_nsexception().reason, None);
 }
 
 #[test]
-fn record_nsexception_truncates_strings_and_null_terminates() {
+fn record_nsexception_keeps_long_reason() {
   let _guard = test_crash_record_guard();
   let mut record = schema::CrashRecord::default();
   unsafe {
     prime_shared_record(&raw mut record);
   }
 
-  let long_name = "a".repeat(schema::NS_EXCEPTION_NAME_CAPACITY * 2);
-  let long_reason = "b".repeat(schema::NS_EXCEPTION_REASON_CAPACITY * 2);
-  record_nsexception(long_name.as_str(), Some(long_reason.as_str()), &[]);
+  // Longer than the fixed reason field used by older layouts.
+  let long_reason = "b".repeat(2048);
+  record_nsexception("NSException", Some(long_reason.as_str()), &[]);
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
-  assert_eq!(
-    record.nsexception.name[schema::NS_EXCEPTION_NAME_CAPACITY - 1],
-    0
-  );
-  assert_eq!(
-    record.nsexception.reason[schema::NS_EXCEPTION_REASON_CAPACITY - 1],
-    0
-  );
-  assert_eq!(
-    &record.nsexception.name[.. schema::NS_EXCEPTION_NAME_CAPACITY - 1],
-    vec![b'a'; schema::NS_EXCEPTION_NAME_CAPACITY - 1].as_slice()
-  );
+  let exception = decoded_nsexception();
+  assert_eq!(exception.reason.unwrap().as_bytes(), long_reason.as_bytes());
+}
+
+#[test]
+fn record_nsexception_truncates_strings_on_char_boundary() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  let long_name = "a".repeat(schema::MAX_ARENA_STRING_LEN - 1) + "é";
+  record_nsexception(long_name.as_str(), None, &[]);
+
+  let name = decoded_nsexception().name.unwrap();
   assert_eq!(
-    &record.nsexception.reason[.. schema::NS_EXCEPTION_REASON_CAPACITY - 1],
-    vec![b'b'; schema::NS_EXCEPTION_REASON_CAPACITY - 1].as_slice()
+    name.as_bytes(),
+    vec![b'a'; schema::MAX_ARENA_STRING_LEN - 1].as_slice()
   );
 }
 
 #[test]
-fn record_nsexception_truncates_return_addresses_to_capacity() {
+fn record_nsexception_truncates_frames_to_capacity() {
   let _guard = test_crash_record_guard();
   let mut record = schema::CrashRecord::default();
   unsafe {
     prime_shared_record(&raw mut record);
   }
 
-  let return_addresses = (0 .. schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES + 10)
+  let return_addresses = (0 .. schema::MAX_CALL_STACK_FRAMES + 10)
     .map(u64::from)
     .collect::<Vec<_>>();
   let frames = frame_records(&return_addresses);
   record_nsexception("NSException", None, &frames);
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
   assert_eq!(
-    record.nsexception.call_stack.frame_count,
-    schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES
+    decoded_nsexception().call_stack.return_addresses,
+    &return_addresses[.. usize::from(schema::MAX_CALL_STACK_FRAMES)]
   );
-  for (raw_frame, expected_address) in record
-    .nsexception
-    .call_stack
-    .frames
-    .iter()
-    .zip(&return_addresses[.. schema::MAX_NS_EXCEPTION_CALL_STACK_FRAMES as usize])
-  {
-    assert_eq!(raw_frame.return_address, *expected_address);
+}
+
+#[test]
+fn record_nsexception_drops_frames_that_do_not_fit_in_arena() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
   }
+
+  let binary_name = "b".repeat(schema::MAX_FRAME_STRING_LEN);
+  let frames = (0 .. schema::MAX_CALL_STACK_FRAMES)
+    .map(|index| StackFrameRecord {
+      return_address: u64::from(index),
+      binary_name: Some(binary_name.as_str()),
+      ..StackFrameRecord::default()
+    })
+    .collect::<Vec<_>>();
+  record_nsexception("NSException", Some("bad reason"), &frames);
+
+  let record = current_record();
+  assert!(record.arena_len as usize <= schema::ARENA_CAPACITY);
+  let exception = decoded_nsexception();
+  assert_eq!(exception.reason.as_deref(), Some(c"bad reason"));
+  let frame_count = exception.call_stack.frames.len();
+  assert!(frame_count > 0 && frame_count < usize::from(schema::MAX_CALL_STACK_FRAMES));
+  assert!(
+    exception
+      .call_stack
+      .return_addresses
+      .iter()
+      .zip(0 ..)
+      .all(|(address, expected)| *address == expected)
+  );
 }
 
 #[test]
-fn record_nsexception_clears_unused_frames() {
+fn record_nsexception_replaces_previous_frames() {
   let _guard = test_crash_record_guard();
   let mut record = schema::CrashRecord::default();
   unsafe {
     prime_shared_record(&raw mut record);
   }
-  record
-    .nsexception
-    .call_stack
-    .frames
-    .fill(schema::RawNSExceptionStackFrame {
-      return_address: u64::MAX,
-      ..schema::RawNSExceptionStackFrame::default()
-    });
 
+  record_nsexception("NSException", None, &frame_records(&[1, 2, 3, 4]));
   record_nsexception("NSException", None, &frame_records(&[0x1234, 0x5678]));
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
-  assert_eq!(record.nsexception.call_stack.frame_count, 2);
-  assert_eq!(
-    record.nsexception.call_stack.frames[0].return_address,
-    0x1234
-  );
   assert_eq!(
-    record.nsexception.call_stack.frames[1].return_address,
-    0x5678
+    decoded_nsexception().call_stack.return_addresses,
+    vec![0x1234, 0x5678]
   );
-  assert_eq!(record.nsexception.call_stack.frames[2].return_address, 0);
 }
 
 #[test]
@@ -181,7 +236,7 @@ fn record_nsexception_persists_binary_name_and_image_id() {
   record_nsexception(
     "NSException",
     None,
-    &[NSExceptionFrameRecord {
+    &[StackFrameRecord {
       return_address: 0x1234,
       image_load_address: 0x1000,
       binary_name: Some("MyApp"),
@@ -189,14 +244,386 @@ fn record_nsexception_persists_binary_name_and_image_id() {
     }],
   );
 
-  let record_ptr = CRASH_RECORD.load(Ordering::Acquire);
-  let record = unsafe { &*record_ptr };
-  let frame = &record.nsexception.call_stack.frames[0];
+  let exception = decoded_nsexception();
+  let frame = &exception.call_stack.frames[0];
   assert_eq!(frame.return_address, 0x1234);
   assert_eq!(frame.image_load_address, 0x1000);
-  assert_eq!(&frame.binary_name[.. 6], b"MyApp\0");
+  assert_eq!(frame.binary_name.as_deref(), Some(c"MyApp"));
+  assert_eq!(
+    frame.image_id.as_deref(),
+    Some(c"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0")
+  );
+}
+
+#[test]
+fn record_signal_commits_after_payload() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_signal(11, 1, 0xdead_beef, 42, []);
+
+  let record = current_record();
+  assert_eq!(record.header.crash_kind, CrashKind::Signal);
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  assert_eq!(record.header.crc32, schema::compute_record_checksum(record));
+  assert_eq!(
+    decode_record(record).details,
+    PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: 11,
+      code: 1,
+      fault_address: 0xdead_beef,
+      thread_id: 42,
+      ..SignalCrashInfo::default()
+    })
+  );
+}
+
+unsafe extern "C" fn write_request_id(scratch: *mut u8, capacity: usize, _: *mut c_void) -> usize {
+  let request_id = b"request-42";
+  assert!(capacity >= request_id.len());
+  unsafe {
+    copy_nonoverlapping(request_id.as_ptr(), scratch, request_id.len());
+  }
+  request_id.len()
+}
+
+#[test]
+fn crash_callback_data_is_committed_with_the_record() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+    set_crash_callback(Some(write_request_id), null_mut());
+  }
+
+  record_rust_panic(Some("boom"), None, None);
+  assert_eq!(
+    decode_record(current_record()).host_data.as_deref(),
+    Some(b"request-42".as_slice())
+  );
+
+  // The data is checksummed with its entry, so a damaged copy is dropped rather than reported.
+  let mut record = *current_record();
+  let offset = record
+    .arena
+    .windows(10)
+    .position(|window| window == b"request-42")
+    .unwrap();
+  record.arena[offset] ^= 0xff;
+  let damaged = decode_record(&record);
+  assert!(damaged.did_crash);
+  assert_eq!(damaged.host_data, None);
+}
+
+#[test]
+fn crash_callback_data_does_not_crowd_out_the_call_stack() {
+  let _guard = test_crash_record_guard();
+  let binary_name = "b".repeat(schema::MAX_FRAME_STRING_LEN);
+  let recorded_frames = || {
+    let frames =
+      (0 .. u64::from(schema::MAX_CALL_STACK_FRAMES)).map(|return_address| StackFrameRecord {
+        return_address,
+        binary_name: Some(binary_name.as_str()),
+        ..StackFrameRecord::default()
+      });
+    record_signal(11, 1, 0, 42, frames);
+    let previous = decode_record(current_record());
+    let PreviousCrashDetails::Signal(signal) = previous.details else {
+      panic!("expected a signal record");
+    };
+    signal.call_stack.frames.len()
+  };
+
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+  let without_callback = recorded_frames();
+
+  unsafe {
+    set_crash_callback(Some(write_request_id), null_mut());
+  }
+  let with_callback = recorded_frames();
+
+  // The callback only gets the space left once the stack is written, so the stack is as long as it
+  // would be without it.
+  assert!(without_callback < usize::from(schema::MAX_CALL_STACK_FRAMES));
+  assert_eq!(with_callback, without_callback);
+}
+
+#[test]
+fn record_cxx_exception_commits_after_payload() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_cxx_exception(
+    Some("std::runtime_error"),
+    Some("the widget is broken"),
+    frame_records(&[0x1234, 0x5678]),
+  );
+
+  let record = current_record();
+  assert_eq!(record.header.crash_kind, CrashKind::CxxException);
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  assert_eq!(record.header.crc32, schema::compute_record_checksum(record));
+  let PreviousCrashDetails::CxxException(exception) = decode_record(record).details else {
+    panic!("expected a C++ exception record");
+  };
+  assert_eq!(exception.type_name.as_deref(), Some(c"std::runtime_error"));
+  assert_eq!(exception.what.as_deref(), Some(c"the widget is broken"));
+  assert_eq!(exception.call_stack.return_addresses, [0x1234, 0x5678]);
+}
+
+#[test]
+fn record_signal_persists_frames() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_signal(
+    11,
+    1,
+    0,
+    42,
+    [
+      StackFrameRecord {
+        return_address: 0x1234,
+        image_load_address: 0x1000,
+        binary_name: Some("/system/lib64/libapp.so"),
+        image_id: Some("0123456789abcdef"),
+      },
+      StackFrameRecord {
+        return_address: 0x5678,
+        ..StackFrameRecord::default()
+      },
+    ],
+  );
+
+  let record = current_record();
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  let PreviousCrashDetails::Signal(signal) = decode_record(record).details else {
+    panic!("expected a signal record");
+  };
+  assert_eq!(signal.thread_id, 42);
+  assert_eq!(signal.call_stack.return_addresses, vec![0x1234, 0x5678]);
+  let frame = &signal.call_stack.frames[0];
+  assert_eq!(frame.image_load_address, 0x1000);
+  assert_eq!(
+    frame.binary_name.as_deref(),
+    Some(c"/system/lib64/libapp.so")
+  );
+  assert_eq!(frame.image_id.as_deref(), Some(c"0123456789abcdef"));
+  assert_eq!(signal.call_stack.frames[1].binary_name, None);
+}
+
+#[test]
+fn record_rust_panic_commits_after_payload() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_rust_panic(Some("boom"), Some(("src/lib.rs", 12, 5)), Some("main"));
+
+  let record = current_record();
+  assert_eq!(record.header.crash_kind, CrashKind::RustPanic);
+  assert_eq!(record.header.record_state, RecordState::Committed);
+  assert_eq!(record.header.crc32, schema::compute_record_checksum(record));
+  assert_eq!(
+    decoded_rust_panic(),
+    RustPanicCrashInfo {
+      message: Some(c"boom".to_owned()),
+      file: Some(c"src/lib.rs".to_owned()),
+      line: 12,
+      column: 5,
+      thread_name: Some(c"main".to_owned()),
+    }
+  );
+}
+
+#[test]
+fn committed_crash_includes_process_state() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_rust_panic(Some("boom"), None, None);
+
+  let previous = decode_record(current_record());
+  assert!(matches!(
+    previous.details,
+    PreviousCrashDetails::RustPanic(_)
+  ));
+  let process_state = previous.process_state.unwrap();
+  #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+  assert!(process_state.thread_count >= 1);
+}
+
+#[test]
+fn record_rust_panic_clears_absent_fields() {
+  let _guard = test_crash_record_guard();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_shared_record(&raw mut record);
+  }
+
+  record_rust_panic(Some("boom"), Some(("src/lib.rs", 12, 5)), Some("main"));
+  record_rust_panic(None, None, None);
+
+  assert_eq!(decoded_rust_panic(), RustPanicCrashInfo::default());
+}
+
+#[test]
+fn committed_crash_includes_breadcrumbs_oldest_first() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<schema::BreadcrumbRing>::default();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+    prime_shared_record(&raw mut record);
+  }
+
+  record_breadcrumb(BreadcrumbLevel::Info, "opened settings");
+  record_breadcrumb(BreadcrumbLevel::Warning, "low memory");
+  record_signal(11, 1, 0, 0, []);
+
+  let breadcrumbs = decode_record(current_record())
+    .breadcrumbs
+    .into_iter()
+    .map(|breadcrumb| (breadcrumb.level, breadcrumb.message))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    breadcrumbs,
+    vec![
+      (BreadcrumbLevel::Info, c"opened settings".to_owned()),
+      (BreadcrumbLevel::Warning, c"low memory".to_owned()),
+    ]
+  );
+}
+
+#[test]
+fn committed_crash_includes_annotations() {
+  let _guard = test_crash_record_guard();
+  let mut table = Box::<schema::AnnotationTable>::default();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_annotation_table(&raw mut *table);
+    prime_shared_record(&raw mut record);
+  }
+
+  assert!(set_annotation("screen", "checkout"));
+  assert!(set_annotation("user_tier", ""));
+  record_rust_panic(Some("boom"), None, None);
+
   assert_eq!(
-    &frame.image_id[.. schema::NS_EXCEPTION_IMAGE_ID_CAPACITY],
-    b"BD9C11B4-BF87-3F60-AEA0-0141BD7F8AC0\0"
+    decode_record(current_record()).annotations,
+    vec![
+      Annotation {
+        key: c"screen".to_owned(),
+        value: c"checkout".to_owned(),
+      },
+      Annotation {
+        key: c"user_tier".to_owned(),
+        value: CString::default(),
+      },
+    ]
   );
 }
+
+#[test]
+fn committed_crash_drops_oldest_breadcrumbs_when_arena_is_full() {
+  let _guard = test_crash_record_guard();
+  let mut ring = Box::<schema::BreadcrumbRing>::default();
+  let mut record = schema::CrashRecord::default();
+  unsafe {
+    prime_breadcrumb_ring(&raw mut *ring);
+    prime_shared_record(&raw mut record);
+  }
+
+  for index in 0 .. schema::BREADCRUMB_CAPACITY {
+    record_breadcrumb(BreadcrumbLevel::Info, &format!("crumb {index}"));
+  }
+  let reason = "r".repeat(schema::MAX_ARENA_STRING_LEN);
+  let binary_name = "b".repeat(schema::MAX_FRAME_STRING_LEN);
+  let frames = (0 .. 40)
+    .map(|index| StackFrameRecord {
+      return_address: index,
+      binary_name: Some(binary_name.as_str()),
+      ..StackFrameRecord::default()
+    })
+    .collect::<Vec<_>>();
+  record_nsexception("NSException", Some(reason.as_str()), &frames);
+
+  let previous = decode_record(current_record());
+  let PreviousCrashDetails::NSException(exception) = &previous.details else {
+    panic!("expected an NSException record");
+  };
+  assert_eq!(exception.call_stack.frames.len(), 40);
+  assert!(!previous.breadcrumbs.is_empty());
+  assert!(previous.breadcrumbs.len() < schema::BREADCRUMB_CAPACITY as usize);
+  assert_eq!(
+    previous.breadcrumbs.last().unwrap().message.as_bytes(),
+    format!("crumb {}", schema::BREADCRUMB_CAPACITY - 1).as_bytes()
+  );
+}
+
+#[test]
+fn rewrite_previous_state_round_trips_details() {
+  let mut record = schema::CrashRecord::default();
+  let state = PreviousCrashState {
+    did_crash: true,
+    timestamp_secs: 123,
+    pid: 456,
+    kind: CrashKind::Signal,
+    details: PreviousCrashDetails::Signal(SignalCrashInfo {
+      signal: 6,
+      code: -6,
+      fault_address: 0,
+      thread_id: 789,
+      call_stack: CallStack {
+        return_addresses: vec![0x1234],
+        frames: vec![StackFrame {
+          return_address: 0x1234,
+          image_load_address: 0x1000,
+          binary_name: Some(c"/system/lib64/libapp.so".to_owned()),
+          image_id: Some(c"0123456789abcdef".to_owned()),
+        }],
+      },
+    }),
+    annotations: vec![Annotation {
+      key: c"screen".to_owned(),
+      value: c"checkout".to_owned(),
+    }],
+    breadcrumbs: vec![Breadcrumb {
+      timestamp_ms: 1_000,
+      level: BreadcrumbLevel::Error,
+      message: c"about to crash".to_owned(),
+    }],
+    process_state: Some(ProcessState {
+      uptime_ms: 2_500,
+      resident_bytes: 64 << 20,
+      thread_count: 12,
+      open_fd_count: 30,
+      app_state: AppState::Background,
+    }),
+    host_data: Some(b"request-42".to_vec()),
+    termination: PreviousTermination::default(),
+    consecutive_launch_crashes: 0,
+    history: Vec::new(),
+  };
+
+  rewrite_previous_state(&mut record, &state);
+
+  assert_eq!(decode_record(&record), state);
+}
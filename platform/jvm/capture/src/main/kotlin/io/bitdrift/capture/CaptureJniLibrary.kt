@@ -164,6 +164,87 @@ internal object CaptureJniLibrary : IBridge, IStreamingReportProcessor {
         key: String,
     )
 
+    /**
+     * Configures the native crash reporter, reading back what it recorded about the previous launch.
+     * Crash annotations, the crash loop state and the foreground state only take effect once it is
+     * configured. Configuring more than once has no effect.
+     *
+     * @param statePath the path of the file the crash reporter persists its state to.
+     * @param processName null for the app's main process, or a name of its own for any other
+     * process sharing the state path. Only ASCII letters, digits, '.', '-' and '_' are allowed.
+     * @return whether the crash reporter is configured.
+     */
+    external fun configureCrashReporter(
+        statePath: String,
+        processName: String?,
+    ): Boolean
+
+    /**
+     * Installs the native crash monitors. The crash reporter must be configured first.
+     *
+     * @return whether the crash monitors were installed.
+     */
+    external fun startCrashReporter(): Boolean
+
+    /**
+     * Marks the current run as cleanly shut down, so the next launch reports a clean exit rather
+     * than an unclean termination unless a crash is recorded first. The crash monitors stay
+     * installed.
+     */
+    external fun markCrashCleanShutdown()
+
+    /**
+     * Sets a crash annotation, replacing any value previously set for the same key. Annotations are
+     * persisted directly to the crash state file and reported alongside the crash on the next launch.
+     *
+     * @param key the name of the annotation, between 1 and 32 bytes long.
+     * @param value the value of the annotation. Values longer than 128 bytes are truncated.
+     * @return whether the annotation was set. This fails when the key is invalid or all annotation
+     * slots are in use.
+     */
+    external fun setCrashAnnotation(
+        key: String,
+        value: String,
+    ): Boolean
+
+    /**
+     * Removes a crash annotation.
+     *
+     * @param key the name of the annotation to remove.
+     * @return whether an annotation was set for the given key.
+     */
+    external fun removeCrashAnnotation(key: String): Boolean
+
+    /**
+     * Returns how many consecutive launches, up to and including the previous one, crashed shortly
+     * after the crash reporter was started. A non-zero value means the app may be crash looping,
+     * so risky initialization can be skipped until the launch is deemed stable.
+     */
+    external fun consecutiveLaunchCrashes(): Int
+
+    /**
+     * Marks the current launch as stable, resetting the consecutive launch crash count. A crash later
+     * in this launch no longer counts towards it.
+     */
+    external fun resetConsecutiveLaunchCrashes()
+
+    /**
+     * Sets how soon after the crash reporter is started a crash has to happen to count towards the
+     * consecutive launch crashes. The window is persisted until it's changed.
+     *
+     * @param windowSeconds the length of the window in seconds.
+     * @return whether the window was set. This fails when the crash reporter is not configured.
+     */
+    external fun setCrashLoopWindow(windowSeconds: Int): Boolean
+
+    /**
+     * Tells the crash reporter whether the app is in the foreground, so that a crash can be
+     * attributed to the state the app was in.
+     *
+     * @param foreground whether the app is in the foreground.
+     */
+    external fun setCrashForeground(foreground: Boolean)
+
     /**
      * Sets a feature flag exposure with a variant.
      *
@@ -336,6 +417,41 @@ internal object CaptureJniLibrary : IBridge, IStreamingReportProcessor {
         durationS: Double,
     )
 
+    /**
+     * Begins measuring an app launch, finishing and writing the log of a launch that is still
+     * being measured. Warm and hot launches can be measured on every transition to the
+     * foreground, but only the first cold launch is measured.
+     *
+     * @param loggerId the ID of the logger to write to.
+     * @param launchType the type of the launch: 0 for cold, 1 for warm and 2 for hot.
+     */
+    external fun beginAppLaunch(
+        loggerId: Long,
+        launchType: Int,
+    )
+
+    /**
+     * Records how long it took the current app launch to reach a phase. Reaching the fully drawn
+     * phase finishes the launch and writes its log.
+     *
+     * @param loggerId the ID of the logger to write to.
+     * @param phase the phase reached: 0 for first frame, 1 for TTI and 2 for fully drawn.
+     * @param durationS the time between the start of the launch and the phase being reached.
+     *        Calls with a negative duration are ignored.
+     */
+    external fun recordAppLaunchPhase(
+        loggerId: Long,
+        phase: Int,
+        durationS: Double,
+    )
+
+    /**
+     * Finishes the current app launch and writes its log with the phases it reached.
+     *
+     * @param loggerId the ID of the logger to write to.
+     */
+    external fun finishAppLaunch(loggerId: Long)
+
     /**
      * Writes a screen view log.
      *
@@ -347,6 +463,77 @@ internal object CaptureJniLibrary : IBridge, IStreamingReportProcessor {
         screenName: String,
     )
 
+    /**
+     * Writes a screen view log for a modal screen, such as a sheet, presented over the current
+     * screen. The current screen becomes current again once the modal screen is dismissed.
+     *
+     * @param loggerId the ID of the logger to write to.
+     * @param screenName the name of the modal screen.
+     */
+    external fun writeModalScreenViewLog(
+        loggerId: Long,
+        screenName: String,
+    )
+
+    /**
+     * Dismisses the current modal screen, writing a screen view log for the screen it was
+     * presented over. Has no effect if no modal screen is presented.
+     *
+     * @param loggerId the ID of the logger to write to.
+     */
+    external fun dismissModalScreen(loggerId: Long)
+
+    /**
+     * Tells the logger whether the app is in the foreground. Moving to the background closes out
+     * the time spent on the current screen.
+     *
+     * @param loggerId the ID of the logger to notify.
+     * @param foreground whether the app is in the foreground.
+     */
+    external fun setAppForeground(
+        loggerId: Long,
+        foreground: Boolean,
+    )
+
+    /**
+     * Starts a span and writes its start log. Returns the handle to end the span with, or 0 if
+     * the span couldn't be started.
+     *
+     * @param loggerId the ID of the logger to write to.
+     * @param name the name of the span.
+     * @param level the log level of the span's logs.
+     * @param fieldKeys the keys of the fields to attach to the span's start and end logs.
+     * @param fieldValues the values of the fields to attach to the span's start and end logs.
+     * @param parentSpanHandle the handle of the span to nest this span under, or 0 for a root span.
+     */
+    external fun startSpan(
+        loggerId: Long,
+        name: String,
+        level: Int,
+        fieldKeys: Array<String>,
+        fieldValues: Array<String>,
+        parentSpanHandle: Long,
+    ): Long
+
+    /**
+     * Ends a span started with [startSpan] and writes its end log. Ending a span that has already
+     * ended has no effect. Spans that are still open when the logger shuts down end as abandoned.
+     *
+     * @param loggerId the ID of the logger to write to.
+     * @param spanHandle the handle returned by [startSpan].
+     * @param result the result of the span: 0 for success, 1 for failure, 2 for canceled and 3
+     *        for unknown.
+     * @param fieldKeys the keys of the fields to attach to the span's end log.
+     * @param fieldValues the values of the fields to attach to the span's end log.
+     */
+    external fun endSpan(
+        loggerId: Long,
+        spanHandle: Long,
+        result: Int,
+        fieldKeys: Array<String>,
+        fieldValues: Array<String>,
+    )
+
     /**
      * Flushes logger's state to disk.
      *
@@ -48,6 +48,11 @@ internal interface IInternalLogger : ILogger {
 
     fun flush(blocking: Boolean)
 
+    /**
+     * Tells the logger and the crash reporter whether the app is in the foreground.
+     */
+    fun setAppForeground(foreground: Boolean)
+
     fun logResourceUtilization(
         arrayFields: ArrayFields,
         duration: Duration,
@@ -10,6 +10,8 @@ package io.bitdrift.capture
 import android.app.ActivityManager
 import android.app.Application
 import android.content.Context
+import android.os.Build
+import android.os.Process
 import android.util.Log
 import androidx.annotation.VisibleForTesting
 import androidx.lifecycle.ProcessLifecycleOwner
@@ -311,6 +313,10 @@ internal class LoggerImpl(
         // Capture logger.
         appExitLogger.installAppExitLogger()
 
+        if (configuration.enableFatalIssueReporting) {
+            startNativeCrashReporter(context, activityManager)
+        }
+
         CaptureJniLibrary.startLogger(this.loggerId)
 
         startDebugOperationsAsNeeded(context)
@@ -624,6 +630,11 @@ internal class LoggerImpl(
         CaptureJniLibrary.flush(this.loggerId, blocking)
     }
 
+    override fun setAppForeground(foreground: Boolean) {
+        CaptureJniLibrary.setAppForeground(this.loggerId, foreground)
+        CaptureJniLibrary.setCrashForeground(foreground)
+    }
+
     internal fun shouldLogAppUpdate(
         appVersion: String,
         appVersionCode: Long,
@@ -745,6 +756,45 @@ internal class LoggerImpl(
         )
     }
 
+    private fun startNativeCrashReporter(
+        context: Context,
+        activityManager: ActivityManager,
+    ) {
+        val statePath = File(sdkDirectory, NATIVE_CRASH_STATE_FILE_NAME).absolutePath
+        val processName = crashReporterProcessName(context, activityManager)
+        if (!CaptureJniLibrary.configureCrashReporter(statePath, processName)) {
+            errorHandler.handleError("failed to configure the native crash reporter")
+            return
+        }
+        if (!CaptureJniLibrary.startCrashReporter()) {
+            errorHandler.handleError("failed to start the native crash reporter")
+            return
+        }
+        // Android apps are usually killed without any callback, which the next launch reports as
+        // an unclean termination. An orderly VM shutdown, e.g. via `System.exit`, is a clean exit.
+        Runtime.getRuntime().addShutdownHook(Thread { CaptureJniLibrary.markCrashCleanShutdown() })
+    }
+
+    // Every process of the app keeps its own crash state file. The main process uses the state path
+    // itself, while other processes, such as `:remote` services, are named after their process name.
+    private fun crashReporterProcessName(
+        context: Context,
+        activityManager: ActivityManager,
+    ): String? {
+        val processName =
+            if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.P) {
+                Application.getProcessName()
+            } else {
+                activityManager.runningAppProcesses
+                    ?.firstOrNull { it.pid == Process.myPid() }
+                    ?.processName
+            }
+        if (processName == null || processName == context.packageName) {
+            return null
+        }
+        return processName.replace(UNSUPPORTED_PROCESS_NAME_CHARACTERS, "_")
+    }
+
     private fun startDebugOperationsAsNeeded(context: Context) {
         if (!BuildTypeChecker.isDebuggable(context)) {
             return
@@ -799,6 +849,11 @@ internal class LoggerImpl(
     @OptIn(ExperimentalBitdriftApi::class)
     private fun getIssueCallbackConfiguration(configuration: Configuration): IssueCallbackConfiguration? =
         if (configuration.enableFatalIssueReporting) configuration.issueCallbackConfiguration else null
+
+    private companion object {
+        private const val NATIVE_CRASH_STATE_FILE_NAME = "native_crash_state"
+        private val UNSUPPORTED_PROCESS_NAME_CHARACTERS = Regex("[^A-Za-z0-9._-]")
+    }
 }
 
 internal sealed class LogAttributesOverrides {
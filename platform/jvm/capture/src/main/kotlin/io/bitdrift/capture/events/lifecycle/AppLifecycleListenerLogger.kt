@@ -63,6 +63,12 @@ internal class AppLifecycleListenerLogger(
         event: Lifecycle.Event,
     ) {
         executor.execute {
+            when (event) {
+                Lifecycle.Event.ON_START -> logger.setAppForeground(true)
+                Lifecycle.Event.ON_STOP -> logger.setAppForeground(false)
+                else -> {}
+            }
+
             if (!runtime.isEnabled(RuntimeFeature.APP_LIFECYCLE_EVENTS)) {
                 return@execute
             }
@@ -63,6 +63,20 @@ class AppLifecycleListenerLoggerTest {
         verify(logger).flush(eq(false))
     }
 
+    @Test
+    fun testForegroundStateFollowsStartAndStop() {
+        // ARRANGE
+        whenever(runtime.isEnabled(RuntimeFeature.APP_LIFECYCLE_EVENTS)).thenReturn(false)
+
+        // ACT
+        appLifecycleLogger.onStateChanged(processLifecycleOwner, Lifecycle.Event.ON_START)
+        appLifecycleLogger.onStateChanged(processLifecycleOwner, Lifecycle.Event.ON_STOP)
+
+        // ASSERT
+        verify(logger).setAppForeground(true)
+        verify(logger).setAppForeground(false)
+    }
+
     @Test
     fun testAppStartInfoFieldsAreSkippedIfNotCreate() {
         // ARRANGE
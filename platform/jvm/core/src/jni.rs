@@ -61,7 +61,9 @@ use jni::sys::{
   jvalue,
 };
 use jni::{JNIEnv, JavaVM};
+use platform_shared::launch::{LaunchPhase, LaunchType};
 use platform_shared::metadata::{AndroidStaticFields, Mobile};
+use platform_shared::span::{SpanHandle, SpanResult};
 use platform_shared::{LoggerHolder, LoggerId, date_to_unix_milliseconds};
 use protobuf::Enum as _;
 use std::borrow::{Borrow, Cow};
@@ -1047,6 +1049,170 @@ pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_removeLogField
   );
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_configureCrashReporter(
+  env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  state_path: JString<'_>,
+  process_name: JString<'_>,
+) -> jboolean {
+  with_handle_unexpected_or(
+    || {
+      let state_path = unsafe { env.get_string_unchecked(&state_path) }?
+        .to_string_lossy()
+        .to_string();
+      let process_name = if process_name.is_null() {
+        None
+      } else {
+        Some(
+          unsafe { env.get_string_unchecked(&process_name) }?
+            .to_string_lossy()
+            .to_string(),
+        )
+      };
+
+      Ok(bd_crash_reporter::configure(
+        &PathBuf::from(state_path),
+        process_name.as_deref(),
+      ))
+    },
+    false,
+    "jni configure crash reporter",
+  )
+  .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_startCrashReporter(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+) -> jboolean {
+  with_handle_unexpected_or(
+    || Ok(bd_crash_reporter::start()),
+    false,
+    "jni start crash reporter",
+  )
+  .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_markCrashCleanShutdown(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      bd_crash_reporter::mark_clean_shutdown();
+      Ok(())
+    },
+    "jni mark crash clean shutdown",
+  );
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_setCrashAnnotation(
+  env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  key: JString<'_>,
+  value: JString<'_>,
+) -> jboolean {
+  with_handle_unexpected_or(
+    || {
+      let key = unsafe { env.get_string_unchecked(&key) }?
+        .to_string_lossy()
+        .to_string();
+      let value = unsafe { env.get_string_unchecked(&value) }?
+        .to_string_lossy()
+        .to_string();
+
+      Ok(bd_crash_reporter::set_annotation(&key, &value))
+    },
+    false,
+    "jni set crash annotation",
+  )
+  .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_removeCrashAnnotation(
+  env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  key: JString<'_>,
+) -> jboolean {
+  with_handle_unexpected_or(
+    || {
+      let key = unsafe { env.get_string_unchecked(&key) }?
+        .to_string_lossy()
+        .to_string();
+
+      Ok(bd_crash_reporter::remove_annotation(&key))
+    },
+    false,
+    "jni remove crash annotation",
+  )
+  .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_consecutiveLaunchCrashes(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+) -> jint {
+  with_handle_unexpected_or(
+    || Ok(jint::try_from(bd_crash_reporter::consecutive_launch_crashes()).unwrap_or(jint::MAX)),
+    0,
+    "jni consecutive launch crashes",
+  )
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_resetConsecutiveLaunchCrashes(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      bd_crash_reporter::reset_consecutive_launch_crashes();
+      Ok(())
+    },
+    "jni reset consecutive launch crashes",
+  );
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_setCrashLoopWindow(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  window_secs: jint,
+) -> jboolean {
+  with_handle_unexpected_or(
+    || {
+      let window_secs = u64::try_from(window_secs).unwrap_or(0);
+      Ok(bd_crash_reporter::set_crash_loop_window(
+        std::time::Duration::from_secs(window_secs),
+      ))
+    },
+    false,
+    "jni set crash loop window",
+  )
+  .into()
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_setCrashForeground(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  foreground: jboolean,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      bd_crash_reporter::set_foreground(foreground == JNI_TRUE);
+      Ok(())
+    },
+    "jni set crash foreground",
+  );
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_setFeatureFlagExposure(
   env: JNIEnv<'_>,
@@ -1365,6 +1531,57 @@ pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_writeAppLaunch
   );
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_beginAppLaunch(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  launch_type: jint,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let launch_type = LaunchType::from_i32(launch_type)
+        .ok_or_else(|| anyhow!("invalid app launch type: {launch_type}"))?;
+      let logger = unsafe { LoggerId::from_raw(logger_id) };
+      logger.begin_app_launch(launch_type);
+
+      Ok(())
+    },
+    "jni begin app launch",
+  );
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_recordAppLaunchPhase(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  phase: jint,
+  duration_s: f64,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let phase =
+        LaunchPhase::from_i32(phase).ok_or_else(|| anyhow!("invalid app launch phase: {phase}"))?;
+      let logger = unsafe { LoggerId::from_raw(logger_id) };
+      logger.record_app_launch_phase(phase, Duration::seconds_f64(duration_s));
+
+      Ok(())
+    },
+    "jni record app launch phase",
+  );
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_finishAppLaunch(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  logger.finish_app_launch();
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_writeScreenViewLog(
   env: JNIEnv<'_>,
@@ -1386,6 +1603,122 @@ pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_writeScreenVie
   );
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_writeModalScreenViewLog(
+  env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  screen_name: JString<'_>,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let screen_name = unsafe { env.get_string_unchecked(&screen_name)? }
+        .to_string_lossy()
+        .to_string();
+      let logger = unsafe { LoggerId::from_raw(logger_id) };
+      logger.log_modal_screen_view(screen_name);
+
+      Ok(())
+    },
+    "jni write modal screen view log",
+  );
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_dismissModalScreen(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  logger.dismiss_modal_screen();
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_setAppForeground(
+  _env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  foreground: jboolean,
+) {
+  let logger = unsafe { LoggerId::from_raw(logger_id) };
+  logger.set_app_foreground(foreground == JNI_TRUE);
+}
+
+#[unsafe(no_mangle)]
+// Java types are always signed, but the log level is unsigned.
+#[allow(clippy::cast_sign_loss)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_startSpan(
+  mut env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  name: JString<'_>,
+  log_level: jint,
+  field_keys: JObjectArray<'_>,
+  field_values: JObjectArray<'_>,
+  parent_span_handle: jlong,
+) -> jlong {
+  with_handle_unexpected_or(
+    || -> anyhow::Result<jlong> {
+      let name = unsafe { env.get_string_unchecked(&name) }?
+        .to_string_lossy()
+        .to_string();
+      let fields = ffi::string_arrays_to_annotated_fields(
+        &mut env,
+        &field_keys,
+        &field_values,
+        LogFieldKind::Ootb,
+      )?;
+
+      let logger = unsafe { LoggerId::from_raw(logger_id) };
+      let handle = logger.start_span(
+        name,
+        log_level as u32,
+        fields,
+        SpanHandle::from_raw(parent_span_handle),
+      );
+
+      Ok(handle.into())
+    },
+    0,
+    "jni start span",
+  )
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_endSpan(
+  mut env: JNIEnv<'_>,
+  _class: JClass<'_>,
+  logger_id: jlong,
+  span_handle: jlong,
+  result: jint,
+  field_keys: JObjectArray<'_>,
+  field_values: JObjectArray<'_>,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let handle = SpanHandle::from_raw(span_handle)
+        .ok_or_else(|| anyhow!("invalid span handle: {span_handle}"))?;
+      let fields = ffi::string_arrays_to_annotated_fields(
+        &mut env,
+        &field_keys,
+        &field_values,
+        LogFieldKind::Ootb,
+      )?;
+
+      let logger = unsafe { LoggerId::from_raw(logger_id) };
+      logger.end_span(
+        handle,
+        SpanResult::from_i32(result).unwrap_or(SpanResult::Unknown),
+        fields,
+      );
+
+      Ok(())
+    },
+    "jni end span",
+  );
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_bitdrift_capture_CaptureJniLibrary_flush(
   _env: JNIEnv<'_>,
@@ -0,0 +1,11 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+fn main() {
+  // Leaves the N-API symbols to be resolved against the Node or Electron process at load time.
+  napi_build::setup();
+}
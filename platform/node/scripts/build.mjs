@@ -0,0 +1,35 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+// Builds the binding with cargo and copies it next to package.json as `capture.node`. With
+// `--with-test-server`, also builds the test API server addon the tests load.
+
+import { execFileSync } from "node:child_process";
+import { copyFileSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+
+const packageDir = join(dirname(fileURLToPath(import.meta.url)), "..");
+const targetDir = join(packageDir, "..", "..", "target", "debug");
+const withTestServer = process.argv.includes("--with-test-server");
+
+const packages = ["capture-node", ...(withTestServer ? ["test_node"] : [])];
+execFileSync("cargo", ["build", ...packages.flatMap((name) => ["-p", name])], {
+  cwd: packageDir,
+  stdio: "inherit",
+});
+
+const libraryName = (name) =>
+  process.platform === "darwin" ? `lib${name}.dylib` : `lib${name}.so`;
+
+copyFileSync(join(targetDir, libraryName("capture_node")), join(packageDir, "capture.node"));
+if (withTestServer) {
+  copyFileSync(
+    join(targetDir, libraryName("capture_test")),
+    join(packageDir, "test", "capture_test.node"),
+  );
+}
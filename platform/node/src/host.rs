@@ -0,0 +1,64 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use bd_logger::LogFields;
+use time::OffsetDateTime;
+
+//
+// MetadataProvider
+//
+
+// Stamps logs with the system time. Fields attached to every log are added through the logger
+// itself, so the provider has none of its own.
+pub(crate) struct MetadataProvider;
+
+impl bd_logger::MetadataProvider for MetadataProvider {
+  fn timestamp(&self) -> anyhow::Result<OffsetDateTime> {
+    Ok(OffsetDateTime::now_utc())
+  }
+
+  fn fields(&self) -> anyhow::Result<(LogFields, LogFields)> {
+    Ok((LogFields::default(), LogFields::default()))
+  }
+}
+
+//
+// SessionCallbacks
+//
+
+// Nothing in the JavaScript API observes session changes, so they're only traced.
+pub(crate) struct SessionCallbacks;
+
+impl bd_session::activity_based::Callbacks for SessionCallbacks {
+  fn session_id_changed(&self, session_id: &str) {
+    log::debug!("session changed to {session_id}");
+  }
+}
+
+//
+// Target
+//
+
+// The main process has no screens to capture and no platform events to listen to, so every target
+// request is ignored.
+pub(crate) struct Target;
+
+impl bd_logger::ResourceUtilizationTarget for Target {
+  fn tick(&self) {}
+}
+
+impl bd_logger::SessionReplayTarget for Target {
+  fn capture_screen(&self) {}
+
+  fn capture_screenshot(&self) {}
+}
+
+impl bd_logger::EventsListenerTarget for Target {
+  fn start(&self) {}
+
+  fn stop(&self) {}
+}
@@ -0,0 +1,278 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use crate::host::{MetadataProvider, SessionCallbacks, Target};
+use bd_error_reporter::reporter::{with_handle_unexpected, with_handle_unexpected_or};
+use bd_hyper_network::HyperNetwork;
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields, Block, CaptureSession, log_level};
+use bd_proto::protos::logging::payload::LogType;
+use bd_session::Strategy;
+use bd_session::fixed::UUIDCallbacks;
+use bd_shutdown::ComponentShutdownTrigger;
+use napi_derive::napi;
+use platform_shared::metadata::Mobile;
+use platform_shared::storage::FileStorage;
+use platform_shared::{LoggerHolder, LoggerId, date_to_unix_milliseconds};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+// The name of the key-value storage file within the SDK directory.
+const STORAGE_FILE_NAME: &str = "capture_storage.json";
+
+// How long an activity-based session lasts without logs unless configured otherwise.
+const DEFAULT_INACTIVITY_THRESHOLD_MINS: i64 = 30;
+
+#[napi]
+pub enum LogLevel {
+  Trace   = 0,
+  Debug   = 1,
+  Info    = 2,
+  Warning = 3,
+  Error   = 4,
+}
+
+impl From<LogLevel> for bd_logger::LogLevel {
+  fn from(level: LogLevel) -> Self {
+    match level {
+      LogLevel::Trace => log_level::TRACE,
+      LogLevel::Debug => log_level::DEBUG,
+      LogLevel::Info => log_level::INFO,
+      LogLevel::Warning => log_level::WARNING,
+      LogLevel::Error => log_level::ERROR,
+    }
+  }
+}
+
+#[napi]
+pub enum SessionStrategy {
+  /// A session that lasts until a new one is started explicitly.
+  Fixed         = 0,
+  /// A session that ends after a period without logs.
+  ActivityBased = 1,
+}
+
+#[napi(object)]
+pub struct LoggerOptions {
+  /// The directory the logger keeps its state in, created if it doesn't exist.
+  pub sdk_directory: String,
+  pub api_key: String,
+  pub api_url: String,
+  pub app_id: Option<String>,
+  pub app_version: Option<String>,
+  /// The host operating system. Defaults to the one Node reports, e.g. `linux`.
+  pub os: Option<String>,
+  pub os_version: Option<String>,
+  /// The host device model. Defaults to `unknown`.
+  pub model: Option<String>,
+  /// Defaults to a fixed session.
+  pub session_strategy: Option<SessionStrategy>,
+  /// How long an activity-based session lasts without logs. Defaults to 30 minutes.
+  pub inactivity_threshold_mins: Option<i64>,
+  pub start_in_sleep_mode: Option<bool>,
+}
+
+/// A point-in-time snapshot of the SDK's operational status. Timestamps are milliseconds since the
+/// Unix epoch, or -1 if not yet available.
+#[napi(object)]
+pub struct SdkStatus {
+  pub initialization_state: i32,
+  pub last_handshake_time_ms: i64,
+  pub last_config_delivery_time_ms: i64,
+}
+
+//
+// Logger
+//
+
+/// A logger running in the Node or Electron main process. The logger is shut down and freed once
+/// the object is garbage collected.
+#[napi]
+pub struct Logger {
+  logger_id: LoggerId<'static>,
+}
+
+#[napi]
+impl Logger {
+  #[napi(constructor)]
+  pub fn new(options: LoggerOptions) -> napi::Result<Self> {
+    let logger_id = create_logger(options)
+      .map_err(|e| napi::Error::from_reason(format!("failed to create logger: {e}")))?;
+    Ok(Self { logger_id })
+  }
+
+  /// Starts the logger's runtime. Calls after the first have no effect.
+  #[napi]
+  pub fn start(&self) {
+    self.logger_id.start();
+  }
+
+  #[napi]
+  pub fn log(&self, level: LogLevel, message: String, fields: Option<HashMap<String, String>>) {
+    let fields = fields
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(key, value)| (key.into(), AnnotatedLogField::new_ootb(value)))
+      .collect::<AnnotatedLogFields>();
+
+    self.logger_id.log(
+      level.into(),
+      LogType::NORMAL,
+      message.into(),
+      fields,
+      [].into(),
+      None,
+      &CaptureSession::default(),
+    );
+  }
+
+  /// Adds a field to every subsequent log, replacing any field with the same key.
+  #[napi]
+  pub fn add_field(&self, key: String, value: String) {
+    self.logger_id.add_log_field(key, value.into());
+  }
+
+  #[napi]
+  pub fn remove_field(&self, key: String) {
+    self.logger_id.remove_log_field(&key);
+  }
+
+  #[napi]
+  pub fn start_new_session(&self) {
+    with_handle_unexpected(
+      || self.logger_id.start_new_session(),
+      "node start new session",
+    );
+  }
+
+  #[napi]
+  pub fn session_id(&self) -> String {
+    with_handle_unexpected_or(
+      || self.logger_id.session_id(),
+      String::new(),
+      "node get session id",
+    )
+  }
+
+  #[napi]
+  pub fn log_screen_view(&self, screen_name: String) {
+    self.logger_id.log_screen_view(screen_name);
+  }
+
+  /// Logs the time it took the app to become interactive. Only the first call is logged.
+  #[napi]
+  pub fn log_app_launch_tti(&self, duration_ms: f64) {
+    self
+      .logger_id
+      .log_app_launch_tti(time::Duration::seconds_f64(duration_ms / 1_000f64));
+  }
+
+  /// Flushes the logger's state to disk, waiting up to a second for it to complete if `blocking`
+  /// is true.
+  #[napi]
+  pub fn flush(&self, blocking: bool) {
+    let block = if blocking {
+      Block::Yes {
+        timeout: std::time::Duration::from_secs(1),
+        poll_callback: None,
+      }
+    } else {
+      Block::No
+    };
+    self.logger_id.flush_state(block);
+  }
+
+  #[napi]
+  pub fn get_sdk_status(&self) -> SdkStatus {
+    let status = self.logger_id.get_sdk_status();
+    SdkStatus {
+      initialization_state: status.initialization_state as i32,
+      last_handshake_time_ms: date_to_unix_milliseconds(status.last_handshake_time),
+      last_config_delivery_time_ms: date_to_unix_milliseconds(status.last_config_delivery_time),
+    }
+  }
+
+  /// Shuts the logger down, waiting for the shutdown to complete if `blocking` is true.
+  #[napi]
+  pub fn shutdown(&self, blocking: bool) {
+    self.logger_id.shutdown(blocking);
+  }
+}
+
+impl Drop for Logger {
+  fn drop(&mut self) {
+    unsafe { LoggerHolder::destroy(self.logger_id.into()) };
+  }
+}
+
+fn create_logger(options: LoggerOptions) -> anyhow::Result<LoggerId<'static>> {
+  std::fs::create_dir_all(&options.sdk_directory)?;
+  let sdk_directory = Path::new(&options.sdk_directory);
+
+  let storage = Box::new(FileStorage::open(sdk_directory.join(STORAGE_FILE_NAME)));
+  let store = Arc::new(bd_key_value::Store::new(storage));
+  let device = Arc::new(bd_logger::Device::new(store.clone()));
+
+  let session = match options.session_strategy.unwrap_or(SessionStrategy::Fixed) {
+    SessionStrategy::Fixed => Strategy::fixed(sdk_directory, Arc::new(UUIDCallbacks)),
+    SessionStrategy::ActivityBased => Strategy::activity_based(
+      sdk_directory,
+      time::Duration::minutes(
+        options
+          .inactivity_threshold_mins
+          .unwrap_or(DEFAULT_INACTIVITY_THRESHOLD_MINS),
+      ),
+      Arc::new(SessionCallbacks),
+      Arc::new(bd_time::SystemTimeProvider {}),
+    ),
+  };
+
+  let static_metadata = Arc::new(Mobile::electron(
+    options.app_id,
+    options.app_version,
+    options
+      .os
+      .unwrap_or_else(|| std::env::consts::OS.to_string()),
+    options.os_version,
+    device.clone(),
+    options.model.unwrap_or_else(|| "unknown".to_string()),
+  ));
+  let initial_ootb_fields = static_metadata.static_log_fields();
+
+  let network_shutdown = ComponentShutdownTrigger::default();
+  let network = HyperNetwork::run_on_thread(&options.api_url, network_shutdown.make_shutdown());
+
+  let logger = bd_logger::LoggerBuilder::new(bd_logger::InitParams {
+    sdk_directory: sdk_directory.to_path_buf(),
+    api_key: options.api_key,
+    session,
+    metadata_provider: Arc::new(MetadataProvider),
+    initial_ootb_fields,
+    resource_utilization_target: Box::new(Target),
+    session_replay_target: Box::new(Target),
+    events_listener_target: Box::new(Target),
+    network: Box::new(network),
+    store,
+    device,
+    static_metadata,
+    start_in_sleep_mode: options.start_in_sleep_mode.unwrap_or(false),
+  })
+  .with_internal_logger(true)
+  .build()
+  .map(|(logger, _, future, _)| {
+    LoggerHolder::new(
+      logger,
+      Box::pin(async move {
+        // The network runs on its own thread for as long as the logger runtime does.
+        let _network_shutdown = network_shutdown;
+        future.await
+      }),
+    )
+  })?;
+
+  Ok(logger.into_raw())
+}
@@ -0,0 +1,83 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+import assert from "node:assert/strict";
+import { mkdtempSync, rmSync } from "node:fs";
+import { createRequire } from "node:module";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { after, before, test } from "node:test";
+
+const require = createRequire(import.meta.url);
+const { Logger, LogLevel, SessionStrategy } = require("../capture.node");
+const server = require("./capture_test.node");
+
+let port;
+let sdkDirectory;
+
+before(() => {
+  port = server.startTestApiServer(-1);
+  sdkDirectory = mkdtempSync(join(tmpdir(), "capture-node-"));
+});
+
+after(() => {
+  server.stopTestApiServer();
+  rmSync(sdkDirectory, { recursive: true, force: true });
+});
+
+// Waits for a log with each of `messages`, skipping any other log, and returns them by message.
+function nextUploadedLogs(messages) {
+  const uploaded = {};
+  while (messages.some((message) => !(message in uploaded))) {
+    const log = server.nextUploadedLog();
+    assert.ok(log, `timed out waiting for ${messages.filter((message) => !(message in uploaded))}`);
+    uploaded[log.message] ??= log;
+  }
+  return uploaded;
+}
+
+test("logger connects to the API server and logs", () => {
+  const logger = new Logger({
+    sdkDirectory,
+    apiKey: "test!",
+    apiUrl: `http://localhost:${port}`,
+    appId: "io.bitdrift.node.test",
+    appVersion: "1.0.0",
+    sessionStrategy: SessionStrategy.Fixed,
+  });
+  logger.start();
+
+  const streamId = server.awaitNextApiStream();
+  assert.notEqual(streamId, -1);
+  server.configureAggressiveContinuousUploads(streamId);
+
+  const status = logger.getSdkStatus();
+  assert.ok(status.lastHandshakeTimeMs > 0);
+
+  const sessionId = logger.sessionId();
+  assert.notEqual(sessionId, "");
+  logger.startNewSession();
+  assert.notEqual(logger.sessionId(), sessionId);
+
+  logger.addField("region", "eu");
+  logger.log(LogLevel.Info, "hello from node", { key: "value" });
+  logger.removeField("region");
+  logger.logScreenView("settings");
+  logger.logAppLaunchTti(250);
+  logger.flush(true);
+
+  const uploaded = nextUploadedLogs(["hello from node", "ScreenView", "AppLaunchTTI"]);
+  assert.equal(uploaded["hello from node"].fields.key, "value");
+  assert.equal(uploaded["hello from node"].fields.region, "eu");
+  assert.equal(uploaded["hello from node"].fields.app_id, "io.bitdrift.node.test");
+  assert.equal(uploaded.ScreenView.fields._screen_name, "settings");
+  assert.equal(uploaded.ScreenView.fields.region, undefined);
+  assert.equal(uploaded.AppLaunchTTI.fields._duration_ms, "250");
+
+  logger.shutdown(true);
+  assert.ok(server.awaitApiServerStreamClosed(streamId, 5000));
+});
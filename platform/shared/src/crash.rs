@@ -0,0 +1,54 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./crash_test.rs"]
+mod tests;
+
+use bd_crash_reporter::PreviousLaunchCrash;
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields};
+
+pub const CRASH_KIND_FIELD: &str = "_crash_kind";
+pub const CRASH_TIMESTAMP_FIELD: &str = "_crash_timestamp_ms";
+pub const EXCEPTION_NAME_FIELD: &str = "_exception_name";
+pub const EXCEPTION_REASON_FIELD: &str = "_exception_reason";
+
+pub(crate) const PREVIOUS_LAUNCH_CRASH_MESSAGE: &str = "PreviousLaunchCrash";
+
+/// Returns the fields of the log for the crash that ended the previous launch. Each of the
+/// crashing thread's top frames gets its own `_frame_<index>` field, innermost first.
+pub(crate) fn previous_launch_crash_fields(crash: &PreviousLaunchCrash) -> AnnotatedLogFields {
+  let timestamp_ms = crash.timestamp_secs.saturating_mul(1_000);
+  let mut fields: AnnotatedLogFields = [
+    (
+      CRASH_KIND_FIELD.into(),
+      AnnotatedLogField::new_ootb(crash.kind.clone()),
+    ),
+    (
+      CRASH_TIMESTAMP_FIELD.into(),
+      AnnotatedLogField::new_ootb(timestamp_ms.to_string()),
+    ),
+    (
+      EXCEPTION_NAME_FIELD.into(),
+      AnnotatedLogField::new_ootb(crash.exception_name.clone()),
+    ),
+  ]
+  .into();
+  if let Some(reason) = &crash.exception_reason {
+    fields.insert(
+      EXCEPTION_REASON_FIELD.into(),
+      AnnotatedLogField::new_ootb(reason.clone()),
+    );
+  }
+  for (index, frame) in crash.top_frames.iter().enumerate() {
+    fields.insert(
+      format!("_frame_{index}").into(),
+      AnnotatedLogField::new_ootb(frame.clone()),
+    );
+  }
+  fields
+}
@@ -0,0 +1,57 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::{
+  CRASH_KIND_FIELD,
+  CRASH_TIMESTAMP_FIELD,
+  EXCEPTION_NAME_FIELD,
+  EXCEPTION_REASON_FIELD,
+  previous_launch_crash_fields,
+};
+use bd_crash_reporter::PreviousLaunchCrash;
+use bd_logger::AnnotatedLogFields;
+
+fn field<'a>(fields: &'a AnnotatedLogFields, key: &str) -> Option<&'a str> {
+  fields.get(key).and_then(|field| field.value.as_str())
+}
+
+#[test]
+fn previous_launch_crash_fields_describe_the_crash() {
+  let fields = previous_launch_crash_fields(&PreviousLaunchCrash {
+    kind: "Signal".to_string(),
+    timestamp_secs: 1_700_000_000,
+    exception_name: "SIGSEGV".to_string(),
+    exception_reason: Some("code 1 at 0xdead on thread 7".to_string()),
+    top_frames: vec!["libapp.so 0x10".to_string(), "libapp.so 0x20".to_string()],
+  });
+
+  assert_eq!(fields.len(), 6);
+  assert_eq!(field(&fields, CRASH_KIND_FIELD), Some("Signal"));
+  assert_eq!(field(&fields, CRASH_TIMESTAMP_FIELD), Some("1700000000000"));
+  assert_eq!(field(&fields, EXCEPTION_NAME_FIELD), Some("SIGSEGV"));
+  assert_eq!(
+    field(&fields, EXCEPTION_REASON_FIELD),
+    Some("code 1 at 0xdead on thread 7")
+  );
+  assert_eq!(field(&fields, "_frame_0"), Some("libapp.so 0x10"));
+  assert_eq!(field(&fields, "_frame_1"), Some("libapp.so 0x20"));
+}
+
+#[test]
+fn previous_launch_crash_without_reason_or_frames() {
+  let fields = previous_launch_crash_fields(&PreviousLaunchCrash {
+    kind: "RustPanic".to_string(),
+    timestamp_secs: 1,
+    exception_name: "panic".to_string(),
+    exception_reason: None,
+    top_frames: Vec::new(),
+  });
+
+  assert_eq!(fields.len(), 3);
+  assert_eq!(field(&fields, EXCEPTION_REASON_FIELD), None);
+  assert_eq!(field(&fields, "_frame_0"), None);
+}
@@ -0,0 +1,190 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./launch_test.rs"]
+mod tests;
+
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields};
+
+pub const LAUNCH_TYPE_FIELD: &str = "_launch_type";
+pub const LAUNCH_DURATION_FIELD: &str = "_duration_ms";
+
+/// How the app was launched. The values are part of the FFI.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchType {
+  /// The process was started to launch the app.
+  Cold = 0,
+  /// The process was running, but the app's UI had to be created again.
+  Warm = 1,
+  /// The app was brought back to the foreground with its UI still in memory.
+  Hot  = 2,
+}
+
+impl LaunchType {
+  #[must_use]
+  pub const fn from_i32(value: i32) -> Option<Self> {
+    match value {
+      0 => Some(Self::Cold),
+      1 => Some(Self::Warm),
+      2 => Some(Self::Hot),
+      _ => None,
+    }
+  }
+
+  const fn as_str(self) -> &'static str {
+    match self {
+      Self::Cold => "cold",
+      Self::Warm => "warm",
+      Self::Hot => "hot",
+    }
+  }
+}
+
+/// A milestone of an app launch, measured from the start of the launch: the process start for a
+/// cold launch, or the foreground transition otherwise. The values are part of the FFI.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchPhase {
+  FirstFrame = 0,
+  /// The app became interactive.
+  Tti        = 1,
+  /// The app finished drawing its initial content, which completes the launch.
+  FullyDrawn = 2,
+}
+
+impl LaunchPhase {
+  const ALL: [Self; 3] = [Self::FirstFrame, Self::Tti, Self::FullyDrawn];
+
+  #[must_use]
+  pub const fn from_i32(value: i32) -> Option<Self> {
+    match value {
+      0 => Some(Self::FirstFrame),
+      1 => Some(Self::Tti),
+      2 => Some(Self::FullyDrawn),
+      _ => None,
+    }
+  }
+
+  #[must_use]
+  pub const fn field_name(self) -> &'static str {
+    match self {
+      Self::FirstFrame => "_first_frame_duration_ms",
+      Self::Tti => "_tti_duration_ms",
+      Self::FullyDrawn => "_fully_drawn_duration_ms",
+    }
+  }
+}
+
+struct Launch {
+  launch_type: LaunchType,
+  // The duration of each phase, indexed by its value.
+  phases: [Option<time::Duration>; LaunchPhase::ALL.len()],
+}
+
+impl Launch {
+  // Returns the fields of the launch's log, or `None` if no phase was recorded.
+  fn into_fields(self) -> Option<AnnotatedLogFields> {
+    let duration = self.phases.iter().flatten().max()?;
+
+    let mut fields: AnnotatedLogFields = [
+      (
+        LAUNCH_TYPE_FIELD.into(),
+        AnnotatedLogField::new_ootb(self.launch_type.as_str().to_string()),
+      ),
+      (
+        LAUNCH_DURATION_FIELD.into(),
+        AnnotatedLogField::new_ootb(duration_ms(*duration)),
+      ),
+    ]
+    .into();
+    for phase in LaunchPhase::ALL {
+      if let Some(duration) = self.phases[phase as usize] {
+        fields.insert(
+          phase.field_name().into(),
+          AnnotatedLogField::new_ootb(duration_ms(duration)),
+        );
+      }
+    }
+
+    Some(fields)
+  }
+}
+
+/// Measures app launches one at a time. There is a single cold launch per process, but a warm or
+/// hot launch for every transition to the foreground.
+#[derive(Default)]
+pub(crate) struct LaunchTracker {
+  state: parking_lot::Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+  current: Option<Launch>,
+  cold_launch_measured: bool,
+}
+
+impl LaunchTracker {
+  /// Begins measuring a launch. A launch that is still being measured is finished first, and its
+  /// log fields returned.
+  pub fn begin(&self, launch_type: LaunchType) -> Option<AnnotatedLogFields> {
+    let mut state = self.state.lock();
+    if launch_type == LaunchType::Cold {
+      if state.cold_launch_measured {
+        log::warn!("ignoring cold app launch: a cold launch was already measured");
+        return None;
+      }
+      state.cold_launch_measured = true;
+    }
+
+    state
+      .current
+      .replace(Launch {
+        launch_type,
+        phases: [None; LaunchPhase::ALL.len()],
+      })
+      .and_then(Launch::into_fields)
+  }
+
+  /// Records how long it took the current launch to reach `phase`. Only the first duration
+  /// recorded for a phase is kept. Reaching the fully drawn phase finishes the launch, returning
+  /// its log fields.
+  pub fn record(&self, phase: LaunchPhase, duration: time::Duration) -> Option<AnnotatedLogFields> {
+    if duration.is_negative() {
+      log::warn!("ignoring app launch phase {phase:?}: reported duration is negative: {duration}");
+      return None;
+    }
+
+    let mut state = self.state.lock();
+    let Some(launch) = &mut state.current else {
+      log::warn!("ignoring app launch phase {phase:?}: no app launch is being measured");
+      return None;
+    };
+    launch.phases[phase as usize].get_or_insert(duration);
+
+    if phase == LaunchPhase::FullyDrawn {
+      state.current.take().and_then(Launch::into_fields)
+    } else {
+      None
+    }
+  }
+
+  /// Finishes the current launch, returning its log fields if any phase was recorded.
+  pub fn finish(&self) -> Option<AnnotatedLogFields> {
+    self
+      .state
+      .lock()
+      .current
+      .take()
+      .and_then(Launch::into_fields)
+  }
+}
+
+fn duration_ms(duration: time::Duration) -> String {
+  (duration.as_seconds_f64() * 1_000f64).to_string()
+}
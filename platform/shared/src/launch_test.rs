@@ -0,0 +1,123 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{LAUNCH_DURATION_FIELD, LAUNCH_TYPE_FIELD, LaunchPhase, LaunchTracker, LaunchType};
+use bd_logger::AnnotatedLogFields;
+use time::Duration;
+
+fn field<'a>(fields: &'a AnnotatedLogFields, key: &str) -> Option<&'a str> {
+  fields.get(key).and_then(|field| field.value.as_str())
+}
+
+#[test]
+fn launch_is_logged_once_fully_drawn() {
+  let tracker = LaunchTracker::default();
+
+  assert!(tracker.begin(LaunchType::Cold).is_none());
+  assert!(
+    tracker
+      .record(LaunchPhase::FirstFrame, Duration::milliseconds(400))
+      .is_none()
+  );
+  assert!(
+    tracker
+      .record(LaunchPhase::Tti, Duration::milliseconds(900))
+      .is_none()
+  );
+  // Only the first duration of a phase counts.
+  assert!(
+    tracker
+      .record(LaunchPhase::FirstFrame, Duration::milliseconds(700))
+      .is_none()
+  );
+
+  let fields = tracker
+    .record(LaunchPhase::FullyDrawn, Duration::milliseconds(1_250))
+    .unwrap();
+  assert_eq!(field(&fields, LAUNCH_TYPE_FIELD), Some("cold"));
+  assert_eq!(field(&fields, LAUNCH_DURATION_FIELD), Some("1250"));
+  assert_eq!(
+    field(&fields, LaunchPhase::FirstFrame.field_name()),
+    Some("400")
+  );
+  assert_eq!(field(&fields, LaunchPhase::Tti.field_name()), Some("900"));
+  assert_eq!(
+    field(&fields, LaunchPhase::FullyDrawn.field_name()),
+    Some("1250")
+  );
+
+  assert!(tracker.finish().is_none());
+}
+
+#[test]
+fn finishing_logs_the_phases_reached() {
+  let tracker = LaunchTracker::default();
+
+  tracker.begin(LaunchType::Hot);
+  tracker.record(LaunchPhase::FirstFrame, Duration::milliseconds(80));
+
+  let fields = tracker.finish().unwrap();
+  assert_eq!(field(&fields, LAUNCH_TYPE_FIELD), Some("hot"));
+  assert_eq!(field(&fields, LAUNCH_DURATION_FIELD), Some("80"));
+  assert_eq!(field(&fields, LaunchPhase::Tti.field_name()), None);
+  assert_eq!(field(&fields, LaunchPhase::FullyDrawn.field_name()), None);
+
+  // A launch without any phase isn't logged.
+  tracker.begin(LaunchType::Hot);
+  assert!(tracker.finish().is_none());
+}
+
+#[test]
+fn warm_and_hot_launches_repeat_but_cold_launches_do_not() {
+  let tracker = LaunchTracker::default();
+
+  tracker.begin(LaunchType::Cold);
+  tracker.record(LaunchPhase::Tti, Duration::milliseconds(500));
+
+  // Beginning another launch finishes the one in progress.
+  let cold = tracker.begin(LaunchType::Warm).unwrap();
+  assert_eq!(field(&cold, LAUNCH_TYPE_FIELD), Some("cold"));
+
+  tracker.record(LaunchPhase::Tti, Duration::milliseconds(200));
+  let warm = tracker.begin(LaunchType::Hot).unwrap();
+  assert_eq!(field(&warm, LAUNCH_TYPE_FIELD), Some("warm"));
+
+  tracker.record(LaunchPhase::Tti, Duration::milliseconds(50));
+  assert!(tracker.begin(LaunchType::Cold).is_none());
+  let hot = tracker.finish().unwrap();
+  assert_eq!(field(&hot, LAUNCH_TYPE_FIELD), Some("hot"));
+}
+
+#[test]
+fn phases_outside_a_launch_are_ignored() {
+  let tracker = LaunchTracker::default();
+
+  assert!(
+    tracker
+      .record(LaunchPhase::FullyDrawn, Duration::milliseconds(100))
+      .is_none()
+  );
+
+  tracker.begin(LaunchType::Warm);
+  assert!(
+    tracker
+      .record(LaunchPhase::FullyDrawn, Duration::milliseconds(-1))
+      .is_none()
+  );
+  assert!(tracker.finish().is_none());
+}
+
+#[test]
+fn launch_values_map_from_their_values() {
+  assert_eq!(LaunchType::from_i32(0), Some(LaunchType::Cold));
+  assert_eq!(LaunchType::from_i32(2), Some(LaunchType::Hot));
+  assert_eq!(LaunchType::from_i32(3), None);
+  assert_eq!(LaunchPhase::from_i32(1), Some(LaunchPhase::Tti));
+  assert_eq!(LaunchPhase::from_i32(3), None);
+}
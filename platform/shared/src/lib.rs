@@ -14,13 +14,20 @@
   clippy::unwrap_used
 )]
 
+mod crash;
 pub mod error;
 pub mod javascript_error;
+pub mod launch;
 pub mod metadata;
+pub mod screen;
+pub mod span;
+pub mod storage;
 
+use bd_crash_reporter::CrashLoggerToken;
 use bd_error_reporter::reporter::handle_unexpected;
 use bd_logger::{
   AnnotatedLogField,
+  AnnotatedLogFields,
   LogFieldKind,
   LogFields,
   LoggerBuilder,
@@ -29,12 +36,17 @@ use bd_logger::{
 };
 use bd_proto::protos::logging::payload::LogType;
 use bd_runtime::runtime::Snapshot;
+use crash::{PREVIOUS_LAUNCH_CRASH_MESSAGE, previous_launch_crash_fields};
+use launch::{LaunchPhase, LaunchTracker, LaunchType};
 use parking_lot::Once;
+use screen::{ScreenLog, ScreenTracker};
+use span::{SpanHandle, SpanLog, SpanResult, Spans};
 use std::collections::HashMap;
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// This is the logger ID that is passed to the platform code. It is a typed wrapper around an i64
 /// that encodes the pointer to the `LoggerHolder` object.
@@ -128,6 +140,11 @@ pub struct LoggerHolder {
   handle: bd_logger::LoggerHandle,
   future: parking_lot::Mutex<Option<LoggerFuture>>,
   app_launch_tti_log: Once,
+  spans: Spans,
+  screens: ScreenTracker,
+  launches: LaunchTracker,
+  // Set once started, so only the crash logger this holder attached is detached on destroy.
+  crash_logger: parking_lot::Mutex<Option<CrashLoggerToken>>,
 }
 
 impl Deref for LoggerHolder {
@@ -146,6 +163,10 @@ impl LoggerHolder {
       handle,
       future: parking_lot::Mutex::new(Some(future)),
       app_launch_tti_log: Once::new(),
+      spans: Spans::default(),
+      screens: ScreenTracker::default(),
+      launches: LaunchTracker::default(),
+      crash_logger: parking_lot::Mutex::new(None),
     }
   }
 
@@ -156,6 +177,14 @@ impl LoggerHolder {
 
     // Start the logger runtime using the defaults provided by the logger builder.
     handle_unexpected(LoggerBuilder::run_logger_runtime(future), "logger runtime");
+
+    // Attach to the crash reporter so a crash that ended the previous launch is logged once the
+    // crash reporter has been configured, regardless of which of the two happens first.
+    let handle = self.logger.new_logger_handle();
+    let token = bd_crash_reporter::attach_crash_logger(move |crash| {
+      log_previous_launch_crash(&handle, crash);
+    });
+    *self.crash_logger.lock() = Some(token);
   }
 
   /// Consumes the logger and returns the raw pointer to it. This effectively leaks the object, so
@@ -169,7 +198,16 @@ impl LoggerHolder {
   }
 
   /// Shuts down the logger, blocking until the logger has finished shutdown if `block` is true.
+  /// Spans that are still open are first logged as abandoned, the time spent on the current
+  /// screen is closed out and an app launch that is still being measured is logged.
   pub fn shutdown(&self, block: bool) {
+    for log in self.spans.abandon_all() {
+      self.log_span(log);
+    }
+    self.finish_app_launch();
+    if let Some(log) = self.screens.close(Instant::now()) {
+      self.log_screen(log);
+    }
     self.logger.shutdown(block);
   }
 
@@ -190,6 +228,9 @@ impl LoggerHolder {
   /// `into_raw`. This function *cannot* be called multiple times for the same id.
   pub unsafe fn destroy(id: i64) {
     let holder = unsafe { Box::from_raw(id as *mut Self) };
+    if let Some(token) = holder.crash_logger.lock().take() {
+      bd_crash_reporter::detach_crash_logger(token);
+    }
     holder.shutdown(false);
     drop(holder);
   }
@@ -225,6 +266,43 @@ impl LoggerHolder {
     });
   }
 
+  /// Begins measuring an app launch, finishing and logging one that is still being measured.
+  /// Unlike the launch TTI log, a warm or hot launch can be measured on every transition to the
+  /// foreground. Only the first cold launch is measured.
+  pub fn begin_app_launch(&self, launch_type: LaunchType) {
+    if let Some(fields) = self.launches.begin(launch_type) {
+      self.log_app_launch(fields);
+    }
+  }
+
+  /// Records how long it took the current app launch to reach `phase`. Reaching the fully drawn
+  /// phase finishes the launch and logs it.
+  pub fn record_app_launch_phase(&self, phase: LaunchPhase, duration: time::Duration) {
+    if let Some(fields) = self.launches.record(phase, duration) {
+      self.log_app_launch(fields);
+    }
+  }
+
+  /// Finishes the current app launch, logging the phases it reached. Has no effect if no app
+  /// launch is being measured or no phase has been reached.
+  pub fn finish_app_launch(&self) {
+    if let Some(fields) = self.launches.finish() {
+      self.log_app_launch(fields);
+    }
+  }
+
+  fn log_app_launch(&self, fields: AnnotatedLogFields) {
+    self.log(
+      log_level::INFO,
+      LogType::LIFECYCLE,
+      "AppLaunch".into(),
+      fields,
+      [].into(),
+      None,
+      &bd_logger::CaptureSession::default(),
+    );
+  }
+
   pub fn process_crash_reports(&mut self, report_processing_session: ReportProcessingSession) {
     if let Err(e) = self.logger.process_crash_reports(report_processing_session) {
       log::error!("failed to process crash reports: {e}");
@@ -235,23 +313,98 @@ impl LoggerHolder {
     self.logger.previous_memory_pressure_level()
   }
 
+  /// Logs a view of a screen that replaces the current one, or the current modal screen if one is
+  /// presented. The log names the screen that was current before and how long it was visible for.
   pub fn log_screen_view(&self, screen_name: String) {
-    let fields = [(
-      "_screen_name".into(),
-      AnnotatedLogField::new_ootb(screen_name),
-    )]
-    .into();
+    let log = self.screens.view(screen_name, Instant::now());
+    self.log_screen(log);
+  }
 
+  /// Logs a view of a modal screen, such as a sheet, presented over the current screen. The
+  /// current screen becomes current again once the modal screen is dismissed.
+  pub fn log_modal_screen_view(&self, screen_name: String) {
+    let log = self.screens.present_modal(screen_name, Instant::now());
+    self.log_screen(log);
+  }
+
+  /// Dismisses the current modal screen, logging a view of the screen it was presented over.
+  pub fn dismiss_modal_screen(&self) {
+    match self.screens.dismiss_modal(Instant::now()) {
+      Some(log) => self.log_screen(log),
+      None => log::debug!("ignoring modal screen dismissal: no modal screen is presented"),
+    }
+  }
+
+  /// Records whether the app is in the foreground. Moving to the background closes out the time
+  /// spent on the current screen.
+  pub fn set_app_foreground(&self, foreground: bool) {
+    if let Some(log) = self.screens.set_foreground(foreground, Instant::now()) {
+      self.log_screen(log);
+    }
+  }
+
+  fn log_screen(&self, log: ScreenLog) {
     self.log(
       log_level::INFO,
       LogType::UX,
-      "ScreenView".into(),
-      fields,
+      log.message.into(),
+      log.fields,
       [].into(),
       None,
       &bd_logger::CaptureSession::default(),
     );
   }
+
+  /// Starts a span, logging its start, and returns the handle to end it with. The span is nested
+  /// under `parent` if that span is still open.
+  pub fn start_span(
+    &self,
+    name: String,
+    level: bd_logger::LogLevel,
+    fields: AnnotatedLogFields,
+    parent: Option<SpanHandle>,
+  ) -> SpanHandle {
+    let (handle, log) = self.spans.start(name, level, fields, parent);
+    self.log_span(log);
+    handle
+  }
+
+  /// Ends a span, logging its duration and result along with the fields it was started with and
+  /// `fields`. Ending a span that has already ended has no effect.
+  pub fn end_span(&self, handle: SpanHandle, result: SpanResult, fields: AnnotatedLogFields) {
+    match self.spans.end(handle, result, fields) {
+      Some(log) => self.log_span(log),
+      None => log::debug!("ignoring end of span {handle:?}: span is not active"),
+    }
+  }
+
+  fn log_span(&self, log: SpanLog) {
+    self.log(
+      log.level,
+      LogType::SPAN,
+      log.message.into(),
+      log.fields,
+      [].into(),
+      None,
+      &bd_logger::CaptureSession::default(),
+    );
+  }
+}
+
+/// Logs the crash that ended the previous launch as an out-of-the-box lifecycle log event.
+fn log_previous_launch_crash(
+  handle: &bd_logger::LoggerHandle,
+  crash: &bd_crash_reporter::PreviousLaunchCrash,
+) {
+  handle.log(
+    log_level::ERROR,
+    LogType::LIFECYCLE,
+    PREVIOUS_LAUNCH_CRASH_MESSAGE.into(),
+    previous_launch_crash_fields(crash),
+    [].into(),
+    None,
+    &bd_logger::CaptureSession::default(),
+  );
 }
 
 impl<'a> From<LoggerId<'a>> for i64 {
@@ -53,7 +53,8 @@ pub struct AppleStaticFields {
 enum PlatformStaticFields {
   Android(AndroidStaticFields),
   Apple(AppleStaticFields),
-  Electron,
+  // Hosts without platform specific fields, which name their OS themselves.
+  Generic,
 }
 
 // A collection of typed metadata that is used to identify the client when communicating with
@@ -133,16 +134,39 @@ impl Mobile {
     os_version: Option<String>,
     device: Arc<bd_logger::Device>,
     model: String,
+  ) -> Self {
+    Self::generic(
+      Platform::Electron,
+      app_id,
+      app_version,
+      os,
+      os_version,
+      device,
+      model,
+    )
+  }
+
+  /// Metadata for a host that identifies as `platform` without providing that platform's specific
+  /// fields, such as a native host embedding the SDK through its C API. `os` is reported as given.
+  #[must_use]
+  pub const fn generic(
+    platform: Platform,
+    app_id: Option<String>,
+    app_version: Option<String>,
+    os: String,
+    os_version: Option<String>,
+    device: Arc<bd_logger::Device>,
+    model: String,
   ) -> Self {
     Self {
       app_id,
       app_version,
-      platform: Platform::Electron,
+      platform,
       os,
       device,
       os_version,
       model,
-      platform_static_fields: PlatformStaticFields::Electron,
+      platform_static_fields: PlatformStaticFields::Generic,
     }
   }
 
@@ -179,13 +203,13 @@ impl Mobile {
       PlatformStaticFields::Apple(apple) => {
         fields.insert("_build_number".into(), apple.build_number.clone().into());
       },
-      PlatformStaticFields::Electron => {},
+      PlatformStaticFields::Generic => {},
     }
 
-    let log_os = match self.platform {
-      Platform::Android => "Android",
-      Platform::Apple => "iOS",
-      Platform::Electron => &self.os,
+    let log_os = match self.platform_static_fields {
+      PlatformStaticFields::Android(_) => "Android",
+      PlatformStaticFields::Apple(_) => "iOS",
+      PlatformStaticFields::Generic => &self.os,
     };
     fields.insert("os".into(), log_os.into());
     fields.insert("model".into(), self.model.clone().into());
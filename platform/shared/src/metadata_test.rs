@@ -157,3 +157,29 @@ fn collect_inner_omits_manufacturer_for_non_android() {
     Some("456")
   );
 }
+
+#[test]
+fn generic_metadata_keeps_the_platform_and_os_it_is_given() {
+  let metadata = Mobile::generic(
+    bd_api::Platform::Apple,
+    Some("daemon".to_string()),
+    None,
+    "macos".to_string(),
+    Some("15.1".to_string()),
+    test_device(),
+    "Mac14,2".to_string(),
+  );
+
+  assert!(matches!(
+    bd_api::Metadata::platform(&metadata),
+    bd_api::Platform::Apple
+  ));
+  assert_eq!(bd_api::Metadata::os(&metadata), "macos");
+
+  let initial_fields = metadata.static_log_fields();
+  assert_eq!(
+    initial_fields.get("os").and_then(|value| value.as_str()),
+    Some("macos")
+  );
+  assert!(!initial_fields.contains_key("_build_number"));
+}
@@ -0,0 +1,233 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./screen_test.rs"]
+mod tests;
+
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields};
+use std::time::{Duration, Instant};
+
+pub const SCREEN_NAME_FIELD: &str = "_screen_name";
+pub const PREVIOUS_SCREEN_NAME_FIELD: &str = "_previous_screen_name";
+pub const PREVIOUS_SCREEN_DURATION_FIELD: &str = "_previous_screen_duration_ms";
+pub const SCREEN_DURATION_FIELD: &str = "_screen_duration_ms";
+pub const MODAL_FIELD: &str = "_modal";
+pub const SCREEN_END_REASON_FIELD: &str = "_reason";
+
+pub(crate) const SCREEN_VIEW_MESSAGE: &str = "ScreenView";
+pub(crate) const SCREEN_VIEW_END_MESSAGE: &str = "ScreenViewEnd";
+
+/// Why the visible screen stopped being visible without another screen being viewed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ScreenEndReason {
+  Background,
+  Shutdown,
+}
+
+impl ScreenEndReason {
+  const fn as_str(self) -> &'static str {
+    match self {
+      Self::Background => "background",
+      Self::Shutdown => "shutdown",
+    }
+  }
+}
+
+/// A screen log for the logger to write.
+#[derive(Debug)]
+pub(crate) struct ScreenLog {
+  pub message: &'static str,
+  pub fields: AnnotatedLogFields,
+}
+
+struct Screen {
+  name: String,
+  // When the screen last became visible, or `None` while it's hidden behind a modal screen, the
+  // app is in the background or its time on screen has been logged.
+  visible_since: Option<Instant>,
+}
+
+impl Screen {
+  fn new(name: String, now: Instant, foreground: bool) -> Self {
+    Self {
+      name,
+      visible_since: foreground.then_some(now),
+    }
+  }
+
+  // Hides the screen, returning how long it was visible for, if it was.
+  fn hide(&mut self, now: Instant) -> Option<Duration> {
+    self
+      .visible_since
+      .take()
+      .map(|visible_since| now.saturating_duration_since(visible_since))
+  }
+
+  // Hides the screen for good, returning its name and how long it was visible for, if it was.
+  fn leave(&mut self, now: Instant) -> (String, Option<Duration>) {
+    let duration = self.hide(now);
+    (std::mem::take(&mut self.name), duration)
+  }
+}
+
+/// Tracks the screen the user is on, so that each screen view names the screen before it and how
+/// long it was on screen. Modal screens are stacked on top of the screen they're presented over,
+/// which becomes current again once they're dismissed.
+pub(crate) struct ScreenTracker {
+  state: parking_lot::Mutex<State>,
+}
+
+struct State {
+  // The root screen followed by the modal screens presented over it, the last being visible.
+  screens: Vec<Screen>,
+  foreground: bool,
+}
+
+impl Default for ScreenTracker {
+  fn default() -> Self {
+    Self {
+      state: parking_lot::Mutex::new(State {
+        screens: Vec::new(),
+        foreground: true,
+      }),
+    }
+  }
+}
+
+impl ScreenTracker {
+  /// Replaces the current screen, or the current modal screen if one is presented.
+  pub fn view(&self, name: String, now: Instant) -> ScreenLog {
+    let mut state = self.state.lock();
+    let previous = state.screens.pop().map(|mut previous| previous.leave(now));
+    let screen = Screen::new(name, now, state.foreground);
+    state.screens.push(screen);
+
+    view_log(&state, previous)
+  }
+
+  /// Presents a modal screen over the current screen.
+  pub fn present_modal(&self, name: String, now: Instant) -> ScreenLog {
+    let mut state = self.state.lock();
+    let previous = state.screens.last_mut().map(|previous| {
+      let duration = previous.hide(now);
+      (previous.name.clone(), duration)
+    });
+    let screen = Screen::new(name, now, state.foreground);
+    state.screens.push(screen);
+
+    view_log(&state, previous)
+  }
+
+  /// Dismisses the current modal screen, making the screen it was presented over current again.
+  /// Returns `None` if no modal screen is presented.
+  pub fn dismiss_modal(&self, now: Instant) -> Option<ScreenLog> {
+    let mut state = self.state.lock();
+    if state.screens.len() < 2 {
+      return None;
+    }
+
+    let dismissed = state
+      .screens
+      .pop()
+      .map(|mut dismissed| dismissed.leave(now));
+    let visible_since = state.foreground.then_some(now);
+    if let Some(screen) = state.screens.last_mut() {
+      screen.visible_since = visible_since;
+    }
+
+    Some(view_log(&state, dismissed))
+  }
+
+  /// Records whether the app is in the foreground. Moving to the background closes out the time
+  /// spent on the current screen, which starts over once the app is back in the foreground.
+  pub fn set_foreground(&self, foreground: bool, now: Instant) -> Option<ScreenLog> {
+    let mut state = self.state.lock();
+    if state.foreground == foreground {
+      return None;
+    }
+    state.foreground = foreground;
+
+    let screen = state.screens.last_mut()?;
+    if foreground {
+      screen.visible_since = Some(now);
+      None
+    } else {
+      end_log(screen, ScreenEndReason::Background, now)
+    }
+  }
+
+  /// Closes out the time spent on the current screen, if it's visible, and forgets all screens.
+  pub fn close(&self, now: Instant) -> Option<ScreenLog> {
+    let mut screens = std::mem::take(&mut self.state.lock().screens);
+    end_log(screens.last_mut()?, ScreenEndReason::Shutdown, now)
+  }
+}
+
+// Logs a view of the current screen, naming the screen that was current before it and how long it
+// was visible for.
+fn view_log(state: &State, previous: Option<(String, Option<Duration>)>) -> ScreenLog {
+  let mut fields = AnnotatedLogFields::default();
+  if let Some(screen) = state.screens.last() {
+    fields.insert(
+      SCREEN_NAME_FIELD.into(),
+      AnnotatedLogField::new_ootb(screen.name.clone()),
+    );
+  }
+  if state.screens.len() > 1 {
+    fields.insert(
+      MODAL_FIELD.into(),
+      AnnotatedLogField::new_ootb("true".to_string()),
+    );
+  }
+  if let Some((name, duration)) = previous {
+    fields.insert(
+      PREVIOUS_SCREEN_NAME_FIELD.into(),
+      AnnotatedLogField::new_ootb(name),
+    );
+    // A screen that wasn't visible, e.g. because the app was in the background, has already had
+    // its time on screen logged.
+    if let Some(duration) = duration {
+      fields.insert(
+        PREVIOUS_SCREEN_DURATION_FIELD.into(),
+        AnnotatedLogField::new_ootb(duration_ms(duration)),
+      );
+    }
+  }
+
+  ScreenLog {
+    message: SCREEN_VIEW_MESSAGE,
+    fields,
+  }
+}
+
+fn end_log(screen: &mut Screen, reason: ScreenEndReason, now: Instant) -> Option<ScreenLog> {
+  let duration = screen.hide(now)?;
+
+  Some(ScreenLog {
+    message: SCREEN_VIEW_END_MESSAGE,
+    fields: [
+      (
+        SCREEN_NAME_FIELD.into(),
+        AnnotatedLogField::new_ootb(screen.name.clone()),
+      ),
+      (
+        SCREEN_DURATION_FIELD.into(),
+        AnnotatedLogField::new_ootb(duration_ms(duration)),
+      ),
+      (
+        SCREEN_END_REASON_FIELD.into(),
+        AnnotatedLogField::new_ootb(reason.as_str().to_string()),
+      ),
+    ]
+    .into(),
+  })
+}
+
+fn duration_ms(duration: Duration) -> String {
+  (duration.as_secs_f64() * 1_000f64).to_string()
+}
@@ -0,0 +1,152 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{
+  MODAL_FIELD,
+  PREVIOUS_SCREEN_DURATION_FIELD,
+  PREVIOUS_SCREEN_NAME_FIELD,
+  SCREEN_DURATION_FIELD,
+  SCREEN_END_REASON_FIELD,
+  SCREEN_NAME_FIELD,
+  SCREEN_VIEW_END_MESSAGE,
+  SCREEN_VIEW_MESSAGE,
+  ScreenLog,
+  ScreenTracker,
+};
+use std::time::{Duration, Instant};
+
+fn field<'a>(log: &'a ScreenLog, key: &str) -> Option<&'a str> {
+  log.fields.get(key).and_then(|field| field.value.as_str())
+}
+
+#[test]
+fn views_name_the_previous_screen_and_its_duration() {
+  let tracker = ScreenTracker::default();
+  let start = Instant::now();
+
+  let first = tracker.view("home".to_string(), start);
+  assert_eq!(first.message, SCREEN_VIEW_MESSAGE);
+  assert_eq!(field(&first, SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&first, PREVIOUS_SCREEN_NAME_FIELD), None);
+  assert_eq!(field(&first, PREVIOUS_SCREEN_DURATION_FIELD), None);
+  assert_eq!(field(&first, MODAL_FIELD), None);
+
+  let second = tracker.view("settings".to_string(), start + Duration::from_millis(1_500));
+  assert_eq!(field(&second, SCREEN_NAME_FIELD), Some("settings"));
+  assert_eq!(field(&second, PREVIOUS_SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&second, PREVIOUS_SCREEN_DURATION_FIELD), Some("1500"));
+}
+
+#[test]
+fn modal_screens_keep_the_screen_they_are_presented_over() {
+  let tracker = ScreenTracker::default();
+  let start = Instant::now();
+
+  tracker.view("home".to_string(), start);
+  let sheet = tracker.present_modal("share".to_string(), start + Duration::from_secs(1));
+  assert_eq!(field(&sheet, SCREEN_NAME_FIELD), Some("share"));
+  assert_eq!(field(&sheet, MODAL_FIELD), Some("true"));
+  assert_eq!(field(&sheet, PREVIOUS_SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&sheet, PREVIOUS_SCREEN_DURATION_FIELD), Some("1000"));
+
+  // Navigating within the modal replaces the modal screen only.
+  let step = tracker.view("share_confirm".to_string(), start + Duration::from_secs(3));
+  assert_eq!(field(&step, MODAL_FIELD), Some("true"));
+  assert_eq!(field(&step, PREVIOUS_SCREEN_NAME_FIELD), Some("share"));
+  assert_eq!(field(&step, PREVIOUS_SCREEN_DURATION_FIELD), Some("2000"));
+
+  let dismissed = tracker
+    .dismiss_modal(start + Duration::from_secs(4))
+    .unwrap();
+  assert_eq!(field(&dismissed, SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&dismissed, MODAL_FIELD), None);
+  assert_eq!(
+    field(&dismissed, PREVIOUS_SCREEN_NAME_FIELD),
+    Some("share_confirm")
+  );
+  assert_eq!(
+    field(&dismissed, PREVIOUS_SCREEN_DURATION_FIELD),
+    Some("1000")
+  );
+
+  // The parent screen's time on screen starts over once the modal is dismissed.
+  let next = tracker.view("profile".to_string(), start + Duration::from_secs(6));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_DURATION_FIELD), Some("2000"));
+
+  assert!(
+    tracker
+      .dismiss_modal(start + Duration::from_secs(7))
+      .is_none()
+  );
+}
+
+#[test]
+fn backgrounding_closes_out_the_current_screen() {
+  let tracker = ScreenTracker::default();
+  let start = Instant::now();
+
+  assert!(tracker.set_foreground(false, start).is_none());
+  assert!(tracker.set_foreground(true, start).is_none());
+
+  tracker.view("home".to_string(), start);
+  let end = tracker
+    .set_foreground(false, start + Duration::from_secs(2))
+    .unwrap();
+  assert_eq!(end.message, SCREEN_VIEW_END_MESSAGE);
+  assert_eq!(field(&end, SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&end, SCREEN_DURATION_FIELD), Some("2000"));
+  assert_eq!(field(&end, SCREEN_END_REASON_FIELD), Some("background"));
+  assert!(
+    tracker
+      .set_foreground(false, start + Duration::from_secs(3))
+      .is_none()
+  );
+
+  // The time spent in the background isn't counted.
+  assert!(
+    tracker
+      .set_foreground(true, start + Duration::from_secs(10))
+      .is_none()
+  );
+  let next = tracker.view("settings".to_string(), start + Duration::from_secs(11));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_NAME_FIELD), Some("home"));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_DURATION_FIELD), Some("1000"));
+}
+
+#[test]
+fn screens_viewed_in_the_background_have_no_duration() {
+  let tracker = ScreenTracker::default();
+  let start = Instant::now();
+
+  tracker.view("home".to_string(), start);
+  tracker.set_foreground(false, start + Duration::from_secs(1));
+  tracker.view("settings".to_string(), start + Duration::from_secs(2));
+
+  let next = tracker.view("profile".to_string(), start + Duration::from_secs(3));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_NAME_FIELD), Some("settings"));
+  assert_eq!(field(&next, PREVIOUS_SCREEN_DURATION_FIELD), None);
+}
+
+#[test]
+fn closing_ends_the_visible_screen() {
+  let tracker = ScreenTracker::default();
+  let start = Instant::now();
+
+  assert!(tracker.close(start).is_none());
+
+  tracker.view("home".to_string(), start);
+  tracker.present_modal("share".to_string(), start + Duration::from_secs(1));
+  let end = tracker.close(start + Duration::from_secs(4)).unwrap();
+  assert_eq!(field(&end, SCREEN_NAME_FIELD), Some("share"));
+  assert_eq!(field(&end, SCREEN_DURATION_FIELD), Some("3000"));
+  assert_eq!(field(&end, SCREEN_END_REASON_FIELD), Some("shutdown"));
+
+  assert!(tracker.close(start + Duration::from_secs(5)).is_none());
+}
@@ -0,0 +1,223 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./span_test.rs"]
+mod tests;
+
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields, LogLevel};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+// The field names are shared with the spans the platform SDKs log themselves, so that both kinds
+// of span are read the same way.
+pub const SPAN_ID_FIELD: &str = "_span_id";
+pub const SPAN_NAME_FIELD: &str = "_span_name";
+pub const SPAN_TYPE_FIELD: &str = "_span_type";
+pub const SPAN_PARENT_ID_FIELD: &str = "_span_parent_id";
+pub const SPAN_DURATION_FIELD: &str = "_duration_ms";
+pub const SPAN_RESULT_FIELD: &str = "_result";
+
+const SPAN_TYPE_START: &str = "start";
+const SPAN_TYPE_END: &str = "end";
+
+/// How a span ended. The values are part of the FFI.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpanResult {
+  Success   = 0,
+  Failure   = 1,
+  Canceled  = 2,
+  Unknown   = 3,
+  /// The span was still open when the logger shut down.
+  Abandoned = 4,
+}
+
+impl SpanResult {
+  #[must_use]
+  pub const fn from_i32(value: i32) -> Option<Self> {
+    match value {
+      0 => Some(Self::Success),
+      1 => Some(Self::Failure),
+      2 => Some(Self::Canceled),
+      3 => Some(Self::Unknown),
+      4 => Some(Self::Abandoned),
+      _ => None,
+    }
+  }
+
+  const fn as_str(self) -> &'static str {
+    match self {
+      Self::Success => "success",
+      Self::Failure => "failure",
+      Self::Canceled => "canceled",
+      Self::Unknown => "unknown",
+      Self::Abandoned => "abandoned",
+    }
+  }
+}
+
+/// Identifies a span started with `LoggerHolder::start_span` until it's ended. Handles are
+/// positive, so the platform code can pass 0 for no span.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpanHandle(u64);
+
+impl SpanHandle {
+  #[must_use]
+  pub fn from_raw(value: i64) -> Option<Self> {
+    u64::try_from(value)
+      .ok()
+      .filter(|value| *value > 0)
+      .map(Self)
+  }
+}
+
+impl From<SpanHandle> for i64 {
+  fn from(handle: SpanHandle) -> Self {
+    // Handles are handed out sequentially, so they never get close to overflowing.
+    Self::try_from(handle.0).unwrap_or(Self::MAX)
+  }
+}
+
+/// A span log for the logger to write.
+#[derive(Debug)]
+pub(crate) struct SpanLog {
+  // The span's name, which both its start and end logs use as their message.
+  pub message: String,
+  pub level: LogLevel,
+  pub fields: AnnotatedLogFields,
+}
+
+struct ActiveSpan {
+  id: String,
+  name: String,
+  parent_id: Option<String>,
+  level: LogLevel,
+  started_at: Instant,
+  // The fields the span was started with, which are repeated on its end log.
+  fields: AnnotatedLogFields,
+}
+
+impl ActiveSpan {
+  fn core_fields(&self, span_type: &str) -> AnnotatedLogFields {
+    let mut fields: AnnotatedLogFields = [
+      (
+        SPAN_ID_FIELD.into(),
+        AnnotatedLogField::new_ootb(self.id.clone()),
+      ),
+      (
+        SPAN_NAME_FIELD.into(),
+        AnnotatedLogField::new_ootb(self.name.clone()),
+      ),
+      (
+        SPAN_TYPE_FIELD.into(),
+        AnnotatedLogField::new_ootb(span_type.to_string()),
+      ),
+    ]
+    .into();
+    if let Some(parent_id) = &self.parent_id {
+      fields.insert(
+        SPAN_PARENT_ID_FIELD.into(),
+        AnnotatedLogField::new_ootb(parent_id.clone()),
+      );
+    }
+    fields
+  }
+
+  fn end(self, result: SpanResult, fields: AnnotatedLogFields) -> SpanLog {
+    let duration_ms = self.started_at.elapsed().as_secs_f64() * 1_000f64;
+
+    let mut end_fields = self.core_fields(SPAN_TYPE_END);
+    end_fields.insert(
+      SPAN_DURATION_FIELD.into(),
+      AnnotatedLogField::new_ootb(duration_ms.to_string()),
+    );
+    end_fields.insert(
+      SPAN_RESULT_FIELD.into(),
+      AnnotatedLogField::new_ootb(result.as_str().to_string()),
+    );
+
+    SpanLog {
+      message: self.name,
+      level: self.level,
+      fields: merge_fields([self.fields, fields, end_fields]),
+    }
+  }
+}
+
+/// The spans that have been started but not yet ended.
+#[derive(Default)]
+pub(crate) struct Spans {
+  next_handle: AtomicU64,
+  active: parking_lot::Mutex<HashMap<SpanHandle, ActiveSpan>>,
+}
+
+impl Spans {
+  /// Starts a span, returning its handle and start log. A parent that isn't active is ignored.
+  pub fn start(
+    &self,
+    name: String,
+    level: LogLevel,
+    fields: AnnotatedLogFields,
+    parent: Option<SpanHandle>,
+  ) -> (SpanHandle, SpanLog) {
+    let handle = SpanHandle(self.next_handle.fetch_add(1, Ordering::Relaxed) + 1);
+
+    let mut active = self.active.lock();
+    let parent_id = parent.and_then(|parent| {
+      let parent_id = active.get(&parent).map(|span| span.id.clone());
+      if parent_id.is_none() {
+        log::warn!("starting span {name:?} as a root span: its parent span is not active");
+      }
+      parent_id
+    });
+
+    let span = ActiveSpan {
+      id: uuid::Uuid::new_v4().to_string(),
+      name,
+      parent_id,
+      level,
+      started_at: Instant::now(),
+      fields,
+    };
+    let log = SpanLog {
+      message: span.name.clone(),
+      level,
+      fields: merge_fields([span.fields.clone(), span.core_fields(SPAN_TYPE_START)]),
+    };
+    active.insert(handle, span);
+
+    (handle, log)
+  }
+
+  /// Ends a span, returning its end log, or `None` if the span isn't active.
+  pub fn end(
+    &self,
+    handle: SpanHandle,
+    result: SpanResult,
+    fields: AnnotatedLogFields,
+  ) -> Option<SpanLog> {
+    let span = self.active.lock().remove(&handle)?;
+    Some(span.end(result, fields))
+  }
+
+  /// Ends every active span as abandoned, returning their end logs.
+  pub fn abandon_all(&self) -> Vec<SpanLog> {
+    self
+      .active
+      .lock()
+      .drain()
+      .map(|(_, span)| span.end(SpanResult::Abandoned, AnnotatedLogFields::default()))
+      .collect()
+  }
+}
+
+// Merges the fields in order, so that later fields replace earlier ones with the same key.
+fn merge_fields<const N: usize>(fields: [AnnotatedLogFields; N]) -> AnnotatedLogFields {
+  fields.into_iter().flatten().collect()
+}
@@ -0,0 +1,179 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#![allow(clippy::unwrap_used)]
+
+use super::{
+  SPAN_DURATION_FIELD,
+  SPAN_ID_FIELD,
+  SPAN_NAME_FIELD,
+  SPAN_PARENT_ID_FIELD,
+  SPAN_RESULT_FIELD,
+  SPAN_TYPE_FIELD,
+  SpanHandle,
+  SpanLog,
+  SpanResult,
+  Spans,
+};
+use bd_logger::{AnnotatedLogField, AnnotatedLogFields, log_level};
+
+fn fields(fields: &[(&'static str, &str)]) -> AnnotatedLogFields {
+  fields
+    .iter()
+    .map(|(key, value)| {
+      (
+        (*key).into(),
+        AnnotatedLogField::new_ootb((*value).to_string()),
+      )
+    })
+    .collect()
+}
+
+fn field<'a>(log: &'a SpanLog, key: &str) -> Option<&'a str> {
+  log.fields.get(key).and_then(|field| field.value.as_str())
+}
+
+#[test]
+fn span_logs_start_and_end() {
+  let spans = Spans::default();
+
+  let (handle, start) = spans.start(
+    "load".to_string(),
+    log_level::DEBUG,
+    fields(&[("screen", "home"), (SPAN_TYPE_FIELD, "custom")]),
+    None,
+  );
+  assert_eq!(start.message, "load");
+  assert_eq!(start.level, log_level::DEBUG);
+  assert_eq!(field(&start, SPAN_NAME_FIELD), Some("load"));
+  // Span fields take precedence over the caller's.
+  assert_eq!(field(&start, SPAN_TYPE_FIELD), Some("start"));
+  assert_eq!(field(&start, "screen"), Some("home"));
+  assert_eq!(field(&start, SPAN_PARENT_ID_FIELD), None);
+  assert_eq!(field(&start, SPAN_DURATION_FIELD), None);
+
+  let end = spans
+    .end(
+      handle,
+      SpanResult::Failure,
+      fields(&[("screen", "settings"), ("items", "3")]),
+    )
+    .unwrap();
+  assert_eq!(end.message, "load");
+  assert_eq!(end.level, log_level::DEBUG);
+  assert_eq!(field(&end, SPAN_ID_FIELD), field(&start, SPAN_ID_FIELD));
+  assert_eq!(field(&end, SPAN_NAME_FIELD), Some("load"));
+  assert_eq!(field(&end, SPAN_TYPE_FIELD), Some("end"));
+  assert_eq!(field(&end, SPAN_RESULT_FIELD), Some("failure"));
+  assert_eq!(field(&end, "screen"), Some("settings"));
+  assert_eq!(field(&end, "items"), Some("3"));
+  assert!(
+    field(&end, SPAN_DURATION_FIELD)
+      .unwrap()
+      .parse::<f64>()
+      .unwrap()
+      >= 0.0
+  );
+
+  // A span can only be ended once.
+  assert!(
+    spans
+      .end(handle, SpanResult::Success, AnnotatedLogFields::default())
+      .is_none()
+  );
+}
+
+#[test]
+fn child_spans_refer_to_their_parent() {
+  let spans = Spans::default();
+
+  let (parent, parent_start) = spans.start(
+    "parent".to_string(),
+    log_level::INFO,
+    AnnotatedLogFields::default(),
+    None,
+  );
+  let (child, child_start) = spans.start(
+    "child".to_string(),
+    log_level::INFO,
+    AnnotatedLogFields::default(),
+    Some(parent),
+  );
+  assert_ne!(parent, child);
+  assert_eq!(
+    field(&child_start, SPAN_PARENT_ID_FIELD),
+    field(&parent_start, SPAN_ID_FIELD)
+  );
+
+  let child_end = spans
+    .end(child, SpanResult::Success, AnnotatedLogFields::default())
+    .unwrap();
+  assert_eq!(
+    field(&child_end, SPAN_PARENT_ID_FIELD),
+    field(&parent_start, SPAN_ID_FIELD)
+  );
+
+  // A parent that has already ended is ignored.
+  spans.end(parent, SpanResult::Success, AnnotatedLogFields::default());
+  let (_, orphan_start) = spans.start(
+    "orphan".to_string(),
+    log_level::INFO,
+    AnnotatedLogFields::default(),
+    Some(parent),
+  );
+  assert_eq!(field(&orphan_start, SPAN_PARENT_ID_FIELD), None);
+}
+
+#[test]
+fn open_spans_are_abandoned() {
+  let spans = Spans::default();
+
+  let (ended, _) = spans.start(
+    "ended".to_string(),
+    log_level::INFO,
+    AnnotatedLogFields::default(),
+    None,
+  );
+  spans.start(
+    "open".to_string(),
+    log_level::INFO,
+    fields(&[("key", "value")]),
+    None,
+  );
+  spans.end(ended, SpanResult::Success, AnnotatedLogFields::default());
+
+  let abandoned = spans.abandon_all();
+  assert_eq!(abandoned.len(), 1);
+  assert_eq!(field(&abandoned[0], SPAN_NAME_FIELD), Some("open"));
+  assert_eq!(field(&abandoned[0], SPAN_RESULT_FIELD), Some("abandoned"));
+  assert_eq!(field(&abandoned[0], "key"), Some("value"));
+
+  assert!(spans.abandon_all().is_empty());
+}
+
+#[test]
+fn handles_round_trip_through_their_raw_value() {
+  let spans = Spans::default();
+  let (handle, _) = spans.start(
+    "span".to_string(),
+    log_level::INFO,
+    AnnotatedLogFields::default(),
+    None,
+  );
+
+  assert_eq!(SpanHandle::from_raw(handle.into()), Some(handle));
+  assert_eq!(SpanHandle::from_raw(0), None);
+  assert_eq!(SpanHandle::from_raw(-1), None);
+}
+
+#[test]
+fn results_map_from_their_values() {
+  assert_eq!(SpanResult::from_i32(0), Some(SpanResult::Success));
+  assert_eq!(SpanResult::from_i32(2), Some(SpanResult::Canceled));
+  assert_eq!(SpanResult::from_i32(4), Some(SpanResult::Abandoned));
+  assert_eq!(SpanResult::from_i32(5), None);
+}
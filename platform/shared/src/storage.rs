@@ -0,0 +1,79 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+#[cfg(test)]
+#[path = "./storage_test.rs"]
+mod tests;
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+//
+// FileStorage
+//
+
+/// Key-value storage kept in a JSON file, for hosts without a platform store such as
+/// `UserDefaults` or `SharedPreferences`. Values are cached in memory, and every change rewrites
+/// the file through a temporary sibling that replaces it, so a process that dies mid-write leaves
+/// the previous contents in place.
+pub struct FileStorage {
+  path: PathBuf,
+  values: Mutex<HashMap<String, String>>,
+}
+
+impl FileStorage {
+  /// Opens the storage kept at `path`, starting empty if the file is missing or unreadable.
+  #[must_use]
+  pub fn open(path: PathBuf) -> Self {
+    let values = match fs::read(&path) {
+      Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        log::warn!("discarding unreadable storage at {}: {e}", path.display());
+        HashMap::new()
+      }),
+      Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+      Err(e) => {
+        log::warn!("failed to read storage at {}: {e}", path.display());
+        HashMap::new()
+      },
+    };
+
+    Self {
+      path,
+      values: Mutex::new(values),
+    }
+  }
+
+  fn persist(&self, values: &HashMap<String, String>) -> anyhow::Result<()> {
+    let temporary_path = self.path.with_extension("tmp");
+    fs::write(&temporary_path, serde_json::to_vec(values)?)?;
+    fs::rename(&temporary_path, &self.path)?;
+    Ok(())
+  }
+}
+
+impl bd_key_value::Storage for FileStorage {
+  fn set_string(&self, key: &str, value: &str) -> anyhow::Result<()> {
+    let mut values = self.values.lock();
+    values.insert(key.to_string(), value.to_string());
+    self.persist(&values)
+  }
+
+  fn get_string(&self, key: &str) -> anyhow::Result<Option<String>> {
+    Ok(self.values.lock().get(key).cloned())
+  }
+
+  fn delete(&self, key: &str) -> anyhow::Result<()> {
+    let mut values = self.values.lock();
+    if values.remove(key).is_none() {
+      return Ok(());
+    }
+    self.persist(&values)
+  }
+}
@@ -0,0 +1,44 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+use super::FileStorage;
+use bd_key_value::Storage as _;
+
+#[test]
+fn values_persist_across_opens() -> anyhow::Result<()> {
+  let directory = tempfile::tempdir()?;
+  let path = directory.path().join("storage.json");
+
+  let storage = FileStorage::open(path.clone());
+  storage.set_string("device_id", "abc")?;
+  storage.set_string("session", "123")?;
+  storage.delete("session")?;
+  storage.delete("missing")?;
+  assert_eq!(storage.get_string("device_id")?.as_deref(), Some("abc"));
+
+  let reopened = FileStorage::open(path);
+  assert_eq!(reopened.get_string("device_id")?.as_deref(), Some("abc"));
+  assert_eq!(reopened.get_string("session")?, None);
+  Ok(())
+}
+
+#[test]
+fn unreadable_file_starts_empty() -> anyhow::Result<()> {
+  let directory = tempfile::tempdir()?;
+  let path = directory.path().join("storage.json");
+  std::fs::write(&path, b"not json")?;
+
+  let storage = FileStorage::open(path.clone());
+  assert_eq!(storage.get_string("device_id")?, None);
+
+  storage.set_string("device_id", "abc")?;
+  assert_eq!(
+    FileStorage::open(path).get_string("device_id")?.as_deref(),
+    Some("abc")
+  );
+  Ok(())
+}
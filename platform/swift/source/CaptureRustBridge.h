@@ -185,6 +185,41 @@ void capture_write_app_launch_tti_log(
     double duration_s
 );
 
+/*
+ * Begins measuring an app launch, finishing and writing the log of a launch that is still being
+ * measured. Warm and hot launches can be measured on every transition to the foreground, but only
+ * the first cold launch is measured.
+ *
+ * @param logger_id the ID of the logger to write to.
+ * @param launch_type the type of the launch: 0 for cold, 1 for warm and 2 for hot.
+ */
+void capture_begin_app_launch(
+    logger_id logger_id,
+    int32_t launch_type
+);
+
+/*
+ * Records how long it took the current app launch to reach a phase. Reaching the fully drawn phase
+ * finishes the launch and writes its log.
+ *
+ * @param logger_id the ID of the logger to write to.
+ * @param phase the phase reached: 0 for first frame, 1 for TTI and 2 for fully drawn.
+ * @param duration_s the time between the start of the launch and the phase being reached. Calls
+ *        with a negative duration are ignored.
+ */
+void capture_record_app_launch_phase(
+    logger_id logger_id,
+    int32_t phase,
+    double duration_s
+);
+
+/*
+ * Finishes the current app launch and writes its log with the phases it reached.
+ *
+ * @param logger_id the ID of the logger to write to.
+ */
+void capture_finish_app_launch(logger_id logger_id);
+
 /*
 * Writes a screen view log.
 *
@@ -196,6 +231,72 @@ void capture_write_screen_view_log(
     NSString *screen_name
 );
 
+/*
+ * Writes a screen view log for a modal screen, such as a sheet, presented over the current screen.
+ * The current screen becomes current again once the modal screen is dismissed.
+ *
+ * @param logger_id the ID of the logger to write to.
+ * @param screen_name the name of the modal screen.
+ */
+void capture_write_modal_screen_view_log(
+    logger_id logger_id,
+    NSString *screen_name
+);
+
+/*
+ * Dismisses the current modal screen, writing a screen view log for the screen it was presented
+ * over. Has no effect if no modal screen is presented.
+ *
+ * @param logger_id the ID of the logger to write to.
+ */
+void capture_dismiss_modal_screen(logger_id logger_id);
+
+/*
+ * Tells the logger whether the app is in the foreground. Moving to the background closes out the
+ * time spent on the current screen.
+ *
+ * @param logger_id the ID of the logger to notify.
+ * @param foreground whether the app is in the foreground.
+ */
+void capture_set_app_foreground(logger_id logger_id, bool foreground);
+
+/*
+ * Starts a span and writes its start log.
+ *
+ * @param logger_id the ID of the logger to write to.
+ * @param name the name of the span.
+ * @param log_level the log level of the span's logs.
+ * @param fields the fields to attach to the span's start and end logs.
+ * @param parent_span_handle the handle of the span to nest this span under, or 0 for a root span.
+ *
+ * @return the handle to end the span with, or 0 if the span couldn't be started.
+ */
+int64_t capture_start_span(
+    logger_id logger_id,
+    NSString *name,
+    int32_t log_level,
+    const NSArray<const Field *> *_Nullable fields,
+    int64_t parent_span_handle
+);
+
+/*
+ * Ends a span started with `capture_start_span` and writes its end log. Ending a span that has
+ * already ended has no effect. Spans that are still open when the logger shuts down end as
+ * abandoned.
+ *
+ * @param logger_id the ID of the logger to write to.
+ * @param span_handle the handle returned by `capture_start_span`.
+ * @param result the result of the span: 0 for success, 1 for failure, 2 for canceled and 3 for
+ *        unknown.
+ * @param fields the fields to attach to the span's end log.
+ */
+void capture_end_span(
+    logger_id logger_id,
+    int64_t span_handle,
+    int32_t result,
+    const NSArray<const Field *> *_Nullable fields
+);
+
 /*
  * Starts new sessions using configured session strategy.
  *
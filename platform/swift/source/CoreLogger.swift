@@ -170,6 +170,10 @@ extension CoreLogger: CoreLogging {
         self.underlyingLogger.flush(blocking: blocking)
     }
 
+    func setAppForeground(_ foreground: Bool) {
+        self.underlyingLogger.setAppForeground(foreground)
+    }
+
     func setFeatureFlagExposure(withName name: String, variant: String) {
         self.underlyingLogger.setFeatureFlagExposure(withName: name, variant: variant)
     }
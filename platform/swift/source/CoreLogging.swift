@@ -157,6 +157,12 @@ protocol CoreLogging: AnyObject {
     ///                       main thread's event processing.
     func flush(blocking: Bool)
 
+    /// Tells the logger and the crash reporter whether the app is in the foreground. Moving to the
+    /// background closes out the time spent on the current screen.
+    ///
+    /// - parameter foreground: Whether the app is in the foreground.
+    func setAppForeground(_ foreground: Bool)
+
     /// Sets a feature flag exposure with a variant.
     ///
     /// - parameter flag:    The name of the flag exposure to set
@@ -267,6 +267,11 @@ final class LoggerBridge: LoggerBridging {
         }
     }
 
+    func setAppForeground(_ foreground: Bool) {
+        capture_set_app_foreground(self.loggerID, foreground)
+        BitdriftCrashHandler.setForeground(foreground)
+    }
+
     func setFeatureFlagExposure(withName flag: String, variant: String) {
         capture_set_feature_flag_exposure(self.loggerID, flag, variant)
     }
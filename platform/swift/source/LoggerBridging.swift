@@ -72,6 +72,11 @@ protocol LoggerBridging {
     ///                       main thread's event processing.
     func flush(blocking: Bool)
 
+    /// Tells the logger and the crash reporter whether the app is in the foreground.
+    ///
+    /// - parameter foreground: Whether the app is in the foreground.
+    func setAppForeground(_ foreground: Bool)
+
     /// Sets a feature flag exposure with a variant.
     ///
     /// - parameter name:    The name of the flag exposure to set
@@ -35,6 +35,8 @@ extension LoggerLifecycleController: EventListener {
             object: nil,
             queue: nil
         ) { [weak logger] _ in
+            logger?.setAppForeground(false)
+
             /// Flush state in a non-blocking way for cases when the app went to the background.
             /// The idea here is that non-active apps have a higher likelihood of being suspended or killed by
             /// the system.
@@ -43,6 +45,14 @@ extension LoggerLifecycleController: EventListener {
             }
         })
 
+        self.tokens.append(notificationCenter.addObserver(
+            forName: UIApplication.willEnterForegroundNotification,
+            object: nil,
+            queue: nil
+        ) { [weak logger] _ in
+            logger?.setAppForeground(true)
+        })
+
         self.tokens.append(notificationCenter.addObserver(
             forName: UIApplication.willResignActiveNotification,
             object: nil,
@@ -16,6 +16,10 @@ typedef NS_ENUM(NSUInteger, BitdriftPreviousCrashKind) {
     BitdriftPreviousCrashKindNone = 0,
     /// The previous launch ended in an uncaught `NSException`. See `BitdriftPreviousCrash.nsexception`.
     BitdriftPreviousCrashKindNSException = 1,
+    /// The previous launch was terminated by a fatal POSIX signal. Only captured on non-Apple platforms.
+    BitdriftPreviousCrashKindSignal = 2,
+    /// The previous launch ended in a Rust panic inside the SDK.
+    BitdriftPreviousCrashKindRustPanic = 3,
 };
 
 /// An uncaught `NSException` captured by the bitdrift crash reporter during the previous launch.
@@ -59,9 +63,11 @@ typedef NS_ENUM(NSUInteger, BitdriftPreviousCrashKind) {
 - (BOOL)configureWithCrashReportDirectory:(NSURL *)crashReportDir error:(NSError **)error;
 - (BOOL)startCrashReporterWithError:(NSError **)error;
 - (void)stopCrashReporter;
+- (void)markCleanShutdown;
 - (NSNumber *_Nullable)didCrashLastLaunch;
 - (NSDate *_Nullable)cachedCrashDate;
 - (BitdriftPreviousCrash *_Nullable)cachedPreviousCrash;
+- (BOOL)writePreviousCrashReportToDirectory:(NSURL *)reportDir sdkVersion:(NSString *)sdkVersion;
 @end
 
 /// Instance API conforms to `BitdriftCrashHandling` for use via `CrashReporterService`; the
@@ -76,9 +82,31 @@ typedef NS_ENUM(NSUInteger, BitdriftPreviousCrashKind) {
 + (NSNumber *_Nullable)didCrashLastLaunch;
 + (NSDate * _Nullable)cachedCrashDate;
 + (BitdriftPreviousCrash * _Nullable)cachedPreviousCrash;
+/// Everything the crash reporter cached about the previous launch, decoded from the JSON document
+/// exported by `bd-crash-reporter`: the crash and its details under `kind` and `details`, plus
+/// annotations, breadcrumbs, crash history and how the launch ended. Returns nil before the
+/// crash reporter has been configured.
++ (NSDictionary * _Nullable)cachedPreviousCrashState;
+/// The previous launch's crashing call stack, arranged for symbolication: `images` lists every
+/// image the stack runs through once, and each entry of `frames` refers to its image by
+/// `image_index` along with its `offset` from the image's load address. Returns nil when the
+/// previous launch didn't crash with a native call stack.
++ (NSDictionary * _Nullable)cachedPreviousCrashSymbolication;
+/// Converts the previous launch's crash into an issue report, tagged with the app's bundle and OS
+/// details, and writes it into `reportDir` so it's uploaded with the other issue reports. Returns
+/// NO when the previous launch didn't crash, the crash reporter hasn't been configured, or the
+/// report couldn't be written.
++ (BOOL)writePreviousCrashReportToDirectory:(NSURL *)reportDir sdkVersion:(NSString *)sdkVersion;
 + (NSString * _Nullable)cachedExceptionName;
 + (NSString * _Nullable)cachedExceptionReason;
+/// Tells the crash reporter whether the app is in the foreground, so that a crash can be
+/// attributed to the state the app was in. The state is process-wide and can be set before the
+/// crash reporter is configured.
++ (void)setForeground:(BOOL)foreground;
 + (void)stopCrashReporter;
+/// Marks the current run as cleanly shut down while keeping the crash monitors installed, so the
+/// next launch reports a clean exit unless a crash is recorded first.
++ (void)markCleanShutdown;
 
 @end
 
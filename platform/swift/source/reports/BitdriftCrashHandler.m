@@ -7,9 +7,23 @@
 
 #import "BitdriftCrashHandler.h"
 
-bool capture_bitdrift_crash_configure(const char *state_path);
+#import <TargetConditionals.h>
+#import <sys/sysctl.h>
+
+typedef struct {
+    const char *_Nullable app_id;
+    const char *_Nullable app_version;
+    const char *_Nullable build_number;
+    const char *_Nullable os_brand;
+    const char *_Nullable os_version;
+    const char *_Nullable manufacturer;
+    const char *_Nullable model;
+} CrashReportMetadata;
+
+bool capture_bitdrift_crash_configure_for_process(const char *state_path, const char *_Nullable process_name);
 bool capture_bitdrift_crash_start(void);
 void capture_bitdrift_crash_stop(void);
+void capture_bitdrift_crash_mark_clean_shutdown(void);
 int8_t capture_bitdrift_crash_did_crash_last_launch(void);
 uint64_t capture_bitdrift_crash_cached_timestamp(void);
 uint8_t capture_bitdrift_crash_cached_kind(void);
@@ -20,6 +34,14 @@ const uint64_t *_Nullable capture_bitdrift_crash_last_exception_call_stack_retur
 uint64_t capture_bitdrift_crash_last_exception_call_stack_image_load_address_at(uint16_t frame_index);
 const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_binary_name_at(uint16_t frame_index);
 const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_at(uint16_t frame_index);
+char *_Nullable capture_bitdrift_crash_previous_state_json(void);
+void capture_bitdrift_crash_free_previous_state_json(char *_Nullable json);
+char *_Nullable capture_bitdrift_crash_previous_symbolication_json(void);
+void capture_bitdrift_crash_free_symbolication_json(char *_Nullable json);
+bool capture_bitdrift_crash_write_previous_crash_report(const char *report_directory,
+                                                        const char *sdk_version,
+                                                        const CrashReportMetadata *_Nullable metadata);
+void capture_bitdrift_crash_set_foreground(bool foreground);
 
 @interface BitdriftNSExceptionCrash ()
 
@@ -112,6 +134,10 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
     capture_bitdrift_crash_stop();
 }
 
+- (void)markCleanShutdown {
+    [BitdriftCrashHandler markCleanShutdown];
+}
+
 - (NSNumber *_Nullable)didCrashLastLaunch {
     return [BitdriftCrashHandler didCrashLastLaunch];
 }
@@ -124,6 +150,10 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
     return [BitdriftCrashHandler cachedPreviousCrash];
 }
 
+- (BOOL)writePreviousCrashReportToDirectory:(NSURL *)reportDir sdkVersion:(NSString *)sdkVersion {
+    return [BitdriftCrashHandler writePreviousCrashReportToDirectory:reportDir sdkVersion:sdkVersion];
+}
+
 // MARK: - Static methods
 
 + (BOOL)configureWithCrashReportDirectory:(NSURL *)crashReportDir error:(NSError **)error {
@@ -148,7 +178,7 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
         }
     }
 
-    BOOL configured = capture_bitdrift_crash_configure(statePath.UTF8String);
+    BOOL configured = capture_bitdrift_crash_configure_for_process(statePath.UTF8String, [self processName].UTF8String);
     if (!configured && error != nil) {
         *error = [NSError errorWithDomain:@"BitdriftCrashHandler" code:0 userInfo:@{
             NSLocalizedDescriptionKey: @"Configuration failed",
@@ -158,6 +188,16 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
     return configured;
 }
 
+// App extensions can share the app's crash report directory through an app group, so each one
+// names its own state file after its bundle identifier. The app itself uses the unnamed state file.
++ (NSString *_Nullable)processName {
+    NSBundle *bundle = NSBundle.mainBundle;
+    if ([bundle.bundlePath.pathExtension isEqualToString:@"appex"]) {
+        return bundle.bundleIdentifier;
+    }
+    return nil;
+}
+
 + (BOOL)startCrashReporterWithError:(NSError **)error {
     BOOL started = capture_bitdrift_crash_start();
     if (!started && error != nil) {
@@ -215,6 +255,84 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
     return [[BitdriftPreviousCrash alloc] initWithKind:kind crashDate:crashDate nsexception:nsexception];
 }
 
++ (NSDictionary * _Nullable)cachedPreviousCrashState {
+    char *json = capture_bitdrift_crash_previous_state_json();
+    if (json == NULL) {
+        return nil;
+    }
+
+    NSData *data = [NSData dataWithBytes:json length:strlen(json)];
+    capture_bitdrift_crash_free_previous_state_json(json);
+    id state = [NSJSONSerialization JSONObjectWithData:data options:0 error:nil];
+    return [state isKindOfClass:[NSDictionary class]] ? state : nil;
+}
+
++ (NSDictionary * _Nullable)cachedPreviousCrashSymbolication {
+    char *json = capture_bitdrift_crash_previous_symbolication_json();
+    if (json == NULL) {
+        return nil;
+    }
+
+    NSData *data = [NSData dataWithBytes:json length:strlen(json)];
+    capture_bitdrift_crash_free_symbolication_json(json);
+    id symbolication = [NSJSONSerialization JSONObjectWithData:data options:0 error:nil];
+    return [symbolication isKindOfClass:[NSDictionary class]] ? symbolication : nil;
+}
+
++ (BOOL)writePreviousCrashReportToDirectory:(NSURL *)reportDir sdkVersion:(NSString *)sdkVersion {
+    NSBundle *bundle = NSBundle.mainBundle;
+    NSString *appVersion = [bundle objectForInfoDictionaryKey:@"CFBundleShortVersionString"];
+    NSString *buildVersion = [bundle objectForInfoDictionaryKey:@"CFBundleVersion"];
+    // Matches the bundle version reported for MetricKit crashes by `DiagnosticEventReporter`.
+    NSString *bundleVersion = appVersion == nil || buildVersion == nil
+        ? nil
+        : [NSString stringWithFormat:@"%@.%@", appVersion, buildVersion];
+    NSOperatingSystemVersion osVersion = NSProcessInfo.processInfo.operatingSystemVersion;
+    NSString *osVersionString = [NSString stringWithFormat:@"%ld.%ld.%ld",
+                                                           (long)osVersion.majorVersion,
+                                                           (long)osVersion.minorVersion,
+                                                           (long)osVersion.patchVersion];
+
+    CrashReportMetadata metadata = {
+        .app_id = bundle.bundleIdentifier.UTF8String,
+        .app_version = appVersion.UTF8String,
+        .build_number = bundleVersion.UTF8String,
+        .os_brand = [self osBrand].UTF8String,
+        .os_version = osVersionString.UTF8String,
+        .manufacturer = "Apple",
+        .model = [self deviceModel].UTF8String,
+    };
+    return capture_bitdrift_crash_write_previous_crash_report(reportDir.absoluteURL.path.UTF8String,
+                                                              sdkVersion.UTF8String,
+                                                              &metadata);
+}
+
++ (NSString *)osBrand {
+#if TARGET_OS_OSX
+    return @"macOS";
+#elif TARGET_OS_TV
+    return @"tvOS";
+#elif TARGET_OS_WATCH
+    return @"watchOS";
+#else
+    return @"iOS";
+#endif
+}
+
+// The hardware model identifier, e.g. `iPhone15,2`.
++ (NSString *_Nullable)deviceModel {
+    size_t size = 0;
+    if (sysctlbyname("hw.machine", NULL, &size, NULL, 0) != 0 || size == 0) {
+        return nil;
+    }
+
+    char machine[size];
+    if (sysctlbyname("hw.machine", machine, &size, NULL, 0) != 0) {
+        return nil;
+    }
+    return [NSString stringWithUTF8String:machine];
+}
+
 + (NSString * _Nullable)cachedExceptionName {
     const char *value = capture_bitdrift_crash_last_exception_name();
     return value == nil ? nil : [NSString stringWithUTF8String:value];
@@ -225,8 +343,16 @@ const char *_Nullable capture_bitdrift_crash_last_exception_call_stack_image_id_
     return value == nil ? nil : [NSString stringWithUTF8String:value];
 }
 
++ (void)setForeground:(BOOL)foreground {
+    capture_bitdrift_crash_set_foreground(foreground);
+}
+
 + (void)stopCrashReporter {
     capture_bitdrift_crash_stop();
 }
 
++ (void)markCleanShutdown {
+    capture_bitdrift_crash_mark_clean_shutdown();
+}
+
 @end
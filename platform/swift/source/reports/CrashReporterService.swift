@@ -8,6 +8,7 @@
 internal import CaptureLoggerBridge
 import Foundation
 import MetricKit
+import UIKit
 
 struct CrashReporterSetupResult {
     let initResult: IssueReporterInitResult
@@ -38,8 +39,10 @@ private struct RuntimeFileState {
     private let fileManager: FileManager
     private let environment: AppEnvironment
     private let previousRunInfoController: PreviousRunInfoController?
+    private let notificationCenter: NotificationCenter
 
     private var isBitdriftCrashHandlerEnabled = false
+    private var willTerminateToken: NSObjectProtocol?
 
     init(
         previousRunInfoController: PreviousRunInfoController?,
@@ -47,7 +50,8 @@ private struct RuntimeFileState {
         bitdriftCrashHandler: any BitdriftCrashHandling = BitdriftCrashHandler(),
         metricManager: MXMetricManager = .shared,
         fileManager: FileManager = .default,
-        environment: AppEnvironment = LiveEnvironment()
+        environment: AppEnvironment = LiveEnvironment(),
+        notificationCenter: NotificationCenter = .default
     ) {
         self.ksCrashHandler = ksCrashHandler
         self.bitdriftCrashHandler = bitdriftCrashHandler
@@ -55,6 +59,7 @@ private struct RuntimeFileState {
         self.fileManager = fileManager
         self.environment = environment
         self.previousRunInfoController = previousRunInfoController
+        self.notificationCenter = notificationCenter
     }
 
     /// Initializes all crash handlers, resolves the previous-run status, and builds the
@@ -134,6 +139,10 @@ private struct RuntimeFileState {
 
     /// Stops all active crash handlers. Called on logger teardown.
     func stop() {
+        if let token = self.willTerminateToken {
+            self.notificationCenter.removeObserver(token)
+            self.willTerminateToken = nil
+        }
         self.ksCrashHandler.stopCrashReporter()
         if isBitdriftCrashHandlerEnabled {
             self.bitdriftCrashHandler.stopCrashReporter()
@@ -199,6 +208,22 @@ private extension CrashReporterService {
             return .failure(error)
         }
 
+        // Without this the next launch can't tell a regular termination from the app being killed.
+        self.willTerminateToken = self.notificationCenter.addObserver(
+            forName: UIApplication.willTerminateNotification,
+            object: nil,
+            queue: nil
+        ) { [bitdriftCrashHandler = self.bitdriftCrashHandler] _ in
+            bitdriftCrashHandler.markCleanShutdown()
+        }
+
+        // The report joins the pending reports, so it's uploaded along with the MetricKit
+        // payloads once the previous run's reports are processed.
+        _ = self.bitdriftCrashHandler.writePreviousCrashReport(
+            toDirectory: url.appendingPathComponent(Constants.reportCollectionDirectory, isDirectory: true),
+            sdkVersion: capture_get_sdk_version()
+        )
+
         return .success(())
     }
 
@@ -43,7 +43,9 @@ use platform_shared::javascript_error::{
   DeviceMetadata,
   persist_javascript_error_report,
 };
+use platform_shared::launch::{LaunchPhase, LaunchType};
 use platform_shared::metadata::{self, AppleStaticFields, Mobile};
+use platform_shared::span::{SpanHandle, SpanResult};
 use platform_shared::{LoggerHolder, LoggerId, date_to_unix_milliseconds};
 use protobuf::Enum as _;
 use std::borrow::{Borrow, Cow};
@@ -878,6 +880,41 @@ extern "C" fn capture_write_app_launch_tti_log(logger_id: LoggerId<'_>, duration
   );
 }
 
+#[unsafe(no_mangle)]
+extern "C" fn capture_begin_app_launch(logger_id: LoggerId<'_>, launch_type: i32) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let launch_type = LaunchType::from_i32(launch_type)
+        .ok_or_else(|| anyhow!("invalid app launch type: {launch_type}"))?;
+      logger_id.begin_app_launch(launch_type);
+      Ok(())
+    },
+    "swift begin app launch",
+  );
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_record_app_launch_phase(
+  logger_id: LoggerId<'_>,
+  phase: i32,
+  duration_s: f64,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let phase =
+        LaunchPhase::from_i32(phase).ok_or_else(|| anyhow!("invalid app launch phase: {phase}"))?;
+      logger_id.record_app_launch_phase(phase, Duration::seconds_f64(duration_s));
+      Ok(())
+    },
+    "swift record app launch phase",
+  );
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_finish_app_launch(logger_id: LoggerId<'_>) {
+  logger_id.finish_app_launch();
+}
+
 #[unsafe(no_mangle)]
 extern "C" fn capture_write_screen_view_log(logger_id: LoggerId<'_>, screen_name: *const Object) {
   with_handle_unexpected(
@@ -890,6 +927,83 @@ extern "C" fn capture_write_screen_view_log(logger_id: LoggerId<'_>, screen_name
   );
 }
 
+#[unsafe(no_mangle)]
+extern "C" fn capture_write_modal_screen_view_log(
+  logger_id: LoggerId<'_>,
+  screen_name: *const Object,
+) {
+  with_handle_unexpected(
+    || -> anyhow::Result<()> {
+      let screen_name = unsafe { nsstring_into_string(screen_name) }?;
+      logger_id.log_modal_screen_view(screen_name);
+      Ok(())
+    },
+    "swift write modal screen view log",
+  );
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_dismiss_modal_screen(logger_id: LoggerId<'_>) {
+  logger_id.dismiss_modal_screen();
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_set_app_foreground(logger_id: LoggerId<'_>, foreground: bool) {
+  logger_id.set_app_foreground(foreground);
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_start_span(
+  logger_id: LoggerId<'_>,
+  name: *const Object,
+  log_level: LogLevel,
+  fields: *const Object,
+  parent_span_handle: i64,
+) -> i64 {
+  with_handle_unexpected_or(
+    || {
+      let name = unsafe { nsstring_into_string(name) }?;
+      let fields = unsafe { ffi::convert_annotated_fields(fields, LogFieldKind::Ootb) }?;
+
+      let handle = logger_id.start_span(
+        name,
+        log_level,
+        fields,
+        SpanHandle::from_raw(parent_span_handle),
+      );
+
+      Ok(handle.into())
+    },
+    0,
+    "swift start span",
+  )
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn capture_end_span(
+  logger_id: LoggerId<'_>,
+  span_handle: i64,
+  result: i32,
+  fields: *const Object,
+) {
+  with_handle_unexpected(
+    move || -> anyhow::Result<()> {
+      let handle = SpanHandle::from_raw(span_handle)
+        .ok_or_else(|| anyhow!("invalid span handle: {span_handle}"))?;
+      let fields = unsafe { ffi::convert_annotated_fields(fields, LogFieldKind::Ootb) }?;
+
+      logger_id.end_span(
+        handle,
+        SpanResult::from_i32(result).unwrap_or(SpanResult::Unknown),
+        fields,
+      );
+
+      Ok(())
+    },
+    "swift end span",
+  );
+}
+
 #[unsafe(no_mangle)]
 extern "C" fn capture_start_new_session(logger_id: LoggerId<'_>) {
   with_handle_unexpected(|| logger_id.start_new_session(), "swift start new session");
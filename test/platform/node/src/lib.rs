@@ -0,0 +1,98 @@
+// capture-sdk - bitdrift's client SDK
+// Copyright Bitdrift, Inc. All rights reserved.
+//
+// Use of this source code is governed by a source available license that can be found in the
+// LICENSE file or at:
+// https://polyformproject.org/wp-content/uploads/2020/06/PolyForm-Shield-1.0.0.txt
+
+// Exposes the test API server to the Node binding's JavaScript tests.
+
+use bd_logger::DataValue;
+use napi_derive::napi;
+use std::collections::HashMap;
+
+#[ctor::ctor(unsafe)]
+fn setup() {
+  bd_test_helpers_core::test_global_init();
+}
+
+/// Starts the test API server without TLS, returning the port it listens on.
+#[napi]
+pub fn start_test_api_server(ping_interval_ms: i32) -> i32 {
+  platform_test_helpers::start_test_api_server(false, ping_interval_ms)
+}
+
+#[napi]
+pub fn stop_test_api_server() {
+  platform_test_helpers::stop_test_api_server();
+}
+
+/// Waits for the next stream the logger opens, returning its ID or -1 on timeout.
+#[napi]
+pub fn await_next_api_stream() -> i32 {
+  platform_test_helpers::await_next_api_stream()
+}
+
+#[napi]
+pub fn await_api_server_received_handshake(stream_id: i32) {
+  platform_test_helpers::await_api_server_received_handshake(stream_id);
+}
+
+#[napi]
+pub fn send_configuration_update(stream_id: i32) {
+  platform_test_helpers::send_configuration_update(stream_id);
+}
+
+#[napi]
+pub fn await_configuration_ack(stream_id: i32) -> napi::Result<()> {
+  platform_test_helpers::await_configuration_ack(stream_id)
+    .map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// Waits up to `wait_time_ms` for the stream to close, returning whether it did.
+#[napi]
+pub fn await_api_server_stream_closed(stream_id: i32, wait_time_ms: i64) -> bool {
+  platform_test_helpers::await_api_server_stream_closed(stream_id, wait_time_ms)
+}
+
+/// Configures the stream to upload every non-internal log as soon as it's written, one log per
+/// upload.
+#[napi]
+pub fn configure_aggressive_continuous_uploads(stream_id: i32) -> napi::Result<()> {
+  platform_test_helpers::configure_aggressive_continuous_uploads(stream_id)
+    .map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// A log received by the test API server. Only string fields are included.
+#[napi(object)]
+pub struct UploadedLog {
+  pub message: String,
+  pub fields: HashMap<String, String>,
+}
+
+/// Waits for the next log upload, returning its first log or null on timeout.
+#[napi]
+pub fn next_uploaded_log() -> Option<UploadedLog> {
+  platform_test_helpers::with_expected_server(|h| {
+    let log_request = h.blocking_next_log_upload()?;
+    let log = log_request.logs().into_iter().next()?;
+    let fields = log
+      .typed_fields()
+      .into_iter()
+      .filter_map(|(key, value)| {
+        let value = match value {
+          DataValue::String(s) => s.to_string(),
+          DataValue::SharedString(s) => s.to_string(),
+          DataValue::StaticString(s) => s.to_string(),
+          _ => return None,
+        };
+        Some((key.to_string(), value))
+      })
+      .collect();
+
+    Some(UploadedLog {
+      message: log.message().to_string(),
+      fields,
+    })
+  })
+}
@@ -16,6 +16,7 @@ final class LoggerLifecycleControllerTests: XCTestCase {
         let notificationCenter = NotificationCenterMock()
         let notifications: [NSNotification.Name] = [
             UIApplication.didEnterBackgroundNotification,
+            UIApplication.willEnterForegroundNotification,
             UIApplication.willResignActiveNotification,
             UIApplication.willTerminateNotification,
         ]
@@ -38,7 +39,7 @@ final class LoggerLifecycleControllerTests: XCTestCase {
         sut.start()
         sut.stop()
 
-        XCTAssertEqual(3, notificationCenter.removedObserversCalledCount)
+        XCTAssertEqual(4, notificationCenter.removedObserversCalledCount)
     }
 
     // MARK: - WillTerminate tests
@@ -141,6 +142,20 @@ final class LoggerLifecycleControllerTests: XCTestCase {
 
         XCTAssertTrue(logger.flushCalls.isEmpty)
     }
+
+    // MARK: - Foreground state tests
+
+    func testForegroundStateFollowsBackgroundAndForegroundNotifications() {
+        let notificationCenter = NotificationCenterMock()
+        let logger = makeLogger()
+        let sut = LoggerLifecycleController(logger: logger, notificationCenter: notificationCenter)
+        sut.start()
+
+        notificationCenter.post(name: UIApplication.didEnterBackgroundNotification, object: nil)
+        notificationCenter.post(name: UIApplication.willEnterForegroundNotification, object: nil)
+
+        XCTAssertEqual([false, true], logger.appForegroundCalls)
+    }
 }
 
 extension LoggerLifecycleControllerTests {
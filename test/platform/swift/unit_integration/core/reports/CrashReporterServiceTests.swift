@@ -113,6 +113,21 @@ final class CrashReporterServiceTests: XCTestCase {
         thenBitdriftCrashHandlerIsNotStarted()
     }
 
+    func testSetupWritesPreviousCrashReportIntoReportCollectionDirectoryWhenBitdriftEnabled() {
+        givenCrashReporterService()
+        whenInvokingSetup()
+        thenBitdriftPreviousCrashReportIsWrittenTo(
+            sdkBaseURL.appendingPathComponent("reports/new", isDirectory: true)
+        )
+    }
+
+    func testSetupDoesNotWritePreviousCrashReportWhenBitdriftConfigurationFails() {
+        givenCrashReporterService()
+        givenBitdriftThrowsOnConfigure()
+        whenInvokingSetup()
+        thenBitdriftPreviousCrashReportIsNotWritten()
+    }
+
     func testSetupContinuesWithBitdriftIfKSCrashConfigurationFails() {
         givenCrashReporterService()
         givenKSCrashThrowsOnConfigure()
@@ -357,6 +372,14 @@ private extension CrashReporterServiceTests {
         XCTAssertFalse(bitdriftCrashHandler.didStart)
     }
 
+    func thenBitdriftPreviousCrashReportIsWrittenTo(_ expected: URL) {
+        XCTAssertEqual(bitdriftCrashHandler.previousCrashReportDirectory, expected)
+    }
+
+    func thenBitdriftPreviousCrashReportIsNotWritten() {
+        XCTAssertNil(bitdriftCrashHandler.previousCrashReportDirectory)
+    }
+
     func thenKSCrashHandlerStopsCrashReporter() {
         XCTAssertTrue(ksCrashHandler.didStop)
     }
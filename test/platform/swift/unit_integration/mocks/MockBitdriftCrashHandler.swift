@@ -11,11 +11,13 @@ public final class MockBitdriftCrashHandler: NSObject, BitdriftCrashHandling {
     public var didConfigure = false
     public var didStart = false
     public var didStop = false
+    public var didMarkCleanShutdown = false
     public var didCallCachedPreviousCrash = false
     public var shouldThrowOnConfigure = false
     public var didCrashLastLaunchValue: NSNumber?
     public var cachedCrashDateValue: Date?
     public var previousCrash: BitdriftPreviousCrash?
+    public var previousCrashReportDirectory: URL?
 
     public func configure(withCrashReportDirectory _: URL) throws {
         if shouldThrowOnConfigure { throw MockError() }
@@ -24,10 +26,16 @@ public final class MockBitdriftCrashHandler: NSObject, BitdriftCrashHandling {
 
     public func startCrashReporter() throws { didStart = true }
     public func stopCrashReporter() { didStop = true }
+    public func markCleanShutdown() { didMarkCleanShutdown = true }
     public func didCrashLastLaunch() -> NSNumber? { didCrashLastLaunchValue }
     public func cachedCrashDate() -> Date? { cachedCrashDateValue }
     public func cachedPreviousCrash() -> BitdriftPreviousCrash? {
         didCallCachedPreviousCrash = true
         return previousCrash
     }
+
+    public func writePreviousCrashReport(toDirectory reportDir: URL, sdkVersion _: String) -> Bool {
+        previousCrashReportDirectory = reportDir
+        return previousCrash != nil
+    }
 }
@@ -50,6 +50,8 @@ public final class MockCoreLogging {
     public private(set) var flushCalls = [Bool]()
     public var flushExpectation: XCTestExpectation?
 
+    public private(set) var appForegroundCalls = [Bool]()
+
     public var shouldLogAppUpdateEvent = false
 
     public private(set) var mockedRuntimeVariables = [String: Any]()
@@ -173,6 +175,10 @@ extension MockCoreLogging: CoreLogging {
         self.flushExpectation?.fulfill()
     }
 
+    public func setAppForeground(_ foreground: Bool) {
+        self.appForegroundCalls.append(foreground)
+    }
+
     public func runtimeValue<T: RuntimeValue>(_ variable: RuntimeVariable<T>) -> T {
         if let value = self.mockedRuntimeVariables[variable.name] {
             // swiftlint:disable:next force_cast
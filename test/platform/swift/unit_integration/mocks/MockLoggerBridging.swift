@@ -129,6 +129,8 @@ extension MockLoggerBridging: LoggerBridging {
 
     public func flush(blocking _: Bool) {}
 
+    public func setAppForeground(_: Bool) {}
+
     public func runtimeValue<T: RuntimeValue>(_ variable: RuntimeVariable<T>) -> T {
         if let value = self.mockedRuntimeVariables[variable.name] {
             // swiftlint:disable:next force_cast